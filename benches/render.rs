@@ -0,0 +1,28 @@
+//! 解析与渲染性能基准：`cargo bench`
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+use xcat::format::markdown::{MarkdownOptions, parse_markdown};
+use xcat::snapshot::render_ansi;
+
+/// 由快照示例重复拼接出的大文档
+fn large_markdown() -> String {
+    let sample = include_str!("../tests/fixtures/markdown.md");
+    // 去掉 front matter，只保留正文
+    let body = sample.splitn(3, "---\n").nth(2).unwrap_or(sample);
+    body.repeat(200)
+}
+
+fn bench_markdown(c: &mut Criterion) {
+    let content = large_markdown();
+    let options = MarkdownOptions::default();
+    c.bench_function("markdown_parse", |b| {
+        b.iter(|| parse_markdown(black_box(&content), &options))
+    });
+
+    let doc = parse_markdown(&content, &options);
+    c.bench_function("markdown_render", |b| b.iter(|| render_ansi(black_box(&doc), 100)));
+}
+
+criterion_group!(benches, bench_markdown);
+criterion_main!(benches);
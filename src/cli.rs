@@ -0,0 +1,275 @@
+//! 命令行参数
+
+use clap::{Command, CommandFactory, Parser};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::str::FromStr;
+use xcat::format::ForcedFormat;
+use xcat::format::markdown::LinkStyle;
+use xcat::format::pdf::PdfBackend;
+use xcat::format::protobuf::Schema;
+use xcat::i18n::{self, Locale};
+use xcat::t;
+
+/// 终端文档预览工具：Markdown、PDF、图片与纯文本
+#[derive(Debug, Parser)]
+#[command(name = "xcat", version, about)]
+pub struct Cli {
+    /// 要预览的文件或目录，可以有多个；`user@host:path` 与 http(s) URL 为远程文件，`-` 或省略时读取标准输入；
+    /// `+N` 表示从第 N 行开始显示
+    #[arg(value_name = "FILE")]
+    pub files: Vec<String>,
+
+    /// 递归打开目录中所有能识别格式的文件（跳过隐藏文件），依次加入多文件列表
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// 强制使用的格式（md、pdf、txt、image、hex、pb），跳过自动检测
+    #[arg(short, long, value_name = "FORMAT")]
+    pub format: Option<ForcedFormat>,
+
+    /// 以十六进制显示，等同于 `--format hex`
+    #[arg(long, conflicts_with = "format")]
+    pub hex: bool,
+
+    /// 解码二进制 protobuf 所用的描述符集（`protoc --descriptor_set_out` 生成），可以用 `:` 接上消息类型，
+    /// 如 `api.pb:pkg.Request`；省略时取最后一个文件的第一个消息
+    #[arg(long, value_name = "FILE[:MESSAGE]")]
+    pub schema: Option<Schema>,
+
+    /// Markdown 链接显示方式：inline（文本后附 URL，默认）、ref（文末编号引用）、osc8（终端超链接）
+    #[arg(long, value_name = "STYLE")]
+    pub links: Option<LinkStyle>,
+
+    /// 为标题自动编号（1.、1.1、1.2.3）
+    #[arg(long)]
+    pub number_headings: bool,
+
+    /// 代码块内显示行号
+    #[arg(long)]
+    pub code_line_numbers: bool,
+
+    /// 严格按 CommonMark 规范解析 Markdown，不启用表格、脚注等扩展语法
+    #[arg(long)]
+    pub commonmark: bool,
+
+    /// 按字符位置还原 PDF 版面，保留分栏、对齐与表格
+    #[arg(long)]
+    pub layout: bool,
+
+    /// 几乎没有文字的扫描页用 tesseract 识别文字（需要以 `ocr` 特性编译）
+    #[arg(long)]
+    pub ocr: bool,
+
+    /// 提取 PDF 文本的后端：auto（内置解析器，出错时改用 poppler）、builtin、poppler（需要以 `poppler` 特性编译）
+    #[arg(long, value_name = "BACKEND")]
+    pub pdf_backend: Option<PdfBackend>,
+
+    /// 只显示指定范围的行，如 `100:200`、`100:`、`:200`
+    #[arg(long, value_name = "A:B")]
+    pub lines: Option<LineRange>,
+
+    /// 制表符展开的宽度（默认 4）
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub tab_width: Option<u16>,
+
+    /// 配置文件路径，默认为 `~/.config/xcat/config.toml`
+    #[arg(long, value_name = "PATH", env = "XCAT_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// 完整解析前需要确认的文件大小，如 `500K`、`20M`（默认 20M，0 为不限制）
+    #[arg(long, value_name = "SIZE", value_parser = xcat::config::parse_size)]
+    pub max_size: Option<u64>,
+
+    /// 打开超过大小上限的文件时不再确认
+    #[arg(long)]
+    pub force: bool,
+
+    /// 显示图片信息：尺寸、颜色、大小与 EXIF（拍摄时间、相机、GPS）
+    #[arg(long)]
+    pub info: bool,
+
+    /// 不进入预览界面，将文档直接输出到标准输出（输出不是终端时自动启用）
+    #[arg(long, visible_alias = "no-tui")]
+    pub plain: bool,
+
+    /// 直接输出时是否带 ANSI 样式：auto（输出到终端时）、always、never
+    #[arg(long, value_name = "WHEN", default_value = "auto")]
+    pub color: ColorMode,
+
+    /// 导出为文件而不进入预览界面，如 `--export html out.html`；输出文件为 `-` 时写到标准输出
+    #[arg(long, num_args = 2, value_names = ["FORMAT", "OUTPUT"])]
+    pub export: Vec<String>,
+
+    /// 幻灯片模式：按 `---` 与一级标题分页
+    #[arg(long)]
+    pub slides: bool,
+
+    /// 文件变化时自动重新渲染
+    #[arg(short, long)]
+    pub watch: bool,
+
+    /// 跟踪文件末尾（类似 `tail -f`），滚动到底部并高亮新增的行；短选项为 `-F`，`-f` 是 `--format`
+    #[arg(short = 'F', long)]
+    pub follow: bool,
+
+    /// 保存与恢复阅读位置的会话名
+    #[arg(long, value_name = "NAME")]
+    pub session: Option<String>,
+
+    /// 预览系统剪贴板中的文本或图片
+    #[arg(long, conflicts_with = "files")]
+    pub clipboard: bool,
+
+    /// 将标准输入按 git diff / log 输出着色
+    #[arg(long)]
+    pub git_pager: bool,
+
+    /// 只输出检测到的格式、匹配的文件签名与字符集，不渲染
+    #[arg(long = "type")]
+    pub show_type: bool,
+
+    /// 退出后输出各阶段耗时与内存峰值
+    #[arg(long)]
+    pub time: bool,
+
+    /// 输出调试日志到文件
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// 不输出错误与警告，只以退出码表示结果
+    #[arg(short, long, conflicts_with = "json_errors")]
+    pub quiet: bool,
+
+    /// 错误与警告以 JSON 格式逐行输出到标准错误
+    #[arg(long)]
+    pub json_errors: bool,
+
+    /// 界面语言（zh、en），默认按 `LC_ALL` / `LANG` 决定
+    #[arg(long, value_name = "LANG")]
+    pub lang: Option<Locale>,
+}
+
+/// 英文界面的帮助文字，按参数 id 对应
+const HELP_EN: &[(&str, &str)] = &[
+    ("files", "Files or directories to preview; `user@host:path` and http(s) URLs are remote files, `-` or nothing reads standard input; `+N` starts at line N"),
+    ("recursive", "Open every file with a recognised format under the given directories (hidden files skipped) as a multi-file list"),
+    ("format", "Force a format (md, pdf, txt, image, hex, pb) instead of detecting it"),
+    ("hex", "Show as a hex dump; same as `--format hex`"),
+    ("schema", "Descriptor set (`protoc --descriptor_set_out`) for decoding binary protobuf, optionally followed by `:` and the message type, e.g. `api.pb:pkg.Request`"),
+    ("links", "How Markdown links are shown: inline (URL after the text, default), ref (numbered references at the end), osc8 (terminal hyperlinks)"),
+    ("number_headings", "Number headings automatically (1., 1.1, 1.2.3)"),
+    ("code_line_numbers", "Show line numbers in code blocks"),
+    ("commonmark", "Parse Markdown as strict CommonMark, without tables, footnotes and other extensions"),
+    ("layout", "Keep the PDF page layout by placing text at its glyph positions, preserving columns, alignment and tables"),
+    ("ocr", "Run tesseract on scanned PDF pages that have almost no text but a large image, marking the result with [OCR] (requires building with the `ocr` feature)"),
+    ("pdf_backend", "PDF text extractor: auto (built-in, falling back to poppler's pdftotext on errors, default), builtin, poppler (requires building with the `poppler` feature)"),
+    ("lines", "Only show a range of lines, e.g. `100:200`, `100:` or `:200`"),
+    ("tab_width", "Width tabs expand to (default 4)"),
+    ("config", "Config file path, `~/.config/xcat/config.toml` by default"),
+    ("max_size", "Files larger than this (e.g. `500K`, `20M`; default 20M, 0 for no limit) need confirmation before being parsed"),
+    ("force", "Open files over the size limit without asking"),
+    ("info", "Show image info: dimensions, color type, file size and EXIF (date taken, camera, GPS)"),
+    ("plain", "Write the document to standard output instead of opening the viewer (automatic when output is not a terminal)"),
+    ("color", "Whether plain output is styled with ANSI escapes: auto (when writing to a terminal), always, never"),
+    ("export", "Export to a file instead of opening the viewer, e.g. `--export html out.html`; an OUTPUT of `-` writes to standard output"),
+    ("slides", "Slide mode: split pages at `---` and level-1 headings"),
+    ("watch", "Re-render when the file changes"),
+    ("follow", "Follow the end of the file like `tail -f`, scrolling to the bottom and highlighting new lines; the short flag is `-F` because `-f` is `--format`"),
+    ("session", "Session name for saving and restoring the reading position"),
+    ("clipboard", "Preview the text or image on the system clipboard"),
+    ("git_pager", "Colour standard input as git diff / log output"),
+    ("show_type", "Only print the detected format, the matched file signature and the charset, without rendering"),
+    ("time", "Print per-stage timings and peak memory on exit"),
+    ("verbose", "Write debug logs to a file"),
+    ("quiet", "Print no errors or warnings; only the exit code reports the result"),
+    ("json_errors", "Print errors and warnings to standard error as one JSON object per line"),
+    ("lang", "Interface language (zh, en), taken from `LC_ALL` / `LANG` by default"),
+];
+
+/// 按当前界面语言构建命令行定义
+pub fn command() -> Command {
+    let mut command = Cli::command();
+    if i18n::current() == Locale::En {
+        command = command.about("Terminal document previewer: Markdown, PDF, images and plain text");
+        for (id, help) in HELP_EN {
+            command = command.mut_arg(id, |arg| arg.help(help));
+        }
+    }
+    command
+}
+
+/// 在解析命令行之前取出选项（如 `--lang`）的值，以便参数错误也按该选项输出
+pub fn early_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1).take_while(|arg| arg != "--");
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// 在解析命令行之前检查开关（如 `--json-errors`）是否给出
+pub fn early_flag(name: &str) -> bool {
+    std::env::args().skip(1).take_while(|arg| arg != "--").any(|arg| arg == name)
+}
+
+/// 直接输出时何时带 ANSI 样式
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => Err(t!("无效的颜色选项: {}（可选 auto、always、never）", s)),
+        }
+    }
+}
+
+/// `--lines` 指定的行范围，行号从 1 开始、包含两端
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineRange {
+    pub first: usize,
+    pub last: Option<usize>,
+}
+
+impl LineRange {
+    /// 转换为从 0 开始的半开区间
+    pub fn to_range(self) -> Range<usize> {
+        self.first - 1..self.last.unwrap_or(usize::MAX)
+    }
+}
+
+impl FromStr for LineRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || t!("无效的行范围: {}（如 100:200、100:、:200）", s);
+        let number = |n: &str| n.trim().parse::<usize>().map_err(|_| invalid());
+        let (first, last) = match s.split_once(':') {
+            Some((a, b)) => {
+                let first = if a.is_empty() { 1 } else { number(a)? };
+                let last = if b.is_empty() { None } else { Some(number(b)?) };
+                (first, last)
+            }
+            None => (number(s)?, Some(number(s)?)),
+        };
+        if first == 0 || last.is_some_and(|last| last < first) {
+            return Err(invalid());
+        }
+        Ok(LineRange { first, last })
+    }
+}
@@ -0,0 +1,38 @@
+//! 系统剪贴板（`--clipboard`）
+//!
+//! 读取剪贴板中的文本或图片：文本按 Markdown 渲染，图片保存为临时 PNG 后显示。
+
+use std::path::PathBuf;
+use xcat::t;
+
+/// 剪贴板内容
+pub enum Content {
+    Text(String),
+    /// 保存到临时目录的 PNG 图片
+    Image(PathBuf),
+}
+
+/// 读取剪贴板，优先取文本
+pub fn read() -> Result<Content, String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| t!("无法访问剪贴板: {}", e))?;
+
+    if let Ok(text) = clipboard.get_text()
+        && !text.is_empty()
+    {
+        return Ok(Content::Text(text));
+    }
+
+    let image = clipboard
+        .get_image()
+        .map_err(|_| t!("剪贴板中没有文本或图片").to_string())?;
+    let path = std::env::temp_dir().join(format!("xcat-clipboard-{}.png", std::process::id()));
+    image::save_buffer(
+        &path,
+        &image.bytes,
+        image.width as u32,
+        image.height as u32,
+        image::ExtendedColorType::Rgba8,
+    )
+    .map_err(|e| t!("无法保存剪贴板图片: {}", e))?;
+    Ok(Content::Image(path))
+}
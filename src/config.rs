@@ -0,0 +1,617 @@
+//! 配置文件
+//!
+//! 启动时读取 `$XDG_CONFIG_HOME/xcat/config.toml`（默认 `~/.config`），
+//! 也可以用 `--config` 或环境变量 `XCAT_CONFIG` 指定其他文件。命令行参数优先于配置文件。
+//!
+//! 解析结果通过 [`init`] 设置后全局共享：渲染读取主题颜色，预览模式读取键位，
+//! 格式解析读取制表符宽度等选项。未设置时使用默认配置。
+//!
+//! ```toml
+//! tab_width = 8
+//! wrap = "wrap"
+//! max_size = "50M"
+//!
+//! [markdown]
+//! links = "osc8"
+//! number_headings = true
+//!
+//! [theme]
+//! heading = "magenta"
+//! cursor_line = "#303030"
+//!
+//! [keys]
+//! down = ["j", "Down"]
+//! up = ["k", "Up"]
+//! back = ["Ctrl-o"]
+//!
+//! [syntax]
+//! theme = "InspiredGitHub"
+//! ```
+
+use crate::format::markdown::LinkStyle;
+use crate::t;
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// 设置全局配置，只在启动时调用一次
+pub fn init(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+/// 全局配置，未调用 [`init`] 时为默认配置
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// 默认配置文件路径
+pub fn default_path() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(config.join("xcat/config.toml"))
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub theme: Theme,
+    pub keys: KeyMap,
+    /// 制表符展开的宽度
+    pub tab_width: usize,
+    /// 超出终端宽度的长行如何显示
+    pub wrap: WrapMode,
+    /// 完整解析前需要确认的文件大小（字节），0 表示不限制
+    pub max_size: u64,
+    pub syntax: SyntaxOptions,
+    pub markdown: MarkdownConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            keys: KeyMap::default(),
+            tab_width: 4,
+            wrap: WrapMode::default(),
+            max_size: 20 * 1024 * 1024,
+            syntax: SyntaxOptions::default(),
+            markdown: MarkdownConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// 读取配置文件，文件不存在时返回默认配置
+    pub fn load(path: &Path) -> Result<Config, String> {
+        match fs::read_to_string(path) {
+            Ok(content) => Config::parse(&content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Config, String> {
+        let file: ConfigFile = toml::from_str(content).map_err(|e| e.message().to_string())?;
+        let mut config = Config::default();
+
+        if let Some(width) = file.tab_width {
+            if width == 0 {
+                return Err(t!("tab_width 必须大于 0").into());
+            }
+            config.tab_width = width;
+        }
+        if let Some(wrap) = file.wrap {
+            config.wrap = wrap.parse()?;
+        }
+        if let Some(size) = file.max_size {
+            config.max_size = parse_size(&size)?;
+        }
+        for (name, value) in &file.theme {
+            let color = Color::from_str(value).map_err(|_| t!("无效的颜色: {}", value))?;
+            config.theme.set(name, color)?;
+        }
+        for (name, keys) in &file.keys {
+            let action = name.parse()?;
+            let keys = keys.iter().map(|k| parse_key(k)).collect::<Result<Vec<_>, _>>()?;
+            config.keys.bind(action, keys);
+        }
+        if let Some(enabled) = file.syntax.enabled {
+            config.syntax.enabled = enabled;
+        }
+        if let Some(theme) = file.syntax.theme {
+            config.syntax.theme = theme;
+        }
+        if let Some(links) = file.markdown.links {
+            config.markdown.links = links.parse()?;
+        }
+        if let Some(number_headings) = file.markdown.number_headings {
+            config.markdown.number_headings = number_headings;
+        }
+        if let Some(code_line_numbers) = file.markdown.code_line_numbers {
+            config.markdown.code_line_numbers = code_line_numbers;
+        }
+        if let Some(smart_punctuation) = file.markdown.smart_punctuation {
+            config.markdown.smart_punctuation = smart_punctuation;
+        }
+        if let Some(commonmark) = file.markdown.commonmark {
+            config.markdown.commonmark = commonmark;
+        }
+
+        Ok(config)
+    }
+}
+
+/// 解析文件大小，如 `500K`、`20M`、`1G`（按 1024 进位），不带单位时为字节数
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let invalid = || t!("无效的大小: {}（如 500K、20M、1G）", s);
+    let lower = s.trim().to_ascii_lowercase();
+    let digits = lower.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let shift = match lower[digits.len()..].trim_end_matches("ib").trim_end_matches('b') {
+        "" => 0,
+        "k" => 10,
+        "m" => 20,
+        "g" => 30,
+        _ => return Err(invalid()),
+    };
+    let n: u64 = digits.trim().parse().map_err(|_| invalid())?;
+    n.checked_mul(1 << shift).ok_or_else(invalid)
+}
+
+/// 配置文件的原始结构，所有字段都可省略
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    tab_width: Option<usize>,
+    wrap: Option<String>,
+    max_size: Option<String>,
+    theme: BTreeMap<String, String>,
+    keys: BTreeMap<String, Vec<String>>,
+    syntax: SyntaxFile,
+    markdown: MarkdownFile,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SyntaxFile {
+    enabled: Option<bool>,
+    theme: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MarkdownFile {
+    links: Option<String>,
+    number_headings: Option<bool>,
+    code_line_numbers: Option<bool>,
+    smart_punctuation: Option<bool>,
+    commonmark: Option<bool>,
+}
+
+/// 各类文本的颜色
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub heading: Color,
+    /// 三级标题
+    pub subheading: Color,
+    pub link: Color,
+    pub note: Color,
+    pub tip: Color,
+    pub important: Color,
+    pub warning: Color,
+    pub caution: Color,
+    pub math: Color,
+    pub added: Color,
+    pub removed: Color,
+    pub quote: Color,
+    pub code: Color,
+    /// JSON 等结构化数据的键
+    pub key: Color,
+    pub string: Color,
+    pub number: Color,
+    /// true / false / null
+    pub constant: Color,
+    /// 光标行背景色
+    pub cursor_line: Color,
+    /// 代码块背景色
+    pub code_block: Color,
+    /// `--follow` 时新追加行的背景色
+    pub new_line: Color,
+    /// 搜索匹配的背景色
+    pub search_match: Color,
+    pub status_bar_fg: Color,
+    pub status_bar_bg: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            heading: Color::Cyan,
+            subheading: Color::LightBlue,
+            link: Color::Blue,
+            note: Color::Blue,
+            tip: Color::Green,
+            important: Color::Magenta,
+            warning: Color::Yellow,
+            caution: Color::Red,
+            math: Color::LightMagenta,
+            added: Color::Green,
+            removed: Color::Red,
+            quote: Color::Yellow,
+            code: Color::Green,
+            key: Color::Blue,
+            string: Color::Green,
+            number: Color::Magenta,
+            constant: Color::Yellow,
+            cursor_line: Color::DarkGray,
+            code_block: Color::Indexed(235),
+            new_line: Color::Indexed(22),
+            search_match: Color::Yellow,
+            status_bar_fg: Color::Black,
+            status_bar_bg: Color::White,
+        }
+    }
+}
+
+impl Theme {
+    fn set(&mut self, name: &str, color: Color) -> Result<(), String> {
+        let slot = match name {
+            "heading" => &mut self.heading,
+            "subheading" => &mut self.subheading,
+            "link" => &mut self.link,
+            "note" => &mut self.note,
+            "tip" => &mut self.tip,
+            "important" => &mut self.important,
+            "warning" => &mut self.warning,
+            "caution" => &mut self.caution,
+            "math" => &mut self.math,
+            "added" => &mut self.added,
+            "removed" => &mut self.removed,
+            "quote" => &mut self.quote,
+            "code" => &mut self.code,
+            "key" => &mut self.key,
+            "string" => &mut self.string,
+            "number" => &mut self.number,
+            "constant" => &mut self.constant,
+            "cursor_line" => &mut self.cursor_line,
+            "code_block" => &mut self.code_block,
+            "new_line" => &mut self.new_line,
+            "search_match" => &mut self.search_match,
+            "status_bar_fg" => &mut self.status_bar_fg,
+            "status_bar_bg" => &mut self.status_bar_bg,
+            _ => return Err(t!("未知的主题颜色: {}", name)),
+        };
+        *slot = color;
+        Ok(())
+    }
+}
+
+/// 长行的显示方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WrapMode {
+    /// 超出终端宽度的部分截断
+    #[default]
+    Clip,
+    /// 在终端宽度处软换行
+    Wrap,
+}
+
+impl FromStr for WrapMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clip" | "none" => Ok(WrapMode::Clip),
+            "wrap" => Ok(WrapMode::Wrap),
+            _ => Err(t!("无效的换行方式: {}（可选 clip、wrap）", s)),
+        }
+    }
+}
+
+/// 代码块语法高亮选项
+#[derive(Clone, Debug)]
+pub struct SyntaxOptions {
+    pub enabled: bool,
+    /// 配色方案名
+    pub theme: String,
+}
+
+impl Default for SyntaxOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            theme: "base16-ocean.dark".into(),
+        }
+    }
+}
+
+/// Markdown 相关的默认选项，对应同名命令行参数
+#[derive(Clone, Debug)]
+pub struct MarkdownConfig {
+    pub links: LinkStyle,
+    pub number_headings: bool,
+    pub code_line_numbers: bool,
+    /// 直引号、`--`、`...` 转换为弯引号、破折号与省略号
+    pub smart_punctuation: bool,
+    /// 严格按 CommonMark 解析，不启用扩展语法
+    pub commonmark: bool,
+}
+
+impl Default for MarkdownConfig {
+    fn default() -> Self {
+        Self {
+            links: LinkStyle::default(),
+            number_headings: false,
+            code_line_numbers: false,
+            smart_punctuation: true,
+            commonmark: false,
+        }
+    }
+}
+
+/// 预览模式中可绑定按键的操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Down,
+    Up,
+    Left,
+    Right,
+    HalfPageDown,
+    HalfPageUp,
+    Top,
+    Bottom,
+    NextHeading,
+    PrevHeading,
+    NextSection,
+    PrevSection,
+    NextHunk,
+    PrevHunk,
+    ToggleFold,
+    Fold,
+    FoldAll,
+    UnfoldAll,
+    NextFile,
+    PrevFile,
+    ToggleListing,
+    ToggleToc,
+    PageImage,
+    Open,
+    Back,
+    Edit,
+    Command,
+    Search,
+    NextMatch,
+    PrevMatch,
+    Parent,
+    Sort,
+    Yank,
+    Save,
+    Info,
+    Quit,
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let action = match s {
+            "down" => Action::Down,
+            "up" => Action::Up,
+            "left" => Action::Left,
+            "right" => Action::Right,
+            "half_page_down" => Action::HalfPageDown,
+            "half_page_up" => Action::HalfPageUp,
+            "top" => Action::Top,
+            "bottom" => Action::Bottom,
+            "next_heading" => Action::NextHeading,
+            "prev_heading" => Action::PrevHeading,
+            "next_section" => Action::NextSection,
+            "prev_section" => Action::PrevSection,
+            "next_hunk" => Action::NextHunk,
+            "prev_hunk" => Action::PrevHunk,
+            "toggle_fold" => Action::ToggleFold,
+            "fold" => Action::Fold,
+            "fold_all" => Action::FoldAll,
+            "unfold_all" => Action::UnfoldAll,
+            "next_file" => Action::NextFile,
+            "prev_file" => Action::PrevFile,
+            "toggle_listing" => Action::ToggleListing,
+            "toggle_toc" => Action::ToggleToc,
+            "page_image" => Action::PageImage,
+            "open" => Action::Open,
+            "back" => Action::Back,
+            "edit" => Action::Edit,
+            "command" => Action::Command,
+            "search" => Action::Search,
+            "next_match" => Action::NextMatch,
+            "prev_match" => Action::PrevMatch,
+            "parent" => Action::Parent,
+            "sort" => Action::Sort,
+            "yank" => Action::Yank,
+            "save" => Action::Save,
+            "info" => Action::Info,
+            "quit" => Action::Quit,
+            _ => return Err(t!("未知的操作: {}", s)),
+        };
+        Ok(action)
+    }
+}
+
+/// 按键到操作的映射
+#[derive(Clone, Debug)]
+pub struct KeyMap {
+    bindings: Vec<(KeyCode, KeyModifiers, Action)>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        use Action::*;
+        let bindings = [
+            ('j', Down),
+            ('k', Up),
+            ('h', Left),
+            ('l', Right),
+            ('d', HalfPageDown),
+            ('u', HalfPageUp),
+            ('g', Top),
+            ('G', Bottom),
+            ('}', NextHeading),
+            ('{', PrevHeading),
+            (')', NextSection),
+            ('(', PrevSection),
+            (' ', ToggleFold),
+            ('z', Fold),
+            (']', NextFile),
+            ('[', PrevFile),
+            ('-', Parent),
+            ('s', Sort),
+            ('t', ToggleToc),
+            ('v', PageImage),
+            ('y', Yank),
+            ('Y', Save),
+            ('i', Info),
+            ('e', Edit),
+            (':', Command),
+            ('/', Search),
+            ('n', NextMatch),
+            ('N', PrevMatch),
+            ('q', Quit),
+            ('Q', Quit),
+        ];
+        let mut bindings: Vec<(KeyCode, KeyModifiers, Action)> = bindings
+            .into_iter()
+            .map(|(c, a)| (KeyCode::Char(c), KeyModifiers::NONE, a))
+            .collect();
+        bindings.push((KeyCode::Tab, KeyModifiers::NONE, ToggleListing));
+        bindings.push((KeyCode::Enter, KeyModifiers::NONE, Open));
+        bindings.push((KeyCode::Char('o'), KeyModifiers::CONTROL, Back));
+        Self { bindings }
+    }
+}
+
+impl KeyMap {
+    /// 不带修饰键时按键对应的操作
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.key_action(code, KeyModifiers::NONE)
+    }
+
+    /// 按键与修饰键对应的操作；Shift 已体现在字符本身中，不参与匹配
+    pub fn key_action(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        let modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        self.bindings
+            .iter()
+            .find(|(c, m, _)| *c == code && *m == modifiers)
+            .map(|(_, _, a)| *a)
+    }
+
+    /// 用 `keys` 替换操作原有的按键
+    fn bind(&mut self, action: Action, keys: Vec<(KeyCode, KeyModifiers)>) {
+        self.bindings.retain(|(c, m, a)| *a != action && !keys.contains(&(*c, *m)));
+        self.bindings.extend(keys.into_iter().map(|(c, m)| (c, m, action)));
+    }
+}
+
+/// 解析按键名：单个字符或 `Enter`、`Tab`、`Space`、`Down` 等特殊键，
+/// 可带 `Ctrl-`（`C-`）或 `Alt-`（`M-`）前缀
+fn parse_key(name: &str) -> Result<(KeyCode, KeyModifiers), String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = name;
+    while rest.len() > 2 {
+        let Some((prefix, key)) = rest.split_once('-') else { break };
+        match prefix.to_ascii_lowercase().as_str() {
+            "ctrl" | "c" => modifiers |= KeyModifiers::CONTROL,
+            "alt" | "m" => modifiers |= KeyModifiers::ALT,
+            _ => break,
+        }
+        rest = key;
+    }
+    if rest.is_empty() {
+        return Err(t!("无效的按键: {}", name));
+    }
+    let mut chars = rest.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok((KeyCode::Char(c), modifiers));
+    }
+    let code = match rest.to_ascii_lowercase().as_str() {
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "space" => KeyCode::Char(' '),
+        "esc" => KeyCode::Esc,
+        "backspace" => KeyCode::Backspace,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        _ => return Err(t!("无效的按键: {}", name)),
+    };
+    Ok((code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            r##"
+            tab_width = 8
+            max_size = "5M"
+
+            [theme]
+            heading = "magenta"
+            cursor_line = "#303030"
+
+            [markdown]
+            smart_punctuation = false
+
+            [keys]
+            down = ["n", "Down"]
+            back = ["Ctrl-b", "M-Left"]
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.tab_width, 8);
+        assert_eq!(config.max_size, 5 * 1024 * 1024);
+        assert!(!config.markdown.smart_punctuation);
+        assert_eq!(config.theme.heading, Color::Magenta);
+        assert_eq!(config.theme.cursor_line, Color::Rgb(0x30, 0x30, 0x30));
+        assert_eq!(config.keys.action(KeyCode::Down), Some(Action::Down));
+        assert_eq!(config.keys.action(KeyCode::Char('n')), Some(Action::Down));
+        assert_eq!(config.keys.action(KeyCode::Char('j')), None);
+        assert_eq!(config.keys.action(KeyCode::Char('k')), Some(Action::Up));
+        assert_eq!(config.keys.key_action(KeyCode::Char('b'), KeyModifiers::CONTROL), Some(Action::Back));
+        assert_eq!(config.keys.key_action(KeyCode::Left, KeyModifiers::ALT), Some(Action::Back));
+        assert_eq!(config.keys.key_action(KeyCode::Char('o'), KeyModifiers::CONTROL), None);
+        assert_eq!(config.keys.action(KeyCode::Char('b')), None);
+        // 单独的 `-` 仍是普通按键
+        assert_eq!(parse_key("-"), Ok((KeyCode::Char('-'), KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Config::parse("[theme]\nheading = \"nope\"").is_err());
+        assert!(Config::parse("[keys]\nfly = [\"f\"]").is_err());
+        assert!(Config::parse("unknown = 1").is_err());
+        assert!(Config::parse("max_size = \"20X\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("500K"), Ok(500 * 1024));
+        assert_eq!(parse_size("20MiB"), Ok(20 * 1024 * 1024));
+        assert_eq!(parse_size("1gb"), Ok(1 << 30));
+        assert!(parse_size("M").is_err());
+    }
+}
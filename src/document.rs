@@ -1,3 +1,8 @@
+use crate::t;
+use std::ops::Range;
+use std::path::PathBuf;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
 /// 样式属性位标志
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct TextStyle(u32);
@@ -10,6 +15,31 @@ impl TextStyle {
     pub const HEADING: Self = Self(1 << 3);
     pub const QUOTE: Self = Self(1 << 4);
     pub const CODE: Self = Self(1 << 5);
+    pub const LINK: Self = Self(1 << 6);
+    // 提示块（admonition）类型，决定标题与边框颜色
+    pub const NOTE: Self = Self(1 << 7);
+    pub const TIP: Self = Self(1 << 8);
+    pub const IMPORTANT: Self = Self(1 << 9);
+    pub const WARNING: Self = Self(1 << 10);
+    pub const CAUTION: Self = Self(1 << 11);
+    pub const MATH: Self = Self(1 << 12);
+    // diff 的新增 / 删除行
+    pub const ADDED: Self = Self(1 << 13);
+    pub const REMOVED: Self = Self(1 << 14);
+    /// `--follow` 时新追加的行
+    pub const NEW: Self = Self(1 << 15);
+    // 结构化数据（JSON 等）的键与各类值
+    pub const KEY: Self = Self(1 << 16);
+    pub const STRING: Self = Self(1 << 17);
+    pub const NUMBER: Self = Self(1 << 18);
+    /// true / false / null
+    pub const CONSTANT: Self = Self(1 << 19);
+    /// 删除线（`~~deleted~~`）
+    pub const STRIKETHROUGH: Self = Self(1 << 20);
+    /// 三级标题，与 HEADING 同时使用时取代其颜色
+    pub const SUBHEADING: Self = Self(1 << 21);
+    /// 搜索匹配的文字，绘制时临时加上
+    pub const SEARCH_MATCH: Self = Self(1 << 22);
 
     pub fn contains(self, other: Self) -> bool {
         self.0 & other.0 == other.0
@@ -32,10 +62,25 @@ impl std::ops::BitOr for TextStyle {
 }
 
 /// 带样式的文本片段
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct TextSpan {
     pub text: String,
     pub style: TextStyle,
+    /// 链接目标（用于终端超链接）
+    pub link: Option<String>,
+    /// 具体的前景色（代码高亮），优先于样式对应的主题颜色
+    pub color: Option<(u8, u8, u8)>,
+}
+
+impl TextSpan {
+    pub fn new(text: impl Into<String>, style: TextStyle) -> Self {
+        Self {
+            text: text.into(),
+            style,
+            link: None,
+            color: None,
+        }
+    }
 }
 
 /// 一行渲染内容
@@ -45,8 +90,827 @@ pub struct RenderLine {
     pub indent: u16,
 }
 
+/// 嵌入文档的图片，占据从 `line` 开始的 `height` 行
+#[derive(Clone, Debug)]
+pub struct ImageBlock {
+    pub line: usize,
+    pub height: u16,
+    pub path: PathBuf,
+    pub alt: String,
+}
+
+/// 代码块：内容行范围 `[start, end)` 及原始代码文本
+#[derive(Clone, Debug)]
+pub struct CodeBlock {
+    pub start: usize,
+    pub end: usize,
+    pub lang: Option<String>,
+    pub text: String,
+}
+
+/// 表格区域：行范围 `[start, end)` 及表格显示宽度（不含缩进）
+#[derive(Clone, Debug)]
+pub struct TableBlock {
+    pub start: usize,
+    pub end: usize,
+    pub width: usize,
+}
+
+/// 可折叠区域：折叠时保留首行 `start`，隐藏 `(start, end)` 内的行
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fold {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 目录预览中的一项：所在行及对应的文件或目录
+#[derive(Clone, Debug)]
+pub struct DirEntry {
+    pub line: usize,
+    pub path: PathBuf,
+}
+
+/// 链接：所在行及目标地址，供预览模式打开光标处的链接
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Link {
+    pub line: usize,
+    pub url: String,
+}
+
+/// 渲染行与源文件行的对应：从 `line` 行起的内容来自源文件第 `source` 行（均从 0 开始）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceLine {
+    pub line: usize,
+    pub source: usize,
+}
+
+/// 标题（用于面包屑、目录等导航）
+#[derive(Clone, Debug)]
+pub struct Heading {
+    pub level: u8,
+    pub line: usize,
+    pub text: String,
+}
+
+/// 文档元数据（如 Markdown front matter）
+#[derive(Clone, Debug, Default)]
+pub struct Metadata {
+    pub title: Option<String>,
+    pub date: Option<String>,
+    pub tags: Vec<String>,
+    /// 其余标量字段，按出现顺序保存
+    pub extra: Vec<(String, String)>,
+}
+
 /// 文档 = 渲染行列表
 #[derive(Clone, Debug, Default)]
 pub struct Document {
     pub lines: Vec<RenderLine>,
+    pub metadata: Metadata,
+    pub images: Vec<ImageBlock>,
+    pub headings: Vec<Heading>,
+    pub code_blocks: Vec<CodeBlock>,
+    pub tables: Vec<TableBlock>,
+    /// 水平分隔线所在行
+    pub rules: Vec<usize>,
+    /// 书籍类文档各章节的起始行（如 EPUB 的章节分隔线）
+    pub sections: Vec<usize>,
+    /// 分页文档（如 PDF）各页的起始行，第一页为 0，其余为页分隔行
+    pub pages: Vec<usize>,
+    /// diff 中各 hunk 头（`@@`）所在行
+    pub hunks: Vec<usize>,
+    /// 可折叠区域（如 JSON 的对象与数组），按起始行排序
+    pub folds: Vec<Fold>,
+    /// 目录预览中文件列表的起始行（其上方为 README）
+    pub listing: Option<usize>,
+    /// 目录预览中可打开的各项
+    pub entries: Vec<DirEntry>,
+    /// 链接，按所在行排序
+    pub links: Vec<Link>,
+    /// 各块的源文件起始行，按渲染行排序，用于在编辑器中打开对应位置
+    pub source_lines: Vec<SourceLine>,
+}
+
+impl Document {
+    /// 最宽一行的显示宽度（含缩进）
+    pub fn width(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|l| l.indent as usize + l.spans.iter().map(|s| s.text.width()).sum::<usize>())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 查找位于 `line` 行的目录项
+    pub fn entry_at(&self, line: usize) -> Option<&DirEntry> {
+        self.entries.iter().find(|e| e.line == line)
+    }
+
+    /// 为标题加上层级编号（1.、1.1、1.2.3），以文档中最高的标题级别为第一级
+    ///
+    /// 每个标题相对最近的更高级标题编号，跳级（如一级标题下直接是四级）时不补 0；
+    /// 编号最多 6 段，更深的标题与第 6 段同级。
+    pub fn number_headings(&mut self) {
+        let min_level = self.headings.iter().map(|h| h.level).min().unwrap_or(1);
+        // 从外到内的 (级别, 同级序号)
+        let mut stack: Vec<(u8, usize)> = Vec::new();
+
+        for heading in &mut self.headings {
+            let level = heading.level.min(min_level.saturating_add(5));
+            // 退回到不低于当前级别的祖先，被弹出的最外一层与当前标题同深度，序号接着它数
+            let mut popped = None;
+            while stack.last().is_some_and(|&(l, _)| l > level) {
+                popped = stack.pop();
+            }
+            match stack.last_mut() {
+                Some((l, count)) if *l == level => *count += 1,
+                _ => stack.push((level, popped.map_or(1, |(_, count)| count + 1))),
+            }
+
+            let mut number = stack
+                .iter()
+                .map(|(_, n)| n.to_string())
+                .collect::<Vec<_>>()
+                .join(".");
+            if stack.len() == 1 {
+                number.push('.');
+            }
+
+            if let Some(line) = self.lines.get_mut(heading.line) {
+                let style = line.spans.first().map_or(TextStyle::NONE, |s| s.style);
+                line.spans.insert(0, TextSpan::new(format!("{number} "), style));
+            }
+            heading.text = format!("{number} {}", heading.text);
+        }
+    }
+
+    /// 查找 `pattern` 出现的位置：(行, 行内文字的字节范围)，按位置排序。
+    /// 不含大写字母时不区分大小写（smart case）；匹配不跨行
+    pub fn find(&self, pattern: &str) -> Vec<(usize, Range<usize>)> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        let ignore_case = !pattern.chars().any(char::is_uppercase);
+        let mut matches = Vec::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            let text: String = line.spans.iter().map(|s| s.text.as_str()).collect();
+            // 转为小写后字节长度变化的行（少见）按原文查找，保证范围对得上
+            let lower = if ignore_case { text.to_lowercase() } else { String::new() };
+            let haystack = if ignore_case && lower.len() == text.len() { &lower } else { &text };
+            matches.extend(haystack.match_indices(pattern).map(|(start, m)| (i, start..start + m.len())));
+        }
+        matches
+    }
+
+    /// 位于 `line` 行的链接
+    pub fn links_at(&self, line: usize) -> impl Iterator<Item = &Link> {
+        self.links.iter().filter(move |l| l.line == line)
+    }
+
+    /// 渲染行对应的源文件行（所在块的起始行）
+    pub fn source_line(&self, line: usize) -> Option<usize> {
+        let index = self.source_lines.partition_point(|s| s.line <= line);
+        index.checked_sub(1).map(|i| self.source_lines[i].source)
+    }
+
+    /// 指定行所在的代码块
+    pub fn code_block_at(&self, line: usize) -> Option<&CodeBlock> {
+        self.code_blocks
+            .iter()
+            .find(|b| (b.start.saturating_sub(1)..=b.end).contains(&line))
+    }
+
+    /// 指定行所在的表格
+    pub fn table_at(&self, line: usize) -> Option<&TableBlock> {
+        self.tables.iter().find(|t| (t.start..t.end).contains(&line))
+    }
+
+    /// 截取 `range` 范围内的行，标题、图片等位置随之平移
+    ///
+    /// 只有部分落在范围内的图片被丢弃，代码块与表格按范围截断。
+    pub fn slice(&self, range: Range<usize>) -> Document {
+        let end = range.end.min(self.lines.len());
+        let start = range.start.min(end);
+        let inside = |line: usize| (start..end).contains(&line);
+        let clamp = |line: usize| line.clamp(start, end) - start;
+
+        Document {
+            lines: self.lines[start..end].to_vec(),
+            metadata: self.metadata.clone(),
+            images: self
+                .images
+                .iter()
+                .filter(|img| img.line >= start && img.line + img.height as usize <= end)
+                .map(|img| ImageBlock {
+                    line: img.line - start,
+                    ..img.clone()
+                })
+                .collect(),
+            headings: self
+                .headings
+                .iter()
+                .filter(|h| inside(h.line))
+                .map(|h| Heading {
+                    line: h.line - start,
+                    ..h.clone()
+                })
+                .collect(),
+            code_blocks: self
+                .code_blocks
+                .iter()
+                .filter(|b| b.start < end && b.end > start)
+                .map(|b| CodeBlock {
+                    start: clamp(b.start),
+                    end: clamp(b.end),
+                    ..b.clone()
+                })
+                .collect(),
+            tables: self
+                .tables
+                .iter()
+                .filter(|t| t.start < end && t.end > start)
+                .map(|t| TableBlock {
+                    start: clamp(t.start),
+                    end: clamp(t.end),
+                    ..t.clone()
+                })
+                .collect(),
+            rules: self.rules.iter().filter(|&&l| inside(l)).map(|l| l - start).collect(),
+            sections: self.sections.iter().filter(|&&l| inside(l)).map(|l| l - start).collect(),
+            pages: self.pages.iter().filter(|&&l| inside(l)).map(|l| l - start).collect(),
+            hunks: self.hunks.iter().filter(|&&l| inside(l)).map(|l| l - start).collect(),
+            folds: self
+                .folds
+                .iter()
+                .filter(|f| inside(f.start))
+                .map(|f| Fold {
+                    start: f.start - start,
+                    end: clamp(f.end),
+                })
+                .collect(),
+            listing: self.listing.filter(|&l| l < end).map(clamp),
+            entries: self
+                .entries
+                .iter()
+                .filter(|e| inside(e.line))
+                .map(|e| DirEntry {
+                    line: e.line - start,
+                    ..e.clone()
+                })
+                .collect(),
+            links: self
+                .links
+                .iter()
+                .filter(|l| inside(l.line))
+                .map(|l| Link {
+                    line: l.line - start,
+                    ..l.clone()
+                })
+                .collect(),
+            // 范围之前最后一项对应截取后的首行
+            source_lines: dedup_source_lines(
+                self.source_lines
+                    .iter()
+                    .filter(|s| s.line < end)
+                    .map(|s| SourceLine {
+                        line: clamp(s.line),
+                        ..*s
+                    }),
+            ),
+        }
+    }
+
+    /// 将另一文档追加到末尾，标题、图片等位置随之平移；元数据保持不变
+    pub fn append(&mut self, other: Document) {
+        let offset = self.lines.len();
+        self.lines.extend(other.lines);
+        self.images.extend(other.images.into_iter().map(|img| ImageBlock {
+            line: img.line + offset,
+            ..img
+        }));
+        self.headings.extend(other.headings.into_iter().map(|h| Heading {
+            line: h.line + offset,
+            ..h
+        }));
+        self.code_blocks.extend(other.code_blocks.into_iter().map(|b| CodeBlock {
+            start: b.start + offset,
+            end: b.end + offset,
+            ..b
+        }));
+        self.tables.extend(other.tables.into_iter().map(|t| TableBlock {
+            start: t.start + offset,
+            end: t.end + offset,
+            ..t
+        }));
+        self.rules.extend(other.rules.iter().map(|l| l + offset));
+        self.sections.extend(other.sections.iter().map(|l| l + offset));
+        self.pages.extend(other.pages.iter().map(|l| l + offset));
+        self.hunks.extend(other.hunks.iter().map(|l| l + offset));
+        self.folds.extend(other.folds.iter().map(|f| Fold {
+            start: f.start + offset,
+            end: f.end + offset,
+        }));
+        self.entries.extend(other.entries.into_iter().map(|e| DirEntry {
+            line: e.line + offset,
+            ..e
+        }));
+        self.links.extend(other.links.into_iter().map(|l| Link {
+            line: l.line + offset,
+            ..l
+        }));
+        self.source_lines.extend(other.source_lines.into_iter().map(|s| SourceLine {
+            line: s.line + offset,
+            ..s
+        }));
+    }
+
+    /// 折叠起始行在 `collapsed` 中的区域，返回折叠后的文档及其每行对应的原始行号
+    ///
+    /// 折叠区域的首行末尾追加隐藏的行数；位于已折叠区域内的折叠不再单独处理。
+    pub fn fold(&self, collapsed: &[usize]) -> (Document, Vec<usize>) {
+        let len = self.lines.len();
+        let mut hidden = vec![false; len];
+        let mut summaries = Vec::new();
+        for fold in self.folds.iter().filter(|f| collapsed.contains(&f.start)) {
+            let end = fold.end.min(len);
+            if fold.start >= end || hidden[fold.start] {
+                continue;
+            }
+            hidden[fold.start + 1..end].fill(true);
+            summaries.push((fold.start, end - fold.start - 1));
+        }
+
+        // 原始行号 → 折叠后的行号（隐藏的行对应其后第一个可见行），多出一项对应文档末尾
+        let mut map = Vec::with_capacity(len + 1);
+        let mut source = Vec::new();
+        for (i, &is_hidden) in hidden.iter().enumerate() {
+            map.push(source.len());
+            if !is_hidden {
+                source.push(i);
+            }
+        }
+        map.push(source.len());
+        let lines = source.iter().map(|&i| self.lines[i].clone()).collect();
+        let mut doc = self.remap(lines, |line| hidden.get(line) == Some(&false), |line| map[line.min(len)]);
+        for (start, count) in summaries {
+            let summary = TextSpan::new(t!(" … （{} 行）", count), TextStyle::DIM);
+            doc.lines[map[start]].spans.push(summary);
+        }
+        (doc, source)
+    }
+
+    /// 把超过 `width` 列的行在词间折为多行，返回折行后的文档及其每行对应的原始行号
+    ///
+    /// 表格（可横向滚动）、代码块与图片所在的行保持原样。续行与首行缩进相同，
+    /// 并重复引用的竖线、对齐到列表标记之后。
+    pub fn wrap(&self, width: usize) -> (Document, Vec<usize>) {
+        let len = self.lines.len();
+        let mut map = Vec::with_capacity(len + 1);
+        let mut source = Vec::new();
+        let mut lines = Vec::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            map.push(lines.len());
+            let keep = self.table_at(i).is_some()
+                || self.code_block_at(i).is_some()
+                || self.images.iter().any(|img| (img.line..img.line + img.height as usize).contains(&i));
+            let rows = if keep { vec![line.clone()] } else { wrap_line(line, width) };
+            source.extend(std::iter::repeat_n(i, rows.len()));
+            lines.extend(rows);
+        }
+        map.push(lines.len());
+        let doc = self.remap(lines, |_| true, |line| map[line.min(len)]);
+        (doc, source)
+    }
+
+    /// 以新的行替换文档，标题、图片等位置按 `to` 换算，`keep` 为 false 的行上的项被丢弃
+    fn remap(&self, lines: Vec<RenderLine>, keep: impl Fn(usize) -> bool, to: impl Fn(usize) -> usize) -> Document {
+        Document {
+            lines,
+            metadata: self.metadata.clone(),
+            images: self
+                .images
+                .iter()
+                .filter(|img| (img.line..img.line + img.height as usize).all(&keep))
+                .map(|img| ImageBlock {
+                    line: to(img.line),
+                    ..img.clone()
+                })
+                .collect(),
+            headings: self
+                .headings
+                .iter()
+                .filter(|h| keep(h.line))
+                .map(|h| Heading {
+                    line: to(h.line),
+                    ..h.clone()
+                })
+                .collect(),
+            code_blocks: self
+                .code_blocks
+                .iter()
+                .filter(|b| keep(b.start))
+                .map(|b| CodeBlock {
+                    start: to(b.start),
+                    end: to(b.end),
+                    ..b.clone()
+                })
+                .collect(),
+            tables: self
+                .tables
+                .iter()
+                .filter(|t| keep(t.start))
+                .map(|t| TableBlock {
+                    start: to(t.start),
+                    end: to(t.end),
+                    ..t.clone()
+                })
+                .collect(),
+            rules: self.rules.iter().filter(|&&l| keep(l)).map(|&l| to(l)).collect(),
+            sections: self.sections.iter().filter(|&&l| keep(l)).map(|&l| to(l)).collect(),
+            pages: self.pages.iter().filter(|&&l| keep(l)).map(|&l| to(l)).collect(),
+            hunks: self.hunks.iter().filter(|&&l| keep(l)).map(|&l| to(l)).collect(),
+            folds: self
+                .folds
+                .iter()
+                .filter(|f| keep(f.start))
+                .map(|f| Fold {
+                    start: to(f.start),
+                    end: to(f.end),
+                })
+                .collect(),
+            listing: self.listing.map(&to),
+            entries: self
+                .entries
+                .iter()
+                .filter(|e| keep(e.line))
+                .map(|e| DirEntry {
+                    line: to(e.line),
+                    ..e.clone()
+                })
+                .collect(),
+            links: self
+                .links
+                .iter()
+                .filter(|l| keep(l.line))
+                .map(|l| Link {
+                    line: to(l.line),
+                    ..l.clone()
+                })
+                .collect(),
+            // 隐藏的行并入其后第一个可见行，该行自身的对应关系在后面，优先保留
+            source_lines: dedup_source_lines(self.source_lines.iter().map(|s| SourceLine {
+                line: to(s.line),
+                ..*s
+            })),
+        }
+    }
+
+    /// 按分隔线与一级标题把文档切分为幻灯片，返回每页的行范围（不含分隔线本身）
+    pub fn slides(&self) -> Vec<Range<usize>> {
+        let mut cuts: Vec<(usize, bool)> = self.rules.iter().map(|&l| (l, true)).collect();
+        cuts.extend(self.headings.iter().filter(|h| h.level == 1).map(|h| (h.line, false)));
+        cuts.sort();
+
+        let mut slides = Vec::new();
+        let mut start = 0;
+        for (line, is_rule) in cuts {
+            if line >= start {
+                slides.push(start..line);
+                start = if is_rule { line + 1 } else { line };
+            }
+        }
+        slides.push(start..self.lines.len());
+
+        slides.retain(|r| self.lines[r.clone()].iter().any(|l| !l.spans.is_empty()));
+        slides
+    }
+
+    /// 指定行所在的章节路径（H1 › H2 › H3）
+    pub fn breadcrumb(&self, line: usize) -> Vec<&Heading> {
+        let mut trail: Vec<&Heading> = Vec::new();
+        for heading in self.headings.iter().take_while(|h| h.line <= line) {
+            while trail.last().is_some_and(|t| t.level >= heading.level) {
+                trail.pop();
+            }
+            trail.push(heading);
+        }
+        trail
+    }
+}
+
+/// 可用宽度不足此值时不折行
+const MIN_WRAP_WIDTH: usize = 10;
+
+/// 将一行折为不超过 `width` 列的多行：优先在空白处断开，过长的词与 CJK 文本在字符间断开
+fn wrap_line(line: &RenderLine, width: usize) -> Vec<RenderLine> {
+    let avail = width.saturating_sub(line.indent as usize);
+    let total: usize = line.spans.iter().map(|s| s.text.width()).sum();
+    if total <= avail || avail < MIN_WRAP_WIDTH {
+        return vec![line.clone()];
+    }
+    let mut wrapper = LineWrapper::new(line, avail);
+    for span in &line.spans {
+        for piece in split_words(&span.text) {
+            wrapper.push(span, piece);
+        }
+    }
+    if wrapper.rows.is_empty() || wrapper.row_width > wrapper.prefix_width {
+        wrapper.finish_row();
+    }
+    wrapper.rows
+}
+
+/// 按空白、单词与宽字符切分，切分点即允许折行的位置
+fn split_words(text: &str) -> Vec<&str> {
+    let class = |c: char| match c {
+        c if c.is_whitespace() => 0,
+        c if c.width() == Some(2) => 2,
+        _ => 1,
+    };
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut prev = None;
+    for (i, c) in text.char_indices() {
+        let current = class(c);
+        if i > start && (prev != Some(current) || current == 2) {
+            pieces.push(&text[start..i]);
+            start = i;
+        }
+        prev = Some(current);
+    }
+    if start < text.len() {
+        pieces.push(&text[start..]);
+    }
+    pieces
+}
+
+struct LineWrapper {
+    rows: Vec<RenderLine>,
+    indent: u16,
+    avail: usize,
+    /// 续行开头重复的片段：引用竖线与列表标记宽度的空白
+    prefix: Vec<TextSpan>,
+    prefix_width: usize,
+    row: Vec<TextSpan>,
+    row_width: usize,
+}
+
+impl LineWrapper {
+    fn new(line: &RenderLine, avail: usize) -> Self {
+        let gutter = line.spans.iter().take_while(|s| s.text == "│ ").count();
+        let mut prefix = line.spans[..gutter].to_vec();
+        // 列表标记（`• `、`1. `、`☐ ` 等不含文字的短片段）之后对齐
+        if let Some(marker) = line.spans.get(gutter)
+            && marker.text.ends_with(' ')
+            && marker.text.width() <= 4
+            && !marker.text.chars().any(char::is_alphabetic)
+        {
+            prefix.push(TextSpan::new(" ".repeat(marker.text.width()), TextStyle::NONE));
+        }
+        let mut prefix_width: usize = prefix.iter().map(|s| s.text.width()).sum();
+        if prefix_width * 2 > avail {
+            prefix.clear();
+            prefix_width = 0;
+        }
+        Self {
+            rows: Vec::new(),
+            indent: line.indent,
+            avail,
+            prefix,
+            prefix_width,
+            row: Vec::new(),
+            row_width: 0,
+        }
+    }
+
+    /// 行首（首行为 0，续行为前缀之后）
+    fn row_start(&self) -> usize {
+        if self.rows.is_empty() { 0 } else { self.prefix_width }
+    }
+
+    fn push(&mut self, span: &TextSpan, piece: &str) {
+        let width = piece.width();
+        let blank = piece.trim().is_empty();
+        if self.row_width + width > self.avail && self.row_width > self.row_start() {
+            self.finish_row();
+            // 断开处的空白不带到下一行
+            if blank {
+                return;
+            }
+        }
+        if blank && self.row_width == self.row_start() && !self.rows.is_empty() {
+            return;
+        }
+        if self.row_width + width <= self.avail {
+            self.append(span, piece, width);
+            return;
+        }
+        // 整行也放不下的词逐字符断开
+        for (i, c) in piece.char_indices() {
+            let w = c.width().unwrap_or(0);
+            if self.row_width + w > self.avail && self.row_width > self.row_start() {
+                self.finish_row();
+            }
+            self.append(span, &piece[i..i + c.len_utf8()], w);
+        }
+    }
+
+    /// 追加文本，样式相同时并入上一个片段
+    fn append(&mut self, span: &TextSpan, text: &str, width: usize) {
+        match self.row.last_mut() {
+            Some(last) if last.style == span.style && last.link == span.link && last.color == span.color => {
+                last.text.push_str(text);
+            }
+            _ => self.row.push(TextSpan {
+                text: text.to_string(),
+                ..span.clone()
+            }),
+        }
+        self.row_width += width;
+    }
+
+    fn finish_row(&mut self) {
+        if let Some(last) = self.row.last_mut() {
+            last.text.truncate(last.text.trim_end().len());
+        }
+        let spans = std::mem::replace(&mut self.row, self.prefix.clone());
+        self.rows.push(RenderLine {
+            spans,
+            indent: self.indent,
+        });
+        self.row_width = self.prefix_width;
+    }
+}
+
+/// 同一渲染行有多项时只保留最后一项
+fn dedup_source_lines(lines: impl Iterator<Item = SourceLine>) -> Vec<SourceLine> {
+    let mut out: Vec<SourceLine> = Vec::new();
+    for line in lines {
+        match out.last_mut() {
+            Some(last) if last.line == line.line => *last = line,
+            _ => out.push(line),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc_with_levels(levels: &[u8]) -> Document {
+        let mut doc = Document::default();
+        for (i, &level) in levels.iter().enumerate() {
+            doc.lines.push(RenderLine {
+                spans: vec![TextSpan::new(format!("h{i}"), TextStyle::HEADING)],
+                indent: 0,
+            });
+            doc.headings.push(Heading {
+                level,
+                line: i,
+                text: format!("h{i}"),
+            });
+        }
+        doc
+    }
+
+    #[test]
+    fn test_number_headings() {
+        let mut doc = doc_with_levels(&[2, 3, 3, 4, 2]);
+        doc.number_headings();
+        let texts: Vec<&str> = doc.headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, ["1. h0", "1.1 h1", "1.2 h2", "1.2.1 h3", "2. h4"]);
+        assert_eq!(doc.lines[3].spans[0].text, "1.2.1 ");
+
+        // 跳级时相对最近的上级编号，不补 0
+        let mut doc = doc_with_levels(&[1, 4, 4, 2, 1]);
+        doc.number_headings();
+        let texts: Vec<&str> = doc.headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, ["1. h0", "1.1 h1", "1.2 h2", "1.3 h3", "2. h4"]);
+
+        // 超过 6 层的标题与第 6 层同级
+        let mut doc = doc_with_levels(&[1, 2, 3, 4, 5, 6, 8, 9]);
+        doc.number_headings();
+        assert_eq!(doc.headings[5].text, "1.1.1.1.1.1 h5");
+        assert_eq!(doc.headings[6].text, "1.1.1.1.1.2 h6");
+        assert_eq!(doc.headings[7].text, "1.1.1.1.1.3 h7");
+    }
+
+    #[test]
+    fn test_find() {
+        let mut doc = Document::default();
+        for spans in [vec!["Hello ", "wor", "ld"], vec!["say hello, HELLO"], vec![]] {
+            doc.lines.push(RenderLine {
+                spans: spans.into_iter().map(|s| TextSpan::new(s, TextStyle::NONE)).collect(),
+                indent: 0,
+            });
+        }
+        assert_eq!(doc.find("hello"), [(0, 0..5), (1, 4..9), (1, 11..16)]);
+        assert_eq!(doc.find("HELLO"), [(1, 11..16)]);
+        assert_eq!(doc.find("o w"), [(0, 4..7)]);
+        assert!(doc.find("").is_empty());
+    }
+
+    #[test]
+    fn test_slice() {
+        let mut doc = doc_with_levels(&[1, 2, 2, 3]);
+        doc.links = vec![
+            Link { line: 0, url: "a".into() },
+            Link { line: 2, url: "b".into() },
+        ];
+        let part = doc.slice(1..3);
+        assert_eq!(part.lines.len(), 2);
+        let lines: Vec<usize> = part.headings.iter().map(|h| h.line).collect();
+        assert_eq!(lines, [0, 1]);
+        let urls: Vec<&str> = part.links_at(1).map(|l| l.url.as_str()).collect();
+        assert_eq!(urls, ["b"]);
+
+        doc.source_lines = vec![SourceLine { line: 0, source: 0 }, SourceLine { line: 2, source: 5 }];
+        assert_eq!(doc.source_line(1), Some(0));
+        let part = doc.slice(1..4);
+        assert_eq!(part.source_lines, [SourceLine { line: 0, source: 0 }, SourceLine { line: 1, source: 5 }]);
+        assert_eq!(part.source_line(2), Some(5));
+        assert_eq!(doc.slice(3..100).lines.len(), 1);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut doc = doc_with_levels(&[1, 2]);
+        let mut other = doc_with_levels(&[1]);
+        other.rules.push(0);
+        doc.append(other);
+        let lines: Vec<usize> = doc.headings.iter().map(|h| h.line).collect();
+        assert_eq!(lines, [0, 1, 2]);
+        assert_eq!(doc.rules, [2]);
+    }
+
+    #[test]
+    fn test_fold() {
+        let mut doc = doc_with_levels(&[1, 2, 3, 2, 1]);
+        doc.folds = vec![Fold { start: 0, end: 4 }, Fold { start: 1, end: 3 }];
+        let (folded, source) = doc.fold(&[1]);
+        assert_eq!(source, [0, 1, 3, 4]);
+        assert_eq!(folded.lines[1].spans.last().unwrap().text, " … （1 行）");
+        assert_eq!(folded.folds, [Fold { start: 0, end: 3 }, Fold { start: 1, end: 2 }]);
+        let lines: Vec<usize> = folded.headings.iter().map(|h| h.line).collect();
+        assert_eq!(lines, [0, 1, 2, 3]);
+
+        // 外层折叠后内层不再单独处理
+        let (folded, source) = doc.fold(&[0, 1]);
+        assert_eq!(source, [0, 4]);
+        assert_eq!(folded.lines[0].spans.last().unwrap().text, " … （3 行）");
+    }
+
+    #[test]
+    fn test_wrap() {
+        let mut doc = doc_with_levels(&[1]);
+        doc.lines.push(RenderLine {
+            spans: vec![
+                TextSpan::new("• ", TextStyle::NONE),
+                TextSpan::new("alpha beta ", TextStyle::NONE),
+                TextSpan::new("gamma", TextStyle::BOLD),
+                TextSpan::new(" 中文中文", TextStyle::NONE),
+            ],
+            indent: 2,
+        });
+        doc.lines.push(RenderLine::default());
+        doc.headings.push(Heading { level: 2, line: 2, text: "end".into() });
+        let (wrapped, source) = doc.wrap(14);
+        let lines: Vec<String> = wrapped
+            .lines
+            .iter()
+            .map(|l| {
+                let text: String = l.spans.iter().map(|s| s.text.as_str()).collect();
+                format!("{}{}", " ".repeat(l.indent as usize), text)
+            })
+            .collect();
+        assert_eq!(lines, ["h0", "  • alpha beta", "    gamma 中文", "    中文", ""]);
+        assert_eq!(source, [0, 1, 1, 1, 2]);
+        assert_eq!(wrapped.headings[1].line, 4);
+        assert_eq!(wrapped.lines[2].spans[1].style, TextStyle::BOLD);
+    }
+
+    #[test]
+    fn test_slides() {
+        let mut doc = doc_with_levels(&[1, 2, 1]);
+        doc.lines.push(RenderLine::default());
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new("───", TextStyle::DIM)],
+            indent: 0,
+        });
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new("tail", TextStyle::NONE)],
+            indent: 0,
+        });
+        doc.rules.push(4);
+        assert_eq!(doc.slides(), [0..2, 2..4, 5..6]);
+    }
+
+    #[test]
+    fn test_breadcrumb() {
+        let doc = doc_with_levels(&[1, 2, 3, 2]);
+        let trail = |line| -> Vec<u8> { doc.breadcrumb(line).iter().map(|h| h.level).collect() };
+        assert_eq!(trail(2), [1, 2, 3]);
+        assert_eq!(trail(3), [1, 2]);
+    }
 }
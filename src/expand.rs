@@ -0,0 +1,108 @@
+//! 展开命令行中的文件参数
+//!
+//! shell 没有展开的通配符（Windows，或加了引号的 `'docs/**/*.md'`）由 xcat 自己展开；
+//! `-r` 时目录展开为其中所有能识别格式的文件，依次加入多文件列表。
+
+use crate::remote;
+use std::fs;
+use std::path::{Path, PathBuf};
+use xcat::format::{self, FormatOptions};
+
+/// 展开文件参数，保持参数顺序；没有匹配的通配符与远程文件原样保留
+pub fn expand(files: Vec<String>, recursive: bool, options: &FormatOptions) -> Vec<String> {
+    let mut expanded = Vec::new();
+    for file in files {
+        let path = Path::new(&file);
+        if file == "-" || remote::parse_url(&file).is_some() || remote::parse_remote(&file).is_some() {
+            expanded.push(file);
+        } else if path.is_dir() && recursive {
+            let mut found = Vec::new();
+            walk(path, options, &mut found);
+            // 目录中没有可预览的文件时仍打开目录列表
+            if found.is_empty() {
+                expanded.push(file);
+            }
+            expanded.extend(found.iter().map(|p| p.to_string_lossy().into_owned()));
+        } else if !path.exists() && file.contains(['*', '?', '[']) {
+            let matched = glob_files(&file, recursive, options);
+            if matched.is_empty() {
+                expanded.push(file);
+            }
+            expanded.extend(matched.iter().map(|p| p.to_string_lossy().into_owned()));
+        } else {
+            expanded.push(file);
+        }
+    }
+    expanded
+}
+
+/// 按通配符匹配文件，`recursive` 时匹配到的目录也展开
+fn glob_files(pattern: &str, recursive: bool, options: &FormatOptions) -> Vec<PathBuf> {
+    // 与 shell 一致，`*` 不匹配以 `.` 开头的文件
+    let match_options = glob::MatchOptions {
+        require_literal_leading_dot: true,
+        ..Default::default()
+    };
+    let Ok(paths) = glob::glob_with(pattern, match_options) else {
+        return Vec::new();
+    };
+    let mut matched = Vec::new();
+    for path in paths.filter_map(Result::ok) {
+        if path.is_file() {
+            matched.push(path);
+        } else if recursive && path.is_dir() {
+            walk(&path, options, &mut matched);
+        }
+    }
+    matched
+}
+
+/// 递归收集目录中能识别格式的文件，按路径排序，跳过隐藏文件与目录
+fn walk(dir: &Path, options: &FormatOptions, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .map(|e| e.path())
+        .collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            walk(&path, options, found);
+        } else if path.is_file() && format::is_recognized(&path, options) {
+            found.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let dir = std::env::temp_dir().join(format!("xcat-expand-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        for name in ["b.md", "a.txt", "sub/c.md", "noext", ".git/d.md"] {
+            fs::write(dir.join(name), "x").unwrap();
+        }
+        let options = FormatOptions::default();
+        let names = |files: Vec<String>| -> Vec<String> {
+            files
+                .iter()
+                .map(|f| Path::new(f).strip_prefix(&dir).unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        let root = dir.to_string_lossy().into_owned();
+        assert_eq!(names(expand(vec![root.clone()], true, &options)), ["a.txt", "b.md", "sub/c.md"]);
+        let pattern = format!("{root}/**/*.md");
+        assert_eq!(names(expand(vec![pattern], false, &options)), ["b.md", "sub/c.md"]);
+        assert_eq!(expand(vec![root.clone()], false, &options), [root]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,283 @@
+//! 导出（`--export`）
+//!
+//! 将解析后的文档写成可以在终端之外分享的文件。样式取自 [`renderer::to_style`]，
+//! 与预览模式中的颜色一致：HTML 用 CSS 还原配色，ANSI 输出与预览模式绘制的转义序列相同，
+//! 可以直接 `cat` 回放或交给 `aha` 等工具转换。
+
+use crate::document::Document;
+use crate::renderer;
+use crate::snapshot;
+use crate::t;
+use base64::Engine;
+use ratatui::style::{Color, Modifier, Style};
+use std::fmt::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+/// 导出格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// 独立的 HTML 文件，内嵌 CSS 与图片
+    Html,
+    /// 带 ANSI 转义序列的文本
+    Ansi,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "html" => Ok(ExportFormat::Html),
+            "ansi" => Ok(ExportFormat::Ansi),
+            _ => Err(t!("无效的导出格式: {}（可选 html、ansi）", s)),
+        }
+    }
+}
+
+/// 终端背景与默认前景色
+const BACKGROUND: &str = "#1e1e1e";
+const FOREGROUND: &str = "#d4d4d4";
+
+/// 导出为独立的 HTML 文件，`docs` 为 (文件名, 文档)，多个文档依次排列
+pub fn to_html(docs: &[(&str, &Document)]) -> String {
+    let mut styles: Vec<Style> = Vec::new();
+    let mut body = String::new();
+
+    for (name, doc) in docs {
+        if docs.len() > 1 {
+            let _ = writeln!(body, "<h1 class=\"file\">{}</h1>", escape(name));
+        }
+        body.push_str("<pre>");
+        write_lines(&mut body, doc, &mut styles);
+        body.push_str("</pre>\n");
+    }
+
+    let title = docs.first().map_or("", |(name, _)| name);
+    let mut css = String::new();
+    for (i, style) in styles.iter().enumerate() {
+        let _ = writeln!(css, ".s{i} {{ {} }}", css_declarations(*style));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ background: {BACKGROUND}; color: {FOREGROUND}; margin: 0; padding: 1em 2em; }}
+pre {{ font-family: ui-monospace, "SF Mono", Menlo, Consolas, monospace; line-height: 1.4; margin: 0 0 2em; }}
+a {{ color: inherit; }}
+img {{ display: block; max-width: 100%; }}
+h1.file {{ font: bold 1em sans-serif; color: #888; border-bottom: 1px solid #444; }}
+{css}</style>
+</head>
+<body>
+{body}</body>
+</html>
+"#,
+        title = escape(title),
+    )
+}
+
+/// 导出为 ANSI 文本，按最宽的行渲染以免截断
+pub fn to_ansi(docs: &[&Document]) -> String {
+    docs.iter()
+        .map(|doc| snapshot::render_ansi(doc, snapshot::fit_width(doc)))
+        .collect()
+}
+
+fn write_lines(out: &mut String, doc: &Document, styles: &mut Vec<Style>) {
+    let mut line = 0;
+    while line < doc.lines.len() {
+        // 图片占据的行整体替换为 <img>，读取失败时保留占位文本
+        let image = doc.images.iter().find(|img| img.line == line);
+        if let Some(img) = image
+            && let Some(uri) = data_uri(&img.path)
+        {
+            let _ = writeln!(out, "<img src=\"{uri}\" alt=\"{}\">", escape(&img.alt));
+            line += (img.height as usize).max(1);
+            continue;
+        }
+
+        let render_line = &doc.lines[line];
+        out.push_str(&" ".repeat(render_line.indent as usize));
+        for span in &render_line.spans {
+            let style = renderer::span_style(span);
+            let text = escape(&span.text);
+            let text = if style == Style::default() {
+                text
+            } else {
+                let class = match styles.iter().position(|s| *s == style) {
+                    Some(i) => i,
+                    None => {
+                        styles.push(style);
+                        styles.len() - 1
+                    }
+                };
+                format!("<span class=\"s{class}\">{text}</span>")
+            };
+            match &span.link {
+                Some(url) => {
+                    let _ = write!(out, "<a href=\"{}\">{text}</a>", escape(url));
+                }
+                None => out.push_str(&text),
+            }
+        }
+        out.push('\n');
+        line += 1;
+    }
+}
+
+/// 样式对应的 CSS 声明
+fn css_declarations(style: Style) -> String {
+    let mut decls = Vec::new();
+    if let Some(fg) = style.fg.and_then(css_color) {
+        decls.push(format!("color: {fg};"));
+    }
+    if let Some(bg) = style.bg.and_then(css_color) {
+        decls.push(format!("background: {bg};"));
+    }
+    let modifiers = style.add_modifier;
+    if modifiers.contains(Modifier::BOLD) {
+        decls.push("font-weight: bold;".into());
+    }
+    if modifiers.contains(Modifier::ITALIC) {
+        decls.push("font-style: italic;".into());
+    }
+    if modifiers.contains(Modifier::DIM) {
+        decls.push("opacity: 0.6;".into());
+    }
+    let mut lines = Vec::new();
+    if modifiers.contains(Modifier::UNDERLINED) {
+        lines.push("underline");
+    }
+    if modifiers.contains(Modifier::CROSSED_OUT) {
+        lines.push("line-through");
+    }
+    if !lines.is_empty() {
+        decls.push(format!("text-decoration: {};", lines.join(" ")));
+    }
+    decls.join(" ")
+}
+
+/// 终端颜色对应的 CSS 颜色（采用 xterm 默认调色板）
+fn css_color(color: Color) -> Option<String> {
+    const PALETTE: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x31, 0x31),
+        (0x0d, 0xbc, 0x79),
+        (0xe5, 0xe5, 0x10),
+        (0x24, 0x72, 0xc8),
+        (0xbc, 0x3f, 0xbc),
+        (0x11, 0xa8, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x66, 0x66, 0x66),
+        (0xf1, 0x4c, 0x4c),
+        (0x23, 0xd1, 0x8b),
+        (0xf5, 0xf5, 0x43),
+        (0x3b, 0x8e, 0xea),
+        (0xd6, 0x70, 0xd6),
+        (0x29, 0xb8, 0xdb),
+        (0xff, 0xff, 0xff),
+    ];
+    let index = match color {
+        Color::Reset => return None,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Rgb(r, g, b) => return Some(format!("#{r:02x}{g:02x}{b:02x}")),
+        Color::Indexed(i) => i,
+    };
+    let (r, g, b) = match index {
+        0..16 => PALETTE[index as usize],
+        // 6×6×6 色立方
+        16..232 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let n = index - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        // 灰阶
+        _ => {
+            let v = 8 + (index - 232) * 10;
+            (v, v, v)
+        }
+    };
+    Some(format!("#{r:02x}{g:02x}{b:02x}"))
+}
+
+/// 读取图片并编码为 data URI
+fn data_uri(path: &Path) -> Option<String> {
+    let data = std::fs::read(path).ok()?;
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    let mime = match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    };
+    let encoded = base64::engine::general_purpose::STANDARD.encode(data);
+    Some(format!("data:{mime};base64,{encoded}"))
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{RenderLine, TextSpan, TextStyle};
+
+    #[test]
+    fn test_html() {
+        let mut link = TextSpan::new("a<b", TextStyle::LINK);
+        link.link = Some("https://example.com/?a=1&b=2".into());
+        let doc = Document {
+            lines: vec![
+                RenderLine {
+                    spans: vec![TextSpan::new("标题", TextStyle::HEADING)],
+                    indent: 0,
+                },
+                RenderLine {
+                    spans: vec![link],
+                    indent: 2,
+                },
+            ],
+            ..Default::default()
+        };
+        let html = to_html(&[("a.md", &doc)]);
+        assert!(html.contains("<span class=\"s0\">标题</span>\n"));
+        assert!(html.contains(".s0 { color: #11a8cd; }"));
+        assert!(html.contains(
+            "  <a href=\"https://example.com/?a=1&amp;b=2\"><span class=\"s1\">a&lt;b</span></a>\n"
+        ));
+    }
+}
@@ -0,0 +1,389 @@
+//! 压缩包列表
+//!
+//! ZIP、tar 与 7z 显示为文件列表（路径、大小、压缩后大小、修改时间），预览模式中在某一项上按回车
+//! 将其解压到内存，再写入临时目录交给常规的格式检测与解析流程打开。
+//! 列表中每项的路径为 `压缩包路径/成员路径`，由 [`split_member`] 拆分。
+//! 7z 由 [`sevenz`] 解析，支持不压缩、LZMA、LZMA2 与 Deflate 编码的数据块。
+
+use crate::document::*;
+use crate::format::dir::{human_age, human_size};
+use crate::format::magic::{self, ArchiveFormat as Kind, DetectedFormat};
+use crate::format::{FileFormat, FormatError, sevenz, table};
+use crate::t;
+use pulldown_cmark::Alignment;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+use zip::ZipArchive;
+
+/// 解压出的单个文件大小上限，防止压缩炸弹占满内存或临时目录
+pub const MAX_EXTRACTED_SIZE: u64 = 100 * 1024 * 1024;
+
+pub struct ArchiveFormat;
+
+impl FileFormat for ArchiveFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let members = list(path).map_err(|e| FormatError::Parse(t!("压缩包读取失败: {}", e)))?;
+        Ok(listing(path, &members))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["zip", "tar", "7z"]
+    }
+
+    fn name(&self) -> &'static str {
+        "archive"
+    }
+}
+
+/// 压缩包中的一个文件
+#[derive(Debug)]
+struct Member {
+    name: String,
+    size: u64,
+    /// 压缩后的大小，tar 不压缩、7z 多个文件固实压缩在一起时为 None
+    compressed: Option<u64>,
+    modified: Option<SystemTime>,
+}
+
+/// 按签名判断压缩包格式，无法识别时按 ZIP 处理
+fn kind(path: &Path) -> io::Result<Kind> {
+    let mut header = [0u8; 512];
+    let n = File::open(path)?.read(&mut header)?;
+    Ok(match magic::detect_by_magic(&header[..n]) {
+        Some(DetectedFormat::Archive(kind)) => kind,
+        _ => Kind::Zip,
+    })
+}
+
+fn list(path: &Path) -> io::Result<Vec<Member>> {
+    let mut members = Vec::new();
+    match kind(path)? {
+        Kind::Tar => {
+            read_tar(BufReader::new(File::open(path)?), |member, _| {
+                members.push(member);
+                Ok(false)
+            })?;
+            return Ok(members);
+        }
+        Kind::SevenZ => {
+            let archive = sevenz::Archive::open(path)?;
+            members.extend(archive.entries.into_iter().filter(|e| !e.is_dir).map(|e| Member {
+                name: e.name,
+                size: e.size,
+                compressed: e.packed,
+                modified: e.modified,
+            }));
+            return Ok(members);
+        }
+        Kind::Zip => {}
+    }
+
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(io::Error::other)?;
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i).map_err(io::Error::other)?;
+        if file.is_dir() {
+            continue;
+        }
+        members.push(Member {
+            name: file.name().to_string(),
+            size: file.size(),
+            compressed: Some(file.compressed_size()),
+            modified: file.last_modified().and_then(|t| {
+                let days = u64::try_from(days_from_civil(t.year().into(), t.month().into(), t.day().into())).ok()?;
+                let secs = days * 86400 + u64::from(t.hour()) * 3600 + u64::from(t.minute()) * 60 + u64::from(t.second());
+                Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+            }),
+        });
+    }
+    Ok(members)
+}
+
+/// 排版文件列表，每个文件记录为可打开的项
+fn listing(path: &Path, members: &[Member]) -> Document {
+    let mut doc = Document::default();
+    let name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+    doc.headings.push(Heading {
+        level: 1,
+        line: 0,
+        text: name.clone(),
+    });
+    doc.lines.push(RenderLine {
+        spans: vec![
+            TextSpan::new(name, TextStyle::BOLD | TextStyle::HEADING),
+            TextSpan::new(format!("  {}", t!("{} 个文件", members.len())), TextStyle::DIM),
+        ],
+        indent: 0,
+    });
+    doc.lines.push(RenderLine::default());
+
+    if members.is_empty() {
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new(t!("（空压缩包）"), TextStyle::DIM)],
+            indent: 2,
+        });
+        return doc;
+    }
+
+    let now = SystemTime::now();
+    let mut rows = vec![vec![
+        t!("路径").to_string(),
+        t!("大小").to_string(),
+        t!("压缩后").to_string(),
+        t!("修改时间").to_string(),
+    ]];
+    rows.extend(members.iter().map(|m| {
+        vec![
+            m.name.clone(),
+            human_size(m.size),
+            m.compressed.map(human_size).unwrap_or_else(|| "-".into()),
+            m.modified.map(|t| human_age(now, t)).unwrap_or_default(),
+        ]
+    }));
+    // 表头与分隔线之后依次是各文件
+    let first = doc.lines.len() + 2;
+    let alignments = [Alignment::Left, Alignment::Right, Alignment::Right, Alignment::Left];
+    table::push_table(&mut doc, &rows, &alignments, 2);
+    for (i, member) in members.iter().enumerate() {
+        doc.entries.push(DirEntry {
+            line: first + i,
+            path: path.join(&member.name),
+        });
+    }
+    doc
+}
+
+/// 将 `压缩包路径/成员路径` 拆分为压缩包与成员名，路径中没有作为文件存在的上级时返回 None
+pub fn split_member(path: &Path) -> Option<(PathBuf, String)> {
+    let archive = path.ancestors().skip(1).find(|p| p.is_file())?;
+    let member = path.strip_prefix(archive).ok()?;
+    let member: Vec<String> = member.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+    Some((archive.to_path_buf(), member.join("/")))
+}
+
+/// 解压一个成员到内存
+pub fn read_member(archive: &Path, member: &str) -> io::Result<Vec<u8>> {
+    let mut data = None;
+    match kind(archive)? {
+        Kind::Tar => {
+            read_tar(BufReader::new(File::open(archive)?), |m, reader| {
+                if m.name != member {
+                    return Ok(false);
+                }
+                // 头部记录的大小不可信，不据此预分配
+                let mut buf = Vec::new();
+                copy_limited(reader, &mut buf)?;
+                data = Some(buf);
+                Ok(true)
+            })?;
+        }
+        Kind::SevenZ => {
+            let mut sevenz = sevenz::Archive::open(archive)?;
+            if let Some(i) = sevenz.entries.iter().position(|e| !e.is_dir && e.name == member) {
+                data = Some(sevenz.read(i)?);
+            }
+        }
+        Kind::Zip => {
+            let mut zip = ZipArchive::new(File::open(archive)?).map_err(io::Error::other)?;
+            let mut file = zip.by_name(member).map_err(io::Error::other)?;
+            let mut buf = Vec::new();
+            copy_limited(&mut file, &mut buf)?;
+            data = Some(buf);
+        }
+    }
+    data.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, member.to_string()))
+}
+
+/// 复制解压出的数据，超过 [`MAX_EXTRACTED_SIZE`] 时中止并报错
+pub fn copy_limited(reader: impl Read, writer: &mut impl Write) -> io::Result<u64> {
+    let n = io::copy(&mut reader.take(MAX_EXTRACTED_SIZE + 1), writer)?;
+    if n > MAX_EXTRACTED_SIZE {
+        return Err(io::Error::other(t!(
+            "解压后的文件过大（上限 {} MiB）",
+            MAX_EXTRACTED_SIZE / (1024 * 1024)
+        )));
+    }
+    Ok(n)
+}
+
+/// 解压成员并写入本进程的临时目录，返回文件路径
+///
+/// 格式检测与各解析器都以文件路径为输入，图片也在显示时才读取，因此需要落盘。
+pub fn extract(archive: &Path, member: &str) -> io::Result<PathBuf> {
+    let data = read_member(archive, member)?;
+    let name = member.rsplit('/').next().filter(|n| !n.is_empty()).unwrap_or("member");
+    let path = extracted_path(name)?;
+    fs::write(&path, data)?;
+    Ok(path)
+}
+
+/// 在本进程的临时目录中为解压出的文件分配路径，每个文件一个子目录以保留原文件名
+pub fn extracted_path(name: &str) -> io::Result<PathBuf> {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNT.fetch_add(1, Ordering::Relaxed);
+    let dir = temp_dir().join(n.to_string());
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(name))
+}
+
+/// 删除解压出的临时文件，退出前调用
+pub fn remove_extracted() {
+    let _ = fs::remove_dir_all(temp_dir());
+}
+
+fn temp_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("xcat-extract-{}", std::process::id()))
+}
+
+/// 依次读取 tar 的各个文件，`visit` 返回 true 时停止
+///
+/// 支持 ustar 的路径前缀、GNU 长文件名（`L`）与 pax 扩展头中的 `path`。
+fn read_tar<R: Read>(
+    mut reader: R,
+    mut visit: impl FnMut(Member, &mut dyn Read) -> io::Result<bool>,
+) -> io::Result<()> {
+    let mut header = [0u8; 512];
+    let mut long_name: Option<String> = None;
+    loop {
+        if let Err(e) = reader.read_exact(&mut header) {
+            // 缺少结尾的两个空块也视为结束
+            return if e.kind() == io::ErrorKind::UnexpectedEof { Ok(()) } else { Err(e) };
+        }
+        if header.iter().all(|&b| b == 0) {
+            return Ok(());
+        }
+        let size = octal(&header[124..136]);
+        let padding = (512 - size % 512) % 512;
+        let mut data = reader.by_ref().take(size);
+
+        match header[156] {
+            b'L' | b'x' => {
+                let mut buf = Vec::new();
+                data.read_to_end(&mut buf)?;
+                long_name = if header[156] == b'L' {
+                    Some(c_string(&buf))
+                } else {
+                    pax_path(&buf).or(long_name)
+                };
+            }
+            b'0' | 0 | b'7' => {
+                let mut name = c_string(&header[..100]);
+                let prefix = c_string(&header[345..500]);
+                if &header[257..262] == b"ustar" && !prefix.is_empty() {
+                    name = format!("{prefix}/{name}");
+                }
+                let member = Member {
+                    name: long_name.take().unwrap_or(name),
+                    size,
+                    compressed: None,
+                    modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(octal(&header[136..148]))),
+                };
+                if visit(member, &mut data)? {
+                    return Ok(());
+                }
+            }
+            _ => long_name = None,
+        }
+        io::copy(&mut data, &mut io::sink())?;
+        io::copy(&mut reader.by_ref().take(padding), &mut io::sink())?;
+    }
+}
+
+/// tar 头中的八进制数字段（最高位为 1 时为 GNU 的二进制大数）
+fn octal(field: &[u8]) -> u64 {
+    if field[0] & 0x80 != 0 {
+        return field[1..].iter().fold(0, |n, &b| (n << 8) | u64::from(b));
+    }
+    let text = c_string(field);
+    u64::from_str_radix(text.trim(), 8).unwrap_or(0)
+}
+
+fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// pax 扩展头中的 `path` 记录（每条为 `长度 键=值\n`）
+fn pax_path(data: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(data)
+        .lines()
+        .find_map(|record| record.split_once(' ')?.1.strip_prefix("path=").map(str::to_string))
+}
+
+/// 公历日期距 1970-01-01 的天数，更早的日期为负数
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tar_header(name: &str, size: usize, kind: u8) -> Vec<u8> {
+        let mut header = vec![0u8; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[124..135].copy_from_slice(format!("{size:011o}").as_bytes());
+        header[136..147].copy_from_slice(b"00000000012");
+        header[156] = kind;
+        header[257..262].copy_from_slice(b"ustar");
+        header
+    }
+
+    #[test]
+    fn test_read_tar() {
+        let mut tar = tar_header("docs/a.md", 5, b'0');
+        tar.extend(b"# abc");
+        tar.extend([0u8; 507]);
+        let long = "x/".repeat(60) + "b.txt";
+        tar.extend(tar_header("././@LongLink", long.len(), b'L'));
+        tar.extend(long.as_bytes());
+        tar.extend(vec![0u8; 512 - long.len() % 512]);
+        tar.extend(tar_header("short", 2, b'0'));
+        tar.extend(b"hi");
+        tar.extend([0u8; 510 + 1024]);
+
+        let mut members = Vec::new();
+        let mut contents = Vec::new();
+        read_tar(tar.as_slice(), |member, reader| {
+            let mut buf = String::new();
+            reader.read_to_string(&mut buf)?;
+            contents.push(buf);
+            members.push(member);
+            Ok(false)
+        })
+        .unwrap();
+        let names: Vec<&str> = members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["docs/a.md", long.as_str()]);
+        assert_eq!(contents, ["# abc", "hi"]);
+        assert_eq!(members[0].modified, Some(SystemTime::UNIX_EPOCH + Duration::from_secs(10)));
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+
+        let path = Path::new("/tmp/a.tar");
+        let doc = listing(path, &members);
+        assert_eq!(doc.entries[0].path, Path::new("/tmp/a.tar/docs/a.md"));
+        let line: String = doc.lines[doc.entries[0].line].spans.iter().map(|s| s.text.as_str()).collect();
+        assert!(line.starts_with("docs/a.md"));
+    }
+
+    #[test]
+    fn test_read_member_limit() {
+        // 头部声称约 8 GiB，实际只有几个字节，不应按头部大小预分配
+        let mut tar = tar_header("big.txt", 0o77777777777, b'0');
+        tar.extend(b"tiny");
+        let path = std::env::temp_dir().join(format!("xcat-tar-test-{}.tar", std::process::id()));
+        fs::write(&path, &tar).unwrap();
+        assert_eq!(read_member(&path, "big.txt").unwrap(), b"tiny");
+        fs::remove_file(&path).unwrap();
+
+        let mut sink = io::sink();
+        assert!(copy_limited(io::repeat(0).take(MAX_EXTRACTED_SIZE), &mut sink).is_ok());
+        assert!(copy_limited(io::repeat(0).take(MAX_EXTRACTED_SIZE + 1), &mut sink).is_err());
+    }
+}
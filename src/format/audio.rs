@@ -0,0 +1,233 @@
+//! 音频文件标签
+//!
+//! 通过 symphonia 读取 MP3（ID3）、FLAC / Ogg（Vorbis 注释）与 M4A（MP4 元数据）的标签，
+//! 排成对齐的字段表：标题、艺术家、专辑等，以及时长、平均码率与编码参数。
+//! 内嵌的封面图片写入临时文件，作为图片块显示在字段表下方。
+
+use crate::document::*;
+use crate::format::archive::extracted_path;
+use crate::format::table::pad_to_width;
+use crate::format::{FileFormat, FormatError};
+use crate::t;
+use std::fs::File;
+use std::path::Path;
+use symphonia::core::codecs::{self, CodecParameters};
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey, StandardVisualKey, Visual};
+use symphonia::core::probe::Hint;
+use unicode_width::UnicodeWidthStr;
+
+/// 封面图片占据的行数
+const COVER_ROWS: u16 = 12;
+
+pub struct AudioFormat;
+
+impl FileFormat for AudioFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let source = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+        let mut hint = Hint::new();
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            hint.with_extension(ext);
+        }
+        let mut probed = symphonia::default::get_probe()
+            .format(&hint, source, &FormatOptions::default(), &MetadataOptions::default())
+            .map_err(|e| FormatError::Parse(t!("音频解析失败: {}", e)))?;
+
+        // 容器内的标签优先，其次是容器之前的 ID3v2 标签
+        let mut revisions = Vec::new();
+        revisions.extend(probed.format.metadata().skip_to_latest().cloned());
+        revisions.extend(probed.metadata.get().and_then(|mut m| m.skip_to_latest().cloned()));
+        let params = probed.format.default_track().map(|t| t.codec_params.clone());
+        let size = std::fs::metadata(path)?.len();
+        Ok(assemble(&revisions, params.as_ref(), size))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["mp3", "flac", "ogg", "oga", "opus", "m4a", "m4b"]
+    }
+
+    fn name(&self) -> &'static str {
+        "audio"
+    }
+}
+
+/// 字段表与封面
+fn assemble(revisions: &[MetadataRevision], params: Option<&CodecParameters>, size: u64) -> Document {
+    let tag = |key: StandardTagKey| {
+        revisions
+            .iter()
+            .flat_map(MetadataRevision::tags)
+            .find(|t| t.std_key == Some(key))
+            .map(|t| t.value.to_string().trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let mut rows: Vec<(&str, String)> = [
+        (t!("标题"), StandardTagKey::TrackTitle),
+        (t!("艺术家"), StandardTagKey::Artist),
+        (t!("专辑"), StandardTagKey::Album),
+        (t!("专辑艺术家"), StandardTagKey::AlbumArtist),
+        (t!("日期"), StandardTagKey::Date),
+        (t!("音轨"), StandardTagKey::TrackNumber),
+        (t!("流派"), StandardTagKey::Genre),
+    ]
+    .into_iter()
+    .filter_map(|(label, key)| tag(key).map(|value| (label, value)))
+    .collect();
+
+    let cover = revisions
+        .iter()
+        .flat_map(MetadataRevision::visuals)
+        .max_by_key(|v| v.usage == Some(StandardVisualKey::FrontCover));
+    if let Some(params) = params {
+        let seconds = params
+            .time_base
+            .zip(params.n_frames)
+            .map(|(base, frames)| {
+                let time = base.calc_time(frames);
+                time.seconds as f64 + time.frac
+            })
+            .filter(|s| *s > 0.0);
+        if let Some(seconds) = seconds {
+            rows.push((t!("时长"), duration(seconds)));
+            // 封面不计入音频数据
+            let audio = size.saturating_sub(cover.map_or(0, |v| v.data.len() as u64));
+            rows.push((t!("码率"), format!("{} kbps", (audio as f64 * 8.0 / seconds / 1000.0).round())));
+        }
+        let mut format = vec![codec_name(params.codec).to_string()];
+        format.extend(params.sample_rate.map(|rate| format!("{rate} Hz")));
+        format.extend(params.bits_per_sample.map(|bits| format!("{bits} bit")));
+        format.extend(params.channels.map(|c| t!("{} 声道", c.count())));
+        rows.push((t!("编码"), format.join(" · ")));
+    }
+
+    let mut doc = Document::default();
+    let width = rows.iter().map(|(label, _)| label.width()).max().unwrap_or(0);
+    for (label, value) in rows {
+        let style = if label == t!("标题") {
+            doc.headings.push(Heading {
+                level: 1,
+                line: doc.lines.len(),
+                text: value.clone(),
+            });
+            TextStyle::BOLD
+        } else {
+            TextStyle::NONE
+        };
+        doc.lines.push(RenderLine {
+            spans: vec![
+                TextSpan::new(format!("{}  ", pad_to_width(label, width)), TextStyle::KEY),
+                TextSpan::new(value, style),
+            ],
+            indent: 0,
+        });
+    }
+    if let Some(cover) = cover {
+        push_cover(&mut doc, cover);
+    }
+    doc
+}
+
+/// 封面写入临时文件后作为图片块加入文档，写入失败时只显示占位文字
+fn push_cover(doc: &mut Document, cover: &Visual) {
+    let ext = match cover.media_type.as_str() {
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/bmp" => "bmp",
+        "image/webp" => "webp",
+        _ => "jpg",
+    };
+    let path = extracted_path(&format!("cover.{ext}"))
+        .and_then(|path| std::fs::write(&path, &cover.data).map(|()| path));
+    doc.lines.push(RenderLine::default());
+    let line = doc.lines.len();
+    doc.lines.push(RenderLine {
+        spans: vec![TextSpan::new(t!("[封面]"), TextStyle::DIM)],
+        indent: 0,
+    });
+    match path {
+        Ok(path) => {
+            doc.lines.extend((1..COVER_ROWS).map(|_| RenderLine::default()));
+            doc.images.push(ImageBlock {
+                line,
+                height: COVER_ROWS,
+                path,
+                alt: t!("封面").to_string(),
+            });
+        }
+        Err(e) => tracing::warn!("无法写入封面图片: {e}"),
+    }
+}
+
+/// 秒数显示为 `M:SS` 或 `H:MM:SS`
+fn duration(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    let (h, m, s) = (total / 3600, total % 3600 / 60, total % 60);
+    if h > 0 { format!("{h}:{m:02}:{s:02}") } else { format!("{m}:{s:02}") }
+}
+
+fn codec_name(codec: codecs::CodecType) -> &'static str {
+    match codec {
+        codecs::CODEC_TYPE_MP1 => "MP1",
+        codecs::CODEC_TYPE_MP2 => "MP2",
+        codecs::CODEC_TYPE_MP3 => "MP3",
+        codecs::CODEC_TYPE_AAC => "AAC",
+        codecs::CODEC_TYPE_ALAC => "ALAC",
+        codecs::CODEC_TYPE_FLAC => "FLAC",
+        codecs::CODEC_TYPE_VORBIS => "Vorbis",
+        codecs::CODEC_TYPE_OPUS => "Opus",
+        _ => "?",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// STREAMINFO 与 Vorbis 注释之后只有一个帧头的 FLAC
+    fn flac() -> Vec<u8> {
+        let mut data = b"fLaC".to_vec();
+        data.extend([0x00, 0x00, 0x00, 34]);
+        data.extend([0x10, 0x00, 0x10, 0x00, 0, 0, 0, 0, 0, 0]);
+        // 44100 Hz、双声道、16 位、共 441000 个采样（10 秒）
+        let packed: u64 = (44100 << 44) | (1 << 41) | (15 << 36) | 441000;
+        data.extend(packed.to_be_bytes());
+        data.extend([0; 16]);
+
+        let mut comments = Vec::new();
+        let vendor = b"xcat";
+        comments.extend((vendor.len() as u32).to_le_bytes());
+        comments.extend(vendor);
+        let fields = ["TITLE=晴天", "ARTIST=周杰伦", "ALBUM=叶惠美"];
+        comments.extend((fields.len() as u32).to_le_bytes());
+        for field in fields {
+            comments.extend((field.len() as u32).to_le_bytes());
+            comments.extend(field.as_bytes());
+        }
+        data.push(0x84);
+        data.extend(&(comments.len() as u32).to_be_bytes()[1..]);
+        data.extend(comments);
+        // 帧头：4096 个采样、44.1 kHz、双声道、16 位、第 0 帧，末字节为 CRC-8
+        data.extend([0xFF, 0xF8, 0xC9, 0x18, 0x00, 0xC2]);
+        data
+    }
+
+    #[test]
+    fn test_audio_tags() {
+        let path = std::env::temp_dir().join(format!("xcat-audio-{}.flac", std::process::id()));
+        std::fs::write(&path, flac()).unwrap();
+        let doc = AudioFormat.parse(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<String> = doc
+            .lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect())
+            .collect();
+        assert_eq!(lines[0], "标题    晴天");
+        assert_eq!(lines[2], "专辑    叶惠美");
+        assert_eq!(lines[3], "时长    0:10");
+        assert_eq!(lines[5], "编码    FLAC · 44100 Hz · 16 bit · 2 声道");
+        assert_eq!(doc.headings[0].text, "晴天");
+        assert_eq!(duration(3725.0), "1:02:05");
+    }
+}
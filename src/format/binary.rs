@@ -0,0 +1,479 @@
+//! 二进制序列化格式：MessagePack、CBOR 与 BSON
+//!
+//! 解码为 JSON 值后交给 [`json::render_value`](super::json::render_value) 排版。JSON 无法直接表示的值
+//! 按可读形式转换：字节串显示为十六进制，非字符串键转为字符串，BSON 的 ObjectId 与日期显示为文本，
+//! 非有限浮点数显示为 `NaN` / `Infinity`。多个首尾相接的 BSON 文档（如 mongodump 的输出）显示为数组。
+
+use crate::document::Document;
+use crate::format::json::render_value;
+use crate::format::xlsx::civil_from_days;
+use crate::format::{FileFormat, FormatError};
+use crate::t;
+use serde_json::{Map, Number, Value};
+use std::path::Path;
+
+/// 最大嵌套深度，防止恶意数据导致栈溢出
+const MAX_DEPTH: usize = 512;
+
+/// 字节串最多显示的字节数
+const MAX_BYTES_SHOWN: usize = 32;
+
+pub struct MsgpackFormat;
+
+impl FileFormat for MsgpackFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        decode_file(path, "MessagePack", |r| r.msgpack(0))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["msgpack", "mpk"]
+    }
+
+    fn name(&self) -> &'static str {
+        "msgpack"
+    }
+}
+
+pub struct CborFormat;
+
+impl FileFormat for CborFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        decode_file(path, "CBOR", |r| r.cbor(0))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cbor"]
+    }
+
+    fn name(&self) -> &'static str {
+        "cbor"
+    }
+}
+
+pub struct BsonFormat;
+
+impl FileFormat for BsonFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        decode_file(path, "BSON", |r| {
+            let mut docs = Vec::new();
+            while !r.at_end() {
+                docs.push(r.bson_document(false, 0)?);
+            }
+            Some(if docs.len() == 1 { docs.remove(0) } else { Value::Array(docs) })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bson"]
+    }
+
+    fn name(&self) -> &'static str {
+        "bson"
+    }
+}
+
+/// 读取整个文件并解码，出错时报告出错的字节位置
+fn decode_file(
+    path: &Path,
+    name: &str,
+    decode: impl FnOnce(&mut Reader) -> Option<Value>,
+) -> Result<Document, FormatError> {
+    let data = std::fs::read(path)?;
+    let value = decode_all(&data, decode)
+        .map_err(|pos| FormatError::Parse(t!("{} 解析失败: 第 {} 字节处数据无效", name, pos)))?;
+    Ok(render_value(&value))
+}
+
+/// 解码并要求数据恰好用完，失败时返回出错位置
+fn decode_all(data: &[u8], decode: impl FnOnce(&mut Reader) -> Option<Value>) -> Result<Value, usize> {
+    let mut reader = Reader { data, pos: 0 };
+    match decode(&mut reader) {
+        Some(value) if reader.at_end() => Ok(value),
+        _ => Err(reader.pos),
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn at_end(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(n)?)?;
+        self.pos += n;
+        Some(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.take(N)?.try_into().ok()
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    /// 大端无符号整数（MessagePack 与 CBOR）
+    fn uint_be(&mut self, n: usize) -> Option<u64> {
+        Some(self.take(n)?.iter().fold(0, |acc, &b| acc << 8 | u64::from(b)))
+    }
+
+    /// 长度不超过剩余字节数，避免按伪造的长度预分配
+    fn len(&self, n: u64) -> Option<usize> {
+        usize::try_from(n).ok().filter(|&n| n <= self.data.len() - self.pos)
+    }
+
+    fn text(&mut self, n: usize) -> Option<Value> {
+        Some(Value::String(String::from_utf8_lossy(self.take(n)?).into_owned()))
+    }
+
+    fn bytes(&mut self, n: usize) -> Option<Value> {
+        Some(bytes_value(self.take(n)?))
+    }
+
+    fn msgpack(&mut self, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let marker = self.u8()?;
+        let value = match marker {
+            0x00..=0x7f => Value::from(marker),
+            0x80..=0x8f => self.msgpack_map(usize::from(marker & 0x0f), depth)?,
+            0x90..=0x9f => self.msgpack_array(usize::from(marker & 0x0f), depth)?,
+            0xa0..=0xbf => self.text(usize::from(marker & 0x1f))?,
+            0xc0 => Value::Null,
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            0xc4..=0xc6 => {
+                let n = self.uint_be(1 << (marker - 0xc4))?;
+                let n = self.len(n)?;
+                self.bytes(n)?
+            }
+            // ext：类型号与数据，时间戳扩展（-1）显示为 ISO 时间
+            0xc7..=0xc9 | 0xd4..=0xd8 => {
+                let n = match marker {
+                    0xc7..=0xc9 => self.uint_be(1 << (marker - 0xc7))?,
+                    _ => 1 << (marker - 0xd4),
+                };
+                let n = self.len(n)?;
+                let kind = self.u8()? as i8;
+                let data = self.take(n)?;
+                msgpack_ext(kind, data)
+            }
+            0xca => float(f64::from(f32::from_be_bytes(self.array()?))),
+            0xcb => float(f64::from_be_bytes(self.array()?)),
+            0xcc..=0xcf => Value::from(self.uint_be(1 << (marker - 0xcc))?),
+            0xd0 => Value::from(self.u8()? as i8),
+            0xd1 => Value::from(i16::from_be_bytes(self.array()?)),
+            0xd2 => Value::from(i32::from_be_bytes(self.array()?)),
+            0xd3 => Value::from(i64::from_be_bytes(self.array()?)),
+            0xd9..=0xdb => {
+                let n = self.uint_be(1 << (marker - 0xd9))?;
+                let n = self.len(n)?;
+                self.text(n)?
+            }
+            0xdc | 0xdd => {
+                let n = self.uint_be(2 << (marker - 0xdc))?;
+                self.msgpack_array(self.len(n)?, depth)?
+            }
+            0xde | 0xdf => {
+                let n = self.uint_be(2 << (marker - 0xde))?;
+                self.msgpack_map(self.len(n)?, depth)?
+            }
+            0xe0..=0xff => Value::from(marker as i8),
+            0xc1 => return None,
+        };
+        Some(value)
+    }
+
+    fn msgpack_array(&mut self, n: usize, depth: usize) -> Option<Value> {
+        (0..n).map(|_| self.msgpack(depth + 1)).collect::<Option<Vec<_>>>().map(Value::Array)
+    }
+
+    fn msgpack_map(&mut self, n: usize, depth: usize) -> Option<Value> {
+        let mut map = Map::new();
+        for _ in 0..n {
+            let key = key_string(self.msgpack(depth + 1)?);
+            map.insert(key, self.msgpack(depth + 1)?);
+        }
+        Some(Value::Object(map))
+    }
+
+    fn cbor(&mut self, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let initial = self.u8()?;
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        // 不定长的字符串、数组与映射以 0xff 结束
+        let indefinite = info == 31 && (2..=5).contains(&major);
+        let arg = match info {
+            0..=23 => u64::from(info),
+            24..=27 => self.uint_be(1 << (info - 24))?,
+            31 if indefinite || major == 7 => 0,
+            _ => return None,
+        };
+        let value = match major {
+            0 => Value::from(arg),
+            1 => match i64::try_from(arg) {
+                Ok(n) => Value::from(-1 - n),
+                Err(_) => Value::String(format!("-{}", u128::from(arg) + 1)),
+            },
+            // 不定长字符串由若干同类型的定长片段组成
+            2 | 3 if indefinite => {
+                let mut chunks = Vec::new();
+                loop {
+                    let initial = self.u8()?;
+                    if initial == 0xff {
+                        break;
+                    }
+                    let (chunk_major, info) = (initial >> 5, initial & 0x1f);
+                    let n = match info {
+                        0..=23 => u64::from(info),
+                        24..=27 => self.uint_be(1 << (info - 24))?,
+                        _ => return None,
+                    };
+                    if chunk_major != major {
+                        return None;
+                    }
+                    let n = self.len(n)?;
+                    chunks.extend_from_slice(self.take(n)?);
+                }
+                if major == 3 {
+                    Value::String(String::from_utf8_lossy(&chunks).into_owned())
+                } else {
+                    bytes_value(&chunks)
+                }
+            }
+            2 => self.bytes(self.len(arg)?)?,
+            3 => self.text(self.len(arg)?)?,
+            4 => {
+                let mut items = Vec::new();
+                while if indefinite { self.data.get(self.pos) != Some(&0xff) } else { (items.len() as u64) < arg } {
+                    items.push(self.cbor(depth + 1)?);
+                }
+                self.pos += usize::from(indefinite);
+                Value::Array(items)
+            }
+            5 => {
+                let mut map = Map::new();
+                let mut count = 0;
+                while if indefinite { self.data.get(self.pos) != Some(&0xff) } else { count < arg } {
+                    let key = key_string(self.cbor(depth + 1)?);
+                    map.insert(key, self.cbor(depth + 1)?);
+                    count += 1;
+                }
+                self.pos += usize::from(indefinite);
+                Value::Object(map)
+            }
+            // 标签是语义标注，显示所标注的值；纪元时间（标签 1）转为 ISO 时间
+            6 => match (arg, self.cbor(depth + 1)?) {
+                (1, Value::Number(n)) => Value::String(iso_time(n.as_f64()? * 1000.0)),
+                (_, inner) => inner,
+            },
+            _ => match info {
+                20 => Value::Bool(false),
+                21 => Value::Bool(true),
+                22 | 23 => Value::Null,
+                25 => float(half(arg as u16)),
+                26 => float(f64::from(f32::from_bits(arg as u32))),
+                27 => float(f64::from_bits(arg)),
+                0..=19 | 24 => Value::String(format!("simple({arg})")),
+                _ => return None,
+            },
+        };
+        Some(value)
+    }
+
+    /// BSON 文档：int32 总长度、若干元素、结尾的 0；`array` 为真时忽略键名
+    fn bson_document(&mut self, array: bool, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let start = self.pos;
+        let size = usize::try_from(i32::from_le_bytes(self.array()?)).ok()?;
+        let end = start.checked_add(size).filter(|&end| end <= self.data.len())?;
+        let mut map = Map::new();
+        let mut items = Vec::new();
+        loop {
+            let kind = self.u8()?;
+            if kind == 0 {
+                break;
+            }
+            let name = self.cstring()?;
+            let value = self.bson_element(kind, depth)?;
+            if array {
+                items.push(value);
+            } else {
+                map.insert(name, value);
+            }
+        }
+        if self.pos != end {
+            return None;
+        }
+        Some(if array { Value::Array(items) } else { Value::Object(map) })
+    }
+
+    fn bson_element(&mut self, kind: u8, depth: usize) -> Option<Value> {
+        let value = match kind {
+            0x01 => float(f64::from_le_bytes(self.array()?)),
+            0x02 | 0x0d | 0x0e => self.bson_string()?,
+            0x03 => self.bson_document(false, depth + 1)?,
+            0x04 => self.bson_document(true, depth + 1)?,
+            0x05 => {
+                let n = usize::try_from(i32::from_le_bytes(self.array()?)).ok()?;
+                self.u8()?;
+                self.bytes(n)?
+            }
+            0x06 | 0x0a => Value::Null,
+            0x07 => Value::String(format!("ObjectId({})", hex(self.take(12)?))),
+            0x08 => Value::Bool(self.u8()? != 0),
+            0x09 => Value::String(iso_time(i64::from_le_bytes(self.array()?) as f64)),
+            0x0b => {
+                let pattern = self.cstring()?;
+                let flags = self.cstring()?;
+                Value::String(format!("/{pattern}/{flags}"))
+            }
+            // 带作用域的 JavaScript 代码：总长度、代码、作用域文档
+            0x0f => {
+                self.take(4)?;
+                let code = self.bson_string()?;
+                self.bson_document(false, depth + 1)?;
+                code
+            }
+            0x10 => Value::from(i32::from_le_bytes(self.array()?)),
+            0x11 => {
+                let increment = u32::from_le_bytes(self.array()?);
+                let seconds = u32::from_le_bytes(self.array()?);
+                Value::String(format!("Timestamp({seconds}, {increment})"))
+            }
+            0x12 => Value::from(i64::from_le_bytes(self.array()?)),
+            0x13 => Value::String(format!("Decimal128(0x{})", hex(self.take(16)?))),
+            0xff => Value::String("MinKey".into()),
+            0x7f => Value::String("MaxKey".into()),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn bson_string(&mut self) -> Option<Value> {
+        let n = usize::try_from(i32::from_le_bytes(self.array()?)).ok()?;
+        let bytes = self.take(n)?;
+        let (&0, text) = bytes.split_last()? else {
+            return None;
+        };
+        Some(Value::String(String::from_utf8_lossy(text).into_owned()))
+    }
+
+    fn cstring(&mut self) -> Option<String> {
+        let len = self.data[self.pos..].iter().position(|&b| b == 0)?;
+        let text = String::from_utf8_lossy(self.take(len)?).into_owned();
+        self.pos += 1;
+        Some(text)
+    }
+}
+
+/// MessagePack 扩展类型：时间戳显示为 ISO 时间，其余显示类型号与数据
+fn msgpack_ext(kind: i8, data: &[u8]) -> Value {
+    let timestamp = match (kind, data.len()) {
+        (-1, 4) => Some((u64::from(u32::from_be_bytes(data.try_into().unwrap())), 0)),
+        (-1, 8) => {
+            let v = u64::from_be_bytes(data.try_into().unwrap());
+            Some((v & 0x3_ffff_ffff, v >> 34))
+        }
+        (-1, 12) => {
+            let nanos = u32::from_be_bytes(data[..4].try_into().unwrap());
+            let secs = i64::from_be_bytes(data[4..].try_into().unwrap());
+            Some((secs as u64, u64::from(nanos)))
+        }
+        _ => None,
+    };
+    match timestamp {
+        Some((secs, nanos)) => Value::String(iso_time(secs as i64 as f64 * 1000.0 + nanos as f64 / 1e6)),
+        None => Value::String(format!("ext({kind}, 0x{})", hex(data))),
+    }
+}
+
+/// 毫秒时间戳转为 `YYYY-MM-DDTHH:MM:SS(.mmm)Z`
+fn iso_time(millis: f64) -> String {
+    let millis = millis.round() as i64;
+    let (secs, ms) = (millis.div_euclid(1000), millis.rem_euclid(1000));
+    let (days, secs) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    let (y, m, d) = civil_from_days(days);
+    let fraction = if ms == 0 { String::new() } else { format!(".{ms:03}") };
+    format!("{y:04}-{m:02}-{d:02}T{:02}:{:02}:{:02}{fraction}Z", secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+/// 字节串显示为 `0x` 开头的十六进制，过长时截断并标注总长度
+pub fn bytes_value(bytes: &[u8]) -> Value {
+    if bytes.len() <= MAX_BYTES_SHOWN {
+        Value::String(format!("0x{}", hex(bytes)))
+    } else {
+        Value::String(t!("0x{}…（{} 字节）", hex(&bytes[..MAX_BYTES_SHOWN]), bytes.len()))
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// 非有限浮点数无法用 JSON 数字表示，显示为字符串
+pub fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or_else(|| Value::String(f.to_string()), Value::Number)
+}
+
+/// IEEE 754 半精度浮点数
+fn half(bits: u16) -> f64 {
+    let exp = i32::from((bits >> 10) & 0x1f);
+    let mant = f64::from(bits & 0x3ff);
+    let value = match exp {
+        0 => mant * 2f64.powi(-24),
+        31 if mant == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (mant + 1024.0) * 2f64.powi(exp - 25),
+    };
+    if bits & 0x8000 != 0 { -value } else { value }
+}
+
+/// 映射的键：字符串原样使用，其他值使用其 JSON 文本
+fn key_string(key: Value) -> String {
+    match key {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        // {"a": [1, -2, true, nil], 7: bin(01 02), "t": 1.5}
+        let msgpack = [
+            0x83, 0xa1, b'a', 0x94, 0x01, 0xfe, 0xc3, 0xc0, 0x07, 0xc4, 0x02, 0x01, 0x02, 0xa1, b't', 0xcb, 0x3f, 0xf8,
+            0, 0, 0, 0, 0, 0,
+        ];
+        let value = decode_all(&msgpack, |r| r.msgpack(0)).unwrap();
+        assert_eq!(value.to_string(), r#"{"a":[1,-2,true,null],"7":"0x0102","t":1.5}"#);
+        assert_eq!(decode_all(&msgpack[..5], |r| r.msgpack(0)), Err(5));
+
+        // {_ "x": [_ 1, 2], "y": -500, "h": 1.0(half), "d": 1(epoch)}
+        let cbor = [
+            0xbf, 0x61, b'x', 0x9f, 0x01, 0x02, 0xff, 0x61, b'y', 0x39, 0x01, 0xf3, 0x61, b'h', 0xf9, 0x3c, 0x00, 0x61,
+            b'd', 0xc1, 0x01, 0xff,
+        ];
+        let value = decode_all(&cbor, |r| r.cbor(0)).unwrap();
+        assert_eq!(value.to_string(), r#"{"x":[1,2],"y":-500,"h":1.0,"d":"1970-01-01T00:00:01Z"}"#);
+
+        // {"n": int32 5, "s": "hi"}
+        let mut bson = vec![0, 0, 0, 0, 0x10, b'n', 0, 5, 0, 0, 0, 0x02, b's', 0, 3, 0, 0, 0, b'h', b'i', 0, 0];
+        bson[0] = bson.len() as u8;
+        let value = decode_all(&bson, |r| r.bson_document(false, 0)).unwrap();
+        assert_eq!(value.to_string(), r#"{"n":5,"s":"hi"}"#);
+    }
+}
@@ -0,0 +1,160 @@
+//! 压缩流解压
+//!
+//! gzip、bzip2、xz、zstd 压缩的单个文件（如 `notes.md.gz`、轮转后的日志）流式解压到临时文件，
+//! 再重新走签名与扩展名检测，按内层格式打开。gzip 由 flate2 解压，文件名优先取 gzip 头中记录的原名；
+//! 其余格式交给系统的 `bzip2`、`xz`、`zstd` 命令解压，文件名去掉压缩后缀。
+//! 解压后的大小受 [`MAX_EXTRACTED_SIZE`](crate::format::archive::MAX_EXTRACTED_SIZE) 限制，防止压缩炸弹占满临时目录。
+
+use crate::format::archive::{copy_limited, extracted_path};
+use crate::format::magic::{self, CompressedFormat, DetectedFormat};
+use crate::t;
+use flate2::bufread::MultiGzDecoder;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// 压缩后缀及去掉后缀时的替换：`a.md.gz` → `a.md`，`a.tgz` → `a.tar`
+const SUFFIXES: &[(&str, &str)] = &[
+    (".tgz", ".tar"),
+    (".svgz", ".svg"),
+    (".gz", ""),
+    (".tbz2", ".tar"),
+    (".tbz", ".tar"),
+    (".bz2", ""),
+    (".txz", ".tar"),
+    (".xz", ""),
+    (".tzst", ".tar"),
+    (".zst", ""),
+];
+
+/// 解压到本进程的临时目录，返回解压后的文件路径
+pub fn decompress(path: &Path) -> io::Result<PathBuf> {
+    let mut header = [0u8; 8];
+    let n = File::open(path)?.read(&mut header)?;
+    let program = match magic::detect_by_magic(&header[..n]) {
+        Some(DetectedFormat::Compressed(CompressedFormat::Gzip)) => return gunzip(path),
+        Some(DetectedFormat::Compressed(CompressedFormat::Bzip2)) => "bzip2",
+        Some(DetectedFormat::Compressed(CompressedFormat::Xz)) => "xz",
+        Some(DetectedFormat::Compressed(CompressedFormat::Zstd)) => "zstd",
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, t!("不是压缩文件"))),
+    };
+
+    let inner = extracted_path(&inner_name(path))?;
+    let mut child = Command::new(program)
+        .args(["-dc", "--"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::other(t!("解压此文件需要安装 {}", program)),
+            _ => e,
+        })?;
+    let stdout = child.stdout.take().expect("stdout 已设置为管道");
+    let written = write_limited(stdout, &inner);
+    if written.is_err() {
+        let _ = child.kill();
+    }
+    let output = child.wait_with_output()?;
+    written?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&inner);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(t!("解压失败: {}", stderr.trim())));
+    }
+    Ok(inner)
+}
+
+fn gunzip(path: &Path) -> io::Result<PathBuf> {
+    // 多个 gzip 成员首尾相接（如 `cat a.gz b.gz`）时依次解压
+    let decoder = MultiGzDecoder::new(BufReader::new(File::open(path)?));
+    let stored = decoder
+        .header()
+        .and_then(|h| h.filename())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .and_then(|name| name.rsplit(['/', '\\']).next().map(str::to_string))
+        .filter(|name| !name.is_empty());
+    let name = stored.unwrap_or_else(|| inner_name(path));
+
+    let inner = extracted_path(&name)?;
+    write_limited(decoder, &inner)?;
+    Ok(inner)
+}
+
+/// 将解压流写入 `path`，失败（包括超过大小上限）时删除写了一半的文件
+fn write_limited(reader: impl Read, path: &Path) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    let result = copy_limited(reader, &mut out).and_then(|_| out.flush());
+    if result.is_err() {
+        drop(out);
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+/// 按压缩文件名推断内层文件名，不认识的后缀原样保留
+fn inner_name(path: &Path) -> String {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    for (suffix, replacement) in SUFFIXES {
+        let Some(stem_len) = name.len().checked_sub(suffix.len()).filter(|&n| n > 0) else {
+            continue;
+        };
+        if name.get(stem_len..).is_some_and(|s| s.eq_ignore_ascii_case(suffix)) {
+            return format!("{}{replacement}", &name[..stem_len]);
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    #[test]
+    fn test_decompress() {
+        assert_eq!(inner_name(Path::new("/x/notes.md.gz")), "notes.md");
+        assert_eq!(inner_name(Path::new("logs.TGZ")), "logs.tar");
+        assert_eq!(inner_name(Path::new("app.log.zst")), "app.log");
+        assert_eq!(inner_name(Path::new("src.tbz2")), "src.tar");
+        assert_eq!(inner_name(Path::new(".xz")), ".xz");
+        assert_eq!(inner_name(Path::new("syslog.1")), "syslog.1");
+
+        let dir = std::env::temp_dir().join(format!("xcat-gz-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.md.gz");
+        let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        encoder.write_all(b"# Title\n").unwrap();
+        encoder.finish().unwrap();
+
+        let inner = decompress(&path).unwrap();
+        assert_eq!(inner.file_name().unwrap(), "notes.md");
+        assert_eq!(std::fs::read_to_string(&inner).unwrap(), "# Title\n");
+
+        // 其余格式依赖系统命令，未安装时跳过
+        for (program, ext) in [("bzip2", "bz2"), ("xz", "xz"), ("zstd", "zst")] {
+            let Ok(mut child) = Command::new(program)
+                .arg("-c")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+            else {
+                continue;
+            };
+            child.stdin.take().unwrap().write_all(b"# Title\n").unwrap();
+            let compressed = child.wait_with_output().unwrap().stdout;
+            let path = dir.join(format!("notes.md.{ext}"));
+            std::fs::write(&path, compressed).unwrap();
+            let inner = decompress(&path).unwrap();
+            assert_eq!(inner.file_name().unwrap(), "notes.md");
+            assert_eq!(std::fs::read_to_string(&inner).unwrap(), "# Title\n", "{program}");
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        crate::format::archive::remove_extracted();
+    }
+}
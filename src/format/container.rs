@@ -0,0 +1,29 @@
+//! ZIP 容器格式（DOCX、EPUB 等）的公共读取逻辑
+
+use std::io::{Read, Seek};
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+/// 读取容器中的文本文件，不存在时返回 None
+pub fn read_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<String>, ZipError> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut text = String::new();
+    entry.read_to_string(&mut text)?;
+    Ok(Some(text))
+}
+
+/// 解析容器内的 XML 文件，允许 DTD 声明
+pub fn parse_xml(text: &str) -> Result<roxmltree::Document<'_>, roxmltree::Error> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    roxmltree::Document::parse_with_options(text, options)
+}
@@ -0,0 +1,135 @@
+//! CSV / TSV 表格
+//!
+//! 按 RFC 4180 解析（引号内可含分隔符、换行与 `""` 转义），首行作为表头，
+//! 各列按显示宽度对齐，全为数字的列右对齐。超宽表格在预览模式中用 `h` / `l` 横向滚动。
+
+use crate::document::*;
+use crate::format::{table, FileFormat, FormatError};
+use pulldown_cmark::Alignment;
+use std::path::Path;
+
+pub struct CsvFormat;
+
+impl FileFormat for CsvFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let content = std::fs::read_to_string(path)?;
+        let is_tsv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("tsv") || ext.eq_ignore_ascii_case("tab"));
+        let delimiter = if is_tsv { '\t' } else { ',' };
+        Ok(parse_csv(&content, delimiter))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["csv", "tsv", "tab"]
+    }
+
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+}
+
+/// 解析 CSV 文本并排版为表格
+pub fn parse_csv(content: &str, delimiter: char) -> Document {
+    let rows = split_rows(content.strip_prefix('\u{feff}').unwrap_or(content), delimiter);
+    let mut doc = Document::default();
+    if rows.is_empty() {
+        return doc;
+    }
+
+    let num_cols = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let alignments: Vec<Alignment> = (0..num_cols)
+        .map(|c| {
+            let mut values = rows[1..].iter().filter_map(|r| r.get(c)).filter(|v| !v.is_empty());
+            let first = values.next();
+            if first.is_some() && first.into_iter().chain(values).all(|v| is_number(v)) {
+                Alignment::Right
+            } else {
+                Alignment::None
+            }
+        })
+        .collect();
+    table::push_table(&mut doc, &rows, &alignments, 0);
+    doc
+}
+
+/// 拆分记录与字段，字段内的换行替换为空格
+pub(crate) fn split_rows(content: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                '\r' => {}
+                '\n' => field.push(' '),
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    // 忽略空行
+    rows.retain(|r| !(r.len() == 1 && r[0].is_empty()));
+    rows
+}
+
+/// 数值（允许千分位逗号、百分号与货币符号）
+fn is_number(value: &str) -> bool {
+    let trimmed = value.trim().trim_end_matches('%').trim_start_matches(['$', '¥', '€', '£']);
+    let cleaned: String = trimmed.chars().filter(|&c| c != ',' && c != '_').collect();
+    !cleaned.is_empty() && cleaned.parse::<f64>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let content = "\u{feff}名称,数量,备注\r\n苹果,12,\"红, 甜\"\n香蕉,3,\"多\n行 \"\"引号\"\"\"\n\n梨,1.5\n";
+        assert_eq!(
+            split_rows(content.trim_start_matches('\u{feff}'), ','),
+            [
+                vec!["名称", "数量", "备注"],
+                vec!["苹果", "12", "红, 甜"],
+                vec!["香蕉", "3", "多 行 \"引号\""],
+                vec!["梨", "1.5"],
+            ]
+        );
+
+        let doc = parse_csv(content, ',');
+        let lines: Vec<String> = doc
+            .lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect::<String>().trim_end().to_string())
+            .collect();
+        assert_eq!(lines[0], "名称  数量  备注");
+        assert_eq!(lines[2], "苹果    12  红, 甜");
+        assert_eq!(lines[4], "梨     1.5");
+        assert!(doc.lines[0].spans[0].style.contains(TextStyle::HEADING));
+        assert_eq!(doc.tables.len(), 1);
+        assert_eq!(parse_csv("a\tb\n1\t2", '\t').lines.len(), 3);
+    }
+}
@@ -0,0 +1,216 @@
+//! git diff / log / show 输出与补丁文件着色
+//!
+//! 作为 git 的分页器（`core.pager = xcat`）时，标准输入是 git 的输出；`.diff` / `.patch` 文件
+//! 以及内容像 unified diff 的文件同样按此显示。提交与文件头记录为标题，可用状态栏路径与章节跳转
+//! 在文件间导航，hunk 头记录在 `hunks` 中，预览模式中用 `]c` / `[c` 跳转。
+
+use crate::document::*;
+use crate::format::text::clean_line;
+use crate::format::{FileFormat, FormatError};
+use std::path::Path;
+
+pub struct DiffFormat;
+
+impl FileFormat for DiffFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let bytes = std::fs::read(path)?;
+        Ok(parse_diff(&String::from_utf8_lossy(&bytes)))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["diff", "patch", "rej"]
+    }
+
+    fn name(&self) -> &'static str {
+        "diff"
+    }
+}
+
+/// 判断内容是否像 git 的 diff / log / show 输出
+pub fn looks_like_git(content: &str) -> bool {
+    let mut lines = content.lines().map(clean_line).filter(|l| !l.is_empty());
+    let Some(first) = lines.next() else {
+        return false;
+    };
+
+    if let Some(hash) = first.strip_prefix("commit ") {
+        return is_hash(hash.split_whitespace().next().unwrap_or(""));
+    }
+    if let Some(rest) = first.strip_prefix("From ") {
+        return is_hash(rest.split_whitespace().next().unwrap_or(""));
+    }
+    if first.starts_with("diff --git ") || first.starts_with("diff --cc ") {
+        return true;
+    }
+    // 普通 unified diff：--- 与 +++ 两行文件头
+    first.starts_with("--- ") && lines.next().is_some_and(|l| l.starts_with("+++ "))
+}
+
+fn is_hash(s: &str) -> bool {
+    s.len() >= 7 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// 解析 git 输出为带颜色的文档
+pub fn parse_diff(content: &str) -> Document {
+    let mut doc = Document::default();
+    // 位于文件头（diff --git 之后、第一个 @@ 之前）
+    let mut in_file_header = false;
+    // 当前提交的标题下标，提交说明的首行作为标题文本
+    let mut pending_subject: Option<usize> = None;
+
+    let mut lines = content.lines().peekable();
+    while let Some(raw) = lines.next() {
+        let line = clean_line(raw);
+        let index = doc.lines.len();
+
+        let spans = if let Some(rest) = line.strip_prefix("commit ") {
+            in_file_header = false;
+            let hash = rest.split_whitespace().next().unwrap_or("");
+            let short = &hash[..hash.len().min(7)];
+            doc.headings.push(Heading {
+                level: 1,
+                line: index,
+                text: short.to_string(),
+            });
+            pending_subject = Some(doc.headings.len() - 1);
+            vec![TextSpan::new(line, TextStyle::QUOTE | TextStyle::BOLD)]
+        } else if line.starts_with("diff --git ") || line.starts_with("diff --cc ") {
+            in_file_header = true;
+            pending_subject = None;
+            doc.headings.push(Heading {
+                level: 2,
+                line: index,
+                text: file_from_header(&line),
+            });
+            vec![TextSpan::new(line, TextStyle::BOLD)]
+        } else if line.starts_with("@@") {
+            in_file_header = false;
+            doc.hunks.push(index);
+            hunk_spans(&line)
+        } else if !in_file_header
+            && line.starts_with("--- ")
+            && lines.peek().is_some_and(|next| clean_line(next).starts_with("+++ "))
+        {
+            // 普通 unified diff 没有 diff --git 行，以 ---/+++ 文件头开始一个文件
+            in_file_header = true;
+            pending_subject = None;
+            let new = lines.peek().map(|next| file_from_unified(next)).unwrap_or_default();
+            doc.headings.push(Heading {
+                level: 2,
+                line: index,
+                text: new,
+            });
+            vec![TextSpan::new(line, TextStyle::BOLD)]
+        } else if in_file_header {
+            vec![TextSpan::new(line, TextStyle::BOLD)]
+        } else if line.starts_with('+') {
+            vec![TextSpan::new(line, TextStyle::ADDED)]
+        } else if line.starts_with('-') {
+            vec![TextSpan::new(line, TextStyle::REMOVED)]
+        } else if ["Author:", "AuthorDate:", "Commit:", "CommitDate:", "Date:", "Merge:"]
+            .iter()
+            .any(|p| line.starts_with(p))
+        {
+            vec![TextSpan::new(line, TextStyle::DIM)]
+        } else {
+            if let Some(i) = pending_subject {
+                let subject = line.trim();
+                if !subject.is_empty() {
+                    let heading = &mut doc.headings[i];
+                    heading.text = format!("{} {subject}", heading.text);
+                    pending_subject = None;
+                }
+            }
+            if line.is_empty() {
+                Vec::new()
+            } else {
+                vec![TextSpan::new(line, TextStyle::NONE)]
+            }
+        };
+
+        doc.lines.push(RenderLine { spans, indent: 0 });
+    }
+
+    doc
+}
+
+/// 从 `diff --git a/x b/y` 中取出文件路径
+fn file_from_header(line: &str) -> String {
+    let rest = line
+        .trim_start_matches("diff --git ")
+        .trim_start_matches("diff --cc ");
+    match rest.rsplit_once(" b/") {
+        Some((_, path)) => path.to_string(),
+        None => rest.trim_start_matches("a/").to_string(),
+    }
+}
+
+/// 从 `+++ b/path\t时间戳` 中取出文件路径（时间戳以制表符分隔，需在展开制表符之前处理）
+fn file_from_unified(raw: &str) -> String {
+    let line = clean_line(raw.split('\t').next().unwrap_or_default());
+    let path = line.trim_start_matches("+++ ");
+    path.strip_prefix("b/").unwrap_or(path).to_string()
+}
+
+/// hunk 头：`@@ -1,2 +1,3 @@` 部分淡化，其后的函数上下文保持普通样式
+fn hunk_spans(line: &str) -> Vec<TextSpan> {
+    let end = line[2..].find("@@").map(|i| i + 4).unwrap_or(line.len());
+    let (range, context) = line.split_at(end);
+    let mut spans = vec![TextSpan::new(range, TextStyle::DIM)];
+    if !context.is_empty() {
+        spans.push(TextSpan::new(context, TextStyle::NONE));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHOW: &str = "\
+commit 0123456789abcdef0123456789abcdef01234567
+Author: A <a@example.com>
+Date:   Mon Jan 1 00:00:00 2024 +0000
+
+    Fix parser
+
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,2 @@ fn main()
+-old
++new
+ same
+";
+
+    #[test]
+    fn test_detection() {
+        assert!(looks_like_git(SHOW));
+        assert!(looks_like_git("\x1b[33mcommit 0123456789abcdef\x1b[m\n"));
+        assert!(looks_like_git("--- a.txt\n+++ b.txt\n"));
+        assert!(!looks_like_git("# Title\n\ncommit message\n"));
+    }
+
+    #[test]
+    fn test_parse() {
+        let doc = parse_diff(SHOW);
+        let headings: Vec<_> = doc.headings.iter().map(|h| (h.level, h.text.as_str())).collect();
+        assert_eq!(headings, [(1, "0123456 Fix parser"), (2, "src/lib.rs")]);
+
+        let style = |i: usize| doc.lines[i].spans[0].style;
+        assert_eq!(style(8), TextStyle::BOLD);
+        assert_eq!(style(9), TextStyle::BOLD);
+        assert_eq!(doc.lines[10].spans[1].text, " fn main()");
+        assert_eq!(style(11), TextStyle::REMOVED);
+        assert_eq!(style(12), TextStyle::ADDED);
+        assert_eq!(doc.hunks, [10]);
+
+        let doc = parse_diff("--- a/x.txt\t2024-01-01\n+++ b/x.txt\t2024-01-02\n@@ -1 +1 @@\n-a\n+b\n");
+        let style = |i: usize| doc.lines[i].spans[0].style;
+        assert_eq!(doc.headings[0].text, "x.txt");
+        assert_eq!(style(1), TextStyle::BOLD);
+        assert_eq!(style(2), TextStyle::DIM);
+        assert_eq!(style(3), TextStyle::REMOVED);
+    }
+}
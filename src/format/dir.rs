@@ -0,0 +1,263 @@
+//! 目录预览
+//!
+//! 参数为目录时列出其中的文件，并像代码托管网站的目录页一样
+//! 在列表上方渲染 README。
+
+use crate::document::*;
+use crate::format::markdown::MarkdownFormat;
+use crate::format::{FileFormat, FormatError, FormatOptions, text};
+use crate::t;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use unicode_width::UnicodeWidthStr;
+
+/// 按优先级查找的 README 文件名（不区分大小写）
+const README_NAMES: &[&str] = &["readme.md", "readme.markdown", "readme.rst", "readme.txt", "readme"];
+
+/// 文件列表的排序方式，目录总是排在文件之前
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// 按名称
+    #[default]
+    Name,
+    /// 按大小，大的在前
+    Size,
+    /// 按修改时间，新的在前
+    Modified,
+}
+
+impl SortKey {
+    /// 循环切换到下一种排序方式
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Name => SortKey::Size,
+            SortKey::Size => SortKey::Modified,
+            SortKey::Modified => SortKey::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Name => t!("按名称排序"),
+            SortKey::Size => t!("按大小排序"),
+            SortKey::Modified => t!("按修改时间排序"),
+        }
+    }
+}
+
+struct Entry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+/// 解析目录：README（如果有）在上，文件列表在下
+pub fn parse_dir(path: &Path, options: &FormatOptions) -> Result<Document, FormatError> {
+    let mut entries: Vec<Entry> = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        let meta = entry.metadata()?;
+        entries.push(Entry {
+            name,
+            is_dir: meta.is_dir(),
+            size: meta.len(),
+            modified: meta.modified().ok(),
+        });
+    }
+    sort_entries(&mut entries, options.sort);
+
+    let mut doc = match find_readme(path) {
+        Some(readme) => {
+            let mut doc = parse_readme(&readme, options)?;
+            doc.lines.push(RenderLine::default());
+            doc
+        }
+        None => Document::default(),
+    };
+
+    let listing = doc.lines.len();
+    doc.listing = Some(listing);
+    let title = format!("{}/", path.display());
+    doc.headings.push(Heading {
+        level: 1,
+        line: listing,
+        text: title.clone(),
+    });
+    doc.lines.push(RenderLine {
+        spans: vec![
+            TextSpan::new(title, TextStyle::BOLD | TextStyle::HEADING),
+            TextSpan::new(format!("  {}", options.sort.label()), TextStyle::DIM),
+        ],
+        indent: 0,
+    });
+    doc.lines.push(RenderLine::default());
+
+    // 上级目录
+    let parent = fs::canonicalize(path)?.parent().map(Path::to_path_buf);
+    if let Some(parent) = parent {
+        doc.entries.push(DirEntry {
+            line: doc.lines.len(),
+            path: parent,
+        });
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new("../", TextStyle::NOTE | TextStyle::BOLD)],
+            indent: 2,
+        });
+    }
+
+    let name_width = entries
+        .iter()
+        .map(|e| e.name.width() + usize::from(e.is_dir))
+        .max()
+        .unwrap_or(0);
+    let now = SystemTime::now();
+    for entry in &entries {
+        let (name, style, size) = if entry.is_dir {
+            (format!("{}/", entry.name), TextStyle::NOTE | TextStyle::BOLD, String::new())
+        } else {
+            (entry.name.clone(), TextStyle::NONE, human_size(entry.size))
+        };
+        let padding = " ".repeat(name_width - name.width() + 2);
+        let age = entry.modified.map(|t| human_age(now, t)).unwrap_or_default();
+        doc.entries.push(DirEntry {
+            line: doc.lines.len(),
+            path: path.join(&entry.name),
+        });
+        doc.lines.push(RenderLine {
+            spans: vec![
+                TextSpan::new(name, style),
+                TextSpan::new(format!("{padding}{size:>9}  {age}"), TextStyle::DIM),
+            ],
+            indent: 2,
+        });
+    }
+
+    if entries.is_empty() {
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new(t!("（空目录）"), TextStyle::DIM)],
+            indent: 2,
+        });
+    }
+
+    Ok(doc)
+}
+
+fn sort_entries(entries: &mut [Entry], key: SortKey) {
+    entries.sort_by(|a, b| {
+        let by_key = match key {
+            SortKey::Name => std::cmp::Ordering::Equal,
+            SortKey::Size => b.size.cmp(&a.size),
+            SortKey::Modified => b.modified.cmp(&a.modified),
+        };
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then(by_key)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+fn find_readme(dir: &Path) -> Option<PathBuf> {
+    let files: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect();
+
+    README_NAMES.iter().find_map(|name| {
+        files
+            .iter()
+            .find(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.eq_ignore_ascii_case(name))
+            })
+            .cloned()
+    })
+}
+
+fn parse_readme(path: &Path, options: &FormatOptions) -> Result<Document, FormatError> {
+    let is_markdown = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"));
+
+    if is_markdown {
+        let format = MarkdownFormat {
+            options: options.markdown.clone(),
+        };
+        format.parse(path)
+    } else {
+        let content = fs::read_to_string(path)?;
+        Ok(text::to_document(&content))
+    }
+}
+
+/// 以 1024 为进制的可读文件大小
+pub(crate) fn human_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "K", "M", "G", "T"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// 修改时间距今的可读描述
+pub(crate) fn human_age(now: SystemTime, time: SystemTime) -> String {
+    let secs = now.duration_since(time).map_or(0, |d| d.as_secs());
+    match secs {
+        0..60 => t!("刚刚").into(),
+        60..3600 => t!("{} 分钟前", secs / 60),
+        3600..86400 => t!("{} 小时前", secs / 3600),
+        86400..2592000 => t!("{} 天前", secs / 86400),
+        2592000..31536000 => t!("{} 个月前", secs / 2592000),
+        _ => t!("{} 年前", secs / 31536000),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, is_dir: bool, size: u64) -> Entry {
+        Entry {
+            name: name.into(),
+            is_dir,
+            size,
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn test_sort_by_size_keeps_dirs_first() {
+        let mut entries = vec![
+            entry("a.txt", false, 10),
+            entry("src", true, 0),
+            entry("b.bin", false, 99),
+        ];
+        sort_entries(&mut entries, SortKey::Size);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["src", "b.bin", "a.txt"]);
+    }
+
+    #[test]
+    fn test_human_age() {
+        let now = SystemTime::now();
+        let hours = now - std::time::Duration::from_secs(7200);
+        assert_eq!(human_age(now, hours), "2 小时前");
+        assert_eq!(human_age(now, now), "刚刚");
+    }
+}
@@ -0,0 +1,403 @@
+//! DOCX（Office Open XML 文字处理文档）解析
+//!
+//! 从 ZIP 容器中读取 `word/document.xml`，将段落、标题、粗体 / 斜体、列表与表格映射为渲染行。
+//! 标题级别来自 `word/styles.xml` 中的样式名或大纲级别，列表编号方式来自 `word/numbering.xml`。
+
+use crate::document::*;
+use crate::format::markdown::BULLETS;
+use crate::format::container::{parse_xml, read_entry};
+use crate::format::{table, FileFormat, FormatError};
+use crate::t;
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use unicode_width::UnicodeWidthStr;
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+/// WordprocessingML 命名空间
+const W_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+
+pub struct DocxFormat;
+
+impl FileFormat for DocxFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let mut archive = ZipArchive::new(File::open(path)?).map_err(zip_error)?;
+        let document = read_entry(&mut archive, "word/document.xml")
+            .and_then(|entry| entry.ok_or(ZipError::FileNotFound))
+            .map_err(zip_error)?;
+        let styles = read_entry(&mut archive, "word/styles.xml").map_err(zip_error)?;
+        let numbering = read_entry(&mut archive, "word/numbering.xml").map_err(zip_error)?;
+        parse_docx(&document, styles.as_deref(), numbering.as_deref())
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["docx"]
+    }
+
+    fn name(&self) -> &'static str {
+        "docx"
+    }
+}
+
+fn zip_error(e: impl std::fmt::Display) -> FormatError {
+    FormatError::Parse(t!("DOCX 解析失败: {}", e))
+}
+
+/// 解析 `document.xml`，`styles` 与 `numbering` 为可选的样式与编号定义
+pub fn parse_docx(
+    document: &str,
+    styles: Option<&str>,
+    numbering: Option<&str>,
+) -> Result<Document, FormatError> {
+    let xml = parse_xml(document).map_err(zip_error)?;
+    let mut state = DocxState {
+        doc: Document::default(),
+        headings: match styles {
+            Some(styles) => heading_styles(&parse_xml(styles).map_err(zip_error)?),
+            None => HashMap::new(),
+        },
+        ordered: match numbering {
+            Some(numbering) => ordered_levels(&parse_xml(numbering).map_err(zip_error)?),
+            None => HashMap::new(),
+        },
+        counters: HashMap::new(),
+        in_list: false,
+    };
+    if let Some(body) = xml.root_element().children().find(|n| is_w(n, "body")) {
+        state.block(body);
+    }
+    while state.doc.lines.last().is_some_and(|l| l.spans.is_empty()) {
+        state.doc.lines.pop();
+    }
+    Ok(state.doc)
+}
+
+struct DocxState {
+    doc: Document,
+    /// 样式 ID → 标题级别
+    headings: HashMap<String, u8>,
+    /// (numId, 级别) → 是否为有序列表
+    ordered: HashMap<(String, u32), bool>,
+    /// 各列表每一级的当前编号
+    counters: HashMap<String, Vec<u64>>,
+    /// 上一个段落是列表条目，列表条目之间不插入空行
+    in_list: bool,
+}
+
+impl DocxState {
+    /// 处理块级元素：段落、表格及包裹它们的内容控件
+    fn block(&mut self, node: Node) {
+        for child in node.children().filter(Node::is_element) {
+            if is_w(&child, "p") {
+                self.paragraph(child);
+            } else if is_w(&child, "tbl") {
+                self.table(child);
+            } else if is_w(&child, "sdt") || is_w(&child, "sdtContent") || is_w(&child, "customXml") {
+                self.block(child);
+            }
+        }
+    }
+
+    fn paragraph(&mut self, p: Node) {
+        let props = child(p, "pPr");
+        let level = props.and_then(|props| self.heading_level(props));
+        let list = props.and_then(|props| child(props, "numPr")).and_then(|num| {
+            let id = val(child(num, "numId")?)?;
+            let ilvl = child(num, "ilvl").and_then(val).and_then(|v| v.parse().ok()).unwrap_or(0);
+            // numId 为 0 表示取消继承的编号
+            (id != "0").then(|| (id.to_string(), ilvl))
+        });
+
+        let base = if level.is_some() {
+            TextStyle::BOLD | TextStyle::HEADING
+        } else {
+            TextStyle::NONE
+        };
+        let mut lines = vec![Vec::new()];
+        runs(p, base, &mut lines);
+        if list.is_none() && lines.iter().all(|spans| spans.iter().all(|s| s.text.trim().is_empty())) {
+            return;
+        }
+
+        if list.is_none() && self.in_list {
+            self.blank();
+        }
+        let (indent, marker) = match &list {
+            Some((id, ilvl)) => (*ilvl as u16 * 2, self.marker(id, *ilvl)),
+            None => (0, String::new()),
+        };
+        if let Some(level) = level {
+            let text: String = lines.iter().flatten().map(|s| s.text.as_str()).collect();
+            self.doc.headings.push(Heading {
+                level,
+                line: self.doc.lines.len(),
+                text: text.trim().to_string(),
+            });
+        }
+
+        let hang = UnicodeWidthStr::width(marker.as_str()) as u16;
+        for (i, mut spans) in lines.into_iter().enumerate() {
+            let indent = if i == 0 {
+                if !marker.is_empty() {
+                    spans.insert(0, TextSpan::new(marker.clone(), base));
+                }
+                indent
+            } else {
+                indent + hang
+            };
+            self.doc.lines.push(RenderLine { spans, indent });
+        }
+
+        self.in_list = list.is_some();
+        if !self.in_list {
+            self.blank();
+        }
+    }
+
+    /// 段落样式对应的标题级别，样式中没有时使用段落的大纲级别
+    fn heading_level(&self, props: Node) -> Option<u8> {
+        child(props, "pStyle")
+            .and_then(val)
+            .and_then(|id| self.headings.get(id).copied())
+            .or_else(|| outline_level(props))
+    }
+
+    /// 列表条目的标记：有序列表为编号，无序列表按级别循环使用圆点
+    fn marker(&mut self, id: &str, ilvl: u32) -> String {
+        let levels = self.counters.entry(id.to_string()).or_default();
+        let depth = ilvl as usize;
+        levels.resize(depth + 1, 0);
+        levels[depth] += 1;
+        if self.ordered.get(&(id.to_string(), ilvl)).copied().unwrap_or(false) {
+            format!("{}. ", levels[depth])
+        } else {
+            format!("{} ", BULLETS[depth % BULLETS.len()])
+        }
+    }
+
+    fn table(&mut self, tbl: Node) {
+        let rows: Vec<Vec<String>> = tbl
+            .children()
+            .filter(|n| is_w(n, "tr"))
+            .map(|tr| tr.children().filter(|n| is_w(n, "tc")).map(cell_text).collect())
+            .collect();
+        if rows.iter().all(Vec::is_empty) {
+            return;
+        }
+        if self.in_list {
+            self.blank();
+        }
+        table::push_table(&mut self.doc, &rows, &[], 0);
+        self.in_list = false;
+        self.blank();
+    }
+
+    /// 追加一个空行，已有空行时不重复
+    fn blank(&mut self) {
+        if self.doc.lines.last().is_some_and(|l| !l.spans.is_empty()) {
+            self.doc.lines.push(RenderLine::default());
+        }
+    }
+}
+
+/// 收集段落中的文本，`w:br` 换行开始新的一行
+fn runs(node: Node, style: TextStyle, lines: &mut Vec<Vec<TextSpan>>) {
+    for child in node.children().filter(Node::is_element) {
+        if is_w(&child, "r") {
+            run(child, style, lines);
+        } else if is_w(&child, "hyperlink") {
+            runs(child, style | TextStyle::LINK, lines);
+        } else if !(is_w(&child, "pPr") || is_w(&child, "del") || is_w(&child, "moveFrom")) {
+            // 修订（w:ins）、域（w:fldSimple）、智能标签等包裹的文本
+            runs(child, style, lines);
+        }
+    }
+}
+
+fn run(r: Node, mut style: TextStyle, lines: &mut Vec<Vec<TextSpan>>) {
+    if let Some(props) = child(r, "rPr") {
+        if on_off(child(props, "b")) {
+            style.insert(TextStyle::BOLD);
+        }
+        if on_off(child(props, "i")) {
+            style.insert(TextStyle::ITALIC);
+        }
+    }
+    for child in r.children().filter(Node::is_element) {
+        let text = match child.tag_name().name() {
+            "t" => child.text().unwrap_or_default(),
+            "tab" => "    ",
+            "noBreakHyphen" => "-",
+            "br" | "cr" => {
+                lines.push(Vec::new());
+                continue;
+            }
+            _ => continue,
+        };
+        if text.is_empty() {
+            continue;
+        }
+        let spans = lines.last_mut().expect("至少有一行");
+        match spans.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(text),
+            _ => spans.push(TextSpan::new(text, style)),
+        }
+    }
+}
+
+/// 单元格内各段落的文本，以空格连接
+fn cell_text(tc: Node) -> String {
+    tc.descendants()
+        .filter(|n| is_w(n, "p"))
+        .map(|p| {
+            p.descendants()
+                .filter(|n| is_w(n, "t"))
+                .filter_map(|t| t.text())
+                .collect::<String>()
+        })
+        .filter(|text| !text.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 从 `styles.xml` 找出标题样式：名称为 `heading N` / `Title`，或带有大纲级别
+fn heading_styles(styles: &roxmltree::Document) -> HashMap<String, u8> {
+    let mut headings = HashMap::new();
+    for style in styles.root_element().children().filter(|n| is_w(n, "style")) {
+        let Some(id) = style.attribute((W_NS, "styleId")) else {
+            continue;
+        };
+        let name = child(style, "name").and_then(val).unwrap_or_default().to_lowercase();
+        let level = match name.strip_prefix("heading ") {
+            Some(n) => n.parse().ok(),
+            None if name == "title" => Some(1),
+            None => child(style, "pPr").and_then(outline_level),
+        };
+        // Word 有 9 级标题，文档模型只区分 6 级，更深的归入第 6 级
+        if let Some(level) = level.filter(|l| (1..=9).contains(l)) {
+            headings.insert(id.to_string(), level.min(6));
+        }
+    }
+    headings
+}
+
+/// `w:outlineLvl` 从 0 开始，9 表示正文
+fn outline_level(props: Node) -> Option<u8> {
+    let level: u8 = child(props, "outlineLvl").and_then(val)?.parse().ok()?;
+    (level < 9).then_some(level + 1)
+}
+
+/// 从 `numbering.xml` 找出各编号的每一级是否为有序列表
+fn ordered_levels(numbering: &roxmltree::Document) -> HashMap<(String, u32), bool> {
+    let root = numbering.root_element();
+    // abstractNumId → 各级别的编号格式
+    let mut abstracts: HashMap<&str, Vec<(u32, bool)>> = HashMap::new();
+    for abs in root.children().filter(|n| is_w(n, "abstractNum")) {
+        let Some(id) = abs.attribute((W_NS, "abstractNumId")) else {
+            continue;
+        };
+        let levels = abs
+            .children()
+            .filter(|n| is_w(n, "lvl"))
+            .filter_map(|lvl| {
+                let ilvl = lvl.attribute((W_NS, "ilvl"))?.parse().ok()?;
+                let format = child(lvl, "numFmt").and_then(val).unwrap_or("decimal");
+                Some((ilvl, !matches!(format, "bullet" | "none")))
+            })
+            .collect();
+        abstracts.insert(id, levels);
+    }
+
+    let mut ordered = HashMap::new();
+    for num in root.children().filter(|n| is_w(n, "num")) {
+        let Some(id) = num.attribute((W_NS, "numId")) else {
+            continue;
+        };
+        let Some(levels) = child(num, "abstractNumId").and_then(val).and_then(|a| abstracts.get(a)) else {
+            continue;
+        };
+        for &(ilvl, is_ordered) in levels {
+            ordered.insert((id.to_string(), ilvl), is_ordered);
+        }
+    }
+    ordered
+}
+
+fn is_w(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name && node.tag_name().namespace() == Some(W_NS)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| is_w(n, name))
+}
+
+fn val<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    node.attribute((W_NS, "val"))
+}
+
+/// 开关属性（如 `<w:b/>`）：存在且值不为 0 / false / off 时开启
+fn on_off(node: Option<Node>) -> bool {
+    node.is_some_and(|n| !matches!(val(n), Some("0" | "false" | "off")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &RenderLine) -> String {
+        line.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_docx() {
+        let document = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+            <w:p><w:pPr><w:pStyle w:val="1"/></w:pPr><w:r><w:t>标题</w:t></w:r></w:p>
+            <w:p><w:r><w:t xml:space="preserve">正文 </w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>粗体</w:t></w:r><w:r><w:rPr><w:i w:val="0"/></w:rPr><w:t>。</w:t></w:r></w:p>
+            <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>一</w:t></w:r></w:p>
+            <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>二</w:t></w:r></w:p>
+            <w:p><w:pPr><w:numPr><w:ilvl w:val="1"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>子项</w:t></w:r></w:p>
+            <w:tbl><w:tr><w:tc><w:p><w:r><w:t>名称</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>值</w:t></w:r></w:p></w:tc></w:tr>
+            <w:tr><w:tc><w:p><w:r><w:t>a</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>1</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+        </w:body></w:document>"#;
+        let styles = r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+            <w:style w:styleId="1"><w:name w:val="heading 1"/></w:style></w:styles>"#;
+        let numbering = r#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+            <w:abstractNum w:abstractNumId="0"><w:lvl w:ilvl="0"><w:numFmt w:val="decimal"/></w:lvl><w:lvl w:ilvl="1"><w:numFmt w:val="bullet"/></w:lvl></w:abstractNum>
+            <w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num></w:numbering>"#;
+
+        let doc = parse_docx(document, Some(styles), Some(numbering)).unwrap();
+        let lines: Vec<String> = doc.lines.iter().map(text).collect();
+        assert_eq!(
+            lines,
+            ["标题", "", "正文 粗体。", "", "1. 一", "2. 二", "◦ 子项", "", "名称  值", "────  ──", "a     1 "]
+        );
+        assert_eq!(doc.headings.len(), 1);
+        assert_eq!(doc.headings[0].level, 1);
+        assert!(doc.lines[0].spans[0].style.contains(TextStyle::HEADING));
+        assert!(doc.lines[2].spans[1].style.contains(TextStyle::BOLD));
+        assert!(!doc.lines[2].spans[2].style.contains(TextStyle::ITALIC));
+        assert_eq!(doc.lines[6].indent, 2);
+        assert_eq!(doc.tables.len(), 1);
+    }
+
+    #[test]
+    fn test_deep_heading() {
+        let document = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+            <w:p><w:pPr><w:pStyle w:val="1"/></w:pPr><w:r><w:t>一级</w:t></w:r></w:p>
+            <w:p><w:pPr><w:pStyle w:val="8"/></w:pPr><w:r><w:t>八级</w:t></w:r></w:p>
+            <w:p><w:pPr><w:pStyle w:val="outline"/></w:pPr><w:r><w:t>大纲九级</w:t></w:r></w:p>
+        </w:body></w:document>"#;
+        let styles = r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+            <w:style w:styleId="1"><w:name w:val="heading 1"/></w:style>
+            <w:style w:styleId="8"><w:name w:val="Heading 8"/></w:style>
+            <w:style w:styleId="outline"><w:name w:val="Custom"/><w:pPr><w:outlineLvl w:val="8"/></w:pPr></w:style></w:styles>"#;
+
+        let mut doc = parse_docx(document, Some(styles), None).unwrap();
+        let levels: Vec<u8> = doc.headings.iter().map(|h| h.level).collect();
+        assert_eq!(levels, [1, 6, 6]);
+        doc.number_headings();
+        assert_eq!(doc.headings[2].text, "1.2 大纲九级");
+    }
+}
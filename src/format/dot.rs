@@ -0,0 +1,974 @@
+//! Graphviz DOT 图
+//!
+//! 解析 DOT 语言的常用子集（节点、边、属性列表、子图），不依赖 graphviz，自行分层布局：
+//! 去环后按最长路径分层，跨层的边经过虚拟节点，重心法排序并与上下层对齐；
+//! 节点画成方框，边用制表符连线，有向图的箭头指向目标。子图展开为其中的节点，布局固定自上而下。
+
+use crate::document::*;
+use crate::format::{FileFormat, FormatError};
+use crate::t;
+use std::collections::HashMap;
+use std::path::Path;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// 同一层相邻节点之间的空列数
+const GAP: i64 = 2;
+/// 排序与对齐的迭代次数
+const SWEEPS: usize = 4;
+
+pub struct DotFormat;
+
+impl FileFormat for DotFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let content = std::fs::read_to_string(path)?;
+        parse_dot(&content)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dot", "gv"]
+    }
+
+    fn name(&self) -> &'static str {
+        "dot"
+    }
+}
+
+pub fn parse_dot(content: &str) -> Result<Document, FormatError> {
+    let tokens = tokenize(content).map_err(syntax_error)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+        graph: Graph::default(),
+    };
+    parser.graph().map_err(syntax_error)?;
+    Ok(render(&parser.graph))
+}
+
+fn syntax_error(line: usize) -> FormatError {
+    FormatError::Parse(t!("DOT 解析失败: 第 {} 行附近语法错误", line))
+}
+
+// === 词法与语法 ===
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// 未加引号的标识符或数字，可能是关键字
+    Id(String),
+    /// 带引号的字符串或 HTML 标签文本
+    Str(String),
+    /// `->` 或 `--`
+    Edge,
+    Punct(char),
+}
+
+/// 切分为带行号的记号，出错时返回行号
+fn tokenize(src: &str) -> Result<Vec<(Token, usize)>, usize> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    let mut line = 1;
+    let mut line_start = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                line += 1;
+                line_start = true;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            // 以 `#` 开头的行是 C 预处理输出
+            '#' if line_start => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+            }
+            '/' if chars.next_if_eq(&'/').is_some() => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' if chars.next_if_eq(&'*').is_some() => {
+                let mut prev = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if prev == '*' => break,
+                        Some(c) => {
+                            line += usize::from(c == '\n');
+                            prev = c;
+                        }
+                        None => return Err(line),
+                    }
+                }
+            }
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if chars.next_if_eq(&'"').is_some() => text.push('"'),
+                        Some('\\') if chars.next_if_eq(&'\n').is_some() => line += 1,
+                        Some(c) => {
+                            line += usize::from(c == '\n');
+                            text.push(c);
+                        }
+                        None => return Err(line),
+                    }
+                }
+                tokens.push((Token::Str(text), line));
+            }
+            // HTML 标签只保留其中的文本
+            '<' => {
+                let (mut depth, mut text, mut in_tag) = (1, String::new(), false);
+                while depth > 0 {
+                    match chars.next().ok_or(line)? {
+                        '<' => {
+                            depth += 1;
+                            in_tag = true;
+                        }
+                        '>' => {
+                            depth -= 1;
+                            in_tag = false;
+                        }
+                        c if !in_tag => {
+                            line += usize::from(c == '\n');
+                            text.push(c);
+                        }
+                        _ => {}
+                    }
+                }
+                tokens.push((Token::Str(text.trim().to_string()), line));
+            }
+            '-' if chars.next_if(|&c| c == '>' || c == '-').is_some() => tokens.push((Token::Edge, line)),
+            '{' | '}' | '[' | ']' | ';' | ',' | '=' | ':' | '+' => tokens.push((Token::Punct(c), line)),
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || !c.is_ascii() => {
+                let mut text = c.to_string();
+                while let Some(c) = chars.next_if(|&c| c.is_alphanumeric() || c == '_' || c == '.' || !c.is_ascii()) {
+                    text.push(c);
+                }
+                tokens.push((Token::Id(text), line));
+            }
+            _ => return Err(line),
+        }
+        line_start = false;
+    }
+    Ok(tokens)
+}
+
+#[derive(Default)]
+struct Graph {
+    name: Option<String>,
+    label: Option<String>,
+    directed: bool,
+    /// 各节点的标识与显示标签
+    nodes: Vec<(String, Option<String>)>,
+    ids: HashMap<String, usize>,
+    edges: Vec<Edge>,
+}
+
+struct Edge {
+    from: usize,
+    to: usize,
+    label: Option<String>,
+}
+
+impl Graph {
+    fn node(&mut self, id: String) -> usize {
+        let next = self.nodes.len();
+        *self.ids.entry(id.clone()).or_insert_with(|| {
+            self.nodes.push((id, None));
+            next
+        })
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    /// 子图嵌套深度，只取顶层的图标签
+    depth: usize,
+    graph: Graph,
+}
+
+type ParseResult<T> = Result<T, usize>;
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn error<T>(&self) -> ParseResult<T> {
+        let line = self.tokens.get(self.pos).or(self.tokens.last()).map_or(1, |(_, l)| *l);
+        Err(line)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let matched = self.peek() == Some(&Token::Punct(c));
+        self.pos += usize::from(matched);
+        matched
+    }
+
+    fn expect(&mut self, c: char) -> ParseResult<()> {
+        if self.eat(c) { Ok(()) } else { self.error() }
+    }
+
+    fn keyword(&self, offset: usize, word: &str) -> bool {
+        matches!(self.tokens.get(self.pos + offset), Some((Token::Id(s), _)) if s.eq_ignore_ascii_case(word))
+    }
+
+    /// 标识符、数字或字符串，字符串可用 `+` 连接
+    fn id(&mut self) -> ParseResult<String> {
+        let mut text = match self.peek() {
+            Some(Token::Id(s)) => s.clone(),
+            Some(Token::Str(s)) => s.clone(),
+            _ => return self.error(),
+        };
+        self.pos += 1;
+        while self.peek() == Some(&Token::Punct('+')) {
+            self.pos += 1;
+            match self.peek() {
+                Some(Token::Str(s)) => text.push_str(s),
+                _ => return self.error(),
+            }
+            self.pos += 1;
+        }
+        Ok(text)
+    }
+
+    fn graph(&mut self) -> ParseResult<()> {
+        if self.keyword(0, "strict") {
+            self.pos += 1;
+        }
+        if self.keyword(0, "digraph") {
+            self.graph.directed = true;
+        } else if !self.keyword(0, "graph") {
+            return self.error();
+        }
+        self.pos += 1;
+        if self.peek() != Some(&Token::Punct('{')) {
+            self.graph.name = Some(self.id()?);
+        }
+        self.expect('{')?;
+        self.stmt_list()?;
+        self.expect('}')
+    }
+
+    /// 语句列表，返回其中出现的节点（用作边两端的子图）
+    fn stmt_list(&mut self) -> ParseResult<Vec<usize>> {
+        let mut members = Vec::new();
+        while !matches!(self.peek(), Some(Token::Punct('}')) | None) {
+            self.stmt(&mut members)?;
+            let _ = self.eat(';') || self.eat(',');
+        }
+        Ok(members)
+    }
+
+    fn stmt(&mut self, members: &mut Vec<usize>) -> ParseResult<()> {
+        let next_is = |p: &Parser, c| p.tokens.get(p.pos + 1).map(|(t, _)| t) == Some(&Token::Punct(c));
+        if ["graph", "node", "edge"].iter().any(|w| self.keyword(0, w)) && next_is(self, '[') {
+            let is_graph = self.keyword(0, "graph");
+            self.pos += 1;
+            let attrs = self.attr_list()?;
+            if is_graph {
+                self.graph_label(&attrs);
+            }
+            return Ok(());
+        }
+        if next_is(self, '=') {
+            let key = self.id()?;
+            self.pos += 1;
+            let value = self.id()?;
+            self.graph_label(&[(key, value)]);
+            return Ok(());
+        }
+
+        let mut operands = vec![self.operand(members)?];
+        while self.peek() == Some(&Token::Edge) {
+            self.pos += 1;
+            operands.push(self.operand(members)?);
+        }
+        let label = attr(&self.attr_list()?, "label");
+        if operands.len() == 1 {
+            if let ([node], Some(label)) = (operands[0].as_slice(), label) {
+                self.graph.nodes[*node].1 = Some(label);
+            }
+            return Ok(());
+        }
+        for pair in operands.windows(2) {
+            for &from in &pair[0] {
+                for &to in &pair[1] {
+                    self.graph.edges.push(Edge { from, to, label: label.clone() });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn graph_label(&mut self, attrs: &[(String, String)]) {
+        if self.depth == 0
+            && let Some(label) = attr(attrs, "label")
+        {
+            self.graph.label = Some(label);
+        }
+    }
+
+    /// 节点或子图，返回其中的节点
+    fn operand(&mut self, members: &mut Vec<usize>) -> ParseResult<Vec<usize>> {
+        if self.keyword(0, "subgraph") || self.peek() == Some(&Token::Punct('{')) {
+            if self.keyword(0, "subgraph") {
+                self.pos += 1;
+                if self.peek() != Some(&Token::Punct('{')) {
+                    self.id()?;
+                }
+            }
+            self.expect('{')?;
+            self.depth += 1;
+            let inner = self.stmt_list()?;
+            self.depth -= 1;
+            self.expect('}')?;
+            members.extend(&inner);
+            return Ok(inner);
+        }
+        let id = self.id()?;
+        // 端口与方位（`node:port:n`）不影响布局
+        while self.eat(':') {
+            self.id()?;
+        }
+        let node = self.graph.node(id);
+        members.push(node);
+        Ok(vec![node])
+    }
+
+    fn attr_list(&mut self) -> ParseResult<Vec<(String, String)>> {
+        let mut attrs = Vec::new();
+        while self.eat('[') {
+            while !self.eat(']') {
+                let key = self.id()?;
+                let value = if self.eat('=') { self.id()? } else { "true".to_string() };
+                attrs.push((key, value));
+                let _ = self.eat(';') || self.eat(',');
+            }
+        }
+        Ok(attrs)
+    }
+}
+
+fn attr(attrs: &[(String, String)], key: &str) -> Option<String> {
+    attrs.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+}
+
+/// 标签中的转义：`\n`、`\l`、`\r` 换行，`\N` 为节点名，`\G` 为图名
+fn label_lines(label: &str, node: &str, graph: &str) -> Vec<String> {
+    let mut text = String::new();
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n' | 'l' | 'r')) => text.push('\n'),
+            ('\\', Some('N')) => text.push_str(node),
+            ('\\', Some('G')) => text.push_str(graph),
+            ('\\', Some(c)) => text.push(c),
+            (c, _) => {
+                text.push(c);
+                continue;
+            }
+        }
+        chars.next();
+    }
+    let lines: Vec<String> = text.trim_end_matches('\n').lines().map(str::to_string).collect();
+    if lines.is_empty() { vec![String::new()] } else { lines }
+}
+
+// === 布局 ===
+
+/// 布局中的节点：真实节点或长边经过的虚拟节点
+struct Vertex {
+    /// 真实节点的标签行，虚拟节点为空
+    lines: Vec<String>,
+    rank: usize,
+    width: i64,
+    left: i64,
+    preds: Vec<usize>,
+    succs: Vec<usize>,
+}
+
+impl Vertex {
+    fn new(lines: Vec<String>, rank: usize, width: i64) -> Self {
+        Self {
+            lines,
+            rank,
+            width,
+            left: 0,
+            preds: Vec::new(),
+            succs: Vec::new(),
+        }
+    }
+
+    fn is_dummy(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    fn height(&self) -> usize {
+        if self.is_dummy() { 0 } else { self.lines.len() + 2 }
+    }
+
+    fn center(&self) -> i64 {
+        self.left + self.width / 2
+    }
+}
+
+/// 一条边经过的节点（自上而下）及其方向
+struct Chain {
+    vertices: Vec<usize>,
+    /// 为去环而反向的边，箭头画在上端
+    reversed: bool,
+    label: Option<String>,
+    /// 连线在各节点处的列：两端为方框上的连接点，中间为虚拟节点的中心
+    xs: Vec<i64>,
+}
+
+/// 按标签与边绘制有向图（自上而下），供 Mermaid 流程图复用布局
+pub(super) fn draw_flowchart(labels: &[String], edges: &[(usize, usize, Option<String>)]) -> Vec<RenderLine> {
+    let graph = Graph {
+        directed: true,
+        nodes: labels.iter().map(|l| (l.clone(), None)).collect(),
+        edges: edges.iter().map(|(from, to, label)| Edge { from: *from, to: *to, label: label.clone() }).collect(),
+        ..Graph::default()
+    };
+    draw(&graph)
+}
+
+fn render(graph: &Graph) -> Document {
+    let mut doc = Document::default();
+    if let Some(title) = graph.label.as_ref().or(graph.name.as_ref()) {
+        let title = label_lines(title, "", graph.name.as_deref().unwrap_or_default()).join(" ");
+        doc.headings.push(Heading {
+            level: 1,
+            line: 0,
+            text: title.clone(),
+        });
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new(title, TextStyle::BOLD | TextStyle::HEADING)],
+            indent: 0,
+        });
+        doc.lines.push(RenderLine::default());
+    }
+    if graph.nodes.is_empty() {
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new(t!("（空图）"), TextStyle::DIM)],
+            indent: 0,
+        });
+        return doc;
+    }
+    doc.lines.extend(draw(graph));
+    doc
+}
+
+fn draw(graph: &Graph) -> Vec<RenderLine> {
+    let n = graph.nodes.len();
+    // 重复的边只画一次，自环标在节点上
+    let mut edges: Vec<&Edge> = Vec::new();
+    let mut looped = vec![false; n];
+    for edge in &graph.edges {
+        if edge.from == edge.to {
+            looped[edge.from] = true;
+        } else if !edges.iter().any(|e| (e.from, e.to) == (edge.from, edge.to)) {
+            edges.push(edge);
+        }
+    }
+
+    let reversed = back_edges(n, &edges);
+    let ranks = longest_path_ranks(n, &edges, &reversed);
+
+    let mut vertices: Vec<Vertex> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, (id, label))| {
+            let name = graph.name.as_deref().unwrap_or_default();
+            let mut lines = label_lines(label.as_deref().unwrap_or(id), id, name);
+            if looped[i] {
+                lines[0].push_str(" ↺");
+            }
+            let width = lines.iter().map(|l| l.width()).max().unwrap_or(0) as i64 + 4;
+            Vertex::new(lines, ranks[i], width)
+        })
+        .collect();
+
+    let mut chains = Vec::new();
+    for (edge, &reversed) in edges.iter().zip(&reversed) {
+        let (top, bottom) = if reversed { (edge.to, edge.from) } else { (edge.from, edge.to) };
+        let mut chain = vec![top];
+        for rank in ranks[top] + 1..ranks[bottom] {
+            vertices.push(Vertex::new(Vec::new(), rank, 1));
+            chain.push(vertices.len() - 1);
+        }
+        chain.push(bottom);
+        for pair in chain.windows(2) {
+            vertices[pair[0]].succs.push(pair[1]);
+            vertices[pair[1]].preds.push(pair[0]);
+        }
+        chains.push(Chain { vertices: chain, reversed, label: edge.label.clone(), xs: Vec::new() });
+    }
+
+    let layer_count = ranks.iter().max().map_or(0, |r| r + 1);
+    let mut layers: Vec<Vec<usize>> = vec![Vec::new(); layer_count];
+    for (i, v) in vertices.iter().enumerate() {
+        layers[v.rank].push(i);
+    }
+    order_layers(&mut layers, &vertices);
+    place_layers(&layers, &mut vertices);
+    assign_ports(&mut chains, &vertices);
+
+    // 纵向：各层高度为其中最高的方框，层间为连线的轨道
+    let layer_heights: Vec<usize> = layers
+        .iter()
+        .map(|layer| layer.iter().map(|&v| vertices[v].height()).max().unwrap_or(0).max(1))
+        .collect();
+    let tracks = assign_tracks(&chains, &vertices, layer_count);
+    let mut layer_tops = vec![0];
+    for l in 0..layer_count - 1 {
+        let gap = tracks.gap_tracks[l] + 2;
+        layer_tops.push(layer_tops[l] + layer_heights[l] + gap);
+    }
+    let height = layer_tops[layer_count - 1] + layer_heights[layer_count - 1];
+    let width = vertices.iter().map(|v| v.left + v.width).max().unwrap_or(0) as usize + 1;
+    let mut canvas = Canvas::new(width, height);
+
+    for v in vertices.iter().filter(|v| !v.is_dummy()) {
+        canvas.draw_box(v, layer_tops[v.rank]);
+    }
+    for (c, chain) in chains.iter().enumerate() {
+        let first = &vertices[chain.vertices[0]];
+        let last = &vertices[*chain.vertices.last().unwrap()];
+        let (x0, y0) = (chain.xs[0] as usize, layer_tops[first.rank] + first.height() - 1);
+        let mut points = vec![(x0, y0)];
+        for (i, pair) in chain.xs.windows(2).enumerate() {
+            if pair[0] != pair[1] {
+                let rank = first.rank + i;
+                let row = layer_tops[rank] + layer_heights[rank] + 1 + tracks.chains[c][i];
+                points.push((pair[0] as usize, row));
+                points.push((pair[1] as usize, row));
+            }
+        }
+        let arrow_down = graph.directed && !chain.reversed;
+        let x1 = *chain.xs.last().unwrap() as usize;
+        let y1 = layer_tops[last.rank] - usize::from(arrow_down);
+        points.push((x1, y1));
+        for pair in points.windows(2) {
+            canvas.line(pair[0], pair[1]);
+        }
+        if arrow_down {
+            canvas.put(x1, y1, "▼", TextStyle::NONE);
+        } else if graph.directed {
+            canvas.put(x0, y0 + 1, "▲", TextStyle::NONE);
+        }
+    }
+    // 标签在连线都画完后放在进入目标的竖线右侧或左侧，两侧都被占用时略过
+    for chain in &chains {
+        if let Some(label) = &chain.label {
+            let last = &vertices[*chain.vertices.last().unwrap()];
+            let x1 = *chain.xs.last().unwrap() as usize;
+            let text = label_lines(label, "", "").join(" ");
+            let (row, width) = (layer_tops[last.rank] - 1, text.width());
+            if canvas.is_free(x1 + 1, row, width + 2) {
+                canvas.put(x1 + 2, row, &text, TextStyle::DIM);
+            } else if x1 > width + 1 && canvas.is_free(x1 - width - 2, row, width + 2) {
+                canvas.put(x1 - width - 1, row, &text, TextStyle::DIM);
+            }
+        }
+    }
+    canvas.into_lines()
+}
+
+/// 按声明顺序深度优先遍历，指向栈中节点的边是回边，反向后图无环
+fn back_edges(n: usize, edges: &[&Edge]) -> Vec<bool> {
+    let mut out: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (i, e) in edges.iter().enumerate() {
+        out[e.from].push(i);
+    }
+    let mut reversed = vec![false; edges.len()];
+    // 0 未访问，1 在栈中，2 已完成
+    let mut state = vec![0u8; n];
+    for root in 0..n {
+        if state[root] != 0 {
+            continue;
+        }
+        state[root] = 1;
+        let mut stack = vec![(root, 0)];
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            match out[node].get(*next) {
+                Some(&e) => {
+                    *next += 1;
+                    let to = edges[e].to;
+                    match state[to] {
+                        0 => {
+                            state[to] = 1;
+                            stack.push((to, 0));
+                        }
+                        1 => reversed[e] = true,
+                        _ => {}
+                    }
+                }
+                None => {
+                    state[node] = 2;
+                    stack.pop();
+                }
+            }
+        }
+    }
+    reversed
+}
+
+/// 每个节点的层号为从源点出发的最长路径长度
+fn longest_path_ranks(n: usize, edges: &[&Edge], reversed: &[bool]) -> Vec<usize> {
+    let directed: Vec<(usize, usize)> = edges
+        .iter()
+        .zip(reversed)
+        .map(|(e, &r)| if r { (e.to, e.from) } else { (e.from, e.to) })
+        .collect();
+    let mut indegree = vec![0; n];
+    for &(_, to) in &directed {
+        indegree[to] += 1;
+    }
+    let mut ranks = vec![0; n];
+    let mut queue: Vec<usize> = (0..n).filter(|&v| indegree[v] == 0).collect();
+    while let Some(v) = queue.pop() {
+        for &(from, to) in directed.iter().filter(|(from, _)| *from == v) {
+            ranks[to] = ranks[to].max(ranks[from] + 1);
+            indegree[to] -= 1;
+            if indegree[to] == 0 {
+                queue.push(to);
+            }
+        }
+    }
+    ranks
+}
+
+/// 重心法：交替按上一层与下一层相邻节点的平均位置排序，减少交叉
+fn order_layers(layers: &mut [Vec<usize>], vertices: &[Vertex]) {
+    let mut position = vec![0.0; vertices.len()];
+    let update = |layer: &[usize], position: &mut Vec<f64>| {
+        for (i, &v) in layer.iter().enumerate() {
+            position[v] = i as f64;
+        }
+    };
+    for layer in layers.iter() {
+        update(layer, &mut position);
+    }
+    for _ in 0..SWEEPS {
+        for down in [true, false] {
+            for l in sweep(layers.len(), down) {
+                let key = |v: usize| {
+                    let neighbors = if down { &vertices[v].preds } else { &vertices[v].succs };
+                    if neighbors.is_empty() {
+                        position[v]
+                    } else {
+                        neighbors.iter().map(|&u| position[u]).sum::<f64>() / neighbors.len() as f64
+                    }
+                };
+                let mut keyed: Vec<(f64, usize)> = layers[l].iter().map(|&v| (key(v), v)).collect();
+                keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+                layers[l] = keyed.into_iter().map(|(_, v)| v).collect();
+                update(&layers[l], &mut position);
+            }
+        }
+    }
+}
+
+/// 自上而下时依次为第 1 层到最后一层，自下而上时为倒数第 2 层到第 0 层
+fn sweep(count: usize, down: bool) -> Vec<usize> {
+    if down { (1..count).collect() } else { (0..count.saturating_sub(1)).rev().collect() }
+}
+
+/// 横向位置：先紧凑排列，再交替让节点对齐上一层与下一层相邻节点的中心，不与左侧节点重叠
+fn place_layers(layers: &[Vec<usize>], vertices: &mut [Vertex]) {
+    for layer in layers {
+        let mut left = 0;
+        for &v in layer {
+            vertices[v].left = left;
+            left += vertices[v].width + GAP;
+        }
+    }
+    for _ in 0..SWEEPS {
+        for down in [true, false] {
+            for l in sweep(layers.len(), down) {
+                let mut min_left = 0;
+                for &v in &layers[l] {
+                    let neighbors = if down { &vertices[v].preds } else { &vertices[v].succs };
+                    let desired = if neighbors.is_empty() {
+                        vertices[v].center()
+                    } else {
+                        neighbors.iter().map(|&u| vertices[u].center()).sum::<i64>() / neighbors.len() as i64
+                    };
+                    let left = (desired - vertices[v].width / 2).max(min_left);
+                    vertices[v].left = left;
+                    min_left = left + vertices[v].width + GAP;
+                }
+            }
+        }
+    }
+    let shift = vertices.iter().map(|v| v.left).min().unwrap_or(0);
+    for v in vertices.iter_mut() {
+        v.left -= shift;
+    }
+}
+
+/// 同一条边上下两端连接点：方框一侧有多条边时按另一端的位置均匀分布在边框上
+fn assign_ports(chains: &mut [Chain], vertices: &[Vertex]) {
+    for chain in chains.iter_mut() {
+        chain.xs = chain.vertices.iter().map(|&v| vertices[v].center()).collect();
+    }
+    for (v, vertex) in vertices.iter().enumerate().filter(|(_, v)| !v.is_dummy()) {
+        for bottom in [true, false] {
+            // （另一端的列，边，连接点在边上的下标）
+            let mut attached: Vec<(i64, usize, usize)> = chains
+                .iter()
+                .enumerate()
+                .filter_map(|(c, chain)| {
+                    let last = chain.vertices.len() - 1;
+                    let (end, next) = if bottom { (0, 1) } else { (last, last - 1) };
+                    (chain.vertices[end] == v).then(|| (vertices[chain.vertices[next]].center(), c, end))
+                })
+                .collect();
+            attached.sort();
+            let count = attached.len() as i64;
+            let inner = vertex.width - 2;
+            for (i, (_, c, end)) in attached.into_iter().enumerate() {
+                chains[c].xs[end] = vertex.left + 1 + (i as i64 + 1) * inner / (count + 1);
+            }
+        }
+    }
+}
+
+/// 各层间隙中横向连线所在的轨道
+struct Tracks {
+    /// 各间隙的轨道数
+    gap_tracks: Vec<usize>,
+    /// 每条边在各间隙中的轨道号
+    chains: Vec<Vec<usize>>,
+}
+
+/// 间隙中的一段横向连线：起止列，以及所属的边与段号
+struct Segment {
+    from: i64,
+    to: i64,
+    chain: usize,
+    step: usize,
+}
+
+/// 横向区间不重叠、或起点 / 终点相同（可以汇合）的连线共用一条轨道
+///
+/// 一段下行的列恰好是另一段的起点时，前者的轨道要在后者之下，否则两条竖线会接在一起。
+fn assign_tracks(chains: &[Chain], vertices: &[Vertex], layer_count: usize) -> Tracks {
+    let mut gaps: Vec<Vec<Segment>> = (0..layer_count).map(|_| Vec::new()).collect();
+    for (c, chain) in chains.iter().enumerate() {
+        let rank = vertices[chain.vertices[0]].rank;
+        for (step, pair) in chain.xs.windows(2).enumerate() {
+            if pair[0] != pair[1] {
+                gaps[rank + step].push(Segment { from: pair[0], to: pair[1], chain: c, step });
+            }
+        }
+    }
+
+    let mut assigned: Vec<Vec<usize>> = chains.iter().map(|c| vec![0; c.xs.len() - 1]).collect();
+    let mut gap_tracks = vec![0; layer_count];
+    for (gap, mut segments) in gaps.into_iter().enumerate() {
+        let mut placed: Vec<(Segment, usize)> = Vec::new();
+        while !segments.is_empty() {
+            let next = (0..segments.len())
+                .find(|&i| !segments.iter().enumerate().any(|(j, r)| j != i && r.from == segments[i].to))
+                .unwrap_or(0);
+            let s = segments.remove(next);
+            let (lo, hi) = (s.from.min(s.to) - 1, s.from.max(s.to) + 1);
+            let below = placed.iter().filter(|(p, _)| p.from == s.to).map(|(_, t)| t + 1).max().unwrap_or(0);
+            let track = (below..)
+                .find(|&t| {
+                    placed.iter().filter(|(_, pt)| *pt == t).all(|(p, _)| {
+                        p.from.max(p.to) + 1 < lo || p.from.min(p.to) - 1 > hi || p.from == s.from || p.to == s.to
+                    })
+                })
+                .unwrap_or(below);
+            assigned[s.chain][s.step] = track;
+            gap_tracks[gap] = gap_tracks[gap].max(track + 1);
+            placed.push((s, track));
+        }
+    }
+    Tracks { gap_tracks, chains: assigned }
+}
+
+// === 画布 ===
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+/// 字符画布：线条按方向位合并为制表符，文字覆盖在线条之上
+pub(super) struct Canvas {
+    lines: Vec<Vec<u8>>,
+    /// 文字及样式，`'\0'` 占据宽字符的第二列
+    text: Vec<Vec<Option<(char, TextStyle)>>>,
+}
+
+impl Canvas {
+    pub(super) fn new(width: usize, height: usize) -> Self {
+        Self {
+            lines: vec![vec![0; width]; height],
+            text: vec![vec![None; width]; height],
+        }
+    }
+
+    /// 横线或竖线，两端含在内
+    pub(super) fn line(&mut self, (x1, y1): (usize, usize), (x2, y2): (usize, usize)) {
+        if x1 == x2 {
+            let (top, bottom) = (y1.min(y2), y1.max(y2));
+            for y in top..=bottom {
+                self.lines[y][x1] |= if y > top { UP } else { 0 } | if y < bottom { DOWN } else { 0 };
+            }
+        } else {
+            let (left, right) = (x1.min(x2), x1.max(x2));
+            for x in left..=right {
+                self.lines[y1][x] |= if x > left { LEFT } else { 0 } | if x < right { RIGHT } else { 0 };
+            }
+        }
+    }
+
+    fn draw_box(&mut self, v: &Vertex, top: usize) {
+        let (left, right) = (v.left as usize, (v.left + v.width - 1) as usize);
+        let bottom = top + v.height() - 1;
+        self.line((left, top), (right, top));
+        self.line((left, bottom), (right, bottom));
+        self.line((left, top), (left, bottom));
+        self.line((right, top), (right, bottom));
+        let inner = v.width as usize - 4;
+        for (i, text) in v.lines.iter().enumerate() {
+            let x = left + 2 + (inner - text.width()) / 2;
+            self.put(x, top + 1 + i, text, TextStyle::BOLD);
+        }
+    }
+
+    pub(super) fn put(&mut self, mut x: usize, y: usize, text: &str, style: TextStyle) {
+        for c in text.chars() {
+            let width = c.width().unwrap_or(0);
+            if width == 0 || x + width > self.text[y].len() {
+                continue;
+            }
+            self.text[y][x] = Some((c, style));
+            if width == 2 {
+                self.text[y][x + 1] = Some(('\0', style));
+            }
+            x += width;
+        }
+    }
+
+    fn is_free(&self, x: usize, y: usize, width: usize) -> bool {
+        (x..x + width).all(|x| self.lines[y].get(x) == Some(&0) && self.text[y][x].is_none())
+    }
+
+    pub(super) fn into_lines(self) -> Vec<RenderLine> {
+        self.lines
+            .into_iter()
+            .zip(self.text)
+            .map(|(lines, text)| {
+                let mut spans: Vec<TextSpan> = Vec::new();
+                for (bits, text) in lines.into_iter().zip(text) {
+                    let (c, style) = text.unwrap_or((glyph(bits), TextStyle::NONE));
+                    if c == '\0' {
+                        continue;
+                    }
+                    match spans.last_mut() {
+                        Some(span) if span.style == style => span.text.push(c),
+                        _ => spans.push(TextSpan::new(c.to_string(), style)),
+                    }
+                }
+                if let Some(last) = spans.last_mut() {
+                    last.text.truncate(last.text.trim_end().len());
+                }
+                spans.retain(|s| !s.text.is_empty());
+                RenderLine { spans, indent: 0 }
+            })
+            .collect()
+    }
+}
+
+fn glyph(bits: u8) -> char {
+    match bits {
+        0 => ' ',
+        UP | DOWN => '│',
+        _ if bits & (LEFT | RIGHT) == 0 => '│',
+        _ if bits & (UP | DOWN) == 0 => '─',
+        b if b == DOWN | RIGHT => '┌',
+        b if b == DOWN | LEFT => '┐',
+        b if b == UP | RIGHT => '└',
+        b if b == UP | LEFT => '┘',
+        b if b == UP | DOWN | RIGHT => '├',
+        b if b == UP | DOWN | LEFT => '┤',
+        b if b == DOWN | LEFT | RIGHT => '┬',
+        b if b == UP | LEFT | RIGHT => '┴',
+        b if b == UP | DOWN | LEFT | RIGHT => '┼',
+        // 只有一侧横线与一侧竖线之外的组合不会出现，按十字处理
+        _ => '┼',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(doc: &Document) -> Vec<String> {
+        doc.lines.iter().map(|l| l.spans.iter().map(|s| s.text.as_str()).collect()).collect()
+    }
+
+    #[test]
+    fn test_parse_dot() {
+        let doc = parse_dot(
+            r#"
+            // 注释
+            digraph build {
+                label = "构建流程";
+                node [shape=box];
+                src [label="源码"];
+                src -> lib -> bin;
+                src -> bin [label="直接"];
+                bin -> src;
+            }"#,
+        )
+        .unwrap();
+        let lines = text(&doc);
+        assert_eq!(doc.headings[0].text, "构建流程");
+        assert_eq!(
+            lines[2..7],
+            ["    ┌──────┐", "    │ 源码 │", "    └─┬─┬┬─┘", "      │ │▲", "   ┌──┘ │└──┐"]
+        );
+        assert_eq!(lines[lines.len() - 4..], ["  ▼▼│", "┌───┴─┐", "│ bin │", "└─────┘"]);
+
+        let doc = parse_dot("digraph { a -> b [label=\"yes\"]; a -> c }").unwrap();
+        assert_eq!(
+            text(&doc),
+            [
+                "   ┌───┐",
+                "   │ a │",
+                "   └─┬┬┘",
+                "     ││",
+                "  ┌──┘│",
+                "  │   └──┐",
+                "  ▼ yes  ▼",
+                "┌───┐  ┌───┐",
+                "│ b │  │ c │",
+                "└───┘  └───┘",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("a->b -- \"x\\\"y\" <<b>t</b>> -1.5").unwrap(),
+            [
+                (Token::Id("a".into()), 1),
+                (Token::Edge, 1),
+                (Token::Id("b".into()), 1),
+                (Token::Edge, 1),
+                (Token::Str("x\"y".into()), 1),
+                (Token::Str("t".into()), 1),
+                (Token::Id("-1.5".into()), 1),
+            ]
+        );
+        assert!(parse_dot("digraph { a -> }").is_err());
+        assert_eq!(label_lines("a\\nb\\N", "id", ""), ["a", "bid"]);
+    }
+}
@@ -0,0 +1,450 @@
+//! 电子邮件（RFC 822 / MIME）解析
+//!
+//! 信头中的 From / To / Cc / Date / Subject 排成信头块，编码字（`=?utf-8?B?...?=`）解码后显示。
+//! 正文按 MIME 结构逐段处理：quoted-printable 与 base64 传输编码解码后，text/plain 原样显示
+//! （引用行淡化），text/html 转换为文档行，text/calendar 邀请按日程显示；multipart/alternative 优先选纯文本。
+//! 其余部分作为附件在末尾列出。
+
+use crate::document::*;
+use crate::format::dir::human_size;
+use crate::format::text::clean_line;
+use crate::format::{html, ics, table, FileFormat, FormatError};
+use crate::t;
+use base64::Engine;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use pulldown_cmark::Alignment;
+use std::path::Path;
+
+/// 信头块中显示的字段
+const SHOWN_HEADERS: &[&str] = &["From", "To", "Cc", "Date", "Subject"];
+
+/// 不要求末尾填充的 base64 解码器，邮件客户端常省略 `=`
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+pub struct EmailFormat;
+
+impl FileFormat for EmailFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let raw = std::fs::read(path)?;
+        Ok(parse_email(&raw))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["eml"]
+    }
+
+    fn name(&self) -> &'static str {
+        "email"
+    }
+}
+
+/// 附件的文件名、类型与解码后的大小
+struct Attachment {
+    name: String,
+    mime: String,
+    size: usize,
+}
+
+pub fn parse_email(raw: &[u8]) -> Document {
+    let message = Part::parse(raw);
+    let mut doc = Document::default();
+    push_headers(&mut doc, &message);
+
+    let mut body = Document::default();
+    let mut attachments = Vec::new();
+    walk(&message, &mut body, &mut attachments);
+    while body.lines.last().is_some_and(|l| l.spans.is_empty()) {
+        body.lines.pop();
+    }
+    doc.append(body);
+
+    if !attachments.is_empty() {
+        doc.lines.push(RenderLine::default());
+        let mut rows = vec![vec![t!("附件").to_string(), t!("类型").to_string(), t!("大小").to_string()]];
+        rows.extend(
+            attachments
+                .into_iter()
+                .map(|a| vec![a.name, a.mime, human_size(a.size as u64)]),
+        );
+        table::push_table(&mut doc, &rows, &[Alignment::Left, Alignment::Left, Alignment::Right], 0);
+    }
+    doc
+}
+
+/// 信头块：字段名对齐，主题加粗并记录为标题，其后为分隔线
+fn push_headers(doc: &mut Document, message: &Part) {
+    let shown: Vec<(&str, &str)> = SHOWN_HEADERS
+        .iter()
+        .filter_map(|&name| message.header(name).map(|value| (name, value)))
+        .collect();
+    let width = shown.iter().map(|(name, _)| name.len() + 1).max().unwrap_or(0);
+    for (name, value) in shown {
+        let style = if name == "Subject" {
+            doc.headings.push(Heading {
+                level: 1,
+                line: doc.lines.len(),
+                text: value.to_string(),
+            });
+            TextStyle::BOLD
+        } else {
+            TextStyle::NONE
+        };
+        doc.lines.push(RenderLine {
+            spans: vec![
+                TextSpan::new(format!("{:<width$}  ", format!("{name}:")), TextStyle::KEY),
+                TextSpan::new(value, style),
+            ],
+            indent: 0,
+        });
+    }
+    if !doc.lines.is_empty() {
+        doc.rules.push(doc.lines.len());
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new("────────────────────────────────", TextStyle::DIM)],
+            indent: 0,
+        });
+        doc.lines.push(RenderLine::default());
+    }
+}
+
+/// 按 MIME 结构处理各部分，正文写入 `doc`，其余收集为附件
+fn walk(part: &Part, doc: &mut Document, attachments: &mut Vec<Attachment>) {
+    let (mime, params) = part.content_type();
+    if let Some(boundary) = mime.starts_with("multipart/").then(|| param(&params, "boundary")).flatten() {
+        let parts: Vec<Part> = split_multipart(part.body, boundary).into_iter().map(Part::parse).collect();
+        if mime == "multipart/alternative" {
+            let find = |wanted: &str| parts.iter().find(|p| p.content_type().0 == wanted);
+            let chosen = find("text/plain").or(find("text/html")).or(parts.last());
+            if let Some(chosen) = chosen {
+                walk(chosen, doc, attachments);
+            }
+        } else {
+            for p in &parts {
+                walk(p, doc, attachments);
+            }
+        }
+        return;
+    }
+
+    let disposition = part.header("Content-Disposition").map(split_params);
+    let filename = disposition
+        .as_ref()
+        .and_then(|(_, p)| param(p, "filename"))
+        .or(param(&params, "name"))
+        .map(str::to_string);
+    let is_attachment = disposition.as_ref().is_some_and(|(kind, _)| kind == "attachment");
+    let data = part.decoded();
+    if is_attachment || !mime.starts_with("text/") {
+        attachments.push(Attachment {
+            name: filename.unwrap_or_else(|| t!("（未命名）").to_string()),
+            mime,
+            size: data.len(),
+        });
+        return;
+    }
+
+    let text = decode_charset(&data, param(&params, "charset").unwrap_or("utf-8"));
+    if !doc.lines.is_empty() {
+        doc.lines.push(RenderLine::default());
+    }
+    if mime == "text/html" {
+        doc.append(html::parse_html(&text));
+    } else if let Some(calendar) = (mime == "text/calendar").then(|| ics::parse_calendar(&text).ok()).flatten() {
+        // 会议邀请按日程显示
+        doc.append(calendar);
+    } else {
+        for line in text.lines() {
+            let line = clean_line(line);
+            // 回复中引用的原文淡化显示
+            let style = if line.starts_with('>') { TextStyle::DIM } else { TextStyle::NONE };
+            let spans = if line.is_empty() { Vec::new() } else { vec![TextSpan::new(line, style)] };
+            doc.lines.push(RenderLine { spans, indent: 0 });
+        }
+    }
+}
+
+/// 邮件或 MIME 部分：解码后的信头与未解码的正文
+struct Part<'a> {
+    headers: Vec<(String, String)>,
+    body: &'a [u8],
+}
+
+impl<'a> Part<'a> {
+    fn parse(raw: &'a [u8]) -> Self {
+        // 信头与正文以第一个空行分隔
+        let (head, body) = match raw.windows(2).position(|w| w == b"\n\n" || w == b"\n\r") {
+            Some(i) => {
+                let rest = &raw[i + 1..];
+                let rest = rest.strip_prefix(b"\r\n").or(rest.strip_prefix(b"\n")).unwrap_or(rest);
+                (&raw[..i], rest)
+            }
+            None => (raw, &raw[raw.len()..]),
+        };
+
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in String::from_utf8_lossy(head).lines() {
+            // 以空白开头的行是上一个字段的折行
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        for (_, value) in &mut headers {
+            *value = decode_words(value);
+        }
+        Part { headers, body }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// 小写的 MIME 类型与参数，缺省为 text/plain
+    fn content_type(&self) -> (String, Vec<(String, String)>) {
+        match self.header("Content-Type") {
+            Some(value) => split_params(value),
+            None => ("text/plain".into(), Vec::new()),
+        }
+    }
+
+    /// 按传输编码解码后的正文
+    fn decoded(&self) -> Vec<u8> {
+        let encoding = self.header("Content-Transfer-Encoding").unwrap_or_default().to_lowercase();
+        match encoding.as_str() {
+            "base64" => {
+                let compact: Vec<u8> = self.body.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect();
+                BASE64.decode(&compact).unwrap_or_else(|_| self.body.to_vec())
+            }
+            "quoted-printable" => quoted_printable(self.body, false),
+            _ => self.body.to_vec(),
+        }
+    }
+}
+
+/// 拆分 `type/subtype; key=value; ...`，类型与参数名转为小写
+fn split_params(value: &str) -> (String, Vec<(String, String)>) {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    let kind = fields[0].trim().to_lowercase();
+    let params = fields[1..]
+        .iter()
+        .filter_map(|f| f.split_once('='))
+        .map(|(key, value)| {
+            let key = key.trim().to_lowercase();
+            // RFC 2231：`filename*=utf-8''%E6%8A%A5.pdf`
+            match key.strip_suffix('*') {
+                Some(key) => {
+                    let (charset, encoded) = match value.trim().splitn(3, '\'').collect::<Vec<_>>()[..] {
+                        [charset, _, encoded] => (charset, encoded),
+                        _ => ("utf-8", value.trim()),
+                    };
+                    (key.to_string(), decode_charset(&percent_decode(encoded), charset))
+                }
+                None => (key, value.trim().to_string()),
+            }
+        })
+        .collect();
+    (kind, params)
+}
+
+fn param<'p>(params: &'p [(String, String)], name: &str) -> Option<&'p str> {
+    params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+}
+
+/// 按 `--boundary` 分隔线切分 multipart 正文，忽略首段前言与结束线之后的内容
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut offset = 0;
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        let trimmed = line.trim_ascii_end();
+        if let Some(rest) = trimmed.strip_prefix(delimiter.as_bytes()) {
+            if let Some(s) = start {
+                // 分隔线前的换行属于分隔线
+                let before = &body[..offset];
+                let end = before.strip_suffix(b"\r\n").or(before.strip_suffix(b"\n")).map_or(offset, <[u8]>::len);
+                parts.push(&body[s..end.max(s)]);
+            }
+            if rest == b"--" {
+                return parts;
+            }
+            start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    if let Some(s) = start {
+        parts.push(&body[s..]);
+    }
+    parts
+}
+
+/// 解码 RFC 2047 编码字，相邻编码字之间的空白省略
+fn decode_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].splitn(4, '?').collect::<Vec<_>>();
+        let [charset, encoding, text, tail] = decoded[..] else {
+            break;
+        };
+        let Some(tail) = tail.strip_prefix('=') else {
+            break;
+        };
+        let bytes = match encoding {
+            "B" | "b" => BASE64.decode(text).ok(),
+            "Q" | "q" => Some(quoted_printable(text.as_bytes(), true)),
+            _ => None,
+        };
+        let Some(bytes) = bytes else {
+            break;
+        };
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            out.push_str(between);
+        }
+        // `charset*language` 形式的语言标记不影响解码
+        out.push_str(&decode_charset(&bytes, charset.split('*').next().unwrap_or(charset)));
+        rest = tail;
+        after_word = true;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// quoted-printable 解码；`header` 为真时按编码字规则把 `_` 解码为空格
+fn quoted_printable(data: &[u8], header: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'=' => {
+                let rest = &data[i + 1..];
+                // 行尾的 `=` 是软换行
+                if let Some(nl) = [b"\r\n".as_slice(), b"\n"].iter().find(|nl| rest.starts_with(nl)) {
+                    i += 1 + nl.len();
+                    continue;
+                }
+                let hex = rest.get(..2).and_then(|h| std::str::from_utf8(h).ok());
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 3;
+                    }
+                    None => {
+                        out.push(b'=');
+                        i += 1;
+                    }
+                }
+            }
+            b'_' if header => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn percent_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match hex.filter(|_| bytes[i] == b'%').and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// 按字符集解码；Latin-1 系逐字节映射，其余按 UTF-8 宽松解码
+fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    match charset.to_lowercase().as_str() {
+        "iso-8859-1" | "latin1" | "windows-1252" | "cp1252" => bytes.iter().map(|&b| char::from(b)).collect(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_email() {
+        let raw = b"From: =?utf-8?B?5byg5LiJ?= <zhang@example.com>\r\n\
+To: team@example.com\r\n\
+Subject: =?utf-8?Q?=E5=91=A8=E6=8A=A5?=\r\n =?utf-8?Q?_draft?=\r\n\
+Content-Type: multipart/mixed; boundary=\"outer\"\r\n\
+\r\n\
+preamble\r\n\
+--outer\r\n\
+Content-Type: multipart/alternative; boundary=inner\r\n\
+\r\n\
+--inner\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+Hello =E4=BD=A0=E5=A5=BD, this is a long line that =\r\nwraps.\r\n\
+> quoted\r\n\
+--inner\r\n\
+Content-Type: text/html\r\n\
+\r\n\
+<p>HTML</p>\r\n\
+--inner--\r\n\
+--outer\r\n\
+Content-Type: application/pdf\r\n\
+Content-Disposition: attachment; filename*=utf-8''%E6%8A%A5%E5%91%8A.pdf\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+JVBERi0x\r\n\
+--outer--\r\n";
+        let doc = parse_email(raw);
+        let lines: Vec<String> = doc
+            .lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect::<String>().trim_end().to_string())
+            .collect();
+        assert_eq!(lines[0], "From:     张三 <zhang@example.com>");
+        assert_eq!(lines[2], "Subject:  周报 draft");
+        assert_eq!(doc.headings[0].text, "周报 draft");
+        assert_eq!(doc.rules, [3]);
+        assert_eq!(lines[5..7], ["Hello 你好, this is a long line that wraps.", "> quoted"]);
+        assert_eq!(doc.lines[6].spans[0].style, TextStyle::DIM);
+        assert_eq!(lines[10], "报告.pdf  application/pdf   6 B");
+    }
+}
@@ -0,0 +1,235 @@
+//! EPUB 电子书解析
+//!
+//! 由 `META-INF/container.xml` 找到 OPF 包文件，按其中 spine 的顺序读取各章 XHTML，
+//! 转换为文档行后依次拼接。每章前插入分隔线并记录为章节起始行，预览模式中用 `(` / `)` 跳转。
+
+use crate::document::*;
+use crate::format::container::{parse_xml, read_entry};
+use crate::format::{html, FileFormat, FormatError};
+use crate::t;
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use tracing::warn;
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+pub struct EpubFormat;
+
+impl FileFormat for EpubFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let mut archive = ZipArchive::new(File::open(path)?).map_err(epub_error)?;
+        let mut read = |name: &str| {
+            read_entry(&mut archive, name)
+                .and_then(|entry| entry.ok_or(ZipError::FileNotFound))
+                .map_err(epub_error)
+        };
+
+        let container = read("META-INF/container.xml")?;
+        let opf_path = rootfile(&container)?;
+        let package = parse_opf(&read(&opf_path)?)?;
+        let base = opf_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+        let mut chapters = Vec::new();
+        for href in &package.spine {
+            let name = resolve(base, href);
+            match read(&name) {
+                Ok(xhtml) => chapters.push(html::parse_html(&xhtml)),
+                Err(e) => warn!(chapter = name, "跳过无法读取的章节: {e}"),
+            }
+        }
+        Ok(assemble(package, chapters))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["epub"]
+    }
+
+    fn name(&self) -> &'static str {
+        "epub"
+    }
+}
+
+fn epub_error(e: impl std::fmt::Display) -> FormatError {
+    FormatError::Parse(t!("EPUB 解析失败: {}", e))
+}
+
+/// OPF 包文件中用到的内容
+#[derive(Debug, Default)]
+struct Package {
+    title: Option<String>,
+    creators: Vec<String>,
+    /// 按阅读顺序排列的章节路径（相对 OPF 文件）
+    spine: Vec<String>,
+}
+
+/// `container.xml` 中第一个 OPF 包文件的路径
+fn rootfile(container: &str) -> Result<String, FormatError> {
+    let xml = parse_xml(container).map_err(epub_error)?;
+    xml.descendants()
+        .find(|n| n.has_tag_name("rootfile"))
+        .and_then(|n| n.attribute("full-path"))
+        .map(str::to_string)
+        .ok_or_else(|| epub_error("container.xml"))
+}
+
+fn parse_opf(opf: &str) -> Result<Package, FormatError> {
+    let xml = parse_xml(opf).map_err(epub_error)?;
+    let root = xml.root_element();
+    let text = |node: Node| node.text().map(str::trim).filter(|t| !t.is_empty()).map(str::to_string);
+
+    let mut package = Package::default();
+    if let Some(metadata) = root.children().find(|n| n.tag_name().name() == "metadata") {
+        package.title = metadata
+            .children()
+            .find(|n| n.tag_name().name() == "title")
+            .and_then(text);
+        package.creators = metadata
+            .children()
+            .filter(|n| n.tag_name().name() == "creator")
+            .filter_map(text)
+            .collect();
+    }
+
+    // manifest: id → href，只保留 (X)HTML 内容
+    let manifest: HashMap<&str, &str> = root
+        .descendants()
+        .filter(|n| n.tag_name().name() == "item")
+        .filter(|n| {
+            matches!(n.attribute("media-type"), Some("application/xhtml+xml" | "text/html"))
+        })
+        .filter_map(|n| Some((n.attribute("id")?, n.attribute("href")?)))
+        .collect();
+    package.spine = root
+        .descendants()
+        .filter(|n| n.tag_name().name() == "itemref")
+        .filter_map(|n| manifest.get(n.attribute("idref")?))
+        .map(|href| href.to_string())
+        .collect();
+    if package.spine.is_empty() {
+        return Err(epub_error("spine"));
+    }
+    Ok(package)
+}
+
+/// 拼接书名与各章内容，每章前插入分隔线
+fn assemble(package: Package, chapters: Vec<Document>) -> Document {
+    let mut doc = Document::default();
+    if let Some(title) = &package.title {
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new(title.clone(), TextStyle::BOLD | TextStyle::HEADING)],
+            indent: 0,
+        });
+        if !package.creators.is_empty() {
+            doc.lines.push(RenderLine {
+                spans: vec![TextSpan::new(package.creators.join(", "), TextStyle::DIM)],
+                indent: 0,
+            });
+        }
+        doc.lines.push(RenderLine::default());
+    }
+
+    let chapters: Vec<Document> = chapters.into_iter().filter(|c| !c.lines.is_empty()).collect();
+    let total = chapters.len();
+    for (i, chapter) in chapters.into_iter().enumerate() {
+        if i > 0 {
+            doc.lines.push(RenderLine::default());
+        }
+        let label = match chapter.headings.first() {
+            Some(heading) => format!("── {}/{} {} ──", i + 1, total, heading.text),
+            None => format!("── {}/{} ──", i + 1, total),
+        };
+        doc.sections.push(doc.lines.len());
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new(label, TextStyle::DIM)],
+            indent: 0,
+        });
+        doc.lines.push(RenderLine::default());
+        doc.append(chapter);
+    }
+
+    doc.metadata.title = package.title;
+    if !package.creators.is_empty() {
+        doc.metadata.extra.push(("creator".into(), package.creators.join(", ")));
+    }
+    doc
+}
+
+/// 将 OPF 中的相对路径解析为容器内路径：处理 `..`、百分号编码与片段
+fn resolve(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut parts: Vec<String> = base
+        .split('/')
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(percent_decode(part)),
+        }
+    }
+    parts.join("/")
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|h| h.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_opf() {
+        let opf = r#"<?xml version="1.0"?>
+            <package xmlns="http://www.idpf.org/2007/opf" xmlns:dc="http://purl.org/dc/elements/1.1/">
+              <metadata><dc:title>书名</dc:title><dc:creator>作者</dc:creator></metadata>
+              <manifest>
+                <item id="c2" href="Text/ch%202.xhtml" media-type="application/xhtml+xml"/>
+                <item id="c1" href="Text/ch1.xhtml" media-type="application/xhtml+xml"/>
+                <item id="css" href="style.css" media-type="text/css"/>
+              </manifest>
+              <spine><itemref idref="c1"/><itemref idref="c2"/><itemref idref="css"/></spine>
+            </package>"#;
+        let package = parse_opf(opf).unwrap();
+        assert_eq!(package.title.as_deref(), Some("书名"));
+        assert_eq!(package.creators, ["作者"]);
+        assert_eq!(package.spine, ["Text/ch1.xhtml", "Text/ch%202.xhtml"]);
+        assert_eq!(resolve("OEBPS", "Text/ch%202.xhtml#p1"), "OEBPS/Text/ch 2.xhtml");
+        assert_eq!(resolve("OEBPS/Text", "../ch1.xhtml"), "OEBPS/ch1.xhtml");
+
+        let chapters = vec![
+            html::parse_html("<h1>开始</h1><p>正文</p>"),
+            html::parse_html("<p>无标题</p>"),
+        ];
+        let doc = assemble(package, chapters);
+        assert_eq!(doc.sections, [3, 9]);
+        assert_eq!(doc.lines[3].spans[0].text, "── 1/2 开始 ──");
+        assert_eq!(doc.lines[9].spans[0].text, "── 2/2 ──");
+        assert_eq!(doc.headings[0].line, 5);
+    }
+}
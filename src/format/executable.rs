@@ -0,0 +1,254 @@
+//! 可执行文件摘要（ELF / PE / Mach-O）
+//!
+//! 通过 goblin 解析文件头，类似 `readelf -h`：格式、类型、架构、字节序、入口地址与符号数排成字段表，
+//! 其后为节表（名称、地址、大小）与依赖的动态库列表。多架构 Mach-O 列出全部架构，摘要取第一个。
+
+use crate::document::*;
+use crate::format::dir::human_size;
+use crate::format::table::{self, pad_to_width};
+use crate::format::{FileFormat, FormatError};
+use crate::t;
+use goblin::mach::{self, Mach, MachO, SingleArch};
+use goblin::{elf, pe, Object};
+use pulldown_cmark::Alignment;
+use std::path::Path;
+use unicode_width::UnicodeWidthStr;
+
+pub struct ExecutableFormat;
+
+impl FileFormat for ExecutableFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let data = std::fs::read(path)?;
+        Ok(render(&summarize(&data)?))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["exe", "dll", "sys", "so", "dylib", "o"]
+    }
+
+    fn name(&self) -> &'static str {
+        "executable"
+    }
+}
+
+/// 各格式共同的摘要信息
+#[derive(Default)]
+struct Summary {
+    format: String,
+    kind: &'static str,
+    arch: String,
+    little_endian: bool,
+    entry: Option<u64>,
+    interpreter: Option<String>,
+    symbols: usize,
+    sections: Vec<Section>,
+    libraries: Vec<String>,
+}
+
+struct Section {
+    name: String,
+    addr: u64,
+    size: u64,
+}
+
+fn exe_error(e: impl std::fmt::Display) -> FormatError {
+    FormatError::Parse(t!("可执行文件解析失败: {}", e))
+}
+
+fn summarize(data: &[u8]) -> Result<Summary, FormatError> {
+    match Object::parse(data).map_err(exe_error)? {
+        Object::Elf(elf) => Ok(summarize_elf(&elf)),
+        Object::PE(pe) => Ok(summarize_pe(&pe)),
+        Object::Mach(Mach::Binary(macho)) => Ok(summarize_macho(&macho)),
+        Object::Mach(Mach::Fat(fat)) => {
+            let arches: Vec<String> = fat
+                .iter_arches()
+                .filter_map(Result::ok)
+                .map(|a| arch_name(a.cputype(), a.cpusubtype()))
+                .collect();
+            let first = (0..arches.len()).find_map(|i| match fat.get(i) {
+                Ok(SingleArch::MachO(macho)) => Some(macho),
+                _ => None,
+            });
+            let mut summary = first.as_ref().map(summarize_macho).unwrap_or_default();
+            summary.format = t!("Mach-O 多架构（{}）", arches.len());
+            summary.arch = arches.join(", ");
+            Ok(summary)
+        }
+        _ => Err(exe_error(t!("不是 ELF、PE 或 Mach-O 文件"))),
+    }
+}
+
+fn summarize_elf(elf: &elf::Elf) -> Summary {
+    let kind = match elf.header.e_type {
+        elf::header::ET_REL => t!("目标文件"),
+        // 位置无关的可执行文件与共享库同为 ET_DYN，有解释器的是可执行文件
+        elf::header::ET_DYN if elf.interpreter.is_none() => t!("共享库"),
+        elf::header::ET_EXEC | elf::header::ET_DYN => t!("可执行文件"),
+        elf::header::ET_CORE => t!("核心转储"),
+        _ => "?",
+    };
+    let sections = elf
+        .section_headers
+        .iter()
+        .skip(1)
+        .map(|sh| Section {
+            name: elf.shdr_strtab.get_at(sh.sh_name).unwrap_or("?").to_string(),
+            addr: sh.sh_addr,
+            size: sh.sh_size,
+        })
+        .collect();
+    Summary {
+        format: if elf.is_64 { "ELF64" } else { "ELF32" }.to_string(),
+        kind,
+        arch: elf::header::machine_to_str(elf.header.e_machine).to_string(),
+        little_endian: elf.little_endian,
+        entry: (elf.entry != 0).then_some(elf.entry),
+        interpreter: elf.interpreter.map(str::to_string),
+        symbols: elf.syms.len() + elf.dynsyms.len(),
+        sections,
+        libraries: elf.libraries.iter().map(|l| l.to_string()).collect(),
+    }
+}
+
+fn summarize_pe(pe: &pe::PE) -> Summary {
+    let sections = pe
+        .sections
+        .iter()
+        .map(|s| Section {
+            name: s.name().unwrap_or("?").to_string(),
+            addr: pe.image_base + u64::from(s.virtual_address),
+            size: u64::from(s.virtual_size),
+        })
+        .collect();
+    Summary {
+        format: if pe.is_64 { "PE32+" } else { "PE32" }.to_string(),
+        kind: if pe.is_lib { t!("动态链接库") } else { t!("可执行文件") },
+        arch: pe::header::machine_to_str(pe.header.coff_header.machine).to_string(),
+        little_endian: true,
+        entry: (pe.entry != 0).then(|| pe.image_base + u64::from(pe.entry)),
+        interpreter: None,
+        symbols: pe.exports.len() + pe.imports.len(),
+        sections,
+        libraries: pe.libraries.iter().map(|l| l.to_string()).collect(),
+    }
+}
+
+fn summarize_macho(macho: &MachO) -> Summary {
+    let kind = match macho.header.filetype {
+        mach::header::MH_OBJECT => t!("目标文件"),
+        mach::header::MH_EXECUTE => t!("可执行文件"),
+        mach::header::MH_DYLIB => t!("共享库"),
+        mach::header::MH_BUNDLE => t!("可加载模块"),
+        mach::header::MH_CORE => t!("核心转储"),
+        _ => "?",
+    };
+    let sections = macho
+        .segments
+        .iter()
+        .filter_map(|segment| segment.sections().ok())
+        .flatten()
+        .map(|(s, _)| Section {
+            name: format!("{},{}", s.segname().unwrap_or("?"), s.name().unwrap_or("?")),
+            addr: s.addr,
+            size: s.size,
+        })
+        .collect();
+    Summary {
+        format: if macho.is_64 { "Mach-O 64" } else { "Mach-O 32" }.to_string(),
+        kind,
+        arch: arch_name(macho.header.cputype(), macho.header.cpusubtype()),
+        little_endian: macho.little_endian,
+        entry: (macho.entry != 0).then_some(macho.entry),
+        interpreter: None,
+        symbols: macho.symbols().count(),
+        sections,
+        // 第一项是文件自身（`self`）
+        libraries: macho.libs.iter().skip(1).map(|l| l.to_string()).collect(),
+    }
+}
+
+fn arch_name(cputype: u32, cpusubtype: u32) -> String {
+    mach::constants::cputype::get_arch_name_from_types(cputype, cpusubtype)
+        .map_or_else(|| format!("{cputype:#x}"), str::to_string)
+}
+
+/// 字段表、节表与动态库列表
+fn render(summary: &Summary) -> Document {
+    let mut rows = vec![
+        (t!("格式"), summary.format.clone()),
+        (t!("类型"), summary.kind.to_string()),
+        (t!("架构"), summary.arch.clone()),
+        (t!("字节序"), if summary.little_endian { t!("小端") } else { t!("大端") }.to_string()),
+    ];
+    rows.extend(summary.entry.map(|entry| (t!("入口地址"), format!("{entry:#x}"))));
+    rows.extend(summary.interpreter.clone().map(|i| (t!("解释器"), i)));
+    rows.push((t!("符号数"), summary.symbols.to_string()));
+
+    let mut doc = Document::default();
+    let width = rows.iter().map(|(label, _)| label.width()).max().unwrap_or(0);
+    for (label, value) in rows {
+        doc.lines.push(RenderLine {
+            spans: vec![
+                TextSpan::new(format!("{}  ", pad_to_width(label, width)), TextStyle::KEY),
+                TextSpan::new(value, TextStyle::NONE),
+            ],
+            indent: 0,
+        });
+    }
+
+    if !summary.sections.is_empty() {
+        push_heading(&mut doc, t!("节（{}）", summary.sections.len()));
+        let mut cells = vec![vec![t!("名称").to_string(), t!("地址").to_string(), t!("大小").to_string()]];
+        cells.extend(
+            summary
+                .sections
+                .iter()
+                .map(|s| vec![s.name.clone(), format!("{:#010x}", s.addr), human_size(s.size)]),
+        );
+        table::push_table(&mut doc, &cells, &[Alignment::Left, Alignment::Left, Alignment::Right], 0);
+    }
+    if !summary.libraries.is_empty() {
+        push_heading(&mut doc, t!("动态库（{}）", summary.libraries.len()));
+        for library in &summary.libraries {
+            doc.lines.push(RenderLine {
+                spans: vec![TextSpan::new(library.clone(), TextStyle::NONE)],
+                indent: 2,
+            });
+        }
+    }
+    doc
+}
+
+fn push_heading(doc: &mut Document, text: String) {
+    doc.lines.push(RenderLine::default());
+    doc.headings.push(Heading {
+        level: 2,
+        line: doc.lines.len(),
+        text: text.clone(),
+    });
+    doc.lines.push(RenderLine {
+        spans: vec![TextSpan::new(text, TextStyle::BOLD | TextStyle::HEADING)],
+        indent: 0,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_self() {
+        let exe = std::env::current_exe().unwrap();
+        let summary = summarize(&std::fs::read(exe).unwrap()).unwrap();
+        assert!(!summary.sections.is_empty());
+        assert!(summary.symbols > 0);
+        assert!(["ELF64", "PE32+", "Mach-O 64"].contains(&summary.format.as_str()));
+
+        let doc = render(&summary);
+        let first: String = doc.lines[0].spans.iter().map(|s| s.text.as_str()).collect();
+        assert!(first.starts_with("格式"));
+        assert_eq!(doc.headings[0].text, t!("节（{}）", summary.sections.len()));
+        assert!(summarize(b"not an executable").is_err());
+    }
+}
@@ -0,0 +1,76 @@
+//! 十六进制查看
+//!
+//! 每行 16 字节：偏移、十六进制字节、可打印字符。
+
+use crate::document::*;
+use crate::format::{FileFormat, FormatError};
+use std::path::Path;
+
+/// 每行显示的字节数
+const BYTES_PER_LINE: usize = 16;
+
+pub struct HexFormat;
+
+impl FileFormat for HexFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let data = std::fs::read(path)?;
+        Ok(hex_dump(&data))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+
+    fn name(&self) -> &'static str {
+        "hex"
+    }
+}
+
+/// 生成十六进制视图
+pub fn hex_dump(data: &[u8]) -> Document {
+    let mut doc = Document::default();
+
+    for (i, chunk) in data.chunks(BYTES_PER_LINE).enumerate() {
+        let mut hex = String::with_capacity(BYTES_PER_LINE * 3 + 1);
+        for j in 0..BYTES_PER_LINE {
+            // 前后 8 字节之间多留一个空格
+            if j == BYTES_PER_LINE / 2 {
+                hex.push(' ');
+            }
+            match chunk.get(j) {
+                Some(b) => hex.push_str(&format!("{b:02x} ")),
+                None => hex.push_str("   "),
+            }
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+
+        doc.lines.push(RenderLine {
+            spans: vec![
+                TextSpan::new(format!("{:08x}  ", i * BYTES_PER_LINE), TextStyle::DIM),
+                TextSpan::new(hex, TextStyle::NONE),
+                TextSpan::new(format!(" │{ascii}│"), TextStyle::CODE),
+            ],
+            indent: 0,
+        });
+    }
+
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_dump() {
+        let doc = hex_dump(b"Hello\x00\x01 world!\n1234");
+        assert_eq!(doc.lines.len(), 2);
+        let text: Vec<String> = doc.lines[0].spans.iter().map(|s| s.text.clone()).collect();
+        assert_eq!(text[0], "00000000  ");
+        assert!(text[1].starts_with("48 65 6c 6c 6f 00 01 20  77 6f"));
+        assert_eq!(text[2], " │Hello.. world!.1│");
+    }
+}
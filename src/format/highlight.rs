@@ -0,0 +1,73 @@
+//! 代码语法高亮
+//!
+//! 通过 syntect 按语言标记（如 `rust`、`py`）逐行高亮，配色方案取自配置的 `[syntax]`。
+//! 语法与配色定义在首次使用时加载，之后全局共享。
+
+use crate::config;
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// 高亮后的片段：文本、前景色、是否加粗
+pub type Token = (String, (u8, u8, u8), bool);
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn themes() -> &'static ThemeSet {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    THEMES.get_or_init(ThemeSet::load_defaults)
+}
+
+/// 按语言高亮代码，返回每行的片段（不含换行符）；未启用高亮或不认识语言时返回 None
+pub fn highlight(code: &str, lang: &str) -> Option<Vec<Vec<Token>>> {
+    let options = &config::get().syntax;
+    if !options.enabled {
+        return None;
+    }
+    let syntaxes = syntaxes();
+    let syntax = syntaxes.find_syntax_by_token(lang)?;
+    let themes = themes();
+    // 不认识的配色方案名使用默认配色
+    let theme = themes
+        .themes
+        .get(&options.theme)
+        .or_else(|| themes.themes.get(&config::SyntaxOptions::default().theme))?;
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut lines = Vec::new();
+    for line in LinesWithEndings::from(code) {
+        let regions = highlighter.highlight_line(line, syntaxes).ok()?;
+        let tokens = regions
+            .into_iter()
+            .map(|(style, text)| (text.trim_end_matches(['\n', '\r']), style))
+            .filter(|(text, _)| !text.is_empty())
+            .map(|(text, style)| {
+                let fg = style.foreground;
+                (text.to_string(), (fg.r, fg.g, fg.b), style.font_style.contains(FontStyle::BOLD))
+            })
+            .collect();
+        lines.push(tokens);
+    }
+    Some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight() {
+        let lines = highlight("fn main() {\n    let x = 1;\n}\n", "rust").unwrap();
+        assert_eq!(lines.len(), 3);
+        let text: String = lines[1].iter().map(|(t, _, _)| t.as_str()).collect();
+        assert_eq!(text, "    let x = 1;");
+        // 关键字与数字的颜色不同
+        let color = |s: &str| lines[1].iter().find(|(t, _, _)| t == s).map(|(_, c, _)| *c);
+        assert_ne!(color("let"), color("1"));
+        assert!(highlight("x", "no-such-language").is_none());
+    }
+}
@@ -0,0 +1,519 @@
+//! HTML / XHTML 转换为文档行
+//!
+//! 容错的简易转换：不构建 DOM，按标签流维护块级状态，标签不配对时尽量保留文本。
+//! 支持段落、标题、强调、列表、引用、预格式文本与表格，忽略脚本、样式与 `<head>`。
+
+use crate::document::*;
+use crate::format::markdown::BULLETS;
+use crate::format::table;
+use crate::t;
+use unicode_width::UnicodeWidthStr;
+
+/// 内容被忽略的标签
+const SKIPPED: &[&str] = &["head", "script", "style", "template"];
+
+/// 前后断行的块级标签
+const BLOCKS: &[&str] = &[
+    "address", "article", "aside", "body", "dd", "div", "dl", "dt", "figcaption", "figure",
+    "footer", "header", "main", "nav", "section",
+];
+
+/// 转换 HTML 片段或完整页面
+pub fn parse_html(content: &str) -> Document {
+    let mut state = HtmlState::default();
+    let mut rest = content;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |i| &after[i + 3..]);
+        } else if rest.starts_with('<') {
+            let Some(end) = rest.find('>') else {
+                state.text(rest);
+                break;
+            };
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+            // 脚本与样式的内容可能含有 `<`，直接跳到结束标签
+            if let Some(name) = state.tag(tag) {
+                let close = format!("</{name}");
+                let at = rest.to_ascii_lowercase().find(&close).unwrap_or(rest.len());
+                rest = &rest[at..];
+            }
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            state.text(&rest[..end]);
+            rest = &rest[end..];
+        }
+    }
+    state.flush();
+    while state.doc.lines.last().is_some_and(|l| l.spans.is_empty()) {
+        state.doc.lines.pop();
+    }
+    state.doc
+}
+
+struct ListContext {
+    ordered: bool,
+    index: u64,
+}
+
+#[derive(Default)]
+struct HtmlState {
+    doc: Document,
+    line: RenderLine,
+    /// 文本前有空白，输出下一段文本前补一个空格
+    space: bool,
+    bold: usize,
+    italic: usize,
+    code: usize,
+    link: usize,
+    quote: usize,
+    pre: usize,
+    heading: Option<u8>,
+    /// 引用、列表等带来的缩进
+    indent: u16,
+    /// 列表条目续行的悬挂缩进
+    hang: u16,
+    lists: Vec<ListContext>,
+    table: Option<Vec<Vec<String>>>,
+    cell: Option<String>,
+}
+
+impl HtmlState {
+    fn style(&self) -> TextStyle {
+        let mut style = TextStyle::NONE;
+        if self.bold > 0 {
+            style.insert(TextStyle::BOLD);
+        }
+        if self.italic > 0 {
+            style.insert(TextStyle::ITALIC);
+        }
+        if self.code > 0 || self.pre > 0 {
+            style.insert(TextStyle::CODE);
+        }
+        if self.link > 0 {
+            style.insert(TextStyle::LINK);
+        }
+        if self.quote > 0 {
+            style.insert(TextStyle::QUOTE);
+        }
+        if self.heading.is_some() {
+            style.insert(TextStyle::BOLD | TextStyle::HEADING);
+        }
+        style
+    }
+
+    /// 处理一个标签；返回需要原样跳过内容的标签名（如 `script`）
+    fn tag(&mut self, tag: &str) -> Option<String> {
+        if tag.starts_with(['!', '?']) {
+            return None;
+        }
+        let closing = tag.starts_with('/');
+        let body = tag.trim_start_matches('/');
+        let raw_name: String = body
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == ':')
+            .collect();
+        // 去掉命名空间前缀（如 `epub:switch`）
+        let name = raw_name.rsplit(':').next().unwrap_or_default().to_ascii_lowercase();
+        let self_closing = body.ends_with('/');
+
+        if SKIPPED.contains(&name.as_str()) {
+            if !closing && !self_closing {
+                return Some(name);
+            }
+            return None;
+        }
+        if closing {
+            self.end(&name);
+        } else {
+            self.start(&name, body);
+            if self_closing {
+                self.end(&name);
+            }
+        }
+        None
+    }
+
+    fn start(&mut self, name: &str, tag: &str) {
+        match name {
+            "p" => self.block(),
+            "br" if self.cell.is_none() => self.flush_line(),
+            "hr" => {
+                self.block();
+                self.doc.rules.push(self.doc.lines.len());
+                self.push_span("────────────────────────────────", TextStyle::DIM);
+                self.flush();
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block();
+                self.heading = name[1..].parse().ok();
+            }
+            "b" | "strong" => self.bold += 1,
+            "i" | "em" | "cite" | "var" | "dfn" => self.italic += 1,
+            "code" | "kbd" | "samp" | "tt" => self.code += 1,
+            "a" => self.link += 1,
+            "img" => {
+                let alt = attr(tag, "alt").unwrap_or_default();
+                self.push_text(&t!("[图片: {}]", alt), TextStyle::DIM);
+            }
+            "blockquote" => {
+                self.block();
+                self.quote += 1;
+                self.indent += 2;
+            }
+            "pre" => {
+                self.block();
+                self.pre += 1;
+            }
+            "ul" | "ol" => {
+                self.flush();
+                self.lists.push(ListContext {
+                    ordered: name == "ol",
+                    index: attr(tag, "start").and_then(|s| s.parse().ok()).unwrap_or(1) - 1,
+                });
+            }
+            "li" => {
+                self.flush();
+                self.hang = 0;
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some(ctx) if ctx.ordered => {
+                        ctx.index += 1;
+                        format!("{}. ", ctx.index)
+                    }
+                    _ => format!("{} ", BULLETS[depth % BULLETS.len()]),
+                };
+                self.push_span(&marker, self.style());
+                self.hang = UnicodeWidthStr::width(marker.as_str()) as u16;
+            }
+            "table" => {
+                self.block();
+                self.table = Some(Vec::new());
+            }
+            "tr" => {
+                if let Some(rows) = self.table.as_mut() {
+                    rows.push(Vec::new());
+                }
+            }
+            "td" | "th" => {
+                self.end_cell();
+                self.cell = Some(String::new());
+            }
+            _ if BLOCKS.contains(&name) => {
+                self.flush();
+                if name == "dd" {
+                    self.indent += 4;
+                }
+                if name == "dt" {
+                    self.bold += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        match name {
+            "p" => self.block(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                if let Some(level) = self.heading.take() {
+                    let text: String = self.line.spans.iter().map(|s| s.text.as_str()).collect();
+                    if !text.trim().is_empty() {
+                        self.doc.headings.push(Heading {
+                            level,
+                            line: self.doc.lines.len(),
+                            text: text.trim().to_string(),
+                        });
+                    }
+                }
+                self.block();
+            }
+            "b" | "strong" => self.bold = self.bold.saturating_sub(1),
+            "i" | "em" | "cite" | "var" | "dfn" => self.italic = self.italic.saturating_sub(1),
+            "code" | "kbd" | "samp" | "tt" => self.code = self.code.saturating_sub(1),
+            "a" => self.link = self.link.saturating_sub(1),
+            "blockquote" => {
+                self.block();
+                self.quote = self.quote.saturating_sub(1);
+                self.indent = self.indent.saturating_sub(2);
+            }
+            "pre" => {
+                self.block();
+                self.pre = self.pre.saturating_sub(1);
+            }
+            "ul" | "ol" => {
+                self.flush();
+                self.hang = 0;
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank();
+                }
+            }
+            "li" => {
+                self.flush();
+                self.hang = 0;
+            }
+            "td" | "th" => self.end_cell(),
+            "table" => {
+                self.end_cell();
+                if let Some(rows) = self.table.take() {
+                    let rows: Vec<_> = rows.into_iter().filter(|r| !r.is_empty()).collect();
+                    table::push_table(&mut self.doc, &rows, &[], self.indent);
+                    self.blank();
+                }
+            }
+            _ if BLOCKS.contains(&name) => {
+                self.flush();
+                if name == "dd" {
+                    self.indent = self.indent.saturating_sub(4);
+                }
+                if name == "dt" {
+                    self.bold = self.bold.saturating_sub(1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end_cell(&mut self) {
+        if let Some(cell) = self.cell.take()
+            && let Some(row) = self.table.as_mut().and_then(|rows| rows.last_mut())
+        {
+            row.push(cell.trim().to_string());
+        }
+    }
+
+    fn text(&mut self, raw: &str) {
+        if raw.is_empty() {
+            return;
+        }
+        let text = decode_entities(raw);
+        if self.pre > 0 {
+            for (i, part) in text.split('\n').enumerate() {
+                if i > 0 {
+                    self.flush_line();
+                }
+                self.push_span(part, self.style());
+            }
+            return;
+        }
+
+        // 连续空白折叠为一个空格（不含不换行空格）
+        let is_space = |c: char| c.is_ascii_whitespace();
+        let mut space = self.space || text.starts_with(is_space);
+        let mut collapsed = String::new();
+        for word in text.split(is_space).filter(|w| !w.is_empty()) {
+            if space {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+            space = true;
+        }
+        self.space = if collapsed.is_empty() { space } else { text.ends_with(is_space) };
+        if let Some(cell) = self.cell.as_mut() {
+            cell.push_str(&collapsed);
+            return;
+        }
+        if self.table.is_some() {
+            return;
+        }
+        let style = self.style();
+        self.push_text(&collapsed, style);
+    }
+
+    /// 输出折叠空白后的文本，行首不保留空格
+    fn push_text(&mut self, text: &str, style: TextStyle) {
+        if let Some(cell) = self.cell.as_mut() {
+            cell.push_str(text);
+            return;
+        }
+        let has_content = self.line.spans.iter().any(|s| !s.text.trim().is_empty());
+        let text = if has_content { text } else { text.trim_start() };
+        self.push_span(text, style);
+    }
+
+    fn push_span(&mut self, text: &str, style: TextStyle) {
+        if text.is_empty() {
+            return;
+        }
+        if self.line.spans.is_empty() {
+            self.line.indent = self.indent + self.list_indent() + self.hang;
+        }
+        match self.line.spans.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(text),
+            _ => self.line.spans.push(TextSpan::new(text, style)),
+        }
+    }
+
+    /// 列表嵌套带来的缩进（不含当前条目的标记）
+    fn list_indent(&self) -> u16 {
+        self.lists.len().saturating_sub(1) as u16 * 2
+    }
+
+    /// 输出当前行（含空行），用于 `<br>` 与预格式文本
+    fn flush_line(&mut self) {
+        if self.line.spans.is_empty() {
+            self.line.indent = self.indent + self.list_indent() + self.hang;
+        }
+        self.doc.lines.push(std::mem::take(&mut self.line));
+        self.space = false;
+    }
+
+    /// 当前行有内容时输出
+    fn flush(&mut self) {
+        if !self.line.spans.is_empty() {
+            self.flush_line();
+        }
+        self.space = false;
+    }
+
+    /// 段落等块的边界：结束当前行，列表外再空一行
+    fn block(&mut self) {
+        self.flush();
+        if self.lists.is_empty() {
+            self.blank();
+        }
+    }
+
+    /// 追加一个空行，已有空行时不重复
+    fn blank(&mut self) {
+        if self.doc.lines.last().is_some_and(|l| !l.spans.is_empty()) {
+            self.doc.lines.push(RenderLine::default());
+        }
+    }
+}
+
+/// 读取标签属性值（引号可省略）
+fn attr(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(found) = lower[from..].find(name) {
+        let at = from + found;
+        from = at + name.len();
+        let before = lower[..at].chars().next_back();
+        let rest = lower[from..].trim_start();
+        if !before.is_some_and(char::is_whitespace) || !rest.starts_with('=') {
+            continue;
+        }
+        let value = tag[tag.len() - rest.len() + 1..].trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+            _ => value.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or_default(),
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+/// 解码字符实体，未知的实体原样保留
+pub fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let entity = rest[1..]
+            .bytes()
+            .take(12)
+            .position(|b| b == b';')
+            .and_then(|end| Some((entity_char(&rest[1..end + 1])?, end + 2)));
+        match entity {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn entity_char(name: &str) -> Option<char> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "middot" => '·',
+        "bull" => '•',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "times" => '×',
+        _ => return None,
+    };
+    Some(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(doc: &Document) -> Vec<String> {
+        doc.lines
+            .iter()
+            .map(|l| {
+                let text: String = l.spans.iter().map(|s| s.text.as_str()).collect();
+                format!("{}{}", " ".repeat(l.indent as usize), text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_html() {
+        let html = r#"<?xml version="1.0"?><!DOCTYPE html>
+            <html><head><title>忽略</title><style>p { color: red }</style></head><body>
+            <h1 id="c1">第一章</h1>
+            <p>正文   <b>粗体</b>&amp;<em>斜体</em>&#x4E2D;</p>
+            <!-- 注释 -->
+            <ul><li>一</li><li>二<ol start="3"><li>三</li></ol></li></ul>
+            <pre>a  b
+c</pre>
+            <table><tr><th>k</th><th>v</th></tr><tr><td>x</td><td> 1 </td></tr></table>
+            <p>图<img src="a.png" alt="示意"/></p>
+            </body></html>"#;
+        let doc = parse_html(html);
+        assert_eq!(
+            texts(&doc),
+            [
+                "第一章", "", "正文 粗体&斜体中", "", "• 一", "• 二", "  3. 三", "", "a  b", "c", "",
+                "k  v", "─  ─", "x  1", "", "图[图片: 示意]",
+            ]
+        );
+        assert_eq!(doc.headings.len(), 1);
+        assert_eq!(doc.headings[0].text, "第一章");
+        assert!(doc.lines[2].spans[1].style.contains(TextStyle::BOLD));
+        assert_eq!(doc.tables.len(), 1);
+    }
+
+    #[test]
+    fn test_attr_and_entities() {
+        assert_eq!(attr(r#"img src="a.png" alt='x &lt; y'"#, "alt").as_deref(), Some("x < y"));
+        assert_eq!(attr("ol start=3", "start").as_deref(), Some("3"));
+        assert_eq!(attr(r#"img data-alt="no""#, "alt"), None);
+        assert_eq!(decode_entities("a &unknown; &#65;&"), "a &unknown; A&");
+    }
+}
@@ -0,0 +1,362 @@
+//! iCalendar 日程解析
+//!
+//! 读取 `VEVENT` 的开始 / 结束时间、摘要、地点与描述，按开始时间排序后逐日列出，
+//! 每天一个标题，每个日程记录为章节，预览模式中用 `(` / `)` 在日程间跳转。
+//! 时间按文件中的写法显示，不做时区换算，`Z` 与 `TZID` 标注在行尾。
+
+use crate::document::*;
+use crate::format::archive::days_from_civil;
+use crate::format::table::pad_to_width;
+use crate::format::xlsx::civil_from_days;
+use crate::format::{FileFormat, FormatError};
+use crate::t;
+use std::path::Path;
+use unicode_width::UnicodeWidthStr;
+
+pub struct IcsFormat;
+
+impl FileFormat for IcsFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let content = std::fs::read_to_string(path)?;
+        parse_calendar(&content)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ics", "ical", "ifb"]
+    }
+
+    fn name(&self) -> &'static str {
+        "ics"
+    }
+}
+
+/// 日期时间：距 1970-01-01 的天数、当天的秒数（全天日程没有）与时区
+#[derive(Clone, Debug, PartialEq)]
+struct Stamp {
+    days: i64,
+    secs: Option<i64>,
+    zone: Option<String>,
+}
+
+#[derive(Default)]
+struct Event {
+    start: Option<Stamp>,
+    end: Option<Stamp>,
+    duration: Option<i64>,
+    summary: String,
+    location: String,
+    description: String,
+    cancelled: bool,
+}
+
+pub fn parse_calendar(content: &str) -> Result<Document, FormatError> {
+    if !content.trim_start_matches('\u{feff}').trim_start().starts_with("BEGIN:VCALENDAR") {
+        return Err(FormatError::Parse(t!("iCalendar 解析失败: {}", "BEGIN:VCALENDAR")));
+    }
+
+    let mut name = None;
+    let mut events = Vec::new();
+    let mut current: Option<Event> = None;
+    // 嵌套在日程中的 VALARM 等组件的属性不属于日程本身
+    let mut nested = 0;
+    for line in unfold(content) {
+        let Some((key, params, value)) = split_line(&line) else {
+            continue;
+        };
+        match (key.as_str(), value) {
+            ("BEGIN", "VEVENT") => current = Some(Event::default()),
+            ("END", "VEVENT") => events.extend(current.take()),
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", _) if current.is_some() => nested -= 1,
+            ("X-WR-CALNAME", value) if current.is_none() => name = Some(unescape(value)),
+            (key, value) if nested == 0 => {
+                let Some(event) = current.as_mut() else {
+                    continue;
+                };
+                match key {
+                    "DTSTART" => event.start = parse_stamp(value, &params),
+                    "DTEND" => event.end = parse_stamp(value, &params),
+                    "DURATION" => event.duration = parse_duration(value),
+                    "SUMMARY" => event.summary = unescape(value),
+                    "LOCATION" => event.location = unescape(value),
+                    "DESCRIPTION" => event.description = unescape(value),
+                    "STATUS" => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut events: Vec<(Stamp, Event)> = events
+        .into_iter()
+        .filter_map(|mut e| e.start.take().map(|start| (start, e)))
+        .collect();
+    events.sort_by_key(|(start, _)| (start.days, start.secs.unwrap_or(-1)));
+    Ok(render(name.as_deref(), &events))
+}
+
+fn render(name: Option<&str>, events: &[(Stamp, Event)]) -> Document {
+    let mut doc = Document::default();
+    let day_level = if let Some(name) = name {
+        doc.headings.push(Heading {
+            level: 1,
+            line: 0,
+            text: name.to_string(),
+        });
+        push(&mut doc, vec![TextSpan::new(name, TextStyle::BOLD | TextStyle::HEADING)], 0);
+        doc.lines.push(RenderLine::default());
+        2
+    } else {
+        1
+    };
+    if events.is_empty() {
+        push(&mut doc, vec![TextSpan::new(t!("（没有日程）"), TextStyle::DIM)], 0);
+        return doc;
+    }
+
+    let times: Vec<String> = events.iter().map(|(start, e)| time_range(start, e)).collect();
+    let width = times.iter().map(|t| t.width()).max().unwrap_or(0);
+    let mut day = None;
+    for ((start, event), time) in events.iter().zip(times) {
+        if day != Some(start.days) {
+            if day.is_some() {
+                doc.lines.push(RenderLine::default());
+            }
+            day = Some(start.days);
+            let text = format!("{} {}", date(start.days), weekday(start.days));
+            doc.headings.push(Heading {
+                level: day_level,
+                line: doc.lines.len(),
+                text: text.clone(),
+            });
+            push(&mut doc, vec![TextSpan::new(text, TextStyle::BOLD | TextStyle::HEADING)], 0);
+        }
+
+        doc.sections.push(doc.lines.len());
+        let summary = if event.summary.is_empty() { t!("（无标题）") } else { &event.summary };
+        let summary_style = if event.cancelled { TextStyle::REMOVED } else { TextStyle::BOLD };
+        let mut spans = vec![
+            TextSpan::new(format!("{}  ", pad_to_width(&time, width)), TextStyle::NUMBER),
+            TextSpan::new(summary, summary_style),
+        ];
+        if let Some(zone) = &start.zone {
+            spans.push(TextSpan::new(format!("  ({zone})"), TextStyle::DIM));
+        }
+        push(&mut doc, spans, 2);
+
+        let indent = 2 + width as u16 + 2;
+        if !event.location.is_empty() {
+            push(&mut doc, vec![TextSpan::new(format!("@ {}", event.location), TextStyle::DIM)], indent);
+        }
+        for line in event.description.lines().filter(|l| !l.trim().is_empty()) {
+            push(&mut doc, vec![TextSpan::new(line.trim_end(), TextStyle::DIM)], indent);
+        }
+    }
+    doc
+}
+
+fn push(doc: &mut Document, spans: Vec<TextSpan>, indent: u16) {
+    doc.lines.push(RenderLine { spans, indent });
+}
+
+/// 时间段：同一天为 `09:00–10:30`，跨天时写出结束日期；全天日程的结束日期不含在内
+fn time_range(start: &Stamp, event: &Event) -> String {
+    let end = event.end.clone().or_else(|| {
+        let duration = event.duration?;
+        let total = start.days * 86400 + start.secs.unwrap_or(0) + duration;
+        Some(Stamp {
+            days: total.div_euclid(86400),
+            secs: start.secs.map(|_| total.rem_euclid(86400)),
+            zone: None,
+        })
+    });
+    let Some(start_secs) = start.secs else {
+        return match end {
+            Some(end) if end.days > start.days + 1 => format!("{} → {}", t!("全天"), date(end.days - 1)),
+            _ => t!("全天").to_string(),
+        };
+    };
+    match end.and_then(|end| end.secs.map(|secs| (end.days, secs))) {
+        Some((days, secs)) if days == start.days => format!("{}–{}", clock(start_secs), clock(secs)),
+        Some((days, secs)) => format!("{} → {} {}", clock(start_secs), date(days), clock(secs)),
+        None => clock(start_secs),
+    }
+}
+
+fn date(days: i64) -> String {
+    let (y, m, d) = civil_from_days(days);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+fn clock(secs: i64) -> String {
+    format!("{:02}:{:02}", secs / 3600, secs % 3600 / 60)
+}
+
+fn weekday(days: i64) -> &'static str {
+    // 1970-01-01 是星期四
+    let names = [t!("周一"), t!("周二"), t!("周三"), t!("周四"), t!("周五"), t!("周六"), t!("周日")];
+    names[(days + 3).rem_euclid(7) as usize]
+}
+
+/// 展开折行：以空格或制表符开头的行接在上一行之后
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// 属性参数，参数名为大写
+type Params = Vec<(String, String)>;
+
+/// 拆分内容行 `NAME;PARAM=VALUE:值`，属性名转为大写
+fn split_line(line: &str) -> Option<(String, Params, &str)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let mut fields = line[..colon].split(';');
+    let key = fields.next()?.trim().to_uppercase();
+    let params = fields
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.to_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some((key, params, &line[colon + 1..]))
+}
+
+/// `20240305`、`20240305T090000` 或 `20240305T090000Z`
+fn parse_stamp(value: &str, params: &[(String, String)]) -> Option<Stamp> {
+    let value = value.trim();
+    let num = |range: std::ops::Range<usize>| value.get(range)?.parse::<i64>().ok();
+    let days = days_from_civil(num(0..4)?, num(4..6)?, num(6..8)?);
+    let secs = match value.get(8..9) {
+        Some("T") => Some(num(9..11)? * 3600 + num(11..13)? * 60 + num(13..15).unwrap_or(0)),
+        _ => None,
+    };
+    let zone = if value.ends_with('Z') {
+        Some("UTC".to_string())
+    } else {
+        params.iter().find(|(k, _)| k == "TZID").map(|(_, v)| v.clone())
+    };
+    Some(Stamp { days, secs, zone })
+}
+
+/// `P1D`、`PT1H30M`、`P1W` 等时长转为秒数
+fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut total = 0;
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = std::mem::take(&mut number).parse().ok()?;
+                total += n * match unit {
+                    'W' => 7 * 86400,
+                    'D' => 86400,
+                    'H' => 3600,
+                    'M' => 60,
+                    'S' => 1,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(sign * total)
+}
+
+/// 还原文本值中的转义：`\n`、`\,`、`\;`、`\\`
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_calendar() {
+        let content = "BEGIN:VCALENDAR\r\n\
+X-WR-CALNAME:工作\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART;TZID=Asia/Shanghai:20240306T140000\r\n\
+DURATION:PT1H30M\r\n\
+SUMMARY:评审\\, 第二轮\r\n\
+LOCATION:3 号会议室\r\n\
+DESCRIPTION:议程：\\n- 接口\r\n\
+\x20设计\r\n\
+BEGIN:VALARM\r\n\
+DESCRIPTION:提醒\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART;VALUE=DATE:20240305\r\n\
+DTEND;VALUE=DATE:20240307\r\n\
+SUMMARY:出差\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART:20240306T090000Z\r\n\
+DTEND:20240306T093000Z\r\n\
+SUMMARY:站会\r\n\
+STATUS:CANCELLED\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let doc = parse_calendar(content).unwrap();
+        let lines: Vec<String> = doc
+            .lines
+            .iter()
+            .map(|l| {
+                let text: String = l.spans.iter().map(|s| s.text.as_str()).collect();
+                format!("{}{}", " ".repeat(l.indent as usize), text.trim_end())
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "工作",
+                "",
+                "2024-03-05 周二",
+                "  全天 → 2024-03-06  出差",
+                "",
+                "2024-03-06 周三",
+                "  09:00–09:30        站会  (UTC)",
+                "  14:00–15:30        评审, 第二轮  (Asia/Shanghai)",
+                "                     @ 3 号会议室",
+                "                     议程：",
+                "                     - 接口设计",
+            ]
+        );
+        assert_eq!(doc.sections, [3, 6, 7]);
+        assert_eq!(doc.headings[1].level, 2);
+        assert_eq!(doc.lines[6].spans[1].style, TextStyle::REMOVED);
+        assert!(parse_calendar("hello").is_err());
+    }
+}
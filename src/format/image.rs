@@ -1,5 +1,24 @@
-use std::io;
+use crate::document::*;
+use crate::format::dir::human_size;
+use crate::format::magic;
+use crate::t;
+use exif::{In, Tag};
+use image::{DynamicImage, ImageDecoder, ImageReader, RgbaImage};
+use resvg::{tiny_skia, usvg};
+use std::fs::File;
+use std::io::{self, BufReader, Write};
 use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use unicode_width::UnicodeWidthStr;
+
+/// SVG 光栅化后长边的像素数，终端按字符块或图片协议再缩放
+const SVG_RASTER_SIZE: f32 = 1024.0;
+
+/// 估算内嵌图片行数时假定的显示宽度（列）
+const FIT_COLUMNS: f32 = 80.0;
+
+/// 一行字符大约对应的像素高度，小图标不放大到超过原尺寸
+const CELL_PIXELS: f32 = 16.0;
 
 /// 在终端中直接显示图片
 pub fn display(path: &Path) -> io::Result<()> {
@@ -8,9 +27,249 @@ pub fn display(path: &Path) -> io::Result<()> {
         ..Default::default()
     };
 
-    viuer::print_from_file(path, &conf).map_err(|e| {
-        io::Error::new(io::ErrorKind::Other, format!("图片显示失败: {e}"))
-    })?;
+    tracing::debug!(
+        kitty = viuer::get_kitty_support() != viuer::KittySupport::None,
+        iterm = viuer::is_iterm_supported(),
+        "图片后端选择"
+    );
+    print(path, &conf)
+}
+
+/// 在终端指定区域内绘制图片（预览模式内嵌图片）
+///
+/// 图片按比例缩放到区域内。只使用字符块输出，kitty/iTerm 协议的图片
+/// 不随单元格刷新而清除，滚动时会残留。
+pub fn print_in_area(path: &Path, x: u16, y: u16, width: u16, height: u16) -> io::Result<()> {
+    let conf = viuer::Config {
+        absolute_offset: true,
+        x,
+        y: y as i16,
+        width: Some(width as u32),
+        height: Some(height as u32),
+        use_kitty: false,
+        use_iterm: false,
+        ..Default::default()
+    };
+
+    print(path, &conf)
+}
+
+/// 在终端指定区域内绘制整幅图片（如 PDF 页面），支持时使用 kitty/iTerm 图片协议，
+/// 否则退回字符块。协议图片不随重绘清除，换页或离开前需调用 [`clear_graphics`]
+pub fn print_page(path: &Path, x: u16, y: u16, width: u16, height: u16) -> io::Result<()> {
+    let conf = viuer::Config {
+        absolute_offset: true,
+        x,
+        y: y as i16,
+        width: Some(width as u32),
+        height: Some(height as u32),
+        ..Default::default()
+    };
+
+    print(path, &conf)
+}
 
+/// 删除终端中以 kitty 协议显示的所有图片
+pub fn clear_graphics() -> io::Result<()> {
+    if viuer::get_kitty_support() != viuer::KittySupport::None {
+        let mut out = io::stdout();
+        out.write_all(b"\x1b_Ga=d\x1b\\")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// 按配置输出图片，SVG 先光栅化
+fn print(path: &Path, conf: &viuer::Config) -> io::Result<()> {
+    let shown = if is_svg(path) {
+        viuer::print(&rasterize_svg(path)?, conf)
+    } else {
+        viuer::print_from_file(path, conf)
+    };
+    shown.map_err(|e| io::Error::other(t!("图片显示失败: {}", e)))?;
     Ok(())
 }
+
+fn is_svg(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("svg"))
+        || magic::detect_file_format(path) == Some(magic::DetectedFormat::Image(magic::ImageFormat::Svg))
+}
+
+/// 用 resvg 将 SVG 渲染到白色背景上，长边缩放到 [`SVG_RASTER_SIZE`]
+fn rasterize_svg(path: &Path) -> io::Result<DynamicImage> {
+    let tree = load_svg(path)?;
+    let size = tree.size();
+    let scale = SVG_RASTER_SIZE / size.width().max(size.height());
+    let width = ((size.width() * scale).ceil() as u32).max(1);
+    let height = ((size.height() * scale).ceil() as u32).max(1);
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| io::Error::other(t!("SVG 解析失败: {}", format!("{width}x{height}"))))?;
+    pixmap.fill(tiny_skia::Color::WHITE);
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+
+    // tiny-skia 的像素是预乘 alpha 的
+    let rgba = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let c = p.demultiply();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        })
+        .collect();
+    let image = RgbaImage::from_raw(width, height, rgba).expect("像素数与尺寸一致");
+    Ok(DynamicImage::ImageRgba8(image))
+}
+
+fn load_svg(path: &Path) -> io::Result<usvg::Tree> {
+    // 系统字体只加载一次，同时打开多张 SVG 时共用
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    let fontdb = FONTS.get_or_init(|| {
+        let mut db = usvg::fontdb::Database::new();
+        db.load_system_fonts();
+        Arc::new(db)
+    });
+
+    let data = std::fs::read(path)?;
+    let options = usvg::Options {
+        resources_dir: path.parent().map(Path::to_path_buf),
+        fontdb: fontdb.clone(),
+        ..Default::default()
+    };
+    usvg::Tree::from_data(&data, &options).map_err(|e| io::Error::other(t!("SVG 解析失败: {}", e)))
+}
+
+/// 内嵌图片占用的行数：按宽高比缩放到 [`FIT_COLUMNS`] 列宽（字符高约为宽的两倍），
+/// 不超过原图高度与 `max`；读取不到尺寸时为 `max`
+pub fn fit_rows(path: &Path, max: u16) -> u16 {
+    let size = if is_svg(path) {
+        load_svg(path).ok().map(|tree| (tree.size().width(), tree.size().height()))
+    } else {
+        image::image_dimensions(path).ok().map(|(w, h)| (w as f32, h as f32))
+    };
+    let Some((width, height)) = size.filter(|(w, h)| *w > 0.0 && *h > 0.0) else {
+        return max;
+    };
+    let rows = (FIT_COLUMNS * height / width / 2.0).min(height / CELL_PIXELS).ceil();
+    (rows as u16).clamp(1, max.max(1))
+}
+
+/// 图片信息：尺寸、颜色类型、格式与文件大小，以及 EXIF 中的拍摄时间、相机型号与 GPS 坐标
+pub fn info(path: &Path) -> io::Result<Vec<(&'static str, String)>> {
+    let mut rows = Vec::new();
+    if is_svg(path) {
+        let size = load_svg(path)?.size();
+        rows.push((t!("尺寸"), format!("{} × {}", size.width().round(), size.height().round())));
+        rows.push((t!("格式"), "SVG".to_string()));
+    } else {
+        let reader = ImageReader::open(path)?.with_guessed_format()?;
+        let format = reader.format();
+        let decoder = reader.into_decoder().map_err(io::Error::other)?;
+        let (width, height) = decoder.dimensions();
+        rows.push((t!("尺寸"), format!("{width} × {height}")));
+        rows.push((t!("颜色"), format!("{:?}", decoder.color_type())));
+        if let Some(format) = format {
+            rows.push((t!("格式"), format!("{format:?}").to_uppercase()));
+        }
+    }
+    rows.push((t!("大小"), human_size(std::fs::metadata(path)?.len())));
+
+    let exif = File::open(path)
+        .ok()
+        .and_then(|f| exif::Reader::new().read_from_container(&mut BufReader::new(f)).ok());
+    if let Some(exif) = exif {
+        let text = |tag| exif.get_field(tag, In::PRIMARY).and_then(|f| ascii(&f.value));
+        // EXIF 日期形如 `2024:05:01 12:00:00`
+        if let Some(date) = text(Tag::DateTimeOriginal).or_else(|| text(Tag::DateTime)) {
+            rows.push((t!("拍摄时间"), date.replacen(':', "-", 2)));
+        }
+        let camera = match (text(Tag::Make), text(Tag::Model)) {
+            // 型号通常已经以厂商名开头
+            (Some(make), Some(model)) if !model.starts_with(&make) => Some(format!("{make} {model}")),
+            (make, model) => model.or(make),
+        };
+        if let Some(camera) = camera {
+            rows.push((t!("相机"), camera));
+        }
+        if let Some((lat, lon)) = gps(&exif) {
+            rows.push(("GPS", format!("{lat:.6}, {lon:.6}")));
+        }
+    }
+    Ok(rows)
+}
+
+/// 直接模式：信息逐行输出，名称对齐
+pub fn display_info(path: &Path) -> io::Result<()> {
+    let rows = info(path)?;
+    let width = rows.iter().map(|(label, _)| label.width()).max().unwrap_or(0);
+    for (label, value) in rows {
+        println!("{}  {value}", crate::format::table::pad_to_width(label, width));
+    }
+    Ok(())
+}
+
+fn ascii(value: &exif::Value) -> Option<String> {
+    let exif::Value::Ascii(parts) = value else {
+        return None;
+    };
+    let text = String::from_utf8_lossy(parts.first()?).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// 度分秒转为带符号的十进制度数，南纬与西经为负
+fn gps(exif: &exif::Exif) -> Option<(f64, f64)> {
+    let coordinate = |tag, ref_tag, negative: &str| {
+        let exif::Value::Rational(dms) = &exif.get_field(tag, In::PRIMARY)?.value else {
+            return None;
+        };
+        let [d, m, s] = dms.get(..3)? else {
+            return None;
+        };
+        let degrees = d.to_f64() + m.to_f64() / 60.0 + s.to_f64() / 3600.0;
+        let reference = exif.get_field(ref_tag, In::PRIMARY).and_then(|f| ascii(&f.value));
+        Some(if reference.as_deref() == Some(negative) { -degrees } else { degrees })
+    };
+    Some((
+        coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef, "S")?,
+        coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef, "W")?,
+    ))
+}
+
+/// 将图片包装为文档（同时打开多个文件时在预览模式中显示），占据 `rows` 行
+pub fn to_document(path: &Path, rows: u16) -> Document {
+    let alt = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut doc = Document::default();
+    doc.lines.push(RenderLine {
+        spans: vec![TextSpan::new(t!("[图片: {}]", alt), TextStyle::DIM)],
+        indent: 0,
+    });
+    doc.lines
+        .extend((1..rows.max(1)).map(|_| RenderLine::default()));
+    doc.images.push(ImageBlock {
+        line: 0,
+        height: rows.max(1),
+        path: path.to_path_buf(),
+        alt,
+    });
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info() {
+        let path = std::env::temp_dir().join(format!("xcat-info-{}.png", std::process::id()));
+        image::save_buffer(&path, &[0; 3 * 4 * 2], 4, 2, image::ExtendedColorType::Rgb8).unwrap();
+        let rows = info(&path).unwrap();
+        assert_eq!(fit_rows(&path, 12), 1);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(rows[0], (t!("尺寸"), "4 × 2".to_string()));
+        assert_eq!(rows[1], (t!("颜色"), "Rgb8".to_string()));
+        assert_eq!(rows[2], (t!("格式"), "PNG".to_string()));
+        assert_eq!(rows.len(), 4);
+    }
+}
@@ -0,0 +1,191 @@
+//! Jupyter Notebook 解析
+//!
+//! Markdown 单元格交给 Markdown 解析器；代码单元格按内核语言作为代码块显示，前面标注执行编号，
+//! 其后依次显示文本输出（标准错误与异常信息标为删除色）。每个单元格的起始行记录为章节，
+//! 预览模式中用 `(` / `)` 在单元格间跳转。
+
+use crate::document::*;
+use crate::format::markdown::{parse_markdown, MarkdownOptions};
+use crate::format::text::clean_line;
+use crate::format::{html, FileFormat, FormatError};
+use crate::t;
+use serde_json::Value;
+use std::path::Path;
+
+pub struct IpynbFormat {
+    pub options: MarkdownOptions,
+}
+
+impl FileFormat for IpynbFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let content = std::fs::read_to_string(path)?;
+        let notebook: Value = serde_json::from_str(&content)
+            .map_err(|e| FormatError::Parse(t!("Notebook 解析失败: {}", e)))?;
+        let mut options = self.options.clone();
+        options.base_dir = path.parent().map(Path::to_path_buf);
+        parse_notebook(&notebook, &options)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ipynb"]
+    }
+
+    fn name(&self) -> &'static str {
+        "ipynb"
+    }
+}
+
+pub fn parse_notebook(notebook: &Value, opts: &MarkdownOptions) -> Result<Document, FormatError> {
+    let cells = notebook["cells"]
+        .as_array()
+        .ok_or_else(|| FormatError::Parse(t!("Notebook 解析失败: {}", "cells")))?;
+    let metadata = &notebook["metadata"];
+    let language = metadata["kernelspec"]["language"]
+        .as_str()
+        .or(metadata["language_info"]["name"].as_str())
+        .unwrap_or("python");
+
+    let mut doc = Document::default();
+    for cell in cells {
+        let source = text(&cell["source"]);
+        if source.trim().is_empty() && cell["outputs"].as_array().is_none_or(Vec::is_empty) {
+            continue;
+        }
+        if !doc.lines.is_empty() {
+            doc.lines.push(RenderLine::default());
+        }
+        doc.sections.push(doc.lines.len());
+        match cell["cell_type"].as_str() {
+            Some("markdown") => doc.append(parse_markdown(&source, opts)),
+            Some("code") => {
+                let count = match cell["execution_count"].as_u64() {
+                    Some(n) => n.to_string(),
+                    None => " ".into(),
+                };
+                push_text(&mut doc, &format!("In [{count}]:"), TextStyle::DIM);
+                doc.append(parse_markdown(&fenced(&source, language), opts));
+                push_outputs(&mut doc, cell["outputs"].as_array().map_or(&[], Vec::as_slice), &count, opts);
+            }
+            // raw 单元格原样显示
+            _ => {
+                for line in source.lines() {
+                    push_text(&mut doc, line, TextStyle::NONE);
+                }
+            }
+        }
+    }
+    Ok(doc)
+}
+
+fn push_outputs(doc: &mut Document, outputs: &[Value], count: &str, opts: &MarkdownOptions) {
+    for output in outputs {
+        match output["output_type"].as_str() {
+            Some("stream") => {
+                let style = match output["name"].as_str() {
+                    Some("stderr") => TextStyle::REMOVED,
+                    _ => TextStyle::NONE,
+                };
+                push_lines(doc, &text(&output["text"]), style);
+            }
+            Some("error") => {
+                let traceback: Vec<String> = output["traceback"]
+                    .as_array()
+                    .map(|lines| lines.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                    .unwrap_or_default();
+                push_lines(doc, &traceback.join("\n"), TextStyle::REMOVED);
+            }
+            Some(kind @ ("execute_result" | "display_data")) => {
+                if kind == "execute_result" {
+                    push_text(doc, &format!("Out[{count}]:"), TextStyle::DIM);
+                }
+                push_data(doc, &output["data"], opts);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 富输出按 纯文本 → Markdown → HTML → 占位文本 的顺序选择一种显示
+fn push_data(doc: &mut Document, data: &Value, opts: &MarkdownOptions) {
+    if data.get("text/plain").is_some() {
+        push_lines(doc, &text(&data["text/plain"]), TextStyle::NONE);
+    } else if data.get("text/markdown").is_some() {
+        doc.append(parse_markdown(&text(&data["text/markdown"]), opts));
+    } else if data.get("text/html").is_some() {
+        doc.append(html::parse_html(&text(&data["text/html"])));
+    } else if let Some(mime) = data.as_object().and_then(|d| d.keys().next()) {
+        push_text(doc, &t!("[图片: {}]", mime), TextStyle::DIM);
+    }
+}
+
+/// 单元格文本可能是字符串或字符串数组
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+/// 包成围栏代码块，围栏比代码中最长的反引号串更长
+fn fenced(source: &str, language: &str) -> String {
+    let longest = source
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{language}\n{}\n{fence}\n", source.trim_end_matches('\n'))
+}
+
+fn push_lines(doc: &mut Document, text: &str, style: TextStyle) {
+    for line in text.trim_end_matches('\n').lines() {
+        push_text(doc, &clean_line(line), style);
+    }
+}
+
+fn push_text(doc: &mut Document, text: &str, style: TextStyle) {
+    doc.lines.push(RenderLine {
+        spans: vec![TextSpan::new(text, style)],
+        indent: 0,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notebook() {
+        let notebook = serde_json::json!({
+            "metadata": {"kernelspec": {"language": "python"}},
+            "cells": [
+                {"cell_type": "markdown", "source": ["# 标题\n", "说明"]},
+                {"cell_type": "code", "execution_count": 3, "source": "print(1)\n1 + 1",
+                 "outputs": [
+                    {"output_type": "stream", "name": "stdout", "text": ["1\n"]},
+                    {"output_type": "execute_result", "data": {"text/plain": "2"}},
+                    {"output_type": "error", "traceback": ["\u{1b}[31mZeroDivisionError\u{1b}[0m"]}
+                 ]},
+                {"cell_type": "code", "execution_count": null, "source": "", "outputs": []}
+            ]
+        });
+        let doc = parse_notebook(&notebook, &MarkdownOptions::default()).unwrap();
+        let lines: Vec<String> = doc
+            .lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect())
+            .collect();
+        let second = doc.sections[1];
+        assert_eq!(doc.sections.len(), 2);
+        assert_eq!(doc.headings[0].text, "标题");
+        assert_eq!(lines[second], "In [3]:");
+        assert_eq!(doc.code_blocks.len(), 1);
+        assert_eq!(doc.code_blocks[0].lang.as_deref(), Some("python"));
+        assert_eq!(doc.code_blocks[0].text, "print(1)\n1 + 1\n");
+        let end = doc.code_blocks[0].end;
+        let outputs: Vec<&str> = lines[end..].iter().map(|l| l.trim()).filter(|l| !l.contains('─')).collect();
+        assert_eq!(outputs, ["1", "Out[3]:", "2", "ZeroDivisionError"]);
+        assert_eq!(doc.lines.last().unwrap().spans[0].style, TextStyle::REMOVED);
+    }
+}
@@ -0,0 +1,128 @@
+//! JSON 树形视图
+//!
+//! 解析后按缩进重新排版，键、字符串、数字与常量分别着色；每个非空的对象与数组
+//! 记录为可折叠区域，预览模式中用空格键折叠 / 展开。键保持文件中的顺序。
+
+use crate::document::*;
+use crate::format::{FileFormat, FormatError};
+use crate::t;
+use serde_json::Value;
+use std::path::Path;
+
+/// 每层嵌套的缩进
+const INDENT: u16 = 2;
+
+pub struct JsonFormat;
+
+impl FileFormat for JsonFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let content = std::fs::read_to_string(path)?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|e| FormatError::Parse(t!("JSON 解析失败: {}", e)))?;
+        Ok(render_value(&value))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json", "geojson", "jsonc"]
+    }
+
+    fn name(&self) -> &'static str {
+        "json"
+    }
+}
+
+/// 将 JSON 值排版为树形文档
+pub fn render_value(value: &Value) -> Document {
+    let mut doc = Document::default();
+    push_value(&mut doc, Vec::new(), value, 0, "");
+    doc.folds.sort_by_key(|f| f.start);
+    doc
+}
+
+/// 输出一个值：`prefix` 为同一行前面的键，`suffix` 为其后的逗号
+fn push_value(doc: &mut Document, mut prefix: Vec<TextSpan>, value: &Value, indent: u16, suffix: &str) {
+    let (open, close, children): (&str, &str, Vec<(Option<&str>, &Value)>) = match value {
+        Value::Object(map) => ("{", "}", map.iter().map(|(k, v)| (Some(k.as_str()), v)).collect()),
+        Value::Array(items) => ("[", "]", items.iter().map(|v| (None, v)).collect()),
+        _ => {
+            prefix.push(scalar(value));
+            prefix.push(TextSpan::new(suffix, TextStyle::NONE));
+            doc.lines.push(RenderLine { spans: prefix, indent });
+            return;
+        }
+    };
+
+    if children.is_empty() {
+        prefix.push(TextSpan::new(format!("{open}{close}{suffix}"), TextStyle::NONE));
+        doc.lines.push(RenderLine { spans: prefix, indent });
+        return;
+    }
+
+    let start = doc.lines.len();
+    prefix.push(TextSpan::new(open, TextStyle::NONE));
+    doc.lines.push(RenderLine { spans: prefix, indent });
+    let last = children.len() - 1;
+    for (i, (key, child)) in children.into_iter().enumerate() {
+        let mut spans = Vec::new();
+        if let Some(key) = key {
+            spans.push(TextSpan::new(quote(key), TextStyle::KEY));
+            spans.push(TextSpan::new(": ", TextStyle::NONE));
+        }
+        push_value(doc, spans, child, indent + INDENT, if i < last { "," } else { "" });
+    }
+    doc.lines.push(RenderLine {
+        spans: vec![TextSpan::new(format!("{close}{suffix}"), TextStyle::NONE)],
+        indent,
+    });
+    doc.folds.push(Fold {
+        start,
+        end: doc.lines.len(),
+    });
+}
+
+fn scalar(value: &Value) -> TextSpan {
+    match value {
+        Value::String(s) => TextSpan::new(quote(s), TextStyle::STRING),
+        Value::Number(n) => TextSpan::new(n.to_string(), TextStyle::NUMBER),
+        Value::Bool(b) => TextSpan::new(b.to_string(), TextStyle::CONSTANT),
+        _ => TextSpan::new("null", TextStyle::CONSTANT),
+    }
+}
+
+/// 加引号并转义，与 JSON 源文本的写法一致
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| format!("\"{s}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_value() {
+        let value: Value =
+            serde_json::from_str(r#"{"z": 1, "a": [true, null, "x\"y"], "e": {}, "n": {"k": 1.5}}"#).unwrap();
+        let doc = render_value(&value);
+        let lines: Vec<String> = doc
+            .lines
+            .iter()
+            .map(|l| {
+                let text: String = l.spans.iter().map(|s| s.text.as_str()).collect();
+                format!("{}{}", " ".repeat(l.indent as usize), text)
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "{", "  \"z\": 1,", "  \"a\": [", "    true,", "    null,", "    \"x\\\"y\"", "  ],",
+                "  \"e\": {},", "  \"n\": {", "    \"k\": 1.5", "  }", "}",
+            ]
+        );
+        assert_eq!(
+            doc.folds,
+            [Fold { start: 0, end: 12 }, Fold { start: 2, end: 7 }, Fold { start: 8, end: 11 }]
+        );
+        assert!(doc.lines[1].spans[0].style.contains(TextStyle::KEY));
+        assert!(doc.lines[1].spans[2].style.contains(TextStyle::NUMBER));
+    }
+}
@@ -0,0 +1,377 @@
+//! 日志文件
+//!
+//! 按行识别三种日志：JSON 行与 logfmt（`key=value` 序列）解析为时间、级别、消息与其余字段，
+//! 消息补齐到同一宽度，字段排成对齐的 `key=value`；其他行（syslog 等）只高亮行首时间戳与级别词。
+//! 级别着色：ERROR 红色、WARN 黄色、INFO 默认、DEBUG 整行变暗。
+
+use crate::document::*;
+use crate::format::table::pad_to_width;
+use crate::format::text::clean_line;
+use crate::format::{FileFormat, FormatError};
+use serde_json::{Map, Value};
+use std::path::Path;
+use unicode_width::UnicodeWidthStr;
+
+/// 消息列对齐宽度的上限，更长的消息不参与对齐
+const MAX_MESSAGE_WIDTH: usize = 48;
+
+/// 普通行中只在时间戳之后的前几个词里查找级别，避免误认正文中的单词
+const LEVEL_WORDS: usize = 4;
+
+const TIME_KEYS: &[&str] = &["time", "ts", "timestamp", "@timestamp", "t", "date"];
+const LEVEL_KEYS: &[&str] = &["level", "lvl", "severity", "levelname", "log.level"];
+const MESSAGE_KEYS: &[&str] = &["msg", "message", "@message"];
+
+pub struct LogFormat;
+
+impl FileFormat for LogFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let data = std::fs::read(path)?;
+        Ok(parse_log(&String::from_utf8_lossy(&data)))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["log"]
+    }
+
+    fn name(&self) -> &'static str {
+        "log"
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    fn parse(word: &str) -> Option<Self> {
+        match word.to_ascii_uppercase().as_str() {
+            "ERROR" | "ERR" | "FATAL" | "CRITICAL" | "CRIT" | "PANIC" | "ALERT" | "EMERG" => Some(Self::Error),
+            "WARN" | "WARNING" => Some(Self::Warn),
+            "INFO" | "NOTICE" => Some(Self::Info),
+            "DEBUG" | "TRACE" | "VERBOSE" => Some(Self::Debug),
+            _ => None,
+        }
+    }
+
+    fn style(self) -> TextStyle {
+        match self {
+            Self::Error => TextStyle::CAUTION | TextStyle::BOLD,
+            Self::Warn => TextStyle::WARNING | TextStyle::BOLD,
+            Self::Info => TextStyle::NONE,
+            Self::Debug => TextStyle::DIM,
+        }
+    }
+}
+
+/// JSON 或 logfmt 行解析出的记录
+struct Entry {
+    time: Option<String>,
+    level: Option<(String, Option<Level>)>,
+    message: Option<String>,
+    fields: Vec<(String, String, TextStyle)>,
+}
+
+impl Entry {
+    /// 从键值对中取出时间、级别与消息，其余作为字段
+    fn from_pairs(pairs: Vec<(String, String, TextStyle)>) -> Self {
+        let mut entry = Entry {
+            time: None,
+            level: None,
+            message: None,
+            fields: Vec::new(),
+        };
+        for (key, value, style) in pairs {
+            let lower = key.to_ascii_lowercase();
+            if entry.time.is_none() && TIME_KEYS.contains(&lower.as_str()) {
+                entry.time = Some(value);
+            } else if entry.level.is_none() && LEVEL_KEYS.contains(&lower.as_str()) {
+                let level = Level::parse(&value);
+                entry.level = Some((value.to_ascii_uppercase(), level));
+            } else if entry.message.is_none() && MESSAGE_KEYS.contains(&lower.as_str()) {
+                entry.message = Some(value);
+            } else if style == TextStyle::STRING {
+                // 字段中的字符串含空白时加引号，其余原样显示
+                entry.fields.push((key, quote(&value), style));
+            } else {
+                entry.fields.push((key, value, style));
+            }
+        }
+        entry
+    }
+}
+
+pub fn parse_log(content: &str) -> Document {
+    let lines: Vec<String> = content.lines().map(clean_line).collect();
+    let entries: Vec<Option<Entry>> = lines
+        .iter()
+        .map(|line| parse_json(line).or_else(|| parse_logfmt(line)))
+        .collect();
+    let message_width = entries
+        .iter()
+        .flatten()
+        .filter_map(|e| e.message.as_ref().map(|m| m.width()))
+        .filter(|&w| w <= MAX_MESSAGE_WIDTH)
+        .max()
+        .unwrap_or(0);
+    let level_width = entries
+        .iter()
+        .flatten()
+        .filter_map(|e| e.level.as_ref().map(|(l, _)| l.width()))
+        .max()
+        .unwrap_or(0);
+
+    let mut doc = Document::default();
+    for (line, entry) in lines.iter().zip(entries) {
+        let spans = match entry {
+            Some(entry) => entry_spans(entry, level_width, message_width),
+            None => plain_spans(line),
+        };
+        doc.lines.push(RenderLine { spans, indent: 0 });
+    }
+    doc
+}
+
+/// 结构化记录：时间、级别与消息各占一列，其后是对齐的 `key=value` 字段
+fn entry_spans(entry: Entry, level_width: usize, message_width: usize) -> Vec<TextSpan> {
+    let dim = match entry.level {
+        Some((_, Some(Level::Debug))) => TextStyle::DIM,
+        _ => TextStyle::NONE,
+    };
+    let mut spans = Vec::new();
+    if let Some(time) = entry.time {
+        spans.push(TextSpan::new(time, TextStyle::NUMBER | dim));
+        spans.push(TextSpan::new(" ", TextStyle::NONE));
+    }
+    if let Some((text, level)) = entry.level {
+        let style = level.map_or(TextStyle::NONE, Level::style);
+        spans.push(TextSpan::new(pad_to_width(&text, level_width), style));
+        spans.push(TextSpan::new(" ", TextStyle::NONE));
+    }
+    let message = entry.message.unwrap_or_default();
+    let message = if entry.fields.is_empty() { message } else { pad_to_width(&message, message_width) };
+    spans.push(TextSpan::new(message, dim));
+    for (key, value, style) in entry.fields {
+        spans.push(TextSpan::new(" ", TextStyle::NONE));
+        spans.push(TextSpan::new(key, TextStyle::KEY | dim));
+        spans.push(TextSpan::new("=", TextStyle::DIM));
+        spans.push(TextSpan::new(value, style | dim));
+    }
+    spans.retain(|s| !s.text.is_empty());
+    spans
+}
+
+/// 普通行：高亮行首的时间戳与前几个词中的级别，DEBUG 行整行变暗
+fn plain_spans(line: &str) -> Vec<TextSpan> {
+    if line.is_empty() {
+        return Vec::new();
+    }
+    let time_len = timestamp_len(line).unwrap_or(0);
+    let found = find_level(line, time_len);
+    let dim = match found {
+        Some((_, Level::Debug)) => TextStyle::DIM,
+        _ => TextStyle::NONE,
+    };
+    let mut spans = vec![TextSpan::new(&line[..time_len], TextStyle::NUMBER | dim)];
+    match found {
+        Some((range, level)) => {
+            spans.push(TextSpan::new(&line[time_len..range.start], dim));
+            spans.push(TextSpan::new(&line[range.clone()], level.style()));
+            spans.push(TextSpan::new(&line[range.end..], dim));
+        }
+        None => spans.push(TextSpan::new(&line[time_len..], TextStyle::NONE)),
+    }
+    spans.retain(|s| !s.text.is_empty());
+    spans
+}
+
+/// 时间戳之后前几个词中的级别词：全大写（`ERROR`）或方括号内（`[error]`）
+fn find_level(line: &str, from: usize) -> Option<(std::ops::Range<usize>, Level)> {
+    let mut pos = from;
+    for word in line[from..].split_whitespace().take(LEVEL_WORDS) {
+        let start = pos + line[pos..].find(word)?;
+        pos = start + word.len();
+        let bracketed = word.starts_with(['[', '<', '(']);
+        let core = word.trim_matches(|c: char| !c.is_ascii_alphabetic());
+        let uppercase = core.bytes().all(|b| b.is_ascii_uppercase());
+        if let Some(level) = Level::parse(core).filter(|_| uppercase || bracketed) {
+            let offset = start + word.find(core)?;
+            return Some((offset..offset + core.len(), level));
+        }
+    }
+    None
+}
+
+/// 按模式匹配开头的字节：`d` 为数字，`?` 为数字或空格，`A` 为字母，其他字符原样匹配
+fn shape(bytes: &[u8], pattern: &str) -> bool {
+    bytes.len() >= pattern.len()
+        && pattern.bytes().zip(bytes).all(|(p, &b)| match p {
+            b'd' => b.is_ascii_digit(),
+            b'?' => b.is_ascii_digit() || b == b' ',
+            b'A' => b.is_ascii_alphabetic(),
+            _ => p == b,
+        })
+}
+
+/// 行首时间戳的字节长度：ISO 8601（`2024-05-01T10:00:00.123Z`）、syslog（`May  1 10:00:00`）
+/// 或只有时间（`10:00:00,123`），可以带方括号
+fn timestamp_len(line: &str) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let bracketed = bytes.first() == Some(&b'[');
+    let s = &bytes[usize::from(bracketed)..];
+    let mut len = if shape(s, "dddd-dd-dd") && matches!(s.get(10), Some(b'T' | b' ')) && shape(&s[11..], "dd:dd") {
+        16
+    } else if shape(s, "AAA ?d dd:dd:dd") {
+        15
+    } else if shape(s, "dd:dd:dd") {
+        8
+    } else {
+        return None;
+    };
+    if shape(&s[len..], ":dd") {
+        len += 3;
+    }
+    if matches!(s.get(len), Some(b'.' | b',')) && s.get(len + 1).is_some_and(u8::is_ascii_digit) {
+        len += 1 + s[len + 1..].iter().take_while(|b| b.is_ascii_digit()).count();
+    }
+    if s.get(len) == Some(&b'Z') {
+        len += 1;
+    } else if matches!(s.get(len), Some(b'+' | b'-')) {
+        let zone = &s[len + 1..];
+        if shape(zone, "dd:dd") {
+            len += 6;
+        } else if shape(zone, "dddd") {
+            len += 5;
+        }
+    }
+    if bracketed {
+        // 方括号内时间之后可能还有时区等内容，一直取到右括号
+        let close = s[len..].iter().position(|&b| b == b']')?;
+        len += close + 2;
+    }
+    Some(len)
+}
+
+/// JSON 行：顶层为对象时按键值对解析
+fn parse_json(line: &str) -> Option<Entry> {
+    if !line.trim_start().starts_with('{') {
+        return None;
+    }
+    let map: Map<String, Value> = serde_json::from_str(line).ok()?;
+    let pairs = map
+        .into_iter()
+        .map(|(key, value)| {
+            let (text, style) = match value {
+                Value::String(s) => (s, TextStyle::STRING),
+                Value::Number(n) => (n.to_string(), TextStyle::NUMBER),
+                Value::Bool(_) | Value::Null => (value.to_string(), TextStyle::CONSTANT),
+                other => (other.to_string(), TextStyle::NONE),
+            };
+            (key, text, style)
+        })
+        .collect();
+    Some(Entry::from_pairs(pairs))
+}
+
+/// logfmt 行：至少两项，且每一项都是 `key=value`（值可以带双引号）
+fn parse_logfmt(line: &str) -> Option<Entry> {
+    let mut pairs = Vec::new();
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        let eq = rest.find('=')?;
+        let key = &rest[..eq];
+        if key.is_empty() || !key.chars().all(|c| c.is_alphanumeric() || "_.-@/".contains(c)) {
+            return None;
+        }
+        rest = &rest[eq + 1..];
+        let (value, style) = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = closing_quote(quoted)?;
+            let value = &quoted[..end];
+            rest = &quoted[end + 1..];
+            (value.replace("\\\"", "\"").replace("\\\\", "\\"), TextStyle::STRING)
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let value = &rest[..end];
+            rest = &rest[end..];
+            (value.to_string(), bare_style(value))
+        };
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        rest = rest.trim_start();
+        pairs.push((key.to_string(), value, style));
+    }
+    if pairs.len() < 2 {
+        return None;
+    }
+    Some(Entry::from_pairs(pairs))
+}
+
+/// 引号内字符串的结束位置，跳过转义的引号
+fn closing_quote(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 未加引号的值：数字与常量分别着色
+fn bare_style(value: &str) -> TextStyle {
+    if value.parse::<f64>().is_ok() {
+        TextStyle::NUMBER
+    } else if matches!(value, "true" | "false" | "null" | "nil") {
+        TextStyle::CONSTANT
+    } else {
+        TextStyle::NONE
+    }
+}
+
+/// 含空白、`=` 或为空的字符串加上双引号
+fn quote(value: &str) -> String {
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '=' || c == '"') {
+        format!("{value:?}")
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log() {
+        let content = "\
+2024-05-01T10:00:00.123Z ERROR [db] connection refused
+May  1 10:00:01 host sshd[42]: [debug] probing
+ts=2024-05-01T10:00:02Z level=warn msg=\"disk almost full\" used=93.5 mount=/data
+{\"time\":\"10:00:03\",\"level\":\"info\",\"msg\":\"ok\",\"user\":\"a b\"}
+an error that is not a level";
+        let doc = parse_log(content);
+        let text = |i: usize| doc.lines[i].spans.iter().map(|s| s.text.as_str()).collect::<String>();
+        let style = |i: usize, t: &str| doc.lines[i].spans.iter().find(|s| s.text == t).map(|s| s.style);
+
+        assert_eq!(style(0, "2024-05-01T10:00:00.123Z"), Some(TextStyle::NUMBER));
+        assert_eq!(style(0, "ERROR"), Some(TextStyle::CAUTION | TextStyle::BOLD));
+        assert_eq!(style(1, "May  1 10:00:01"), Some(TextStyle::NUMBER | TextStyle::DIM));
+        assert_eq!(style(1, "debug"), Some(TextStyle::DIM));
+
+        assert_eq!(text(2), "2024-05-01T10:00:02Z WARN disk almost full used=93.5 mount=/data");
+        assert_eq!(style(2, "WARN"), Some(TextStyle::WARNING | TextStyle::BOLD));
+        assert_eq!(style(2, "93.5"), Some(TextStyle::NUMBER));
+        assert_eq!(text(3), "10:00:03 INFO ok               user=\"a b\"");
+        assert_eq!(text(4), "an error that is not a level");
+        assert_eq!(doc.lines[4].spans.len(), 1);
+        assert_eq!(timestamp_len("[2024-05-01 10:00:00 +0800] x"), Some(27));
+    }
+}
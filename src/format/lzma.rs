@@ -0,0 +1,482 @@
+//! LZMA / LZMA2 解码
+//!
+//! 供 7z 压缩包使用：7z 的文件头与成员数据通常以 LZMA 或 LZMA2 压缩，依赖中没有对应的解码库，
+//! 这里按 LZMA SDK 的规范实现解码器。整个输出保存在内存中并直接作为字典窗口，
+//! 输出超过调用方给出的上限时报错。
+
+use crate::format::archive::MAX_EXTRACTED_SIZE;
+use crate::t;
+use std::io;
+
+/// 概率模型的位数与初始值
+const PROB_BITS: u32 = 11;
+const PROB_INIT: u16 = 1 << (PROB_BITS - 1);
+const MOVE_BITS: u32 = 5;
+const NUM_STATES: usize = 12;
+/// pb 最大为 4，按 16 个位置状态分配
+const POS_STATES_MAX: usize = 1 << 4;
+const END_POS_MODEL_INDEX: u32 = 14;
+const NUM_FULL_DISTANCES: usize = 1 << (END_POS_MODEL_INDEX >> 1);
+const MATCH_MIN_LEN: usize = 2;
+
+fn corrupted() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, t!("LZMA 数据损坏"))
+}
+
+/// 区间解码器
+struct RangeDecoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    range: u32,
+    code: u32,
+}
+
+impl<'a> RangeDecoder<'a> {
+    fn new(data: &'a [u8]) -> io::Result<Self> {
+        if data.len() < 5 || data[0] != 0 {
+            return Err(corrupted());
+        }
+        let code = u32::from_be_bytes([data[1], data[2], data[3], data[4]]);
+        let range = u32::MAX;
+        if code == range {
+            return Err(corrupted());
+        }
+        Ok(Self {
+            data,
+            pos: 5,
+            range,
+            code,
+        })
+    }
+
+    fn next_byte(&mut self) -> io::Result<u8> {
+        let byte = *self.data.get(self.pos).ok_or_else(corrupted)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn normalize(&mut self) -> io::Result<()> {
+        if self.range < 1 << 24 {
+            self.range <<= 8;
+            self.code = (self.code << 8) | u32::from(self.next_byte()?);
+        }
+        Ok(())
+    }
+
+    fn direct_bits(&mut self, count: u32) -> io::Result<u32> {
+        let mut result = 0;
+        for _ in 0..count {
+            self.range >>= 1;
+            self.code = self.code.wrapping_sub(self.range);
+            let t = 0u32.wrapping_sub(self.code >> 31);
+            self.code = self.code.wrapping_add(self.range & t);
+            if self.code == self.range {
+                return Err(corrupted());
+            }
+            self.normalize()?;
+            result = (result << 1) + t.wrapping_add(1);
+        }
+        Ok(result)
+    }
+
+    fn bit(&mut self, prob: &mut u16) -> io::Result<u32> {
+        let bound = (self.range >> PROB_BITS) * u32::from(*prob);
+        let bit = if self.code < bound {
+            *prob += ((1 << PROB_BITS) - *prob) >> MOVE_BITS;
+            self.range = bound;
+            0
+        } else {
+            *prob -= *prob >> MOVE_BITS;
+            self.code -= bound;
+            self.range -= bound;
+            1
+        };
+        self.normalize()?;
+        Ok(bit)
+    }
+
+    /// 高位在前的位树
+    fn bit_tree(&mut self, probs: &mut [u16], bits: u32) -> io::Result<u32> {
+        let mut m = 1;
+        for _ in 0..bits {
+            m = (m << 1) + self.bit(&mut probs[m as usize])?;
+        }
+        Ok(m - (1 << bits))
+    }
+
+    /// 低位在前的位树
+    fn bit_tree_reverse(&mut self, probs: &mut [u16], bits: u32) -> io::Result<u32> {
+        let mut m = 1;
+        let mut symbol = 0;
+        for i in 0..bits {
+            let bit = self.bit(&mut probs[m as usize])?;
+            m = (m << 1) + bit;
+            symbol |= bit << i;
+        }
+        Ok(symbol)
+    }
+}
+
+/// 匹配长度解码器
+struct LenDecoder {
+    choice: u16,
+    choice2: u16,
+    low: [[u16; 1 << 3]; POS_STATES_MAX],
+    mid: [[u16; 1 << 3]; POS_STATES_MAX],
+    high: [u16; 1 << 8],
+}
+
+impl LenDecoder {
+    fn new() -> Self {
+        Self {
+            choice: PROB_INIT,
+            choice2: PROB_INIT,
+            low: [[PROB_INIT; 1 << 3]; POS_STATES_MAX],
+            mid: [[PROB_INIT; 1 << 3]; POS_STATES_MAX],
+            high: [PROB_INIT; 1 << 8],
+        }
+    }
+
+    fn decode(&mut self, rc: &mut RangeDecoder, pos_state: usize) -> io::Result<usize> {
+        let len = if rc.bit(&mut self.choice)? == 0 {
+            rc.bit_tree(&mut self.low[pos_state], 3)?
+        } else if rc.bit(&mut self.choice2)? == 0 {
+            8 + rc.bit_tree(&mut self.mid[pos_state], 3)?
+        } else {
+            16 + rc.bit_tree(&mut self.high, 8)?
+        };
+        Ok(len as usize)
+    }
+}
+
+/// LZMA 解码器状态；LZMA2 的各块之间可以沿用
+struct Decoder {
+    props: u8,
+    lc: u32,
+    lp: u32,
+    pb: u32,
+    literal: Vec<u16>,
+    pos_slot: [[u16; 1 << 6]; 4],
+    pos_decoders: [u16; 1 + NUM_FULL_DISTANCES - END_POS_MODEL_INDEX as usize],
+    align: [u16; 1 << 4],
+    len: LenDecoder,
+    rep_len: LenDecoder,
+    is_match: [u16; NUM_STATES << 4],
+    is_rep: [u16; NUM_STATES],
+    is_rep_g0: [u16; NUM_STATES],
+    is_rep_g1: [u16; NUM_STATES],
+    is_rep_g2: [u16; NUM_STATES],
+    is_rep0_long: [u16; NUM_STATES << 4],
+    state: usize,
+    reps: [usize; 4],
+}
+
+impl Decoder {
+    /// 由属性字节 `(pb * 5 + lp) * 9 + lc` 创建
+    fn new(props: u8) -> io::Result<Self> {
+        if props >= 9 * 5 * 5 {
+            return Err(corrupted());
+        }
+        let (lc, lp, pb) = (u32::from(props) % 9, u32::from(props) / 9 % 5, u32::from(props) / 45);
+        Ok(Self {
+            props,
+            lc,
+            lp,
+            pb,
+            literal: vec![PROB_INIT; 0x300 << (lc + lp)],
+            pos_slot: [[PROB_INIT; 1 << 6]; 4],
+            pos_decoders: [PROB_INIT; 1 + NUM_FULL_DISTANCES - END_POS_MODEL_INDEX as usize],
+            align: [PROB_INIT; 1 << 4],
+            len: LenDecoder::new(),
+            rep_len: LenDecoder::new(),
+            is_match: [PROB_INIT; NUM_STATES << 4],
+            is_rep: [PROB_INIT; NUM_STATES],
+            is_rep_g0: [PROB_INIT; NUM_STATES],
+            is_rep_g1: [PROB_INIT; NUM_STATES],
+            is_rep_g2: [PROB_INIT; NUM_STATES],
+            is_rep0_long: [PROB_INIT; NUM_STATES << 4],
+            state: 0,
+            reps: [0; 4],
+        })
+    }
+
+    fn decode_literal(&mut self, rc: &mut RangeDecoder, out: &mut Vec<u8>, start: usize) -> io::Result<()> {
+        let pos = out.len() - start;
+        let prev = if pos > 0 { out[out.len() - 1] } else { 0 };
+        let lit_state = ((pos & ((1 << self.lp) - 1)) << self.lc) + (usize::from(prev) >> (8 - self.lc));
+        let probs = &mut self.literal[0x300 * lit_state..0x300 * (lit_state + 1)];
+        let mut symbol: usize = 1;
+        if self.state >= 7 {
+            let mut match_byte = out[out.len() - self.reps[0] - 1];
+            while symbol < 0x100 {
+                let match_bit = usize::from(match_byte >> 7) & 1;
+                match_byte <<= 1;
+                let bit = rc.bit(&mut probs[((1 + match_bit) << 8) + symbol])? as usize;
+                symbol = (symbol << 1) | bit;
+                if match_bit != bit {
+                    break;
+                }
+            }
+        }
+        while symbol < 0x100 {
+            symbol = (symbol << 1) | rc.bit(&mut probs[symbol])? as usize;
+        }
+        out.push((symbol - 0x100) as u8);
+        Ok(())
+    }
+
+    fn decode_distance(&mut self, rc: &mut RangeDecoder, len: usize) -> io::Result<u32> {
+        let len_state = len.min(3);
+        let slot = rc.bit_tree(&mut self.pos_slot[len_state], 6)?;
+        if slot < 4 {
+            return Ok(slot);
+        }
+        let direct = (slot >> 1) - 1;
+        let mut dist = (2 | (slot & 1)) << direct;
+        if slot < END_POS_MODEL_INDEX {
+            dist += rc.bit_tree_reverse(&mut self.pos_decoders[(dist - slot) as usize..], direct)?;
+        } else {
+            dist += rc.direct_bits(direct - 4)? << 4;
+            dist += rc.bit_tree_reverse(&mut self.align, 4)?;
+        }
+        Ok(dist)
+    }
+
+    /// 解码到输出达到 `end` 字节为止；`end` 为 None 时解码到结束标记。
+    /// `start` 为字典窗口的起点，匹配不能引用它之前的数据
+    fn decode(&mut self, rc: &mut RangeDecoder, out: &mut Vec<u8>, start: usize, end: Option<usize>) -> io::Result<()> {
+        let pos_mask = (1 << self.pb) - 1;
+        loop {
+            if end.is_some_and(|end| out.len() >= end) {
+                return Ok(());
+            }
+            if out.len() as u64 > MAX_EXTRACTED_SIZE {
+                return Err(too_large());
+            }
+            let pos_state = (out.len() - start) & pos_mask;
+            let state = self.state;
+
+            if rc.bit(&mut self.is_match[(state << 4) + pos_state])? == 0 {
+                self.decode_literal(rc, out, start)?;
+                self.state = match state {
+                    0..4 => 0,
+                    4..10 => state - 3,
+                    _ => state - 6,
+                };
+                continue;
+            }
+
+            let len;
+            if rc.bit(&mut self.is_rep[state])? != 0 {
+                if out.len() == start {
+                    return Err(corrupted());
+                }
+                if rc.bit(&mut self.is_rep_g0[state])? == 0 {
+                    // 单字节重复
+                    if rc.bit(&mut self.is_rep0_long[(state << 4) + pos_state])? == 0 {
+                        if self.reps[0] >= out.len() - start {
+                            return Err(corrupted());
+                        }
+                        self.state = if state < 7 { 9 } else { 11 };
+                        out.push(out[out.len() - self.reps[0] - 1]);
+                        continue;
+                    }
+                } else {
+                    let dist = if rc.bit(&mut self.is_rep_g1[state])? == 0 {
+                        self.reps[1]
+                    } else {
+                        let dist = if rc.bit(&mut self.is_rep_g2[state])? == 0 {
+                            self.reps[2]
+                        } else {
+                            let dist = self.reps[3];
+                            self.reps[3] = self.reps[2];
+                            dist
+                        };
+                        self.reps[2] = self.reps[1];
+                        dist
+                    };
+                    self.reps[1] = self.reps[0];
+                    self.reps[0] = dist;
+                }
+                len = self.rep_len.decode(rc, pos_state)?;
+                self.state = if state < 7 { 8 } else { 11 };
+            } else {
+                self.reps.copy_within(0..3, 1);
+                len = self.len.decode(rc, pos_state)?;
+                self.state = if state < 7 { 7 } else { 10 };
+                let dist = self.decode_distance(rc, len)?;
+                if dist == u32::MAX {
+                    // 结束标记
+                    return if end.is_none() { Ok(()) } else { Err(corrupted()) };
+                }
+                self.reps[0] = dist as usize;
+            }
+
+            if self.reps[0] >= out.len() - start {
+                return Err(corrupted());
+            }
+            // 只需要前面一部分时，跨过 `end` 的匹配截断即可
+            let len = end.map_or(len + MATCH_MIN_LEN, |end| (len + MATCH_MIN_LEN).min(end - out.len()));
+            let from = out.len() - self.reps[0] - 1;
+            for i in 0..len {
+                out.push(out[from + i]);
+            }
+        }
+    }
+
+    /// 概率与状态恢复初始值
+    fn reset(&mut self) {
+        *self = Self::new(self.props).expect("属性已在创建时校验");
+    }
+}
+
+fn too_large() -> io::Error {
+    io::Error::other(t!(
+        "解压后的文件过大（上限 {} MiB）",
+        MAX_EXTRACTED_SIZE / (1024 * 1024)
+    ))
+}
+
+/// 解码 LZMA 数据；`props` 为 5 字节的属性（lc/lp/pb 与字典大小），
+/// `size` 为解压后的大小，未知时以结束标记为准
+pub fn decode_lzma(props: &[u8], data: &[u8], size: Option<u64>) -> io::Result<Vec<u8>> {
+    let &[props, ..] = props else {
+        return Err(corrupted());
+    };
+    let end = match size {
+        Some(size) if size > MAX_EXTRACTED_SIZE => return Err(too_large()),
+        size => size.map(|s| s as usize),
+    };
+    let mut decoder = Decoder::new(props)?;
+    let mut rc = RangeDecoder::new(data)?;
+    let mut out = Vec::new();
+    decoder.decode(&mut rc, &mut out, 0, end)?;
+    Ok(out)
+}
+
+/// 解码 LZMA2 数据：由未压缩块与 LZMA 块组成，块头决定是否重置状态、属性与字典。
+/// 给出 `size` 时解码到至少这么多字节即停止，结果可能稍长
+pub fn decode_lzma2(data: &[u8], size: Option<u64>) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    let mut decoder: Option<Decoder> = None;
+    let mut start = 0;
+    loop {
+        if size.is_some_and(|size| out.len() as u64 >= size) {
+            return Ok(out);
+        }
+        let control = *data.get(pos).ok_or_else(corrupted)?;
+        pos += 1;
+        if control == 0 {
+            return Ok(out);
+        }
+        let header = |len: usize| data.get(pos..pos + len).ok_or_else(corrupted);
+
+        if control < 0x80 {
+            // 1：重置字典的未压缩块，2：不重置字典的未压缩块
+            if control > 2 {
+                return Err(corrupted());
+            }
+            let size = usize::from(u16::from_be_bytes(header(2)?.try_into().unwrap())) + 1;
+            pos += 2;
+            if control == 1 {
+                start = out.len();
+            }
+            out.extend_from_slice(data.get(pos..pos + size).ok_or_else(corrupted)?);
+            pos += size;
+        } else {
+            let bytes = header(4)?;
+            let unpacked =
+                ((usize::from(control) & 0x1F) << 16) + usize::from(u16::from_be_bytes([bytes[0], bytes[1]])) + 1;
+            let packed = usize::from(u16::from_be_bytes([bytes[2], bytes[3]])) + 1;
+            pos += 4;
+            // 0：沿用状态，1：重置状态，2：重置状态并给出新属性，3：另外重置字典
+            match (control >> 5) & 3 {
+                0 => {}
+                1 => decoder.as_mut().ok_or_else(corrupted)?.reset(),
+                mode => {
+                    let props = *data.get(pos).ok_or_else(corrupted)?;
+                    pos += 1;
+                    decoder = Some(Decoder::new(props)?);
+                    if mode == 3 {
+                        start = out.len();
+                    }
+                }
+            }
+            let decoder = decoder.as_mut().ok_or_else(corrupted)?;
+            let mut rc = RangeDecoder::new(data.get(pos..pos + packed).ok_or_else(corrupted)?)?;
+            let end = out.len() + unpacked;
+            decoder.decode(&mut rc, &mut out, start, Some(end))?;
+            pos += packed;
+        }
+        if out.len() as u64 > MAX_EXTRACTED_SIZE {
+            return Err(too_large());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    /// 用系统的 xz 压缩，未安装时返回 None
+    fn xz(args: &[&str], input: &[u8]) -> Option<Vec<u8>> {
+        let mut child = Command::new("xz")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        // 另开线程写入，避免输出填满管道时互相等待
+        let mut stdin = child.stdin.take()?;
+        let input = input.to_vec();
+        let writer = std::thread::spawn(move || stdin.write_all(&input));
+        let output = child.wait_with_output().ok()?;
+        writer.join().ok()?.ok()?;
+        output.status.success().then_some(output.stdout)
+    }
+
+    fn sample() -> Vec<u8> {
+        let mut text = String::new();
+        for i in 0..5000u32 {
+            text.push_str(&format!("line {i} {}\n", i.wrapping_mul(2654435761) % 977));
+        }
+        text.into_bytes()
+    }
+
+    #[test]
+    fn test_decode_lzma() {
+        let input = sample();
+        // .lzma：5 字节属性、8 字节大小（全 1 表示未知，以结束标记结尾），之后是压缩数据
+        let Some(alone) = xz(&["--format=lzma", "-c"], &input) else {
+            return;
+        };
+        let size = u64::from_le_bytes(alone[5..13].try_into().unwrap());
+        let size = (size != u64::MAX).then_some(size);
+        assert_eq!(decode_lzma(&alone[..5], &alone[13..], size).unwrap(), input);
+        assert!(decode_lzma(&alone[..5], &alone[13..alone.len() / 2], size).is_err());
+    }
+
+    #[test]
+    fn test_decode_lzma2() {
+        // 不可压缩的数据会写成未压缩块，与 LZMA 块交替出现
+        let mut input = sample();
+        let mut x: u32 = 1;
+        for _ in 0..200_000 {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            input.push(x as u8);
+        }
+        input.extend(sample());
+        let Some(raw) = xz(&["--format=raw", "--lzma2=preset=1,dict=64KiB", "-c"], &input) else {
+            return;
+        };
+        assert_eq!(decode_lzma2(&raw, None).unwrap(), input);
+        assert_eq!(decode_lzma2(&[0], None).unwrap(), b"");
+        assert!(decode_lzma2(&raw[..raw.len() / 2], None).is_err());
+    }
+}
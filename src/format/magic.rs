@@ -11,6 +11,52 @@ pub enum DetectedFormat {
     Image(ImageFormat),
     /// 文档格式
     Document(DocumentFormat),
+    /// 压缩包
+    Archive(ArchiveFormat),
+    /// 单个文件的压缩流，解压后重新检测
+    Compressed(CompressedFormat),
+    /// 音频文件
+    Audio(AudioFormat),
+    /// 可执行文件与目标文件
+    Executable(ExecutableFormat),
+}
+
+impl DetectedFormat {
+    /// 签名对应的格式名
+    pub fn name(self) -> &'static str {
+        match self {
+            DetectedFormat::Image(ImageFormat::Png) => "PNG",
+            DetectedFormat::Image(ImageFormat::Jpeg) => "JPEG",
+            DetectedFormat::Image(ImageFormat::Gif) => "GIF",
+            DetectedFormat::Image(ImageFormat::Bmp) => "BMP",
+            DetectedFormat::Image(ImageFormat::WebP) => "WebP",
+            DetectedFormat::Image(ImageFormat::Tiff) => "TIFF",
+            DetectedFormat::Image(ImageFormat::Ico) => "ICO",
+            DetectedFormat::Image(ImageFormat::Svg) => "SVG",
+            DetectedFormat::Document(DocumentFormat::Pdf) => "PDF",
+            DetectedFormat::Document(DocumentFormat::Docx) => "DOCX",
+            DetectedFormat::Document(DocumentFormat::Epub) => "EPUB",
+            DetectedFormat::Document(DocumentFormat::Xlsx) => "XLSX",
+            DetectedFormat::Document(DocumentFormat::Odt) => "ODT",
+            DetectedFormat::Document(DocumentFormat::Ods) => "ODS",
+            DetectedFormat::Document(DocumentFormat::Odp) => "ODP",
+            DetectedFormat::Document(DocumentFormat::Cbor) => "CBOR",
+            DetectedFormat::Archive(ArchiveFormat::Zip) => "ZIP",
+            DetectedFormat::Archive(ArchiveFormat::Tar) => "TAR",
+            DetectedFormat::Archive(ArchiveFormat::SevenZ) => "7Z",
+            DetectedFormat::Compressed(CompressedFormat::Gzip) => "GZIP",
+            DetectedFormat::Compressed(CompressedFormat::Bzip2) => "BZIP2",
+            DetectedFormat::Compressed(CompressedFormat::Xz) => "XZ",
+            DetectedFormat::Compressed(CompressedFormat::Zstd) => "ZSTD",
+            DetectedFormat::Audio(AudioFormat::Mp3) => "MP3",
+            DetectedFormat::Audio(AudioFormat::Flac) => "FLAC",
+            DetectedFormat::Audio(AudioFormat::Ogg) => "OGG",
+            DetectedFormat::Audio(AudioFormat::M4a) => "M4A",
+            DetectedFormat::Executable(ExecutableFormat::Elf) => "ELF",
+            DetectedFormat::Executable(ExecutableFormat::Pe) => "PE",
+            DetectedFormat::Executable(ExecutableFormat::MachO) => "Mach-O",
+        }
+    }
 }
 
 /// 支持的图片格式
@@ -23,12 +69,70 @@ pub enum ImageFormat {
     WebP,
     Tiff,
     Ico,
+    /// 根元素为 `<svg>` 的 XML
+    Svg,
 }
 
 /// 支持的文档格式（为未来扩展准备）
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DocumentFormat {
     Pdf,
+    /// ZIP 容器中含有 `word/document.xml`
+    Docx,
+    /// ZIP 容器中含有 `META-INF/container.xml`
+    Epub,
+    /// ZIP 容器中含有 `xl/workbook.xml`
+    Xlsx,
+    /// ZIP 容器的 `mimetype` 为 OpenDocument 文本
+    Odt,
+    /// ZIP 容器的 `mimetype` 为 OpenDocument 电子表格
+    Ods,
+    /// ZIP 容器的 `mimetype` 为 OpenDocument 演示文稿
+    Odp,
+    /// 以 CBOR 自描述标签开头
+    Cbor,
+}
+
+/// 支持的压缩包格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// 不是 DOCX、EPUB 等文档容器的 ZIP 文件
+    Zip,
+    /// POSIX ustar 及 GNU tar
+    Tar,
+    /// 7-Zip
+    SevenZ,
+}
+
+/// 支持的压缩流格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressedFormat {
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+/// 支持的音频格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    /// 以 ID3v2 标签或 MPEG Layer III 帧同步开头
+    Mp3,
+    Flac,
+    /// Ogg 容器（Vorbis、Opus 等）
+    Ogg,
+    /// `ftyp` 品牌为 `M4A ` 或 `M4B ` 的 MP4 容器
+    M4a,
+}
+
+/// 支持的可执行文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutableFormat {
+    Elf,
+    /// DOS 头 `e_lfanew` 指向 `PE\0\0` 签名的 Windows 可执行文件
+    Pe,
+    /// 单架构与多架构（fat）Mach-O
+    MachO,
 }
 
 /// 文件签名定义
@@ -54,6 +158,16 @@ impl FileSignature {
         }
     }
 
+    /// 创建按位与后比较的签名
+    const fn masked(magic: &'static [u8], mask: &'static [u8], offset: usize, format: DetectedFormat) -> Self {
+        Self {
+            magic,
+            offset,
+            mask: Some(mask),
+            format,
+        }
+    }
+
     /// 检查数据是否匹配此签名
     fn matches(&self, data: &[u8]) -> bool {
         let start = self.offset;
@@ -140,10 +254,130 @@ const SIGNATURES: &[FileSignature] = &[
         0,
         DetectedFormat::Document(DocumentFormat::Pdf),
     ),
+    // CBOR 自描述标签 55799: D9 D9 F7
+    FileSignature::exact(
+        &[0xD9, 0xD9, 0xF7],
+        0,
+        DetectedFormat::Document(DocumentFormat::Cbor),
+    ),
+    // MP3: "ID3" 标签
+    FileSignature::exact(
+        b"ID3",
+        0,
+        DetectedFormat::Audio(AudioFormat::Mp3),
+    ),
+    // MP3: 无标签时以帧同步开头，11 位同步字后 Layer 为 01（Layer III）
+    FileSignature::masked(
+        &[0xFF, 0xE2],
+        &[0xFF, 0xE6],
+        0,
+        DetectedFormat::Audio(AudioFormat::Mp3),
+    ),
+    // FLAC: "fLaC"
+    FileSignature::exact(
+        b"fLaC",
+        0,
+        DetectedFormat::Audio(AudioFormat::Flac),
+    ),
+    // Ogg: "OggS"
+    FileSignature::exact(
+        b"OggS",
+        0,
+        DetectedFormat::Audio(AudioFormat::Ogg),
+    ),
+    // M4A: 偏移 4 处为 "ftypM4A " 或 "ftypM4B "
+    FileSignature::exact(
+        b"ftypM4A ",
+        4,
+        DetectedFormat::Audio(AudioFormat::M4a),
+    ),
+    FileSignature::exact(
+        b"ftypM4B ",
+        4,
+        DetectedFormat::Audio(AudioFormat::M4a),
+    ),
+    // ELF: 7F "ELF"
+    FileSignature::exact(
+        b"\x7FELF",
+        0,
+        DetectedFormat::Executable(ExecutableFormat::Elf),
+    ),
+    // PE: "MZ"，需要额外验证 PE 签名
+    FileSignature::exact(
+        b"MZ",
+        0,
+        DetectedFormat::Executable(ExecutableFormat::Pe),
+    ),
+    // Mach-O: 32 / 64 位，大端与小端
+    FileSignature::exact(
+        &[0xFE, 0xED, 0xFA, 0xCE],
+        0,
+        DetectedFormat::Executable(ExecutableFormat::MachO),
+    ),
+    FileSignature::exact(
+        &[0xFE, 0xED, 0xFA, 0xCF],
+        0,
+        DetectedFormat::Executable(ExecutableFormat::MachO),
+    ),
+    FileSignature::exact(
+        &[0xCE, 0xFA, 0xED, 0xFE],
+        0,
+        DetectedFormat::Executable(ExecutableFormat::MachO),
+    ),
+    FileSignature::exact(
+        &[0xCF, 0xFA, 0xED, 0xFE],
+        0,
+        DetectedFormat::Executable(ExecutableFormat::MachO),
+    ),
+    // 多架构 Mach-O: CA FE BA BE，与 Java class 文件相同，需要额外验证
+    FileSignature::exact(
+        &[0xCA, 0xFE, 0xBA, 0xBE],
+        0,
+        DetectedFormat::Executable(ExecutableFormat::MachO),
+    ),
+    // gzip: 1F 8B
+    FileSignature::exact(
+        &[0x1F, 0x8B],
+        0,
+        DetectedFormat::Compressed(CompressedFormat::Gzip),
+    ),
+    // bzip2: "BZh" 后跟块大小 '1'-'9'，需要额外验证
+    FileSignature::exact(
+        b"BZh",
+        0,
+        DetectedFormat::Compressed(CompressedFormat::Bzip2),
+    ),
+    // xz: FD "7zXZ" 00
+    FileSignature::exact(
+        &[0xFD, b'7', b'z', b'X', b'Z', 0x00],
+        0,
+        DetectedFormat::Compressed(CompressedFormat::Xz),
+    ),
+    // zstd: 28 B5 2F FD
+    FileSignature::exact(
+        &[0x28, 0xB5, 0x2F, 0xFD],
+        0,
+        DetectedFormat::Compressed(CompressedFormat::Zstd),
+    ),
+    // 7z: "7z" BC AF 27 1C
+    FileSignature::exact(
+        &[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C],
+        0,
+        DetectedFormat::Archive(ArchiveFormat::SevenZ),
+    ),
+    // tar: 偏移 257 处为 "ustar"
+    FileSignature::exact(
+        b"ustar",
+        257,
+        DetectedFormat::Archive(ArchiveFormat::Tar),
+    ),
 ];
 
+/// ZIP 本地文件头签名，DOCX、EPUB 等文档都以 ZIP 为容器
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
 /// 需要读取的最大字节数（用于检测）
-const MAX_HEADER_SIZE: usize = 32;
+const MAX_HEADER_SIZE: usize = 512;
 
 /// 通过 magic number 检测文件格式
 ///
@@ -162,12 +396,64 @@ pub fn detect_by_magic(data: &[u8]) -> Option<DetectedFormat> {
                 }
                 continue;
             }
+            if sig.format == DetectedFormat::Executable(ExecutableFormat::Pe) && !has_pe_signature(data) {
+                continue;
+            }
+            if sig.magic == [0xCA, 0xFE, 0xBA, 0xBE] && !is_fat_macho(data) {
+                continue;
+            }
+            if sig.format == DetectedFormat::Compressed(CompressedFormat::Bzip2)
+                && !data.get(3).is_some_and(|b| (b'1'..=b'9').contains(b))
+            {
+                continue;
+            }
             return Some(sig.format);
         }
     }
+    if looks_like_svg(data) {
+        return Some(DetectedFormat::Image(ImageFormat::Svg));
+    }
     None
 }
 
+/// DOS 头偏移 0x3C 处的 `e_lfanew` 是否指向 `PE\0\0`（只检查读入的文件头部）
+fn has_pe_signature(data: &[u8]) -> bool {
+    let Some(offset) = data.get(0x3C..0x40) else {
+        return false;
+    };
+    let offset = u32::from_le_bytes(offset.try_into().unwrap()) as usize;
+    data.get(offset..offset + 4) == Some(b"PE\0\0")
+}
+
+/// 多架构 Mach-O 的架构数很小，Java class 文件在同一位置是不小于 45 的主版本号
+fn is_fat_macho(data: &[u8]) -> bool {
+    data.get(4..8)
+        .is_some_and(|n| (1..=30).contains(&u32::from_be_bytes(n.try_into().unwrap())))
+}
+
+/// 跳过 XML 声明、注释与 DOCTYPE 后，根元素是否为 `<svg>`
+fn looks_like_svg(data: &[u8]) -> bool {
+    let find = |text: &[u8], pat: &[u8]| text.windows(pat.len()).position(|w| w == pat).map(|i| i + pat.len());
+    let mut rest = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    loop {
+        rest = rest.trim_ascii_start();
+        let end = if rest.starts_with(b"<?") {
+            find(rest, b"?>")
+        } else if rest.starts_with(b"<!--") {
+            find(rest, b"-->")
+        } else if rest.starts_with(b"<!DOCTYPE") {
+            find(rest, b">")
+        } else {
+            let after = rest.get(4).copied();
+            return rest.starts_with(b"<svg") && after.is_some_and(|b| b.is_ascii_whitespace() || b == b'>');
+        };
+        match end {
+            Some(i) => rest = &rest[i..],
+            None => return false,
+        }
+    }
+}
+
 /// 从文件路径检测格式
 ///
 /// # 参数
@@ -179,11 +465,138 @@ pub fn detect_file_format(path: &Path) -> Option<DetectedFormat> {
     use std::fs::File;
     use std::io::Read;
 
-    let mut file = File::open(path).ok()?;
+    let mut file = File::open(path)
+        .inspect_err(|e| tracing::warn!(path = %path.display(), "无法读取文件头: {e}"))
+        .ok()?;
     let mut buffer = [0u8; MAX_HEADER_SIZE];
-    let bytes_read = file.read(&mut buffer).ok()?;
+    let bytes_read = file
+        .read(&mut buffer)
+        .inspect_err(|e| tracing::warn!(path = %path.display(), "无法读取文件头: {e}"))
+        .ok()?;
+
+    let header = &buffer[..bytes_read];
+    if header.starts_with(ZIP_MAGIC) {
+        return detect_zip_container(file);
+    }
+    detect_by_magic(header)
+}
+
+/// 按 ZIP 容器内的特征文件区分文档格式，其余为普通压缩包
+fn detect_zip_container(file: std::fs::File) -> Option<DetectedFormat> {
+    let mut archive = zip::ZipArchive::new(file)
+        .inspect_err(|e| tracing::debug!("无法读取 ZIP 目录: {e}"))
+        .ok()?;
+    if archive.index_for_name("word/document.xml").is_some() {
+        return Some(DetectedFormat::Document(DocumentFormat::Docx));
+    }
+    if archive.index_for_name("META-INF/container.xml").is_some() {
+        return Some(DetectedFormat::Document(DocumentFormat::Epub));
+    }
+    if archive.index_for_name("xl/workbook.xml").is_some() {
+        return Some(DetectedFormat::Document(DocumentFormat::Xlsx));
+    }
+    // OpenDocument 的首个条目 `mimetype` 以未压缩形式保存 MIME 类型
+    if let Ok(Some(mime)) = crate::format::container::read_entry(&mut archive, "mimetype") {
+        let odf = match mime.trim().strip_prefix("application/vnd.oasis.opendocument.") {
+            Some("text" | "text-template") => Some(DocumentFormat::Odt),
+            Some("spreadsheet" | "spreadsheet-template") => Some(DocumentFormat::Ods),
+            Some("presentation" | "presentation-template") => Some(DocumentFormat::Odp),
+            _ => None,
+        };
+        if let Some(odf) = odf {
+            return Some(DetectedFormat::Document(odf));
+        }
+    }
+    Some(DetectedFormat::Archive(ArchiveFormat::Zip))
+}
+
+/// 文本编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    /// 只含 ASCII 字符
+    Ascii,
+    Utf8,
+    /// 带 BOM 的 UTF-8
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// 含有 NUL 字节，不是文本
+    Binary,
+    /// 其他编码（如 GBK、Latin-1）
+    Unknown,
+}
+
+impl Charset {
+    pub fn name(self) -> &'static str {
+        match self {
+            Charset::Ascii => "ascii",
+            Charset::Utf8 => "utf-8",
+            Charset::Utf8Bom => "utf-8 (BOM)",
+            Charset::Utf16Le => "utf-16le",
+            Charset::Utf16Be => "utf-16be",
+            Charset::Binary => "binary",
+            Charset::Unknown => "unknown",
+        }
+    }
+}
 
-    detect_by_magic(&buffer[..bytes_read])
+/// 检测编码时读取的最大字节数
+const MAX_CHARSET_SAMPLE: u64 = 64 * 1024;
+
+/// 根据文件开头的字节推测编码
+pub fn detect_charset(data: &[u8]) -> Charset {
+    if data.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return Charset::Utf8Bom;
+    }
+    if data.starts_with(&[0xFF, 0xFE]) {
+        return Charset::Utf16Le;
+    }
+    if data.starts_with(&[0xFE, 0xFF]) {
+        return Charset::Utf16Be;
+    }
+    if data.contains(&0) {
+        return Charset::Binary;
+    }
+    if data.is_ascii() {
+        return Charset::Ascii;
+    }
+    match std::str::from_utf8(data) {
+        Ok(_) => Charset::Utf8,
+        // 采样在多字节字符中间截断
+        Err(e) if e.error_len().is_none() => Charset::Utf8,
+        Err(_) => Charset::Unknown,
+    }
+}
+
+/// 读取文件开头推测编码
+pub fn detect_file_charset(path: &Path) -> std::io::Result<Charset> {
+    Ok(detect_charset(&read_sample(path)?))
+}
+
+/// 读取文件开头用于推测编码与内容类型的字节
+pub fn read_sample(path: &Path) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut data = Vec::new();
+    std::fs::File::open(path)?
+        .take(MAX_CHARSET_SAMPLE)
+        .read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// 内容是否为二进制：含 NUL 字节，或控制字符超过一成（换行、制表符与 ESC 等常见于文本的除外）
+pub fn looks_binary(data: &[u8]) -> bool {
+    match detect_charset(data) {
+        Charset::Binary => true,
+        Charset::Utf8Bom | Charset::Utf16Le | Charset::Utf16Be => false,
+        Charset::Ascii | Charset::Utf8 | Charset::Unknown => {
+            let control = data
+                .iter()
+                .filter(|&&b| (b < 0x20 && !b"\t\n\r\x0c\x1b\x08".contains(&b)) || b == 0x7f)
+                .count();
+            control * 10 > data.len()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -272,9 +685,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_svg_detection() {
+        let svg = b"<?xml version=\"1.0\"?>\n<!-- icon -->\n<!DOCTYPE svg>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>";
+        assert_eq!(detect_by_magic(svg), Some(DetectedFormat::Image(ImageFormat::Svg)));
+        assert_eq!(detect_by_magic(b"<svgx/>"), None);
+        assert_eq!(detect_by_magic(b"<html><svg></svg></html>"), None);
+    }
+
+    #[test]
+    fn test_audio_detection() {
+        assert_eq!(detect_by_magic(b"ID3\x04\x00"), Some(DetectedFormat::Audio(AudioFormat::Mp3)));
+        assert_eq!(detect_by_magic(&[0xFF, 0xFB, 0x90, 0x64]), Some(DetectedFormat::Audio(AudioFormat::Mp3)));
+        assert_eq!(detect_by_magic(b"fLaC\x00"), Some(DetectedFormat::Audio(AudioFormat::Flac)));
+        assert_eq!(detect_by_magic(b"\x00\x00\x00\x20ftypM4A \x00"), Some(DetectedFormat::Audio(AudioFormat::M4a)));
+        assert_eq!(detect_by_magic(b"\x00\x00\x00\x20ftypisom\x00"), None);
+    }
+
+    #[test]
+    fn test_executable_detection() {
+        assert_eq!(detect_by_magic(b"\x7FELF\x02\x01"), Some(DetectedFormat::Executable(ExecutableFormat::Elf)));
+        let mut pe = vec![0; 0x84];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3C] = 0x80;
+        pe[0x80..].copy_from_slice(b"PE\0\0");
+        assert_eq!(detect_by_magic(&pe), Some(DetectedFormat::Executable(ExecutableFormat::Pe)));
+        assert_eq!(detect_by_magic(&pe[..0x40]), None);
+        let fat = [0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 2];
+        assert_eq!(detect_by_magic(&fat), Some(DetectedFormat::Executable(ExecutableFormat::MachO)));
+        let class = [0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 52];
+        assert_eq!(detect_by_magic(&class), None);
+    }
+
+    #[test]
+    fn test_compressed_detection() {
+        assert_eq!(detect_by_magic(&[0x1F, 0x8B, 0x08]), Some(DetectedFormat::Compressed(CompressedFormat::Gzip)));
+        assert_eq!(detect_by_magic(b"BZh91AY&SY"), Some(DetectedFormat::Compressed(CompressedFormat::Bzip2)));
+        assert_eq!(detect_by_magic(b"BZhello"), None);
+        assert_eq!(detect_by_magic(b"\xFD7zXZ\x00\x00"), Some(DetectedFormat::Compressed(CompressedFormat::Xz)));
+        assert_eq!(detect_by_magic(&[0x28, 0xB5, 0x2F, 0xFD, 0x24]), Some(DetectedFormat::Compressed(CompressedFormat::Zstd)));
+        assert_eq!(
+            detect_by_magic(&[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C, 0, 4]),
+            Some(DetectedFormat::Archive(ArchiveFormat::SevenZ))
+        );
+    }
+
     #[test]
     fn test_unknown_format() {
         let unknown = b"Hello, World!";
         assert_eq!(detect_by_magic(unknown), None);
     }
+
+    #[test]
+    fn test_charset_detection() {
+        assert_eq!(detect_charset(b"hello"), Charset::Ascii);
+        assert_eq!(detect_charset("中文".as_bytes()), Charset::Utf8);
+        // 截断在多字节字符中间
+        assert_eq!(detect_charset(&"中文".as_bytes()[..4]), Charset::Utf8);
+        assert_eq!(detect_charset(&[0xEF, 0xBB, 0xBF, b'a']), Charset::Utf8Bom);
+        assert_eq!(detect_charset(&[0xFF, 0xFE, b'a', 0]), Charset::Utf16Le);
+        assert_eq!(detect_charset(&[0xD6, 0xD0, 0xCE, 0xC4]), Charset::Unknown);
+        assert_eq!(detect_charset(&[0x7F, 0x45, 0x4C, 0x46, 0x02, 0x00]), Charset::Binary);
+    }
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(!looks_binary(b"line\tone\r\n\x1b[1mbold\x1b[0m\n"));
+        assert!(!looks_binary(&[0xFF, 0xFE, b'a', 0]));
+        assert!(looks_binary(&[0x7F, 0x45, 0x4C, 0x46, 0x02, 0x00]));
+        assert!(looks_binary(&[0x01, 0x02, 0x03, b'a', b'b', 0x90, 0x91]));
+    }
 }
\ No newline at end of file
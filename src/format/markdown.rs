@@ -1,80 +1,711 @@
 use crate::document::*;
-use crate::format::{FileFormat, FormatError};
-use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
-use std::path::Path;
+use crate::format::table::{self, pad_to_width};
+use crate::format::{highlight, html, image, math, mermaid, FileFormat, FormatError};
+use crate::t;
+use pulldown_cmark::{
+    Alignment, BlockQuoteKind, BrokenLink, CodeBlockKind, CowStr, Event, HeadingLevel, LinkType, MetadataBlockKind,
+    Options, Parser, Tag, TagEnd, TextMergeWithOffset,
+};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use unicode_width::UnicodeWidthStr;
 
-pub struct MarkdownFormat;
+/// 链接目标的显示方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LinkStyle {
+    /// 文本后附加 `(url)`
+    #[default]
+    Inline,
+    /// 文本后附加编号，URL 统一列在文末
+    Reference,
+    /// 只显示文本，通过 OSC 8 终端超链接跳转
+    Hyperlink,
+}
+
+impl FromStr for LinkStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "inline" => Ok(LinkStyle::Inline),
+            "ref" | "reference" => Ok(LinkStyle::Reference),
+            "osc8" | "hyperlink" => Ok(LinkStyle::Hyperlink),
+            _ => Err(t!("无效的链接显示方式: {}（可选 inline、ref、osc8）", s)),
+        }
+    }
+}
+
+/// Markdown 解析选项
+#[derive(Clone, Debug)]
+pub struct MarkdownOptions {
+    /// 是否在正文前显示 front matter 元数据块
+    pub show_frontmatter: bool,
+    /// 链接目标的显示方式
+    pub link_style: LinkStyle,
+    /// 相对路径资源（图片等）的解析基准目录
+    pub base_dir: Option<PathBuf>,
+    /// 代码块内显示行号
+    pub code_line_numbers: bool,
+    /// 排版标点：弯引号、破折号与省略号
+    pub smart_punctuation: bool,
+    /// 启用的扩展语法
+    pub extensions: MarkdownExtensions,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            show_frontmatter: true,
+            link_style: LinkStyle::default(),
+            base_dir: None,
+            code_line_numbers: false,
+            smart_punctuation: true,
+            extensions: MarkdownExtensions::default(),
+        }
+    }
+}
+
+/// 可单独开关的扩展语法，默认全部启用，全部关闭即严格按 CommonMark 规范解析
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MarkdownExtensions {
+    pub tables: bool,
+    pub footnotes: bool,
+    pub task_lists: bool,
+    pub strikethrough: bool,
+    /// GitHub 提示块（`> [!NOTE]`）
+    pub alerts: bool,
+    /// `$...$` / `$$...$$` 公式
+    pub math: bool,
+    /// `[[笔记名]]`
+    pub wikilinks: bool,
+    pub definition_lists: bool,
+    /// YAML `---` 与 TOML `+++` front matter
+    pub front_matter: bool,
+}
+
+impl Default for MarkdownExtensions {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            footnotes: true,
+            task_lists: true,
+            strikethrough: true,
+            alerts: true,
+            math: true,
+            wikilinks: true,
+            definition_lists: true,
+            front_matter: true,
+        }
+    }
+}
+
+impl MarkdownExtensions {
+    /// 不启用任何扩展
+    pub fn commonmark() -> Self {
+        Self {
+            tables: false,
+            footnotes: false,
+            task_lists: false,
+            strikethrough: false,
+            alerts: false,
+            math: false,
+            wikilinks: false,
+            definition_lists: false,
+            front_matter: false,
+        }
+    }
+
+    fn options(self) -> Options {
+        let mut options = Options::empty();
+        let flags = [
+            (self.tables, Options::ENABLE_TABLES),
+            (self.footnotes, Options::ENABLE_FOOTNOTES),
+            (self.task_lists, Options::ENABLE_TASKLISTS),
+            (self.strikethrough, Options::ENABLE_STRIKETHROUGH),
+            (self.alerts, Options::ENABLE_GFM),
+            (self.math, Options::ENABLE_MATH),
+            (self.wikilinks, Options::ENABLE_WIKILINKS),
+            (self.definition_lists, Options::ENABLE_DEFINITION_LIST),
+            (self.front_matter, Options::ENABLE_YAML_STYLE_METADATA_BLOCKS),
+            (self.front_matter, Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS),
+        ];
+        for (enabled, flag) in flags {
+            if enabled {
+                options.insert(flag);
+            }
+        }
+        options
+    }
+}
+
+/// 内嵌图片占用的最大行数
+const IMAGE_ROWS: u16 = 12;
+
+/// 水平分隔线与一级标题下粗线的宽度
+const RULE_WIDTH: usize = 32;
+
+pub struct MarkdownFormat {
+    pub options: MarkdownOptions,
+}
 
 impl FileFormat for MarkdownFormat {
     fn parse(&self, path: &Path) -> Result<Document, FormatError> {
         let content = std::fs::read_to_string(path)?;
-        Ok(parse_markdown(&content))
+        let mut options = self.options.clone();
+        options.base_dir = path.parent().map(Path::to_path_buf);
+        Ok(parse_markdown(&content, &options))
     }
 
     fn extensions(&self) -> &[&str] {
         &["md", "markdown", "mdown", "mkd"]
     }
+
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
 }
 
+/// 无序列表按嵌套深度循环使用的圆点
+pub const BULLETS: &[&str] = &["•", "◦", "▪"];
+
 struct ListContext {
     is_ordered: bool,
     item_index: u64,
+    /// 有序列表最大编号的位数，用于右侧补齐
+    number_width: usize,
+    /// 条目标记的显示宽度（续行的悬挂缩进）
+    marker_width: u16,
+    /// 进入列表前的缩进，列表结束时恢复
+    base_indent: u16,
+    /// 当前条目是已完成的任务（内容变暗）
+    task_done: bool,
 }
 
 struct ParseState {
     doc: Document,
+    link_style: LinkStyle,
+    base_dir: Option<PathBuf>,
+    code_line_numbers: bool,
     current_line: RenderLine,
     current_style: TextStyle,
     indent_level: u16,
     in_code_block: bool,
+    // 当前代码块：(首个内容行, 语言, 原始文本)
+    code_block: Option<(usize, Option<String>, String)>,
     line_has_content: bool,
 
+    // 引用嵌套栈：每层左侧竖线的样式
+    quote_stack: Vec<TextStyle>,
+    /// 刚进入普通引用，首段文本可能是 Obsidian 风格的提示块标记
+    callout_pending: bool,
+
     // 列表嵌套栈
     list_stack: Vec<ListContext>,
+    /// 预扫描得到的各列表条目数（按出现顺序）
+    list_sizes: std::vec::IntoIter<u64>,
+    /// 列表条目续行的悬挂缩进
+    hang: u16,
 
     // 表格状态
     in_table: bool,
     in_table_head: bool,
     in_table_cell: bool,
+    table_alignments: Vec<Alignment>,
     table_rows: Vec<Vec<String>>,
+    /// 各行的源文件行号
+    table_sources: Vec<usize>,
     current_row: Vec<String>,
     current_cell_text: String,
+
+    // 脚注状态：label 按首次引用顺序编号，定义内容收集后统一输出到文末
+    footnote_labels: Vec<String>,
+    footnote_defs: Vec<(String, Vec<RenderLine>)>,
+    footnote_start: Option<(String, usize)>,
+
+    // front matter 原文
+    in_metadata: bool,
+    metadata_text: String,
+
+    // HTML 块原文
+    html_block: Option<String>,
+
+    // 链接状态：当前所在链接的 (类型, 目标)，以及引用模式下收集的 URL
+    link_stack: Vec<(LinkType, String)>,
+    link_refs: Vec<String>,
+
+    // 图片状态：(类型, 目标地址) 与 alt 文本
+    image_url: Option<(LinkType, String)>,
+    image_alt: String,
 }
 
 impl ParseState {
-    fn new() -> Self {
+    fn new(list_sizes: Vec<u64>, opts: &MarkdownOptions) -> Self {
         Self {
             doc: Document::default(),
+            link_style: opts.link_style,
+            base_dir: opts.base_dir.clone(),
+            code_line_numbers: opts.code_line_numbers,
             current_line: RenderLine::default(),
             current_style: TextStyle::NONE,
             indent_level: 0,
             in_code_block: false,
+            code_block: None,
             line_has_content: false,
+            quote_stack: Vec::new(),
+            callout_pending: false,
             list_stack: Vec::new(),
+            list_sizes: list_sizes.into_iter(),
+            hang: 0,
             in_table: false,
             in_table_head: false,
             in_table_cell: false,
+            table_alignments: Vec::new(),
             table_rows: Vec::new(),
+            table_sources: Vec::new(),
             current_row: Vec::new(),
             current_cell_text: String::new(),
+            footnote_labels: Vec::new(),
+            footnote_defs: Vec::new(),
+            footnote_start: None,
+            in_metadata: false,
+            metadata_text: String::new(),
+            html_block: None,
+            link_stack: Vec::new(),
+            link_refs: Vec::new(),
+            image_url: None,
+            image_alt: String::new(),
+        }
+    }
+
+    /// 代码块结束：Mermaid 图表画成字符图，其余按语言标记高亮；补全上下分隔线并记录代码块范围
+    fn end_code_block(&mut self) {
+        let Some((start, lang, text)) = self.code_block.take() else {
+            return;
+        };
+        let diagram = if lang.as_deref() == Some("mermaid") { mermaid::render(&text) } else { None };
+        match diagram {
+            Some(diagram) => self.replace_with_diagram(start, diagram),
+            None => self.decorate_code(start, lang.as_deref(), &text),
+        }
+        let end = self.doc.lines.len();
+        self.draw_code_rules(start, lang.as_deref());
+        self.doc.code_blocks.push(CodeBlock {
+            start,
+            end,
+            lang,
+            text,
+        });
+    }
+
+    /// 高亮代码块，按需在每行前加行号
+    fn decorate_code(&mut self, start: usize, lang: Option<&str>, text: &str) {
+        let end = self.doc.lines.len();
+
+        // 只替换代码片段，保留引用竖线等行首片段
+        if let Some(highlighted) = lang.and_then(|lang| highlight::highlight(text, lang)) {
+            for (line, tokens) in self.doc.lines[start..end].iter_mut().zip(highlighted) {
+                line.spans.retain(|s| !s.style.contains(TextStyle::CODE));
+                line.spans.extend(tokens.into_iter().map(|(text, color, bold)| {
+                    let style = if bold { TextStyle::CODE | TextStyle::BOLD } else { TextStyle::CODE };
+                    TextSpan {
+                        color: Some(color),
+                        ..TextSpan::new(text, style)
+                    }
+                }));
+            }
+        }
+
+        if self.code_line_numbers {
+            let width = (end - start).to_string().len();
+            for (i, line) in self.doc.lines[start..end].iter_mut().enumerate() {
+                let number = format!("{:>width$} │ ", i + 1);
+                line.spans.insert(0, TextSpan::new(number, TextStyle::DIM));
+            }
+        }
+    }
+
+    /// 代码块上下的分隔线与内容等宽，上方的线中标出语言
+    fn draw_code_rules(&mut self, start: usize, lang: Option<&str>) {
+        let width = self.doc.lines[start..]
+            .iter()
+            .map(|l| l.spans.iter().skip_while(|s| s.text == "│ ").map(|s| s.text.width()).sum::<usize>())
+            .max()
+            .unwrap_or(0)
+            .max(3);
+        let top = match lang {
+            Some(lang) => {
+                let label = format!("── {lang} ");
+                let rest = width.saturating_sub(label.width()).max(2);
+                label + &"─".repeat(rest)
+            }
+            None => "─".repeat(width),
+        };
+        let bottom = "─".repeat(top.width());
+        if let Some(rule) = self.doc.lines[start - 1].spans.last_mut() {
+            rule.text = top;
+        }
+        self.push_span(bottom, TextStyle::DIM);
+        self.flush_line();
+    }
+
+    /// 用图表的字符画替换代码块的源码行，保留引用竖线等行首片段与缩进
+    fn replace_with_diagram(&mut self, start: usize, diagram: Vec<RenderLine>) {
+        let source = self.doc.lines.split_off(start);
+        let (prefix, indent) = source.first().map_or((Vec::new(), self.indent_level), |line| {
+            let prefix = line.spans.iter().take_while(|s| !s.style.contains(TextStyle::CODE)).cloned().collect();
+            (prefix, line.indent)
+        });
+        for mut line in diagram {
+            line.spans.splice(0..0, prefix.iter().cloned());
+            line.indent += indent;
+            self.doc.lines.push(line);
+        }
+    }
+
+    /// 图片结束：本地文件按宽高比预留若干行供查看器绘制，否则仅输出 alt 占位文本
+    fn end_image(&mut self, url: String) {
+        let alt = std::mem::take(&mut self.image_alt);
+        let placeholder = t!("[图片: {}]", alt);
+        if self.in_table_cell {
+            self.current_cell_text.push_str(&placeholder);
+            return;
+        }
+
+        let local = self
+            .resolve_local(&url)
+            .filter(|p| p.is_file() && self.footnote_start.is_none());
+        let Some(path) = local else {
+            self.push_span(placeholder, TextStyle::DIM);
+            return;
+        };
+
+        if self.line_has_content {
+            self.flush_line();
+        }
+        let line = self.doc.lines.len();
+        let height = image::fit_rows(&path, IMAGE_ROWS);
+        self.push_span(placeholder, TextStyle::DIM);
+        self.flush_line();
+        for _ in 1..height {
+            self.flush_line();
+        }
+        self.doc.images.push(ImageBlock {
+            line,
+            height,
+            path,
+            alt,
+        });
+    }
+
+    /// 将相对路径解析为本地文件路径，远程地址返回 None
+    fn resolve_local(&self, url: &str) -> Option<PathBuf> {
+        if url.contains("://") || url.starts_with("data:") || url.is_empty() {
+            return None;
+        }
+        let path = Path::new(url.strip_prefix("file:").unwrap_or(url));
+        match &self.base_dir {
+            Some(base) if path.is_relative() => Some(base.join(path)),
+            _ => Some(path.to_path_buf()),
+        }
+    }
+
+    /// 链接结束时记录目标所在行，并按显示方式输出目标
+    fn end_link(&mut self, link_type: LinkType, url: String) {
+        if is_unresolved(link_type) {
+            self.push_unresolved_marker();
+            return;
+        }
+        // 表格单元格稍后才排版、脚注定义会移到文末，两者的行号此时都未确定
+        if !self.in_table_cell && self.footnote_start.is_none() && !url.is_empty() {
+            self.doc.links.push(Link {
+                line: self.doc.lines.len(),
+                url: url.clone(),
+            });
+        }
+        // 自动链接的文本即 URL，无需重复；Wiki 链接的文本即笔记名
+        if matches!(link_type, LinkType::Autolink | LinkType::Email | LinkType::WikiLink { .. }) {
+            return;
+        }
+
+        let suffix = match self.link_style {
+            LinkStyle::Inline => format!(" ({url})"),
+            LinkStyle::Reference => {
+                let number = match self.link_refs.iter().position(|u| *u == url) {
+                    Some(i) => i + 1,
+                    None => {
+                        self.link_refs.push(url);
+                        self.link_refs.len()
+                    }
+                };
+                format!("<{number}>")
+            }
+            LinkStyle::Hyperlink => return,
+        };
+
+        if self.in_table_cell {
+            self.current_cell_text.push_str(&suffix);
+        } else {
+            self.push_span(suffix, TextStyle::DIM);
+        }
+    }
+
+    /// HTML 块：去掉标签转换为文本；只有标签没有文本时（如 `<img>`）变暗显示源码，注释不显示
+    fn end_html_block(&mut self, source: &str) {
+        let converted = html::parse_html(source);
+        if converted.lines.iter().any(|l| !l.spans.is_empty()) {
+            for line in converted.lines {
+                if line.spans.is_empty() {
+                    self.add_empty_line();
+                    continue;
+                }
+                self.indent_level += line.indent;
+                for span in line.spans {
+                    self.push_span(span.text, self.current_style | span.style);
+                }
+                self.flush_line();
+                self.indent_level -= line.indent;
+            }
+        } else {
+            let mut rest = source.trim();
+            let mut visible = String::new();
+            while let Some(at) = rest.find("<!--") {
+                visible.push_str(&rest[..at]);
+                rest = rest[at..].find("-->").map_or("", |end| &rest[at + end + 3..]);
+            }
+            visible.push_str(rest);
+            let visible: Vec<&str> = visible.lines().map(str::trim_end).filter(|l| !l.trim().is_empty()).collect();
+            if visible.is_empty() {
+                return;
+            }
+            for line in visible {
+                self.push_span(line.to_string(), TextStyle::DIM);
+                self.flush_line();
+            }
+        }
+        self.add_empty_line();
+    }
+
+    /// 行内 HTML 标签：`<br>` 换行，常见的强调标签切换样式，其余标签忽略
+    fn inline_html(&mut self, tag: &str) {
+        let body = tag.trim_start_matches('<').trim_end_matches('>').trim_end_matches('/');
+        let closing = body.starts_with('/');
+        let name: String = body
+            .trim_start_matches('/')
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if name == "br" {
+            if self.in_table_cell {
+                self.current_cell_text.push(' ');
+            } else {
+                self.flush_line();
+            }
+            return;
+        }
+        let style = match name.as_str() {
+            "b" | "strong" => TextStyle::BOLD,
+            "i" | "em" => TextStyle::ITALIC,
+            "s" | "del" | "strike" => TextStyle::STRIKETHROUGH,
+            "code" | "kbd" | "samp" => TextStyle::CODE,
+            _ => return,
+        };
+        if closing {
+            self.current_style.remove(style);
+        } else {
+            self.current_style.insert(style);
+        }
+    }
+
+    /// 记录当前行对应的源文件行；脚注定义稍后移到文末、表格稍后排版，这两处不记录
+    fn mark_source(&mut self, source: usize) {
+        if self.footnote_start.is_some() || self.in_table {
+            return;
+        }
+        let line = self.doc.lines.len();
+        match self.doc.source_lines.last_mut() {
+            Some(last) if last.line == line => last.source = source,
+            _ => self.doc.source_lines.push(SourceLine { line, source }),
+        }
+    }
+
+    /// 未定义的引用链接：文本照常显示，后面加变暗的 `[?]`
+    fn push_unresolved_marker(&mut self) {
+        if self.in_table_cell {
+            self.current_cell_text.push_str("[?]");
+        } else {
+            self.push_span("[?]".into(), TextStyle::DIM);
+        }
+    }
+
+    /// 引用模式下在文末输出链接列表
+    fn render_link_refs(&mut self) {
+        if self.link_refs.is_empty() {
+            return;
+        }
+        if self.doc.lines.last().is_some_and(|l| !l.spans.is_empty()) {
+            self.add_empty_line();
+        }
+        self.push_span("────────".into(), TextStyle::DIM);
+        self.flush_line();
+        for (i, url) in std::mem::take(&mut self.link_refs).into_iter().enumerate() {
+            self.doc.links.push(Link {
+                line: self.doc.lines.len(),
+                url: url.clone(),
+            });
+            self.push_span(format!("<{}> ", i + 1), TextStyle::DIM);
+            self.push_span(url, TextStyle::LINK);
+            self.flush_line();
+        }
+    }
+
+    /// 输出 front matter 元数据块：键名对齐，键 DIM、值正常
+    fn render_metadata(&mut self) {
+        let meta = &self.doc.metadata;
+        let mut rows: Vec<(String, String, TextStyle)> = Vec::new();
+        if let Some(title) = &meta.title {
+            rows.push(("title".into(), title.clone(), TextStyle::BOLD | TextStyle::HEADING));
+        }
+        if let Some(date) = &meta.date {
+            rows.push(("date".into(), date.clone(), TextStyle::NONE));
+        }
+        if !meta.tags.is_empty() {
+            let tags = meta
+                .tags
+                .iter()
+                .map(|t| format!("#{t}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            rows.push(("tags".into(), tags, TextStyle::QUOTE));
+        }
+        for (key, value) in &meta.extra {
+            rows.push((key.clone(), value.clone(), TextStyle::NONE));
+        }
+        if rows.is_empty() {
+            return;
+        }
+
+        let key_width = rows
+            .iter()
+            .map(|(k, _, _)| UnicodeWidthStr::width(k.as_str()))
+            .max()
+            .unwrap_or(0);
+        for (key, value, style) in rows {
+            self.push_span(pad_to_width(&key, key_width + 2), TextStyle::DIM);
+            self.push_span(value, style);
+            self.flush_line();
+        }
+        self.push_span("────────".into(), TextStyle::DIM);
+        self.flush_line();
+        self.add_empty_line();
+    }
+
+    /// 获取脚注编号（从 1 开始），首次出现时分配新编号
+    fn footnote_number(&mut self, label: &str) -> usize {
+        match self.footnote_labels.iter().position(|l| l == label) {
+            Some(i) => i + 1,
+            None => {
+                self.footnote_labels.push(label.to_string());
+                self.footnote_labels.len()
+            }
+        }
+    }
+
+    /// 在文末输出脚注定义区
+    fn render_footnotes(&mut self) {
+        if self.footnote_defs.is_empty() {
+            return;
+        }
+
+        // 未被引用的定义也分配编号，排在最后
+        let labels: Vec<String> = self.footnote_defs.iter().map(|(l, _)| l.clone()).collect();
+        for label in &labels {
+            self.footnote_number(label);
+        }
+
+        let mut defs = std::mem::take(&mut self.footnote_defs);
+        defs.sort_by_key(|(label, _)| self.footnote_number(label));
+
+        self.push_span("────────".into(), TextStyle::DIM);
+        self.flush_line();
+
+        for (label, mut lines) in defs {
+            let number = self.footnote_number(&label);
+            while lines.last().is_some_and(|l| l.spans.is_empty()) {
+                lines.pop();
+            }
+            if lines.is_empty() {
+                lines.push(RenderLine::default());
+            }
+
+            lines[0].spans.insert(
+                0,
+                TextSpan::new(format!("[{number}] "), TextStyle::DIM),
+            );
+            for line in lines.iter_mut().skip(1) {
+                line.indent += 4;
+            }
+            self.doc.lines.extend(lines);
         }
     }
 
     fn flush_line(&mut self) {
-        self.current_line.indent = self.indent_level;
+        self.current_line.indent = self.indent_level + self.hang;
         let line = std::mem::take(&mut self.current_line);
         self.doc.lines.push(line);
         self.line_has_content = false;
+        // 条目首行之后的内容对齐到标记之后
+        if let Some(ctx) = self.list_stack.last() {
+            self.hang = ctx.marker_width;
+        }
     }
 
     fn add_empty_line(&mut self) {
-        self.doc.lines.push(RenderLine::default());
+        if self.quote_stack.is_empty() {
+            self.doc.lines.push(RenderLine::default());
+        } else {
+            // 引用内的空行保留左侧竖线
+            self.push_gutter();
+            self.flush_line();
+        }
+    }
+
+    /// 在行首输出每层引用的竖线
+    fn push_gutter(&mut self) {
+        for &style in &self.quote_stack {
+            self.current_line.spans.push(TextSpan::new("│ ", style));
+        }
+    }
+
+    /// 引用结束时去掉末尾只含竖线的空行
+    fn trim_gutter_lines(&mut self) {
+        while self
+            .doc
+            .lines
+            .last()
+            .is_some_and(|l| !l.spans.is_empty() && l.spans.iter().all(|s| s.text == "│ "))
+        {
+            self.doc.lines.pop();
+        }
     }
 
     fn push_span(&mut self, text: String, style: TextStyle) {
         if !text.is_empty() {
-            self.current_line.spans.push(TextSpan { text, style });
+            if self.current_line.spans.is_empty() {
+                self.push_gutter();
+            }
+            let mut span = TextSpan::new(text, style);
+            if self.link_style == LinkStyle::Hyperlink {
+                // Wiki 链接的目标是笔记名、未定义的引用没有目标，都不是终端能打开的地址
+                span.link = self
+                    .link_stack
+                    .last()
+                    .filter(|(link_type, _)| {
+                        !matches!(link_type, LinkType::WikiLink { .. }) && !is_unresolved(*link_type)
+                    })
+                    .map(|(_, url)| url.clone());
+            }
+            self.current_line.spans.push(span);
             self.line_has_content = true;
         }
     }
@@ -83,183 +714,467 @@ impl ParseState {
         if self.table_rows.is_empty() {
             return;
         }
-
-        let num_cols = self.table_rows.iter().map(|r| r.len()).max().unwrap_or(0);
-        if num_cols == 0 {
-            return;
+        let rows = std::mem::take(&mut self.table_rows);
+        let start = self.doc.lines.len();
+        table::push_table(&mut self.doc, &rows, &self.table_alignments, self.indent_level);
+        // 表头之后有一行分隔线
+        for (r, source) in std::mem::take(&mut self.table_sources).into_iter().enumerate() {
+            let line = start + r + usize::from(r > 0);
+            self.doc.source_lines.push(SourceLine { line, source });
         }
+        self.add_empty_line();
+    }
+}
+
+/// 解析 YAML front matter，提取 title/date/tags，其余标量字段放入 extra
+fn parse_frontmatter(text: &str, kind: MetadataBlockKind) -> Metadata {
+    let mut meta = Metadata::default();
+    let value = match kind {
+        MetadataBlockKind::YamlStyle => serde_yaml::from_str(text).ok(),
+        MetadataBlockKind::PlusesStyle => text.parse::<toml::Table>().ok().map(|t| toml_to_yaml(toml::Value::Table(t))),
+    };
+    let Some(serde_yaml::Value::Mapping(map)) = value else {
+        return meta;
+    };
 
-        // 计算每列最大显示宽度
-        let mut col_widths = vec![0usize; num_cols];
-        for row in &self.table_rows {
-            for (i, cell) in row.iter().enumerate() {
-                let w = UnicodeWidthStr::width(cell.as_str());
-                col_widths[i] = col_widths[i].max(w);
+    for (key, value) in map {
+        let Some(key) = key.as_str() else { continue };
+        match key.to_lowercase().as_str() {
+            "title" => meta.title = yaml_scalar(&value),
+            "date" => meta.date = yaml_scalar(&value),
+            "tags" | "keywords" => {
+                meta.tags = match &value {
+                    serde_yaml::Value::Sequence(seq) => seq.iter().filter_map(yaml_scalar).collect(),
+                    _ => yaml_scalar(&value)
+                        .map(|s| {
+                            s.split(',')
+                                .map(|t| t.trim().to_string())
+                                .filter(|t| !t.is_empty())
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                };
+            }
+            _ => {
+                if let Some(v) = yaml_scalar(&value) {
+                    meta.extra.push((key.to_string(), v));
+                }
             }
         }
+    }
 
-        for (r, row) in self.table_rows.iter().enumerate() {
-            let is_header = r == 0;
-            let attrs = if is_header {
-                TextStyle::BOLD | TextStyle::HEADING
-            } else {
-                TextStyle::NONE
-            };
-
-            let mut line = RenderLine {
-                indent: self.indent_level,
-                ..Default::default()
-            };
-
-            for c in 0..num_cols {
-                if c > 0 {
-                    line.spans.push(TextSpan {
-                        text: "  ".into(),
-                        style: TextStyle::NONE,
-                    });
-                }
-                let cell_text = row.get(c).map(|s| s.as_str()).unwrap_or("");
-                let padded = pad_to_width(cell_text, col_widths[c]);
-                line.spans.push(TextSpan {
-                    text: padded,
-                    style: attrs,
-                });
+    meta
+}
+
+/// TOML front matter（`+++` 包围）转为 YAML 值统一处理，日期时间保存为字符串
+fn toml_to_yaml(value: toml::Value) -> serde_yaml::Value {
+    use serde_yaml::Value as Yaml;
+    match value {
+        toml::Value::String(s) => Yaml::String(s),
+        toml::Value::Integer(i) => Yaml::Number(i.into()),
+        toml::Value::Float(f) => Yaml::Number(f.into()),
+        toml::Value::Boolean(b) => Yaml::Bool(b),
+        toml::Value::Datetime(d) => Yaml::String(d.to_string()),
+        toml::Value::Array(items) => Yaml::Sequence(items.into_iter().map(toml_to_yaml).collect()),
+        toml::Value::Table(table) => {
+            Yaml::Mapping(table.into_iter().map(|(k, v)| (Yaml::String(k), toml_to_yaml(v))).collect())
+        }
+    }
+}
+
+fn yaml_scalar(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// GitHub 提示块类型对应的样式、图标与标题
+fn alert_info(kind: BlockQuoteKind) -> (TextStyle, &'static str, &'static str) {
+    match kind {
+        BlockQuoteKind::Note => (TextStyle::NOTE, "ℹ", t!("备注")),
+        BlockQuoteKind::Tip => (TextStyle::TIP, "💡", t!("提示")),
+        BlockQuoteKind::Important => (TextStyle::IMPORTANT, "❗", t!("重要")),
+        BlockQuoteKind::Warning => (TextStyle::WARNING, "⚠", t!("警告")),
+        BlockQuoteKind::Caution => (TextStyle::CAUTION, "⛔", t!("注意")),
+    }
+}
+
+/// 将 `:rocket:` 形式的 emoji 短代码（GitHub gemoji 表）替换为 emoji，不认识的保持原样
+fn replace_shortcodes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let name_len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-')))
+            .unwrap_or(after.len());
+        let emoji = after[name_len..]
+            .starts_with(':')
+            .then(|| emojis::get_by_shortcode(&after[..name_len]))
+            .flatten();
+        match emoji {
+            Some(emoji) => {
+                out.push_str(emoji.as_str());
+                rest = &after[name_len + 1..];
             }
-            self.doc.lines.push(line);
+            // 第二个冒号可能是下一个短代码的开头
+            None => {
+                out.push(':');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
 
-            // 表头后插入分隔线
-            if is_header {
-                let mut sep = RenderLine {
-                    indent: self.indent_level,
-                    ..Default::default()
-                };
-                for c in 0..num_cols {
-                    if c > 0 {
-                        sep.spans.push(TextSpan {
-                            text: "  ".into(),
-                            style: TextStyle::NONE,
-                        });
-                    }
-                    let dash = "─".repeat(col_widths[c]);
-                    sep.spans.push(TextSpan {
-                        text: dash,
-                        style: TextStyle::DIM,
-                    });
+/// 识别 Obsidian 风格的提示块标记 `[!type]±  标题`，返回样式、图标与标题
+///
+/// 类型不区分大小写，Obsidian 的其余类型沿用相近的 GitHub 提示块样式；没有标题时
+/// GitHub 的五种类型使用其默认标题，其余类型以首字母大写的类型名为标题。
+fn callout_info(text: &str) -> Option<(TextStyle, &'static str, String)> {
+    let rest = text.strip_prefix("[!")?;
+    let (name, rest) = rest.split_once(']')?;
+    let name = name.to_lowercase();
+    let kind = match name.as_str() {
+        "note" | "info" | "todo" => BlockQuoteKind::Note,
+        "tip" | "hint" | "abstract" | "summary" | "tldr" | "success" | "check" | "done" => BlockQuoteKind::Tip,
+        "important" | "question" | "help" | "faq" | "example" | "quote" | "cite" => BlockQuoteKind::Important,
+        "warning" | "attention" => BlockQuoteKind::Warning,
+        "caution" | "danger" | "error" | "failure" | "fail" | "missing" | "bug" => BlockQuoteKind::Caution,
+        _ => return None,
+    };
+    let (style, icon, default_title) = alert_info(kind);
+    // `+` / `-` 表示可折叠，这里总是展开显示
+    let title = match rest.trim_start_matches(['+', '-']).trim() {
+        "" if ["note", "tip", "important", "warning", "caution"].contains(&name.as_str()) => default_title.to_string(),
+        "" => name[..1].to_uppercase() + &name[1..],
+        title => title.to_string(),
+    };
+    Some((style, icon, title))
+}
+
+/// 预扫描文档，按 Start(List) 出现顺序统计每个列表的直接条目数
+fn count_list_items(content: &str, options: Options) -> Vec<u64> {
+    let mut sizes = Vec::new();
+    let mut stack: Vec<usize> = Vec::new();
+    for event in Parser::new_ext(content, options) {
+        match event {
+            Event::Start(Tag::List(_)) => {
+                stack.push(sizes.len());
+                sizes.push(0);
+            }
+            Event::End(TagEnd::List(_)) => {
+                stack.pop();
+            }
+            Event::Start(Tag::Item) => {
+                if let Some(&idx) = stack.last() {
+                    sizes[idx] += 1;
                 }
-                self.doc.lines.push(sep);
             }
+            _ => {}
         }
-
-        self.add_empty_line();
-        self.table_rows.clear();
     }
+    sizes
 }
 
-fn pad_to_width(text: &str, target: usize) -> String {
-    let current = UnicodeWidthStr::width(text);
-    if current >= target {
-        text.to_string()
-    } else {
-        format!("{}{}", text, " ".repeat(target - current))
+/// 找不到定义的引用链接或图片
+fn is_unresolved(link_type: LinkType) -> bool {
+    matches!(link_type, LinkType::ReferenceUnknown | LinkType::CollapsedUnknown | LinkType::ShortcutUnknown)
+}
+
+/// 标题样式：一二级用标题色，三级用次级标题色，四级及以下变暗
+fn heading_style(level: HeadingLevel) -> TextStyle {
+    match level {
+        HeadingLevel::H1 | HeadingLevel::H2 => TextStyle::BOLD | TextStyle::HEADING,
+        HeadingLevel::H3 => TextStyle::BOLD | TextStyle::HEADING | TextStyle::SUBHEADING,
+        _ => TextStyle::BOLD | TextStyle::HEADING | TextStyle::DIM,
     }
 }
 
-pub fn parse_markdown(content: &str) -> Document {
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_TABLES);
+pub fn parse_markdown(content: &str, opts: &MarkdownOptions) -> Document {
+    let mut options = opts.extensions.options();
+    if opts.smart_punctuation {
+        options.insert(Options::ENABLE_SMART_PUNCTUATION);
+    }
 
-    let parser = Parser::new_ext(content, options);
-    let mut state = ParseState::new();
+    // 合并相邻的文本事件，提示块标记 `[!tip]` 才能作为一段文本识别；偏移量用于记录各块的源文件行
+    // 显式写出标识的引用（`[文本][id]`、`[文本][]`）找不到定义时仍作为链接，以便标出；
+    // `[文本]` 这类简写找不到定义时只是普通文本
+    let broken_link = |link: BrokenLink| {
+        (link.link_type != LinkType::Shortcut).then(|| (CowStr::from(""), CowStr::from("")))
+    };
+    let parser = Parser::new_with_broken_link_callback(content, options, Some(broken_link));
+    let parser = TextMergeWithOffset::new(parser.into_offset_iter());
+    let newlines: Vec<usize> = content.match_indices('\n').map(|(i, _)| i).collect();
+    let mut state = ParseState::new(count_list_items(content, options), opts);
 
-    for event in parser {
+    for (event, range) in parser {
+        let callout_pending = std::mem::take(&mut state.callout_pending);
+        let source = newlines.partition_point(|&i| i < range.start);
+        match &event {
+            Event::Start(Tag::TableHead | Tag::TableRow) => state.table_sources.push(source),
+            Event::Start(
+                Tag::Paragraph
+                | Tag::Heading { .. }
+                | Tag::CodeBlock(_)
+                | Tag::Item
+                | Tag::BlockQuote(_)
+                | Tag::HtmlBlock
+                | Tag::MetadataBlock(_)
+                | Tag::DefinitionListTitle
+                | Tag::DefinitionListDefinition,
+            )
+            | Event::Rule => state.mark_source(source),
+            _ => {}
+        }
         match event {
             // === Block 级 ===
-            Event::Start(Tag::Heading { .. }) => {
-                state.current_style.insert(TextStyle::BOLD | TextStyle::HEADING);
+            Event::Start(Tag::Heading { level, .. }) => {
+                state.current_style.insert(heading_style(level));
             }
-            Event::End(TagEnd::Heading(_)) => {
-                state.current_style.remove(TextStyle::BOLD | TextStyle::HEADING);
+            Event::End(TagEnd::Heading(level)) => {
+                state.current_style.remove(heading_style(level));
+                let text: String = state.current_line.spans.iter().map(|s| s.text.as_str()).collect();
+                state.doc.headings.push(Heading {
+                    level: level as u8,
+                    line: state.doc.lines.len(),
+                    text: text.trim().to_string(),
+                });
                 state.flush_line();
+                // 一级标题下加一条与分隔线等宽的粗线，标题更长时随标题加宽
+                if level == HeadingLevel::H1 {
+                    state.push_span("━".repeat(text.trim().width().max(RULE_WIDTH)), TextStyle::HEADING);
+                    state.flush_line();
+                }
                 state.add_empty_line();
             }
 
-            Event::Start(Tag::Paragraph) => {}
-            Event::End(TagEnd::Paragraph) => {
-                if !state.in_table {
+            Event::Start(Tag::Paragraph) => state.callout_pending = callout_pending,
+            Event::End(TagEnd::Paragraph) if !state.in_table => {
+                if state.line_has_content {
                     state.flush_line();
-                    state.add_empty_line();
                 }
+                state.add_empty_line();
             }
 
-            Event::Start(Tag::CodeBlock(_)) => {
+            Event::Start(Tag::CodeBlock(kind)) => {
                 state.in_code_block = true;
                 state.current_style.insert(TextStyle::CODE);
                 state.flush_line();
                 state.push_span("───".into(), TextStyle::DIM);
                 state.flush_line();
+
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .map(|l| l.to_string()),
+                    CodeBlockKind::Indented => None,
+                };
+                state.code_block = Some((state.doc.lines.len(), lang, String::new()));
             }
             Event::End(TagEnd::CodeBlock) => {
                 if state.line_has_content {
                     state.flush_line();
                 }
-                state.push_span("───".into(), TextStyle::DIM);
-                state.flush_line();
+                state.end_code_block();
                 state.in_code_block = false;
                 state.current_style.remove(TextStyle::CODE);
             }
 
             Event::Start(Tag::List(first_index)) => {
+                if state.line_has_content {
+                    state.flush_line();
+                }
+                let base_indent = state.indent_level;
+                // 顶层列表缩进 4 列，嵌套列表对齐到上级条目的正文
+                if state.list_stack.is_empty() {
+                    state.indent_level += 4;
+                } else {
+                    state.indent_level += state.hang;
+                }
+                state.hang = 0;
+
+                let item_count = state.list_sizes.next().unwrap_or(1);
+                let first = first_index.unwrap_or(1);
+                let last = first + item_count.saturating_sub(1);
                 state.list_stack.push(ListContext {
                     is_ordered: first_index.is_some(),
-                    item_index: first_index.unwrap_or(1).saturating_sub(1),
+                    item_index: first.saturating_sub(1),
+                    number_width: last.to_string().len(),
+                    marker_width: 0,
+                    base_indent,
+                    task_done: false,
                 });
-                state.indent_level += 4;
             }
             Event::End(TagEnd::List(_)) => {
-                state.list_stack.pop();
-                state.indent_level = state.indent_level.saturating_sub(4);
-                if state.indent_level == 0 {
+                if let Some(ctx) = state.list_stack.pop() {
+                    state.indent_level = ctx.base_indent;
+                }
+                state.hang = state.list_stack.last().map_or(0, |ctx| ctx.marker_width);
+                let ends_blank = state.doc.lines.last().is_some_and(|l| l.spans.is_empty());
+                if state.list_stack.is_empty() && !ends_blank {
                     state.add_empty_line();
                 }
             }
 
             Event::Start(Tag::Item) => {
+                if state.line_has_content {
+                    state.flush_line();
+                }
+                state.hang = 0;
+                let depth = state.list_stack.len().saturating_sub(1);
                 if let Some(ctx) = state.list_stack.last_mut() {
-                    if ctx.is_ordered {
+                    let marker = if ctx.is_ordered {
                         ctx.item_index += 1;
-                        let marker = format!("{}. ", ctx.item_index);
-                        state.push_span(marker, state.current_style);
+                        let number = format!("{}.", ctx.item_index);
+                        format!("{:<width$} ", number, width = ctx.number_width + 1)
                     } else {
-                        state.push_span("• ".into(), state.current_style);
-                    }
+                        format!("{} ", BULLETS[depth % BULLETS.len()])
+                    };
+                    ctx.marker_width = UnicodeWidthStr::width(marker.as_str()) as u16;
+                    state.push_span(marker, state.current_style);
                 }
             }
             Event::End(TagEnd::Item) => {
-                state.flush_line();
+                if state.line_has_content {
+                    state.flush_line();
+                }
+                state.hang = 0;
+                if let Some(ctx) = state.list_stack.last_mut()
+                    && std::mem::take(&mut ctx.task_done)
+                {
+                    state.current_style.remove(TextStyle::DIM);
+                }
             }
 
-            Event::Start(Tag::BlockQuote(_)) => {
-                state.indent_level += 2;
-                state.current_style.insert(TextStyle::QUOTE);
-                state.push_span("│ ".into(), TextStyle::QUOTE | TextStyle::DIM);
+            // 任务列表：复选框替换无序列表的圆点，已完成的条目变暗
+            Event::TaskListMarker(checked) => {
+                let is_bullet = state
+                    .current_line
+                    .spans
+                    .last()
+                    .is_some_and(|s| BULLETS.iter().any(|b| s.text == format!("{b} ")));
+                if is_bullet {
+                    state.current_line.spans.pop();
+                }
+                let marker = if checked { "☑ " } else { "☐ " };
+                if let Some(ctx) = state.list_stack.last_mut() {
+                    if is_bullet {
+                        ctx.marker_width = 0;
+                    }
+                    ctx.marker_width += UnicodeWidthStr::width(marker) as u16;
+                    ctx.task_done = checked;
+                }
+                state.push_span(marker.into(), state.current_style);
+                if checked {
+                    state.current_style.insert(TextStyle::DIM);
+                }
+            }
+
+            Event::Start(Tag::BlockQuote(kind)) => {
+                if state.line_has_content {
+                    state.flush_line();
+                }
+                // 嵌套引用通过多层竖线区分，只有最外层增加缩进
+                if state.quote_stack.is_empty() {
+                    state.indent_level += 2;
+                }
+                match kind.map(alert_info) {
+                    Some((style, icon, title)) => {
+                        state.quote_stack.push(style);
+                        state.push_span(format!("{icon} {title}"), style | TextStyle::BOLD);
+                        state.flush_line();
+                    }
+                    None => {
+                        state.quote_stack.push(TextStyle::QUOTE | TextStyle::DIM);
+                        state.current_style.insert(TextStyle::QUOTE);
+                        state.callout_pending = opts.extensions.alerts;
+                    }
+                }
             }
             Event::End(TagEnd::BlockQuote(_)) => {
-                state.indent_level = state.indent_level.saturating_sub(2);
-                state.current_style.remove(TextStyle::QUOTE);
+                if state.line_has_content {
+                    state.flush_line();
+                }
+                state.trim_gutter_lines();
+                state.quote_stack.pop();
+                if state.quote_stack.is_empty() {
+                    state.indent_level = state.indent_level.saturating_sub(2);
+                }
+                if !state.quote_stack.iter().any(|s| s.contains(TextStyle::QUOTE)) {
+                    state.current_style.remove(TextStyle::QUOTE);
+                }
+                if state.quote_stack.is_empty() {
+                    state.add_empty_line();
+                }
+            }
+
+            // === 定义列表：术语加粗，定义缩进在其下 ===
+            Event::Start(Tag::DefinitionListTitle) => {
+                if state.line_has_content {
+                    state.flush_line();
+                }
+                state.current_style.insert(TextStyle::BOLD);
+            }
+            Event::End(TagEnd::DefinitionListTitle) => {
+                state.current_style.remove(TextStyle::BOLD);
                 state.flush_line();
+            }
+            Event::Start(Tag::DefinitionListDefinition) => {
+                if state.line_has_content {
+                    state.flush_line();
+                }
+                state.indent_level += 4;
+            }
+            Event::End(TagEnd::DefinitionListDefinition) => {
+                if state.line_has_content {
+                    state.flush_line();
+                }
+                state.indent_level -= 4;
+            }
+            // 松散的定义由段落组成，段落之后已有空行
+            Event::End(TagEnd::DefinitionList) if state.doc.lines.last().is_some_and(|l| !l.spans.is_empty()) => {
                 state.add_empty_line();
             }
 
+            // === HTML ===
+            Event::Start(Tag::HtmlBlock) => {
+                if state.line_has_content {
+                    state.flush_line();
+                }
+                state.html_block = Some(String::new());
+            }
+            Event::End(TagEnd::HtmlBlock) => {
+                if let Some(source) = state.html_block.take() {
+                    state.end_html_block(&source);
+                }
+            }
+            Event::Html(html) => {
+                if let Some(source) = state.html_block.as_mut() {
+                    source.push_str(&html);
+                }
+            }
+            Event::InlineHtml(tag) if state.image_url.is_none() => state.inline_html(&tag),
+
             Event::Rule => {
                 state.flush_line();
-                state.push_span("────────────────────────────────".into(), TextStyle::DIM);
+                state.doc.rules.push(state.doc.lines.len());
+                state.push_span("─".repeat(RULE_WIDTH), TextStyle::DIM);
                 state.flush_line();
             }
 
             // === 表格 ===
-            Event::Start(Tag::Table(_)) => {
+            Event::Start(Tag::Table(alignments)) => {
                 state.in_table = true;
+                state.table_alignments = alignments;
                 state.table_rows.clear();
+                state.table_sources.clear();
             }
             Event::End(TagEnd::Table) => {
                 state.in_table = false;
@@ -267,9 +1182,13 @@ pub fn parse_markdown(content: &str) -> Document {
             }
             Event::Start(Tag::TableHead) => {
                 state.in_table_head = true;
+                state.current_row.clear();
             }
             Event::End(TagEnd::TableHead) => {
+                // 表头单元格直接位于 TableHead 内，没有 TableRow 包裹
                 state.in_table_head = false;
+                let row = std::mem::take(&mut state.current_row);
+                state.table_rows.push(row);
             }
             Event::Start(Tag::TableRow) => {
                 state.current_row.clear();
@@ -288,6 +1207,46 @@ pub fn parse_markdown(content: &str) -> Document {
                 state.current_row.push(text);
             }
 
+            // === Front matter ===
+            Event::Start(Tag::MetadataBlock(_)) => {
+                state.in_metadata = true;
+                state.metadata_text.clear();
+            }
+            Event::End(TagEnd::MetadataBlock(kind)) => {
+                state.in_metadata = false;
+                state.doc.metadata = parse_frontmatter(&state.metadata_text, kind);
+                if opts.show_frontmatter {
+                    state.render_metadata();
+                }
+            }
+
+            // === 脚注 ===
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                if state.line_has_content {
+                    state.flush_line();
+                }
+                state.footnote_start = Some((label.into_string(), state.doc.lines.len()));
+            }
+            Event::End(TagEnd::FootnoteDefinition) => {
+                if state.line_has_content {
+                    state.flush_line();
+                }
+                // 定义内容从正文中移出，文末统一输出
+                if let Some((label, start)) = state.footnote_start.take() {
+                    let lines = state.doc.lines.split_off(start);
+                    state.footnote_defs.push((label, lines));
+                }
+            }
+            Event::FootnoteReference(label) => {
+                let number = state.footnote_number(&label);
+                let marker = format!("[{number}]");
+                if state.in_table_cell {
+                    state.current_cell_text.push_str(&marker);
+                } else {
+                    state.push_span(marker, state.current_style | TextStyle::DIM);
+                }
+            }
+
             // === Inline 级 ===
             Event::Start(Tag::Strong) => {
                 state.current_style.insert(TextStyle::BOLD);
@@ -301,6 +1260,71 @@ pub fn parse_markdown(content: &str) -> Document {
             Event::End(TagEnd::Emphasis) => {
                 state.current_style.remove(TextStyle::ITALIC);
             }
+            Event::Start(Tag::Strikethrough) => {
+                state.current_style.insert(TextStyle::STRIKETHROUGH);
+            }
+            Event::End(TagEnd::Strikethrough) => {
+                state.current_style.remove(TextStyle::STRIKETHROUGH);
+            }
+
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                ..
+            }) => {
+                if !is_unresolved(link_type) {
+                    state.current_style.insert(TextStyle::LINK);
+                }
+                state.link_stack.push((link_type, dest_url.into_string()));
+            }
+            Event::End(TagEnd::Link) => {
+                state.current_style.remove(TextStyle::LINK);
+                if let Some((link_type, url)) = state.link_stack.pop() {
+                    state.end_link(link_type, url);
+                }
+            }
+
+            Event::Start(Tag::Image { link_type, dest_url, .. }) => {
+                state.image_url = Some((link_type, dest_url.into_string()));
+                state.image_alt.clear();
+            }
+            Event::End(TagEnd::Image) => {
+                if let Some((link_type, url)) = state.image_url.take() {
+                    state.end_image(url);
+                    if is_unresolved(link_type) {
+                        state.push_unresolved_marker();
+                    }
+                }
+            }
+
+            // 块级公式：缩进显示，转换为 Unicode 近似形式
+            Event::DisplayMath(tex) => {
+                if state.in_table_cell {
+                    state.current_cell_text.push_str(&math::to_unicode(&tex));
+                } else {
+                    if state.line_has_content {
+                        state.flush_line();
+                    }
+                    state.indent_level += 4;
+                    for line in math::to_unicode(&tex).lines() {
+                        let line = line.trim();
+                        if !line.is_empty() {
+                            state.push_span(line.to_string(), TextStyle::MATH);
+                            state.flush_line();
+                        }
+                    }
+                    state.indent_level -= 4;
+                }
+            }
+            // 行内公式：转换为 Unicode 近似形式
+            Event::InlineMath(tex) => {
+                let text = math::to_unicode(&tex);
+                if state.in_table_cell {
+                    state.current_cell_text.push_str(&text);
+                } else {
+                    state.push_span(text, state.current_style | TextStyle::MATH);
+                }
+            }
 
             // 行内代码（叶子事件）
             Event::Code(text) => {
@@ -316,9 +1340,26 @@ pub fn parse_markdown(content: &str) -> Document {
 
             // === 文本 ===
             Event::Text(text) => {
-                if state.in_table_cell {
-                    state.current_cell_text.push_str(&text);
+                if callout_pending && let Some((style, icon, title)) = callout_info(&text) {
+                    // Obsidian 风格提示块：引用改用提示块的样式，标记行换成标题
+                    if let Some(top) = state.quote_stack.last_mut() {
+                        *top = style;
+                    }
+                    if !state.quote_stack.iter().any(|s| s.contains(TextStyle::QUOTE)) {
+                        state.current_style.remove(TextStyle::QUOTE);
+                    }
+                    state.push_span(format!("{icon} {title}"), style | TextStyle::BOLD);
+                    state.flush_line();
+                } else if state.in_metadata {
+                    state.metadata_text.push_str(&text);
+                } else if state.image_url.is_some() {
+                    state.image_alt.push_str(&text);
+                } else if state.in_table_cell {
+                    state.current_cell_text.push_str(&replace_shortcodes(&text));
                 } else if state.in_code_block {
+                    if let Some((_, _, code)) = state.code_block.as_mut() {
+                        code.push_str(&text);
+                    }
                     // 代码块按换行拆分
                     let mut first = true;
                     for line in text.split('\n') {
@@ -331,19 +1372,16 @@ pub fn parse_markdown(content: &str) -> Document {
                         first = false;
                     }
                 } else {
-                    state.push_span(text.into_string(), state.current_style);
+                    state.push_span(replace_shortcodes(&text), state.current_style);
                 }
             }
 
-            Event::SoftBreak => {
-                if !state.in_table_cell {
-                    state.push_span(" ".into(), state.current_style);
-                }
+            // 行首的软换行（如提示块标题之后）不产生空格
+            Event::SoftBreak if !state.in_table_cell && state.line_has_content => {
+                state.push_span(" ".into(), state.current_style);
             }
-            Event::HardBreak => {
-                if !state.in_table_cell {
-                    state.flush_line();
-                }
+            Event::HardBreak if !state.in_table_cell => {
+                state.flush_line();
             }
 
             _ => {}
@@ -353,6 +1391,30 @@ pub fn parse_markdown(content: &str) -> Document {
     if state.line_has_content {
         state.flush_line();
     }
+    state.doc.folds = section_folds(&state.doc);
+    state.render_footnotes();
+    state.render_link_refs();
 
     state.doc
 }
+
+/// 每个标题到下一个同级或更高级标题之间的章节可以折叠，章节末尾的空行保留在折叠之外
+fn section_folds(doc: &Document) -> Vec<Fold> {
+    let mut folds = Vec::new();
+    for (i, heading) in doc.headings.iter().enumerate() {
+        let mut end = doc.headings[i + 1..]
+            .iter()
+            .find(|h| h.level <= heading.level)
+            .map_or(doc.lines.len(), |h| h.line);
+        while end > heading.line + 1 && doc.lines[end - 1].spans.is_empty() {
+            end -= 1;
+        }
+        if end > heading.line + 1 {
+            folds.push(Fold {
+                start: heading.line,
+                end,
+            });
+        }
+    }
+    folds
+}
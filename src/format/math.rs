@@ -0,0 +1,365 @@
+//! TeX 数学公式的 Unicode 近似转换
+//!
+//! 终端无法排版公式，这里把常见结构转换为 Unicode 字符，保证可读性：
+//! 希腊字母、运算符、上下标、分数、根号、重音符号与黑板粗体等。无法识别的命令保留命令名。
+
+/// 希腊字母与常用符号命令
+const SYMBOLS: &[(&str, &str)] = &[
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ε"),
+    ("varepsilon", "ε"),
+    ("zeta", "ζ"),
+    ("eta", "η"),
+    ("theta", "θ"),
+    ("vartheta", "ϑ"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("nu", "ν"),
+    ("xi", "ξ"),
+    ("pi", "π"),
+    ("rho", "ρ"),
+    ("sigma", "σ"),
+    ("tau", "τ"),
+    ("upsilon", "υ"),
+    ("phi", "φ"),
+    ("varphi", "φ"),
+    ("chi", "χ"),
+    ("psi", "ψ"),
+    ("omega", "ω"),
+    ("Gamma", "Γ"),
+    ("Delta", "Δ"),
+    ("Theta", "Θ"),
+    ("Lambda", "Λ"),
+    ("Xi", "Ξ"),
+    ("Pi", "Π"),
+    ("Sigma", "Σ"),
+    ("Upsilon", "Υ"),
+    ("Phi", "Φ"),
+    ("Psi", "Ψ"),
+    ("Omega", "Ω"),
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("int", "∫"),
+    ("iint", "∬"),
+    ("oint", "∮"),
+    ("infty", "∞"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+    ("cdot", "·"),
+    ("times", "×"),
+    ("div", "÷"),
+    ("pm", "±"),
+    ("mp", "∓"),
+    ("leq", "≤"),
+    ("le", "≤"),
+    ("geq", "≥"),
+    ("ge", "≥"),
+    ("neq", "≠"),
+    ("ne", "≠"),
+    ("approx", "≈"),
+    ("equiv", "≡"),
+    ("sim", "∼"),
+    ("propto", "∝"),
+    ("to", "→"),
+    ("rightarrow", "→"),
+    ("leftarrow", "←"),
+    ("Rightarrow", "⇒"),
+    ("Leftarrow", "⇐"),
+    ("iff", "⇔"),
+    ("mapsto", "↦"),
+    ("in", "∈"),
+    ("notin", "∉"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("cup", "∪"),
+    ("cap", "∩"),
+    ("emptyset", "∅"),
+    ("forall", "∀"),
+    ("exists", "∃"),
+    ("neg", "¬"),
+    ("land", "∧"),
+    ("lor", "∨"),
+    ("ldots", "…"),
+    ("cdots", "⋯"),
+    ("dots", "…"),
+    ("circ", "∘"),
+    ("degree", "°"),
+    ("langle", "⟨"),
+    ("rangle", "⟩"),
+    ("quad", "  "),
+    ("qquad", "    "),
+];
+
+/// 只保留参数内容的命令（字体、文本等）
+const TRANSPARENT: &[&str] = &[
+    "text", "mathrm", "mathbf", "mathit", "mathsf", "mathtt", "mathcal", "operatorname",
+    "boldsymbol",
+];
+
+/// 重音命令及对应的组合字符，加在参数的每个字符之后
+const ACCENTS: &[(&str, char)] = &[
+    ("hat", '\u{302}'),
+    ("widehat", '\u{302}'),
+    ("tilde", '\u{303}'),
+    ("widetilde", '\u{303}'),
+    ("bar", '\u{304}'),
+    ("overline", '\u{305}'),
+    ("vec", '\u{20D7}'),
+    ("dot", '\u{307}'),
+    ("ddot", '\u{308}'),
+];
+
+/// 直接丢弃的命令
+const IGNORED: &[&str] = &["left", "right", "displaystyle", "big", "Big", "bigg", "Bigg"];
+
+fn superscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'n' => 'ⁿ',
+        'i' => 'ⁱ',
+        'T' => 'ᵀ',
+        ' ' => ' ',
+        _ => return None,
+    })
+}
+
+/// 黑板粗体（`\mathbb`）：常用数集有专门的字符，其余字母在数学字母区
+fn double_struck(c: char) -> char {
+    match c {
+        'C' => 'ℂ',
+        'H' => 'ℍ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        'A'..='Z' => char::from_u32(0x1D538 + (c as u32 - 'A' as u32)).unwrap_or(c),
+        'a'..='z' => char::from_u32(0x1D552 + (c as u32 - 'a' as u32)).unwrap_or(c),
+        '0'..='9' => char::from_u32(0x1D7D8 + (c as u32 - '0' as u32)).unwrap_or(c),
+        _ => c,
+    }
+}
+
+fn subscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'x' => 'ₓ',
+        ' ' => ' ',
+        _ => return None,
+    })
+}
+
+/// 将 TeX 公式转换为 Unicode 近似文本
+pub fn to_unicode(tex: &str) -> String {
+    let chars: Vec<char> = tex.chars().collect();
+    let mut pos = 0;
+    convert(&chars, &mut pos, false)
+}
+
+/// 转换直到输入结束，或 `in_group` 时遇到匹配的 `}`
+fn convert(chars: &[char], pos: &mut usize, in_group: bool) -> String {
+    let mut out = String::new();
+
+    while *pos < chars.len() {
+        let c = chars[*pos];
+        *pos += 1;
+        match c {
+            '}' if in_group => break,
+            '{' => out.push_str(&convert(chars, pos, true)),
+            '^' => {
+                let arg = argument(chars, pos);
+                out.push_str(&script(&arg, superscript, '^'));
+            }
+            '_' => {
+                let arg = argument(chars, pos);
+                out.push_str(&script(&arg, subscript, '_'));
+            }
+            '\\' => out.push_str(&command(chars, pos)),
+            // 对齐符号与换行在终端中无意义
+            '&' => {}
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// 读取命令名并转换
+fn command(chars: &[char], pos: &mut usize) -> String {
+    let start = *pos;
+    while *pos < chars.len() && chars[*pos].is_ascii_alphabetic() {
+        *pos += 1;
+    }
+    if *pos == start {
+        // 转义字符：\{ \} \\ \, 等
+        let Some(&c) = chars.get(*pos) else {
+            return String::new();
+        };
+        *pos += 1;
+        return match c {
+            '\\' => "\n".into(),
+            ',' | ';' | ':' | '!' => " ".into(),
+            _ => c.to_string(),
+        };
+    }
+
+    let name: String = chars[start..*pos].iter().collect();
+    if let Some((_, sym)) = SYMBOLS.iter().find(|(n, _)| *n == name) {
+        return sym.to_string();
+    }
+    if TRANSPARENT.contains(&name.as_str()) {
+        return argument(chars, pos);
+    }
+    if IGNORED.contains(&name.as_str()) {
+        return String::new();
+    }
+    if let Some(&(_, mark)) = ACCENTS.iter().find(|(n, _)| *n == name) {
+        return argument(chars, pos).chars().flat_map(|c| [c, mark]).collect();
+    }
+
+    match name.as_str() {
+        "frac" | "dfrac" | "tfrac" => {
+            let num = argument(chars, pos);
+            let den = argument(chars, pos);
+            format!("{}/{}", wrap_compound(&num), wrap_compound(&den))
+        }
+        "mathbb" => argument(chars, pos).chars().map(double_struck).collect(),
+        "sqrt" => {
+            // 可选的根指数 `\sqrt[n]{x}`：三次、四次根号有专门的字符
+            let index = optional_argument(chars, pos);
+            let arg = argument(chars, pos);
+            let sign = match index.as_deref() {
+                None | Some("2") => "√".to_string(),
+                Some("3") => "∛".to_string(),
+                Some("4") => "∜".to_string(),
+                Some(n) => format!("{}√", script(n, superscript, '^')),
+            };
+            format!("{sign}{}", wrap_compound(&arg))
+        }
+        _ => name,
+    }
+}
+
+/// 读取一个参数：`{...}` 分组、命令或单个字符
+fn argument(chars: &[char], pos: &mut usize) -> String {
+    while *pos < chars.len() && chars[*pos] == ' ' {
+        *pos += 1;
+    }
+    let Some(&c) = chars.get(*pos) else {
+        return String::new();
+    };
+    *pos += 1;
+    match c {
+        '{' => convert(chars, pos, true),
+        '\\' => command(chars, pos),
+        _ => c.to_string(),
+    }
+}
+
+/// 读取 `[...]` 可选参数，没有时不移动位置
+fn optional_argument(chars: &[char], pos: &mut usize) -> Option<String> {
+    if chars.get(*pos) != Some(&'[') {
+        return None;
+    }
+    let end = chars[*pos..].iter().position(|&c| c == ']')? + *pos;
+    let inner: Vec<char> = chars[*pos + 1..end].to_vec();
+    *pos = end + 1;
+    let mut inner_pos = 0;
+    Some(convert(&inner, &mut inner_pos, false))
+}
+
+/// 上下标：全部字符都有对应的 Unicode 形式时直接转换，否则保留 `^(...)` 形式
+fn script(arg: &str, map: fn(char) -> Option<char>, marker: char) -> String {
+    if let Some(converted) = arg.chars().map(map).collect::<Option<String>>() {
+        return converted;
+    }
+    format!("{marker}{}", wrap_compound(arg))
+}
+
+/// 多字符的表达式加括号，避免分数、根号的作用范围产生歧义
+fn wrap_compound(s: &str) -> String {
+    if s.chars().count() <= 1 || s.chars().all(|c| c.is_alphanumeric()) {
+        s.to_string()
+    } else {
+        format!("({s})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_greek_and_operators() {
+        assert_eq!(to_unicode(r"\alpha + \beta \leq \pi"), "α + β ≤ π");
+    }
+
+    #[test]
+    fn test_scripts() {
+        assert_eq!(to_unicode("x^2 + y_{10}"), "x² + y₁₀");
+        assert_eq!(to_unicode("e^{x+y}"), "e^(x+y)");
+    }
+
+    #[test]
+    fn test_fraction_and_sqrt() {
+        assert_eq!(to_unicode(r"\frac{a+b}{2}"), "(a+b)/2");
+        assert_eq!(to_unicode(r"\sqrt{x^2 + 1}"), "√(x² + 1)");
+    }
+
+    #[test]
+    fn test_accents_and_blackboard() {
+        assert_eq!(to_unicode(r"\vec{v} + \hat x"), "v\u{20D7} + x\u{302}");
+        assert_eq!(to_unicode(r"x \in \mathbb{R}^n"), "x ∈ ℝⁿ");
+        assert_eq!(to_unicode(r"\sqrt[3]{x} + \sqrt[n]{y}"), "∛x + ⁿ√y");
+    }
+
+    #[test]
+    fn test_sum() {
+        assert_eq!(to_unicode(r"\sum_{i=0}^{n} i"), "∑ᵢ₌₀ⁿ i");
+    }
+
+    #[test]
+    fn test_text_and_sizing() {
+        assert_eq!(to_unicode(r"\left( \text{if } x \right)"), "( if  x )");
+    }
+}
@@ -0,0 +1,393 @@
+//! Mermaid 图表的字符画渲染
+//!
+//! Markdown 中标记为 `mermaid` 的代码块：流程图（`graph` / `flowchart`）解析节点与连线后
+//! 交给 DOT 图的分层布局绘制（方向固定自上而下）；时序图（`sequenceDiagram`）按参与者分列，
+//! 消息画成带箭头的横线。其他图表类型或无法解析时返回 None，由调用方显示源码。
+
+use crate::document::*;
+use crate::format::dot::{self, Canvas};
+use std::collections::HashMap;
+use unicode_width::UnicodeWidthStr;
+
+/// 时序图相邻参与者方框之间的最小空列数
+const GAP: usize = 3;
+
+/// 渲染 Mermaid 源码，不支持的图表返回 None
+pub fn render(source: &str) -> Option<Vec<RenderLine>> {
+    let mut statements = source
+        .lines()
+        .flat_map(|line| line.split(';'))
+        .map(str::trim)
+        .filter(|s| !s.is_empty() && !s.starts_with("%%"));
+    let header = statements.next()?;
+    let kind = header.split_whitespace().next()?;
+    match kind {
+        "graph" | "flowchart" => flowchart(statements),
+        "sequenceDiagram" => sequence(statements),
+        _ => None,
+    }
+}
+
+// === 流程图 ===
+
+/// 只影响样式或分组的语句，绘制时略过
+const FLOWCHART_IGNORED: &[&str] = &["subgraph", "end", "classDef", "class", "style", "linkStyle", "click", "direction"];
+
+#[derive(Default)]
+struct Flowchart {
+    labels: Vec<String>,
+    ids: HashMap<String, usize>,
+    edges: Vec<(usize, usize, Option<String>)>,
+}
+
+impl Flowchart {
+    /// 节点序号，`label` 为 Some 时更新显示文字
+    fn node(&mut self, id: &str, label: Option<String>) -> usize {
+        let index = *self.ids.entry(id.to_string()).or_insert_with(|| {
+            self.labels.push(id.to_string());
+            self.labels.len() - 1
+        });
+        if let Some(label) = label {
+            self.labels[index] = label;
+        }
+        index
+    }
+}
+
+fn flowchart<'a>(statements: impl Iterator<Item = &'a str>) -> Option<Vec<RenderLine>> {
+    let mut chart = Flowchart::default();
+    for statement in statements {
+        let first = statement.split_whitespace().next().unwrap_or_default();
+        if FLOWCHART_IGNORED.contains(&first) {
+            continue;
+        }
+        parse_chain(&mut chart, statement)?;
+    }
+    if chart.labels.is_empty() {
+        return None;
+    }
+    Some(dot::draw_flowchart(&chart.labels, &chart.edges))
+}
+
+/// 解析 `A[文字] -->|标签| B & C --- D` 形式的语句：节点组之间两两连线
+fn parse_chain(chart: &mut Flowchart, statement: &str) -> Option<()> {
+    let mut rest = statement;
+    let mut previous: Vec<usize> = Vec::new();
+    let mut pending: Option<Option<String>> = None;
+    loop {
+        // 一组以 `&` 连接的节点
+        let mut group = Vec::new();
+        loop {
+            let (id, label, after) = parse_node(rest)?;
+            group.push(chart.node(id, label));
+            rest = after.trim_start();
+            match rest.strip_prefix('&') {
+                Some(after) => rest = after.trim_start(),
+                None => break,
+            }
+        }
+        if let Some(label) = pending.take() {
+            for &from in &previous {
+                for &to in &group {
+                    chart.edges.push((from, to, label.clone()));
+                }
+            }
+        }
+        if rest.is_empty() {
+            return Some(());
+        }
+        let (label, after) = parse_link(rest)?;
+        pending = Some(label);
+        previous = group;
+        rest = after.trim_start();
+    }
+}
+
+/// 读取节点：标识及可选的形状括号，返回 (标识, 文字, 剩余部分)
+fn parse_node(s: &str) -> Option<(&str, Option<String>, &str)> {
+    let end = s.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(s.len());
+    if end == 0 {
+        return None;
+    }
+    let (id, rest) = s.split_at(end);
+    if !rest.starts_with(['[', '(', '{', '>']) {
+        return Some((id, None, rest));
+    }
+    // 括号配对找到形状的结尾，引号内的括号不计
+    let (mut depth, mut quoted) = (0usize, false);
+    let mut close = None;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' | '(' | '{' if !quoted => depth += 1,
+            '>' if !quoted && i == 0 => depth += 1,
+            ']' | ')' | '}' if !quoted => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    close = Some(i + 1);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+    Some((id, Some(shape_text(&rest[..close])), &rest[close..]))
+}
+
+/// 去掉形状括号（如 `((…))`、`[(…)]`、`{{…}}`）与引号，`<br>` 换行
+fn shape_text(shape: &str) -> String {
+    const SHAPE: &[char] = &['[', ']', '(', ')', '{', '}', '>', '/', '\\'];
+    let opening = shape.chars().take_while(|c| SHAPE.contains(c)).count();
+    let mut text = &shape[opening..];
+    for _ in 0..opening {
+        text = text.strip_suffix(SHAPE).unwrap_or(text);
+    }
+    let text = text.trim();
+    let text = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')).unwrap_or(text);
+    text.replace("<br>", "\\n").replace("<br/>", "\\n").replace("<br />", "\\n")
+}
+
+/// 读取连线：`-->`、`---`、`-.->`、`==>` 等，标签写作 `-->|标签|` 或 `-- 标签 -->`
+fn parse_link(s: &str) -> Option<(Option<String>, &str)> {
+    let is_line = |c: char| matches!(c, '-' | '=' | '.');
+    let s = s.strip_prefix('<').unwrap_or(s);
+    let run = s.find(|c: char| !is_line(c)).unwrap_or(s.len());
+    if run < 2 {
+        return None;
+    }
+    let mut rest = &s[run..];
+    let mut label = None;
+    if let Some(after) = rest.strip_prefix(['>', 'x', 'o']) {
+        rest = after;
+    } else if run == 2 && rest.starts_with(' ') {
+        // `-- 标签 -->`：标签延续到下一段连线
+        let start = rest.find(|c: char| !c.is_whitespace())?;
+        let end = ["--", "==", ".-"].iter().filter_map(|s| rest[start..].find(s)).min()? + start;
+        label = Some(rest[start..end].trim().to_string());
+        let after = &rest[end..];
+        let run = after.find(|c: char| !is_line(c)).unwrap_or(after.len());
+        rest = after[run..].strip_prefix(['>', 'x', 'o']).unwrap_or(&after[run..]);
+    }
+    let rest = rest.trim_start();
+    if let Some(after) = rest.strip_prefix('|') {
+        let end = after.find('|')?;
+        label = Some(after[..end].trim().to_string());
+        return Some((label, &after[end + 1..]));
+    }
+    Some((label, rest))
+}
+
+// === 时序图 ===
+
+/// 时序图中的一行事件
+enum Step {
+    Message { from: usize, to: usize, text: String, dashed: bool },
+    Note { at: usize, text: String },
+}
+
+/// 消息箭头，长的在前
+const ARROWS: &[(&str, bool)] = &[
+    ("-->>", true),
+    ("->>", false),
+    ("-->", true),
+    ("->", false),
+    ("--x", true),
+    ("-x", false),
+    ("--)", true),
+    ("-)", false),
+];
+
+/// 只影响分组、激活状态或编号的语句，绘制时略过
+const SEQUENCE_IGNORED: &[&str] = &[
+    "autonumber", "activate", "deactivate", "loop", "alt", "else", "opt", "par", "and", "critical", "break",
+    "rect", "end", "title", "box",
+];
+
+fn sequence<'a>(statements: impl Iterator<Item = &'a str>) -> Option<Vec<RenderLine>> {
+    let mut names: Vec<String> = Vec::new();
+    let mut labels: Vec<String> = Vec::new();
+    let mut participant = |name: &str, label: Option<&str>| {
+        let index = names.iter().position(|n| n == name).unwrap_or_else(|| {
+            names.push(name.to_string());
+            labels.push(name.to_string());
+            names.len() - 1
+        });
+        if let Some(label) = label {
+            labels[index] = label.to_string();
+        }
+        index
+    };
+
+    let mut steps = Vec::new();
+    for statement in statements {
+        let (first, rest) = statement.split_once(char::is_whitespace).unwrap_or((statement, ""));
+        if SEQUENCE_IGNORED.contains(&first) {
+            continue;
+        }
+        if first == "participant" || first == "actor" {
+            match rest.split_once(" as ") {
+                Some((name, label)) => participant(name.trim(), Some(label.trim())),
+                None => participant(rest.trim(), None),
+            };
+            continue;
+        }
+        if first.eq_ignore_ascii_case("note") {
+            // `Note right of A: 文字`、`Note over A,B: 文字`，标在第一个参与者处
+            let (place, text) = rest.split_once(':')?;
+            let name = place.rsplit(" of ").next().unwrap_or(place).trim_start_matches("over").trim();
+            let at = participant(name.split(',').next()?.trim(), None);
+            steps.push(Step::Note { at, text: text.trim().to_string() });
+            continue;
+        }
+        let (head, text) = statement.split_once(':').unwrap_or((statement, ""));
+        let dash = head.find('-')?;
+        let &(arrow, dashed) = ARROWS.iter().find(|(a, _)| head[dash..].starts_with(a))?;
+        let from = participant(head[..dash].trim(), None);
+        let target = head[dash + arrow.len()..].trim().trim_start_matches(['+', '-']).trim();
+        if target.is_empty() {
+            return None;
+        }
+        let to = participant(target, None);
+        steps.push(Step::Message { from, to, text: text.trim().to_string(), dashed });
+    }
+    if names.is_empty() {
+        return None;
+    }
+    Some(draw_sequence(&labels, &steps))
+}
+
+fn draw_sequence(labels: &[String], steps: &[Step]) -> Vec<RenderLine> {
+    let widths: Vec<usize> = labels.iter().map(|l| l.width() + 4).collect();
+    // 相邻参与者中心的间距：容下两侧方框，再按消息文字加宽
+    let mut gaps: Vec<usize> = widths.windows(2).map(|w| w[0] - w[0] / 2 + w[1] / 2 + GAP).collect();
+    let mut tail = widths.last().map_or(0, |w| w - w / 2);
+    for step in steps {
+        match *step {
+            Step::Message { from, to, ref text, .. } if from != to => {
+                let (a, b) = (from.min(to), from.max(to));
+                let need = text.width() + 4;
+                let span: usize = gaps[a..b].iter().sum();
+                if span < need {
+                    gaps[b - 1] += need - span;
+                }
+            }
+            Step::Message { from: at, ref text, .. } | Step::Note { at, ref text } => {
+                // 自身消息与注释写在生命线右侧
+                let need = text.width() + 6;
+                match gaps.get_mut(at) {
+                    Some(gap) => *gap = (*gap).max(need),
+                    None => tail = tail.max(need),
+                }
+            }
+        }
+    }
+    let mut centers = vec![widths[0] / 2];
+    for gap in &gaps {
+        centers.push(centers.last().unwrap() + gap);
+    }
+    let width = centers.last().unwrap() + tail + 1;
+
+    let rows: usize = steps
+        .iter()
+        .map(|s| match s {
+            Step::Message { .. } => 2,
+            Step::Note { .. } => 1,
+        })
+        .sum();
+    let height = 3 + rows + 1 + 3;
+    let mut canvas = Canvas::new(width, height);
+    for &x in &centers {
+        canvas.line((x, 2), (x, height - 3));
+    }
+    for (i, label) in labels.iter().enumerate() {
+        draw_participant(&mut canvas, label, centers[i], widths[i], 0);
+        draw_participant(&mut canvas, label, centers[i], widths[i], height - 3);
+    }
+
+    let mut y = 3;
+    for step in steps {
+        match *step {
+            Step::Message { from, to, ref text, dashed } if from != to => {
+                let (x1, x2) = (centers[from], centers[to]);
+                let left = x1.min(x2);
+                canvas.put(left + 2, y, text, TextStyle::NONE);
+                canvas.line((x1, y + 1), (x2, y + 1));
+                if dashed {
+                    canvas.put(left + 1, y + 1, &"╌".repeat(x1.abs_diff(x2) - 1), TextStyle::NONE);
+                }
+                let (x, head) = if x2 > x1 { (x2 - 1, "▶") } else { (x2 + 1, "◀") };
+                canvas.put(x, y + 1, head, TextStyle::NONE);
+                y += 2;
+            }
+            Step::Message { from, ref text, .. } => {
+                let x = centers[from];
+                canvas.line((x, y), (x + 2, y));
+                canvas.line((x + 2, y), (x + 2, y + 1));
+                canvas.line((x + 2, y + 1), (x, y + 1));
+                canvas.put(x + 1, y + 1, "◀", TextStyle::NONE);
+                canvas.put(x + 4, y, text, TextStyle::NONE);
+                y += 2;
+            }
+            Step::Note { at, ref text } => {
+                canvas.put(centers[at] + 2, y, text, TextStyle::DIM);
+                y += 1;
+            }
+        }
+    }
+    canvas.into_lines()
+}
+
+/// 参与者方框，`top` 为上边框所在行
+fn draw_participant(canvas: &mut Canvas, label: &str, center: usize, width: usize, top: usize) {
+    let (left, right) = (center - width / 2, center - width / 2 + width - 1);
+    canvas.line((left, top), (right, top));
+    canvas.line((left, top + 2), (right, top + 2));
+    canvas.line((left, top), (left, top + 2));
+    canvas.line((right, top), (right, top + 2));
+    canvas.put(left + 2, top + 1, label, TextStyle::BOLD);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(lines: &[RenderLine]) -> Vec<String> {
+        lines.iter().map(|l| l.spans.iter().map(|s| s.text.as_str()).collect()).collect()
+    }
+
+    #[test]
+    fn test_flowchart() {
+        let lines = render("graph TD\n  A[开始] -->|是| B(处理)\n  A -- 否 --> C{结束}; B --> C").unwrap();
+        let lines = text(&lines);
+        assert!(lines[1].contains("│ 开始 │"));
+        assert!(lines.iter().any(|l| l.contains("是")) && lines.iter().any(|l| l.contains("否")));
+        assert!(lines.last().unwrap().starts_with("└"));
+        assert!(render("pie\n  \"a\" : 1").is_none());
+        assert!(render("graph LR\n  A --> ").is_none());
+    }
+
+    #[test]
+    fn test_sequence() {
+        let lines = render("sequenceDiagram\n  participant A as Alice\n  A->>B: hi\n  B-->>A: ok\n  B->>B: think")
+            .unwrap();
+        assert_eq!(
+            text(&lines),
+            [
+                "┌───────┐   ┌───┐",
+                "│ Alice │   │ B │",
+                "└───┬───┘   └─┬─┘",
+                "    │ hi      │",
+                "    ├────────▶┤",
+                "    │ ok      │",
+                "    ├◀╌╌╌╌╌╌╌╌┤",
+                "    │         ├─┐ think",
+                "    │         ├◀┘",
+                "    │         │",
+                "┌───┴───┐   ┌─┴─┐",
+                "│ Alice │   │ B │",
+                "└───────┘   └───┘",
+            ]
+        );
+    }
+}
@@ -1,27 +1,134 @@
+pub mod archive;
+pub mod audio;
+pub mod binary;
+pub mod compressed;
+pub mod container;
+pub mod csv;
+pub mod diff;
+pub mod dir;
+pub mod docx;
+pub mod dot;
+pub mod email;
+pub mod executable;
+pub mod epub;
+pub mod html;
+pub mod hex;
+pub mod highlight;
+pub mod ics;
 pub mod image;
+pub mod ipynb;
+pub mod json;
+pub mod log;
+pub mod lzma;
 pub mod magic;
 pub mod markdown;
+pub mod math;
+pub mod mermaid;
+pub mod odf;
 pub mod pdf;
+pub mod protobuf;
+pub mod rst;
+pub mod sevenz;
+pub mod structured;
+pub mod table;
 pub mod text;
+pub mod xlsx;
+pub mod xml;
 
 use crate::document::Document;
+use crate::t;
 use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
+use tracing::debug;
 
 /// 文件格式解析 trait（文档类格式）
 pub trait FileFormat {
     fn parse(&self, path: &Path) -> Result<Document, FormatError>;
     fn extensions(&self) -> &[&str];
+    /// 格式名，用于 `--type` 等诊断输出
+    fn name(&self) -> &'static str;
+}
+
+/// 各格式解析器的选项
+#[derive(Clone, Debug, Default)]
+pub struct FormatOptions {
+    pub markdown: markdown::MarkdownOptions,
+    /// 强制使用的格式，跳过 magic number 与扩展名检测
+    pub forced: Option<ForcedFormat>,
+    /// 目录预览的排序方式
+    pub sort: dir::SortKey,
+    /// 解码二进制 protobuf 所用的描述符集（`--schema`）
+    pub schema: Option<protobuf::Schema>,
+    pub pdf: pdf::PdfOptions,
+}
+
+/// `--format` 可指定的格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForcedFormat {
+    Markdown,
+    Pdf,
+    Text,
+    Image,
+    Hex,
+    Protobuf,
+}
+
+impl FromStr for ForcedFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "md" | "markdown" => Ok(ForcedFormat::Markdown),
+            "pdf" => Ok(ForcedFormat::Pdf),
+            "txt" | "text" => Ok(ForcedFormat::Text),
+            "image" | "img" => Ok(ForcedFormat::Image),
+            "hex" => Ok(ForcedFormat::Hex),
+            "pb" | "protobuf" => Ok(ForcedFormat::Protobuf),
+            _ => Err(t!("无效的格式: {}（可选 md、pdf、txt、image、hex、pb）", s)),
+        }
+    }
 }
 
 /// 格式分类
 pub enum FormatKind {
-    /// 文档格式 - 进入预览模式（Markdown, PDF）
+    /// 文档格式 - 进入预览模式（Markdown, PDF, DOCX, EPUB）
     Document(Box<dyn FileFormat>),
     /// 图片格式 - 直接模式显示
     Image,
     /// 纯文本格式 - 直接模式显示
     Text,
+    /// 压缩流（gzip、bzip2、xz、zstd）- 解压后重新检测内层格式
+    Compressed,
+}
+
+impl FormatKind {
+    /// 格式大类名
+    pub fn name(&self) -> &'static str {
+        match self {
+            FormatKind::Document(format) => format.name(),
+            FormatKind::Image => "image",
+            FormatKind::Text => "text",
+            FormatKind::Compressed => "compressed",
+        }
+    }
+}
+
+/// 格式检测的依据
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DetectedBy {
+    /// `--format` 指定
+    Forced,
+    /// 文件签名
+    Magic(magic::DetectedFormat),
+    /// 扩展名（小写）
+    Extension(String),
+    /// 无法识别且内容为二进制，按十六进制显示
+    Binary,
+    /// 无法识别但内容像 unified diff
+    Diff,
+    /// 无法识别，按纯文本处理
+    Fallback,
 }
 
 #[derive(Debug)]
@@ -39,20 +146,19 @@ impl From<std::io::Error> for FormatError {
 impl fmt::Display for FormatError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            FormatError::Io(e) => write!(f, "IO错误: {e}"),
-            FormatError::Parse(msg) => write!(f, "解析错误: {msg}"),
+            FormatError::Io(e) => f.write_str(&t!("IO错误: {}", e)),
+            FormatError::Parse(msg) => f.write_str(&t!("解析错误: {}", msg)),
         }
     }
 }
 
 const IMAGE_EXTENSIONS: &[&str] = &[
-    "png", "jpg", "jpeg", "gif", "bmp", "webp", "tiff", "tif", "ico",
+    "png", "jpg", "jpeg", "gif", "bmp", "webp", "tiff", "tif", "ico", "svg",
 ];
 
 /// 文本文件扩展名
 const TEXT_EXTENSIONS: &[&str] = &[
-    "txt", "text", "log", "csv", "json", "xml", "yaml", "yml",
-    "toml", "ini", "cfg", "conf", "sh", "bash", "zsh", "fish",
+    "txt", "text", "ini", "cfg", "conf", "sh", "bash", "zsh", "fish",
     "py", "rb", "js", "ts", "go", "rs", "c", "cpp", "h", "hpp",
     "java", "kt", "swift", "lua", "perl", "php", "sql", "html",
     "css", "scss", "sass", "less", "vue", "svelte",
@@ -61,36 +167,109 @@ const TEXT_EXTENSIONS: &[&str] = &[
 /// 检测文件格式
 ///
 /// 检测策略：
+/// 0. `options.forced` 指定了格式时直接使用
 /// 1. 优先使用 magic number（文件签名）检测 - 更可靠
 /// 2. 如果 magic number 检测失败，回退到扩展名检测
-/// 3. 如果仍然无法识别，作为纯文本处理（fallback）
+/// 3. 如果仍然无法识别，作为纯文本处理（fallback）；内容为二进制时以十六进制显示，像 diff 时按补丁着色
 ///
 /// # 参数
 /// - `path`: 文件路径
+/// - `options`: 传给文档解析器的选项
 ///
 /// # 返回
 /// 检测到的格式类型，无法识别时返回 Text 作为 fallback
-pub fn detect_format(path: &Path) -> Option<FormatKind> {
+pub fn detect_format(path: &Path, options: &FormatOptions) -> Option<FormatKind> {
+    detect_format_with_reason(path, options).map(|(kind, _)| kind)
+}
+
+/// 检测文件格式，同时返回检测的依据（用于 `--type`）
+pub fn detect_format_with_reason(
+    path: &Path,
+    options: &FormatOptions,
+) -> Option<(FormatKind, DetectedBy)> {
+    // 0. 用户指定的格式优先
+    if let Some(forced) = options.forced {
+        debug!(path = %path.display(), ?forced, "使用指定的格式");
+        let kind = match forced {
+            ForcedFormat::Markdown => FormatKind::Document(Box::new(markdown::MarkdownFormat {
+                options: options.markdown.clone(),
+            })),
+            ForcedFormat::Pdf => FormatKind::Document(Box::new(pdf::PdfFormat { options: options.pdf })),
+            ForcedFormat::Text => FormatKind::Text,
+            ForcedFormat::Image => FormatKind::Image,
+            ForcedFormat::Hex => FormatKind::Document(Box::new(hex::HexFormat)),
+            ForcedFormat::Protobuf => FormatKind::Document(Box::new(protobuf::ProtobufFormat {
+                schema: options.schema.clone(),
+            })),
+        };
+        return Some((kind, DetectedBy::Forced));
+    }
+
     // 1. 优先使用 magic number 检测
     if let Some(detected) = magic::detect_file_format(path) {
-        return match detected {
-            magic::DetectedFormat::Image(_) => Some(FormatKind::Image),
+        debug!(path = %path.display(), ?detected, "magic number 检测命中");
+        let kind = match detected {
+            magic::DetectedFormat::Image(_) => FormatKind::Image,
             magic::DetectedFormat::Document(magic::DocumentFormat::Pdf) => {
-                Some(FormatKind::Document(Box::new(pdf::PdfFormat)))
+                FormatKind::Document(Box::new(pdf::PdfFormat { options: options.pdf }))
+            }
+            magic::DetectedFormat::Document(magic::DocumentFormat::Docx) => {
+                FormatKind::Document(Box::new(docx::DocxFormat))
+            }
+            magic::DetectedFormat::Document(magic::DocumentFormat::Epub) => {
+                FormatKind::Document(Box::new(epub::EpubFormat))
+            }
+            magic::DetectedFormat::Document(magic::DocumentFormat::Xlsx | magic::DocumentFormat::Ods) => {
+                FormatKind::Document(Box::new(xlsx::XlsxFormat))
+            }
+            magic::DetectedFormat::Document(magic::DocumentFormat::Odt | magic::DocumentFormat::Odp) => {
+                FormatKind::Document(Box::new(odf::OdfFormat))
             }
+            magic::DetectedFormat::Document(magic::DocumentFormat::Cbor) => {
+                FormatKind::Document(Box::new(binary::CborFormat))
+            }
+            magic::DetectedFormat::Archive(_) => FormatKind::Document(Box::new(archive::ArchiveFormat)),
+            magic::DetectedFormat::Compressed(_) => FormatKind::Compressed,
+            magic::DetectedFormat::Audio(_) => FormatKind::Document(Box::new(audio::AudioFormat)),
+            magic::DetectedFormat::Executable(_) => FormatKind::Document(Box::new(executable::ExecutableFormat)),
         };
+        return Some((kind, DetectedBy::Magic(detected)));
+    }
+
+    // 2. 回退到扩展名检测（按文本处理的扩展名仍要检查内容是否为二进制）
+    let by_extension = detect_format_by_extension(path, options);
+    if let Some(kind) = by_extension.filter(|k| !matches!(k, FormatKind::Text) || !is_binary(path)) {
+        let ext = path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+        return Some((kind, DetectedBy::Extension(ext)));
+    }
+
+    // 3. 最终 fallback：二进制内容以十六进制显示，其余作为纯文本处理
+    if is_binary(path) {
+        debug!(path = %path.display(), "内容为二进制，以十六进制显示");
+        return Some((FormatKind::Document(Box::new(hex::HexFormat)), DetectedBy::Binary));
     }
+    if magic::read_sample(path).is_ok_and(|data| diff::looks_like_git(&String::from_utf8_lossy(&data))) {
+        debug!(path = %path.display(), "内容像 diff，按补丁显示");
+        return Some((FormatKind::Document(Box::new(diff::DiffFormat)), DetectedBy::Diff));
+    }
+    debug!(path = %path.display(), "无法识别格式，按纯文本处理");
+    Some((FormatKind::Text, DetectedBy::Fallback))
+}
+
+fn is_binary(path: &Path) -> bool {
+    magic::read_sample(path).is_ok_and(|data| magic::looks_binary(&data))
+}
 
-    // 2. 回退到扩展名检测
-    detect_format_by_extension(path).or_else(|| {
-        // 3. 最终 fallback：作为纯文本处理
-        Some(FormatKind::Text)
-    })
+/// 文件能否按签名或扩展名识别（不计按纯文本处理的 fallback），用于 `-r` 挑选文件
+pub fn is_recognized(path: &Path, options: &FormatOptions) -> bool {
+    magic::detect_file_format(path).is_some()
+        || detect_format_by_extension(path, options).is_some()
 }
 
 /// 根据文件扩展名检测格式（作为 fallback）
-fn detect_format_by_extension(path: &Path) -> Option<FormatKind> {
+fn detect_format_by_extension(path: &Path, options: &FormatOptions) -> Option<FormatKind> {
     let ext = path.extension()?.to_str()?.to_lowercase();
+    debug!(ext, "按扩展名检测格式");
 
     if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
         return Some(FormatKind::Image);
@@ -101,8 +280,40 @@ fn detect_format_by_extension(path: &Path) -> Option<FormatKind> {
     }
 
     let formats: Vec<Box<dyn FileFormat>> = vec![
-        Box::new(markdown::MarkdownFormat),
-        Box::new(pdf::PdfFormat),
+        Box::new(markdown::MarkdownFormat {
+            options: options.markdown.clone(),
+        }),
+        Box::new(pdf::PdfFormat { options: options.pdf }),
+        Box::new(docx::DocxFormat),
+        Box::new(epub::EpubFormat),
+        Box::new(archive::ArchiveFormat),
+        Box::new(xlsx::XlsxFormat),
+        Box::new(odf::OdfFormat),
+        Box::new(email::EmailFormat),
+        Box::new(ics::IcsFormat),
+        Box::new(diff::DiffFormat),
+        Box::new(dot::DotFormat),
+        Box::new(log::LogFormat),
+        Box::new(csv::CsvFormat),
+        Box::new(json::JsonFormat),
+        Box::new(xml::XmlFormat),
+        Box::new(binary::MsgpackFormat),
+        Box::new(binary::CborFormat),
+        Box::new(binary::BsonFormat),
+        Box::new(protobuf::ProtoFormat),
+        Box::new(protobuf::ProtobufFormat {
+            schema: options.schema.clone(),
+        }),
+        Box::new(audio::AudioFormat),
+        Box::new(executable::ExecutableFormat),
+        Box::new(ipynb::IpynbFormat {
+            options: options.markdown.clone(),
+        }),
+        Box::new(rst::RstFormat {
+            options: options.markdown.clone(),
+        }),
+        Box::new(structured::YamlFormat),
+        Box::new(structured::TomlFormat),
     ];
 
     formats
@@ -0,0 +1,404 @@
+//! ODF（OpenDocument 文本与演示文稿）解析
+//!
+//! 从 ZIP 容器中读取 `content.xml`，将段落、标题、粗体 / 斜体、列表与表格映射为渲染行。
+//! 字体样式来自 `content.xml` 的自动样式与 `styles.xml` 的公共样式，列表编号方式来自列表样式。
+//! 演示文稿的每一页前插入分隔线并记录为章节。电子表格（ods）由 [`xlsx`](super::xlsx) 读取。
+
+use crate::document::*;
+use crate::format::container::{parse_xml, read_entry};
+use crate::format::markdown::BULLETS;
+use crate::format::{table, FileFormat, FormatError};
+use crate::t;
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use unicode_width::UnicodeWidthStr;
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+const TEXT_NS: &str = "urn:oasis:names:tc:opendocument:xmlns:text:1.0";
+const TABLE_NS: &str = "urn:oasis:names:tc:opendocument:xmlns:table:1.0";
+const STYLE_NS: &str = "urn:oasis:names:tc:opendocument:xmlns:style:1.0";
+const FO_NS: &str = "urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0";
+const DRAW_NS: &str = "urn:oasis:names:tc:opendocument:xmlns:drawing:1.0";
+const OFFICE_NS: &str = "urn:oasis:names:tc:opendocument:xmlns:office:1.0";
+
+pub struct OdfFormat;
+
+impl FileFormat for OdfFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let mut archive = ZipArchive::new(File::open(path)?).map_err(odf_error)?;
+        let content = read_entry(&mut archive, "content.xml")
+            .and_then(|entry| entry.ok_or(ZipError::FileNotFound))
+            .map_err(odf_error)?;
+        let styles = read_entry(&mut archive, "styles.xml").map_err(odf_error)?;
+        parse_odf(&content, styles.as_deref())
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["odt", "ott", "odp"]
+    }
+
+    fn name(&self) -> &'static str {
+        "odf"
+    }
+}
+
+fn odf_error(e: impl std::fmt::Display) -> FormatError {
+    FormatError::Parse(t!("ODF 解析失败: {}", e))
+}
+
+/// 解析 `content.xml`，`styles` 为可选的公共样式
+pub fn parse_odf(content: &str, styles: Option<&str>) -> Result<Document, FormatError> {
+    let xml = parse_xml(content).map_err(odf_error)?;
+    let mut state = OdfState::default();
+    let styles_xml = styles.map(parse_xml).transpose().map_err(odf_error)?;
+    for root in styles_xml.iter().map(|x| x.root_element()).chain([xml.root_element()]) {
+        state.collect_styles(root);
+    }
+
+    let body = xml.root_element().children().find(|n| is_ns(n, OFFICE_NS, "body"));
+    for part in body.iter().flat_map(|b| b.children().filter(Node::is_element)) {
+        if is_ns(&part, OFFICE_NS, "presentation") {
+            let pages: Vec<Node> = part.children().filter(|n| is_ns(n, DRAW_NS, "page")).collect();
+            for (i, page) in pages.iter().enumerate() {
+                state.blank();
+                let label = match page.attribute((DRAW_NS, "name")) {
+                    Some(name) => format!("── {}/{} {} ──", i + 1, pages.len(), name),
+                    None => format!("── {}/{} ──", i + 1, pages.len()),
+                };
+                state.doc.sections.push(state.doc.lines.len());
+                state.doc.lines.push(RenderLine {
+                    spans: vec![TextSpan::new(label, TextStyle::DIM)],
+                    indent: 0,
+                });
+                state.doc.lines.push(RenderLine::default());
+                state.block(*page, 0);
+            }
+        } else {
+            state.block(part, 0);
+        }
+    }
+    while state.doc.lines.last().is_some_and(|l| l.spans.is_empty()) {
+        state.doc.lines.pop();
+    }
+    Ok(state.doc)
+}
+
+#[derive(Default)]
+struct OdfState {
+    doc: Document,
+    /// 样式名 → 粗体 / 斜体
+    text_styles: HashMap<String, TextStyle>,
+    /// 列表样式名 → 各级别是否为有序列表
+    list_styles: HashMap<String, Vec<bool>>,
+    /// 上一个段落是列表条目，列表条目之间不插入空行
+    in_list: bool,
+}
+
+impl OdfState {
+    fn collect_styles(&mut self, root: Node) {
+        for style in root.descendants() {
+            if is_ns(&style, STYLE_NS, "style") {
+                let Some(name) = style.attribute((STYLE_NS, "name")) else {
+                    continue;
+                };
+                let mut text_style = style
+                    .attribute((STYLE_NS, "parent-style-name"))
+                    .and_then(|parent| self.text_styles.get(parent).copied())
+                    .unwrap_or(TextStyle::NONE);
+                if let Some(props) = style.children().find(|n| is_ns(n, STYLE_NS, "text-properties")) {
+                    let weight = props.attribute((FO_NS, "font-weight")).unwrap_or_default();
+                    if weight == "bold" || weight.parse::<u16>().is_ok_and(|w| w >= 600) {
+                        text_style.insert(TextStyle::BOLD);
+                    }
+                    if props.attribute((FO_NS, "font-style")) == Some("italic") {
+                        text_style.insert(TextStyle::ITALIC);
+                    }
+                }
+                self.text_styles.insert(name.to_string(), text_style);
+            } else if is_ns(&style, TEXT_NS, "list-style") {
+                let Some(name) = style.attribute((STYLE_NS, "name")) else {
+                    continue;
+                };
+                let mut levels = Vec::new();
+                for level in style.children().filter(Node::is_element) {
+                    let depth: usize = level.attribute((TEXT_NS, "level")).and_then(|l| l.parse().ok()).unwrap_or(1);
+                    // ODF 列表最多 10 级，超出范围的定义忽略
+                    if !(1..=10).contains(&depth) {
+                        continue;
+                    }
+                    levels.resize(levels.len().max(depth), false);
+                    levels[depth - 1] = level.tag_name().name() == "list-level-style-number";
+                }
+                self.list_styles.insert(name.to_string(), levels);
+            }
+        }
+    }
+
+    /// 处理块级元素：标题、段落、列表、表格及包裹它们的节、框架
+    fn block(&mut self, node: Node, depth: u16) {
+        for child in node.children().filter(Node::is_element) {
+            let name = child.tag_name().name();
+            match child.tag_name().namespace() {
+                Some(TEXT_NS) if name == "h" => {
+                    let level = child
+                        .attribute((TEXT_NS, "outline-level"))
+                        .and_then(|l| l.parse().ok())
+                        .unwrap_or(1u8);
+                    self.paragraph(child, Some(level.clamp(1, 6)), None);
+                }
+                Some(TEXT_NS) if name == "p" => self.paragraph(child, None, None),
+                Some(TEXT_NS) if name == "list" => {
+                    let style = child.attribute((TEXT_NS, "style-name")).unwrap_or_default().to_string();
+                    self.list(child, &style, depth);
+                }
+                Some(TABLE_NS) if name == "table" => self.table(child),
+                // 目录、修订记录、表单等不是正文
+                Some(TEXT_NS) if matches!(name, "tracked-changes" | "sequence-decls" | "table-of-content") => {}
+                Some(OFFICE_NS) if name == "forms" => {}
+                _ => self.block(child, depth),
+            }
+        }
+    }
+
+    fn list(&mut self, list: Node, style: &str, depth: u16) {
+        let ordered = self
+            .list_styles
+            .get(style)
+            .and_then(|levels| levels.get(depth as usize))
+            .copied()
+            .unwrap_or(false);
+        let mut number = 0;
+        for item in list.children().filter(|n| is_ns(n, TEXT_NS, "list-item") || is_ns(n, TEXT_NS, "list-header")) {
+            number += 1;
+            let marker = if ordered {
+                format!("{number}. ")
+            } else {
+                format!("{} ", BULLETS[depth as usize % BULLETS.len()])
+            };
+            let mut first = true;
+            for child in item.children().filter(Node::is_element) {
+                if is_ns(&child, TEXT_NS, "list") {
+                    self.list(child, style, depth + 1);
+                } else if is_ns(&child, TEXT_NS, "p") || is_ns(&child, TEXT_NS, "h") {
+                    let marker = if first { marker.clone() } else { " ".repeat(marker.width()) };
+                    self.paragraph(child, None, Some((depth * 2, marker)));
+                    first = false;
+                }
+            }
+        }
+        if depth == 0 {
+            self.in_list = false;
+            self.blank();
+        }
+    }
+
+    fn paragraph(&mut self, p: Node, level: Option<u8>, list: Option<(u16, String)>) {
+        let base = match level {
+            Some(_) => TextStyle::BOLD | TextStyle::HEADING,
+            None => TextStyle::NONE,
+        };
+        let base = base | self.style_of(p);
+        let mut lines = vec![Vec::new()];
+        self.spans(p, base, &mut lines);
+        if list.is_none() && lines.iter().all(|spans| spans.iter().all(|s| s.text.trim().is_empty())) {
+            return;
+        }
+
+        if list.is_none() && self.in_list {
+            self.blank();
+        }
+        if let Some(level) = level {
+            let text: String = lines.iter().flatten().map(|s| s.text.as_str()).collect();
+            self.doc.headings.push(Heading {
+                level,
+                line: self.doc.lines.len(),
+                text: text.trim().to_string(),
+            });
+        }
+        let (indent, marker) = list.clone().unwrap_or_default();
+        let hang = marker.width() as u16;
+        for (i, mut spans) in lines.into_iter().enumerate() {
+            if i == 0 && !marker.is_empty() {
+                spans.insert(0, TextSpan::new(marker.clone(), base));
+            }
+            let indent = if i == 0 { indent } else { indent + hang };
+            self.doc.lines.push(RenderLine { spans, indent });
+        }
+
+        self.in_list = list.is_some();
+        if !self.in_list {
+            self.blank();
+        }
+    }
+
+    /// 收集段落中的文本，`text:line-break` 换行开始新的一行
+    fn spans(&self, node: Node, style: TextStyle, lines: &mut Vec<Vec<TextSpan>>) {
+        for child in node.children() {
+            let owned;
+            let text = if child.is_text() {
+                child.text().unwrap_or_default()
+            } else if is_ns(&child, TEXT_NS, "span") {
+                self.spans(child, style | self.style_of(child), lines);
+                continue;
+            } else if is_ns(&child, TEXT_NS, "a") {
+                self.spans(child, style | TextStyle::LINK, lines);
+                continue;
+            } else if is_ns(&child, TEXT_NS, "s") {
+                let count = child.attribute((TEXT_NS, "c")).and_then(|c| c.parse().ok()).unwrap_or(1);
+                owned = " ".repeat(count);
+                &owned
+            } else if is_ns(&child, TEXT_NS, "tab") {
+                "    "
+            } else if is_ns(&child, TEXT_NS, "line-break") {
+                lines.push(Vec::new());
+                continue;
+            } else if is_ns(&child, TEXT_NS, "note") {
+                // 脚注只显示编号
+                owned = child
+                    .children()
+                    .find(|n| is_ns(n, TEXT_NS, "note-citation"))
+                    .and_then(|n| n.text())
+                    .map(|c| format!("[{c}]"))
+                    .unwrap_or_default();
+                &owned
+            } else {
+                self.spans(child, style, lines);
+                continue;
+            };
+            if text.is_empty() {
+                continue;
+            }
+            let spans = lines.last_mut().expect("至少有一行");
+            match spans.last_mut() {
+                Some(last) if last.style == style => last.text.push_str(text),
+                _ => spans.push(TextSpan::new(text, style)),
+            }
+        }
+    }
+
+    fn style_of(&self, node: Node) -> TextStyle {
+        node.attribute((TEXT_NS, "style-name"))
+            .and_then(|name| self.text_styles.get(name).copied())
+            .unwrap_or(TextStyle::NONE)
+    }
+
+    fn table(&mut self, tbl: Node) {
+        let rows: Vec<Vec<String>> = tbl
+            .descendants()
+            .filter(|n| is_ns(n, TABLE_NS, "table-row"))
+            .map(|tr| {
+                let mut cells = Vec::new();
+                for tc in tr.children().filter(|n| is_ns(n, TABLE_NS, "table-cell")) {
+                    let text = cell_text(tc);
+                    // 重复的空单元格只保留一个，避免整行铺满上千列
+                    let repeat = match tc.attribute((TABLE_NS, "number-columns-repeated")) {
+                        Some(n) if !text.is_empty() => n.parse().unwrap_or(1).min(256),
+                        _ => 1,
+                    };
+                    cells.extend(std::iter::repeat_n(text, repeat));
+                }
+                while cells.last().is_some_and(String::is_empty) {
+                    cells.pop();
+                }
+                cells
+            })
+            .collect();
+        if rows.iter().all(Vec::is_empty) {
+            return;
+        }
+        if self.in_list {
+            self.blank();
+        }
+        table::push_table(&mut self.doc, &rows, &[], 0);
+        self.in_list = false;
+        self.blank();
+    }
+
+    /// 追加一个空行，已有空行时不重复
+    fn blank(&mut self) {
+        if self.doc.lines.last().is_some_and(|l| !l.spans.is_empty()) {
+            self.doc.lines.push(RenderLine::default());
+        }
+    }
+}
+
+/// 单元格内各段落的文本，以空格连接
+fn cell_text(tc: Node) -> String {
+    tc.descendants()
+        .filter(|n| is_ns(n, TEXT_NS, "p"))
+        .map(|p| p.descendants().filter(Node::is_text).filter_map(|t| t.text()).collect::<String>())
+        .filter(|text| !text.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_ns(node: &Node, ns: &str, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name && node.tag_name().namespace() == Some(ns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_odf() {
+        let content = r#"<?xml version="1.0"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+  xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0"
+  xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0"
+  xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0"
+  xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0">
+ <office:automatic-styles>
+  <style:style style:name="T1" style:family="text"><style:text-properties fo:font-weight="bold"/></style:style>
+  <text:list-style style:name="L1"><text:list-level-style-number text:level="1"/><text:list-level-style-bullet text:level="2"/></text:list-style>
+ </office:automatic-styles>
+ <office:body><office:text>
+  <text:h text:outline-level="2">标题</text:h>
+  <text:p>普通<text:span text:style-name="T1">粗体</text:span><text:s text:c="2"/>末尾</text:p>
+  <text:list text:style-name="L1">
+   <text:list-item><text:p>一</text:p>
+    <text:list><text:list-item><text:p>子项</text:p></text:list-item></text:list>
+   </text:list-item>
+   <text:list-item><text:p>二</text:p></text:list-item>
+  </text:list>
+  <table:table><table:table-row><table:table-cell><text:p>a</text:p></table:table-cell>
+   <table:table-cell table:number-columns-repeated="1000"/></table:table-row>
+   <table:table-row><table:table-cell><text:p>1</text:p></table:table-cell></table:table-row></table:table>
+ </office:text></office:body>
+</office:document-content>"#;
+        let doc = parse_odf(content, None).unwrap();
+        let lines: Vec<String> = doc
+            .lines
+            .iter()
+            .map(|l| {
+                let text: String = l.spans.iter().map(|s| s.text.as_str()).collect();
+                format!("{}{}", " ".repeat(l.indent as usize), text.trim_end())
+            })
+            .collect();
+        assert_eq!(lines, ["标题", "", "普通粗体  末尾", "", "1. 一", "  ◦ 子项", "2. 二", "", "a", "─", "1"]);
+        assert_eq!(doc.headings[0].level, 2);
+        assert_eq!(doc.lines[2].spans[1].style, TextStyle::BOLD);
+    }
+
+    #[test]
+    fn test_malformed_list_style() {
+        let content = r#"<?xml version="1.0"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+  xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0"
+  xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+ <office:automatic-styles>
+  <text:list-style style:name="L1"><text:list-level-style-number text:level="0"/>
+   <text:list-level-style-number text:level="4000000000"/><text:list-level-style-number text:level="1"/></text:list-style>
+ </office:automatic-styles>
+ <office:body><office:text>
+  <text:list text:style-name="L1"><text:list-item><text:p>一</text:p></text:list-item></text:list>
+ </office:text></office:body>
+</office:document-content>"#;
+        let doc = parse_odf(content, None).unwrap();
+        let text: String = doc.lines[0].spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(text, "1. 一");
+    }
+}
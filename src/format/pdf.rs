@@ -1,44 +1,1287 @@
+//! PDF 文本提取
+//!
+//! 逐页提取文本，页与页之间插入分隔行，各页起始行记录在 `pages` 中。
+//! PDF 书签（大纲）转为标题，在目录面板中选择即跳到对应页；没有书签时按文本猜测标题（[`guess_heading`]）。
+//! 预览时先显示第一页，其余页由 [`PageLoader`] 在后台提取后依次追加。
+//! 文档开头以暗色显示信息字典（缺少时取 XMP）中的标题、作者等以及页数与 PDF 版本。
+//! `--layout` 时按字符坐标把文本排到等宽网格上，多栏论文与表格保持原有的版面。
+//!
+//! 文本由 [`Extractor`] 后端提取：内置的 pdf-extract，或系统中 poppler 的 `pdftotext`（`poppler` 特性）。
+//! 启用 poppler 后端时默认先用内置解析器，某页出错或提取出乱码时改用 poppler（已安装时）。
+//! 链接注释按区域内的文字定位到所在行，网址与跳到本文档某页（`#page=N`）的链接可以在预览模式中打开。
+//! 提取出的文本经过整理（连字拆开、合并空白、去掉行末断词的连字符），便于搜索与阅读。
+//! 以 `ocr` 特性编译并指定 `--ocr` 时，几乎没有文字而有大幅图片的扫描页交给 tesseract 识别，识别出的文字前标注 `[OCR]`。
+//! 预览模式中可以按页显示为图片（[`render_page`]），用于版面或图形比文字更重要的文档。
+
 use crate::document::*;
+use crate::format::table::pad_to_width;
 use crate::format::{FileFormat, FormatError};
-use std::path::Path;
+use crate::t;
+use pdf_extract::{Dictionary, MediaBox, Object, ObjectId, OutputDev, OutputError, PlainTextOutput, Transform};
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::mpsc::{self, TryRecvError};
+use unicode_width::UnicodeWidthStr;
+
+/// 提取 PDF 文本的后端
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PdfBackend {
+    /// 内置解析器，出错时改用 poppler（启用 `poppler` 特性时）
+    #[default]
+    Auto,
+    /// 只用内置的 pdf-extract
+    Builtin,
+    /// 只用 poppler 的 `pdftotext`
+    Poppler,
+}
+
+impl FromStr for PdfBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(PdfBackend::Auto),
+            "builtin" | "pdf-extract" => Ok(PdfBackend::Builtin),
+            "poppler" | "pdftotext" => Ok(PdfBackend::Poppler),
+            _ => Err(t!("无效的 PDF 后端: {}（可选 auto、builtin、poppler）", s)),
+        }
+    }
+}
 
-pub struct PdfFormat;
+/// PDF 解析选项
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PdfOptions {
+    /// 按字符位置还原版面（`--layout`）
+    pub layout: bool,
+    /// 提取文本的后端（`--pdf-backend`）
+    pub backend: PdfBackend,
+    /// 扫描页用 tesseract 识别文字（`--ocr`，需要 `ocr` 特性）
+    pub ocr: bool,
+}
+
+pub struct PdfFormat {
+    pub options: PdfOptions,
+}
 
 impl FileFormat for PdfFormat {
     fn parse(&self, path: &Path) -> Result<Document, FormatError> {
-        let pages = pdf_extract::extract_text_by_pages(path)
-            .map_err(|e| FormatError::Parse(format!("PDF 解析失败: {e}")))?;
+        Ok(Source::open(path, self.options)?.document())
+    }
 
-        let mut doc = Document::default();
+    fn extensions(&self) -> &[&str] {
+        &["pdf"]
+    }
 
-        for (i, page_text) in pages.iter().enumerate() {
-            if i > 0 {
-                doc.lines.push(RenderLine::default());
-                doc.lines.push(RenderLine {
-                    spans: vec![TextSpan {
-                        text: format!("── 第 {} 页 ──", i + 1),
-                        style: TextStyle::DIM,
-                    }],
-                    indent: 0,
-                });
-                doc.lines.push(RenderLine::default());
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+}
+
+/// 后台逐页提取文本，提取完的页依次送出
+pub struct PageLoader {
+    pages: mpsc::Receiver<Document>,
+    loaded: u32,
+    total: u32,
+}
+
+impl PageLoader {
+    /// 取出已提取完的页，不阻塞；全部提取完（或某页提取失败）后返回 None
+    pub fn poll(&mut self) -> Option<Vec<Document>> {
+        let mut pages = Vec::new();
+        loop {
+            match self.pages.try_recv() {
+                Ok(page) => {
+                    self.loaded += 1;
+                    pages.push(page);
+                }
+                Err(TryRecvError::Disconnected) if pages.is_empty() => return None,
+                Err(_) => return Some(pages),
             }
+        }
+    }
+
+    /// 已提取的页数与总页数
+    pub fn progress(&self) -> (u32, u32) {
+        (self.loaded, self.total)
+    }
+}
+
+/// 用 poppler 的 `pdftoppm` 将第 `n` 页（从 1 开始）渲染为临时目录中的 PNG，返回图片路径
+pub fn render_page(path: &Path, n: usize) -> io::Result<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    let base = std::env::temp_dir().join(format!("xcat-page-{}-{:x}-{n}", std::process::id(), hasher.finish()));
+    let page = n.to_string();
+    let output = Command::new("pdftoppm")
+        .args(["-f", &page, "-l", &page, "-r", &RASTER_DPI.to_string(), "-png", "-singlefile"])
+        .arg(path)
+        .arg(&base)
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::other(t!("按页显示图片需要安装 poppler（pdftoppm）")),
+            _ => e,
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(t!("页面渲染失败: {}", stderr.trim())));
+    }
+    Ok(base.with_extension("png"))
+}
+
+/// 只提取第一页，其余页交给后台线程，大文件也能立即显示
+pub fn parse_lazily(path: &Path, options: &PdfOptions) -> Result<(Document, PageLoader), FormatError> {
+    let source = Source::open(path, *options)?;
+    let outline = source.outline();
+    let total = source.pages;
+    let mut doc = source.header();
+    if total > 0 {
+        doc.append(page_document(1, &source.page_text(1), &outline));
+    }
 
-            for line in page_text.lines() {
-                doc.lines.push(RenderLine {
-                    spans: vec![TextSpan {
-                        text: line.to_string(),
-                        style: TextStyle::NONE,
-                    }],
-                    indent: 0,
-                });
+    let (sender, pages) = mpsc::channel();
+    std::thread::spawn(move || {
+        for n in 2..=total {
+            // 预览已关闭时停止提取
+            if sender.send(page_document(n, &source.page_text(n), &outline)).is_err() {
+                return;
             }
         }
+    });
+    let loader = PageLoader {
+        pages,
+        loaded: 1,
+        total,
+    };
+    Ok((doc, loader))
+}
+
+/// 打开的 PDF
+struct Source {
+    /// 内置解析器读取的文档结构，用于书签与文档信息；无法读取（改用 poppler）时为 None
+    pdf: Option<Arc<pdf_extract::Document>>,
+    extractor: Box<dyn Extractor>,
+    pages: u32,
+    /// 文字识别时交给 `pdftoppm` 转换页面
+    #[cfg(feature = "ocr")]
+    path: PathBuf,
+    options: PdfOptions,
+}
+
+/// 一页的文本及其中的链接，`ocr` 表示由文字识别得到，`failed` 表示提取失败
+#[derive(Default)]
+struct PageText {
+    text: String,
+    ocr: bool,
+    failed: bool,
+    links: Vec<PageLink>,
+}
+
+/// 页面中的链接：链接区域内的文字及目标（网址，或 `#page=N` 表示本文档第 N 页）
+struct PageLink {
+    text: String,
+    url: String,
+    /// 链接所在的一行去掉空白后的文字，及其中链接之前的字符数，用于在提取出的文本中定位
+    row: String,
+    before: usize,
+}
+
+/// 可见字符少于此数的页视为没有文字
+#[cfg(feature = "ocr")]
+const OCR_MAX_CHARS: usize = 16;
+/// 需要识别的图片的短边至少为此像素数
+#[cfg(feature = "ocr")]
+const OCR_MIN_SIDE: i64 = 300;
+/// 猜测为标题的行最多的字符数
+const HEADING_MAX_CHARS: usize = 60;
+/// 前后都是空行的短行最多的词数
+const HEADING_MAX_WORDS: usize = 8;
+/// 查找命名目标时名称树与间接目标的最大层数，避免循环引用
+const NAME_TREE_DEPTH: u8 = 32;
+/// 按页显示图片时的渲染分辨率（DPI），终端中再按显示区域缩放
+const RASTER_DPI: u32 = 110;
 
-        Ok(doc)
+impl Source {
+    fn open(path: &Path, options: PdfOptions) -> Result<Self, FormatError> {
+        let poppler = || poppler(path);
+        let (pdf, extractor): (_, Box<dyn Extractor>) = match (options.backend, load(path)) {
+            (PdfBackend::Builtin, loaded) => {
+                let pdf = Arc::new(loaded?);
+                (Some(pdf.clone()), Box::new(Builtin(pdf)))
+            }
+            (PdfBackend::Poppler, loaded) => (loaded.ok().map(Arc::new), poppler()?),
+            (PdfBackend::Auto, Ok(pdf)) => {
+                let pdf = Arc::new(pdf);
+                let builtin = Box::new(Builtin(pdf.clone()));
+                match poppler() {
+                    Ok(poppler) => (Some(pdf), Box::new(Fallback(builtin, poppler))),
+                    Err(_) => (Some(pdf), builtin),
+                }
+            }
+            // 内置解析器无法读取时整个交给 poppler，poppler 也不可用则报告内置解析器的错误
+            (PdfBackend::Auto, Err(e)) => {
+                tracing::warn!(path = %path.display(), "内置 PDF 解析器无法读取，改用 poppler: {e}");
+                (None, poppler().map_err(|_| e)?)
+            }
+        };
+        let pages = match &pdf {
+            Some(pdf) => page_count(pdf),
+            None => extractor.page_count().unwrap_or(0),
+        };
+        Ok(Self {
+            pdf,
+            extractor,
+            pages,
+            #[cfg(feature = "ocr")]
+            path: path.to_path_buf(),
+            options,
+        })
     }
 
-    fn extensions(&self) -> &[&str] {
-        &["pdf"]
+    /// 依次提取所有页
+    fn document(&self) -> Document {
+        let outline = self.outline();
+        let mut doc = self.header();
+        for n in 1..=self.pages {
+            doc.append(page_document(n, &self.page_text(n), &outline));
+        }
+        doc
+    }
+
+    /// 提取第 `n` 页（从 1 开始）的文本；开启 OCR 时扫描页改为识别结果。
+    /// 某页失败时只标记这一页，后面的页照常提取，页码保持对应
+    fn page_text(&self, n: u32) -> PageText {
+        let layout = self.options.layout;
+        let text = match self.extractor.page_text(n, layout) {
+            Ok(text) => text,
+            Err(e) => {
+                tracing::warn!(page = n, "PDF 文本提取失败: {e}");
+                return PageText { failed: true, ..Default::default() };
+            }
+        };
+        #[cfg(feature = "ocr")]
+        if self.options.ocr
+            && text.chars().filter(|c| !c.is_whitespace()).count() < OCR_MAX_CHARS
+            && let Some(text) = self.recognize(n)
+        {
+            let text = normalize(&text, layout);
+            return PageText { text, ocr: true, failed: false, links: self.links(n) };
+        }
+        let text = normalize(&text, layout);
+        PageText { text, ocr: false, failed: false, links: self.links(n) }
+    }
+
+    /// 第 `n` 页的链接注释，链接文字取注释区域内的字符
+    fn links(&self, n: u32) -> Vec<PageLink> {
+        let Some(pdf) = self.pdf.as_deref() else {
+            return Vec::new();
+        };
+        let pages = pdf.get_pages();
+        let Some(&page) = pages.get(&n) else {
+            return Vec::new();
+        };
+        let annotations = pdf.get_page_annotations(page).unwrap_or_default();
+        let targets: Vec<_> = annotations.into_iter().filter_map(|a| link_target(pdf, &pages, a)).collect();
+        if targets.is_empty() {
+            return Vec::new();
+        }
+        let mut output = LayoutOutput::default();
+        if let Err(e) = pdf_extract::output_doc_page(pdf, &mut output, n) {
+            tracing::warn!(page = n, "PDF 链接文字提取失败: {e}");
+            return Vec::new();
+        }
+        targets
+            .into_iter()
+            .filter_map(|([left, bottom, right, top], url)| {
+                // 注释区域以左下角为原点，字符坐标以左上角为原点、y 为基线
+                let (top, bottom) = (output.height - top, output.height - bottom);
+                let glyphs: Vec<&Glyph> = output
+                    .glyphs
+                    .iter()
+                    .filter(|g| (left..=right).contains(&(g.x + g.width / 2.0)))
+                    .filter(|g| (top..=bottom).contains(&(g.y - g.size / 3.0)))
+                    .collect();
+                let baseline = glyphs.first()?.y;
+                let mut row: Vec<&Glyph> = output
+                    .glyphs
+                    .iter()
+                    .filter(|g| (g.y - baseline).abs() < g.size / 2.0)
+                    .collect();
+                row.sort_by(|a, b| a.x.total_cmp(&b.x));
+                let before = row.iter().take_while(|g| g.x + g.width / 2.0 < left).map(|g| g.text.as_str());
+                let before = compact(&before.collect::<String>()).chars().count();
+                let row = compact(&row.iter().map(|g| g.text.as_str()).collect::<String>());
+
+                // 字符之间有间隙或换行处补上空格
+                let mut text = String::new();
+                let mut end: Option<(f64, f64)> = None;
+                for g in glyphs {
+                    if let Some((x, y)) = end
+                        && (g.x - x > 0.15 * g.size || (g.y - y).abs() > g.size / 2.0)
+                    {
+                        text.push(' ');
+                    }
+                    text.push_str(&g.text);
+                    end = Some((g.x + g.width, g.y));
+                }
+                let text = normalize(&text, false).trim().to_string();
+                tracing::debug!(page = n, text, url, "PDF 链接");
+                (!text.is_empty()).then_some(PageLink { text, url, row, before })
+            })
+            .collect()
+    }
+
+    /// 页面中有足够大的图片时用 tesseract 识别文字：JPEG 图片直接识别，
+    /// 其他编码的图片由 poppler 的 `pdftoppm` 把整页转为 PNG 后识别（`ocr` 特性）
+    #[cfg(feature = "ocr")]
+    fn recognize(&self, n: u32) -> Option<String> {
+        let pdf = self.pdf.as_deref()?;
+        let page = *pdf.get_pages().get(&n)?;
+        let images = pdf.get_page_images(page).ok()?;
+        let image = images
+            .iter()
+            .filter(|i| i.width.min(i.height) >= OCR_MIN_SIDE)
+            .max_by_key(|i| i.width * i.height)?;
+
+        let base = std::env::temp_dir().join(format!("xcat-ocr-{}-{n}", std::process::id()));
+        let file = if image.filters.as_deref() == Some(&["DCTDecode".to_string()]) {
+            let file = base.with_extension("jpg");
+            std::fs::write(&file, image.content).ok()?;
+            file
+        } else {
+            let page = n.to_string();
+            let status = Command::new("pdftoppm")
+                .args(["-f", &page, "-l", &page, "-r", "300", "-png", "-singlefile"])
+                .arg(&self.path)
+                .arg(&base)
+                .status()
+                .ok()?;
+            status.success().then(|| base.with_extension("png"))?
+        };
+        let output = Command::new("tesseract").arg(&file).arg("stdout").output();
+        std::fs::remove_file(&file).ok();
+        match output {
+            Ok(output) if output.status.success() => Some(String::from_utf8_lossy(&output.stdout).into_owned()),
+            Ok(output) => {
+                tracing::warn!(page = n, "文字识别失败: {}", String::from_utf8_lossy(&output.stderr).trim());
+                None
+            }
+            Err(e) => {
+                tracing::warn!(page = n, "无法运行 tesseract: {e}");
+                None
+            }
+        }
+    }
+
+    fn outline(&self) -> Vec<(u32, Heading)> {
+        self.pdf.as_deref().map(outline).unwrap_or_default()
+    }
+
+    fn header(&self) -> Document {
+        header(self.pdf.as_deref(), self.pages)
+    }
+}
+
+/// 链接注释的区域（左、下、右、上）及目标：`URI` 动作为网址，`GoTo` 动作或 `Dest` 为 `#page=N`
+fn link_target(pdf: &pdf_extract::Document, pages: &BTreeMap<u32, ObjectId>, annotation: &Dictionary) -> Option<([f64; 4], String)> {
+    if annotation.get(b"Subtype").and_then(Object::as_name).ok()? != b"Link" {
+        return None;
+    }
+    let rect: Vec<f64> = resolve(pdf, annotation.get(b"Rect").ok()?)?
+        .as_array()
+        .ok()?
+        .iter()
+        .filter_map(|o| o.as_float().ok().map(f64::from))
+        .collect();
+    let [x1, y1, x2, y2] = rect[..] else {
+        return None;
+    };
+    let rect = [x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)];
+
+    let action = annotation.get(b"A").ok().and_then(|a| resolve(pdf, a)?.as_dict().ok());
+    let dest = match action {
+        Some(action) => match action.get(b"S").and_then(Object::as_name).ok()? {
+            b"URI" => {
+                let uri = resolve(pdf, action.get(b"URI").ok()?)?.as_str().ok()?;
+                return Some((rect, String::from_utf8_lossy(uri).trim().to_string()));
+            }
+            b"GoTo" => action.get(b"D").ok()?,
+            _ => return None,
+        },
+        None => annotation.get(b"Dest").ok()?,
+    };
+    let page = destination_page(pdf, pages, dest, 0)?;
+    Some((rect, format!("#page={page}")))
+}
+
+/// 目标所在的页码：目标数组的第一项为页面，命名目标先在目录的 `Dests` 字典与名称树中查找
+fn destination_page(pdf: &pdf_extract::Document, pages: &BTreeMap<u32, ObjectId>, dest: &Object, depth: u8) -> Option<u32> {
+    if depth > NAME_TREE_DEPTH {
+        return None;
+    }
+    match resolve(pdf, dest)? {
+        Object::Array(dest) => {
+            let page = dest.first()?.as_reference().ok()?;
+            pages.iter().find(|&(_, &id)| id == page).map(|(&n, _)| n)
+        }
+        Object::Dictionary(dest) => destination_page(pdf, pages, dest.get(b"D").ok()?, depth + 1),
+        Object::Name(name) | Object::String(name, _) => {
+            let catalog = pdf.catalog().ok()?;
+            let dests = pdf.get_dict_in_dict(catalog, b"Dests").ok();
+            let named = dests.and_then(|d| d.get(name).ok()).or_else(|| {
+                let names = pdf.get_dict_in_dict(catalog, b"Names").ok()?;
+                find_name(pdf, pdf.get_dict_in_dict(names, b"Dests").ok()?, name, 0)
+            })?;
+            destination_page(pdf, pages, named, depth + 1)
+        }
+        _ => None,
+    }
+}
+
+/// 在名称树中查找 `name` 对应的值
+fn find_name<'a>(pdf: &'a pdf_extract::Document, node: &'a Dictionary, name: &[u8], depth: u8) -> Option<&'a Object> {
+    if depth > NAME_TREE_DEPTH {
+        return None;
+    }
+    if let Ok(names) = node.get(b"Names").and_then(Object::as_array) {
+        let found = names.chunks(2).find(|pair| pair[0].as_str().ok() == Some(name));
+        if let Some([_, value]) = found {
+            return Some(value);
+        }
+    }
+    let kids = node.get(b"Kids").and_then(Object::as_array).ok()?;
+    kids.iter()
+        .filter_map(|kid| pdf.get_dictionary(kid.as_reference().ok()?).ok())
+        .find_map(|kid| find_name(pdf, kid, name, depth + 1))
+}
+
+/// 间接引用解析为所指的对象
+fn resolve<'a>(pdf: &'a pdf_extract::Document, object: &'a Object) -> Option<&'a Object> {
+    pdf.dereference(object).ok().map(|(_, object)| object)
+}
+
+/// 拆开连字并去掉所有空白，用于比较不同方式提取出的同一段文字
+fn compact(text: &str) -> String {
+    normalize(text, true).chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// 提取文本的后端
+trait Extractor: Send {
+    /// 提取第 `n` 页（从 1 开始）的文本，`layout` 时保留版面
+    fn page_text(&self, n: u32, layout: bool) -> Result<String, String>;
+
+    /// 总页数，未知时为 None
+    fn page_count(&self) -> Option<u32> {
+        None
+    }
+}
+
+/// 内置的 pdf-extract
+struct Builtin(Arc<pdf_extract::Document>);
+
+impl Extractor for Builtin {
+    fn page_text(&self, n: u32, layout: bool) -> Result<String, String> {
+        if layout {
+            let mut output = LayoutOutput::default();
+            pdf_extract::output_doc_page(&self.0, &mut output, n).map_err(|e| e.to_string())?;
+            return Ok(layout_text(output.glyphs));
+        }
+        let mut text = String::new();
+        pdf_extract::output_doc_page(&self.0, &mut PlainTextOutput::new(&mut text), n).map_err(|e| e.to_string())?;
+        Ok(text)
+    }
+}
+
+/// 打开 poppler 后端，未启用 `poppler` 特性时报错
+#[cfg(feature = "poppler")]
+fn poppler(path: &Path) -> Result<Box<dyn Extractor>, FormatError> {
+    Ok(Box::new(Poppler::open(path)?))
+}
+
+#[cfg(not(feature = "poppler"))]
+fn poppler(_: &Path) -> Result<Box<dyn Extractor>, FormatError> {
+    Err(FormatError::Parse(t!("未启用 poppler 后端（需要以 `--features poppler` 编译）").to_string()))
+}
+
+/// 系统中 poppler 的 `pdftotext` / `pdfinfo`（`poppler` 特性）
+#[cfg(feature = "poppler")]
+struct Poppler(PathBuf);
+
+#[cfg(feature = "poppler")]
+impl Poppler {
+    /// 确认 `pdftotext` 可以运行
+    fn open(path: &Path) -> Result<Self, FormatError> {
+        match Command::new("pdftotext").arg("-v").output() {
+            Ok(_) => Ok(Self(path.to_path_buf())),
+            Err(e) => Err(FormatError::Parse(t!("无法运行 pdftotext（需要安装 poppler）: {}", e))),
+        }
+    }
+}
+
+#[cfg(feature = "poppler")]
+impl Extractor for Poppler {
+    fn page_text(&self, n: u32, layout: bool) -> Result<String, String> {
+        let page = n.to_string();
+        let mut command = Command::new("pdftotext");
+        command.args(["-q", "-enc", "UTF-8", "-f", &page, "-l", &page]);
+        if layout {
+            command.arg("-layout");
+        }
+        let output = command.arg(&self.0).arg("-").output().map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            return Err(t!("pdftotext 退出码 {}", output.status.code().unwrap_or(-1)));
+        }
+        // 每页末尾有换页符
+        Ok(String::from_utf8_lossy(&output.stdout).trim_end_matches('\x0c').to_string())
+    }
+
+    fn page_count(&self) -> Option<u32> {
+        let output = Command::new("pdfinfo").arg(&self.0).output().ok()?;
+        let info = String::from_utf8_lossy(&output.stdout);
+        info.lines().find_map(|l| l.strip_prefix("Pages:")?.trim().parse().ok())
+    }
+}
+
+/// 先用第一个后端提取，出错或提取出乱码时改用第二个
+struct Fallback(Box<dyn Extractor>, Box<dyn Extractor>);
+
+impl Extractor for Fallback {
+    fn page_text(&self, n: u32, layout: bool) -> Result<String, String> {
+        match self.0.page_text(n, layout) {
+            Ok(text) if !garbled(&text) => Ok(text),
+            first => {
+                tracing::debug!(page = n, "改用备用的 PDF 后端");
+                self.1.page_text(n, layout).or(first)
+            }
+        }
+    }
+}
+
+/// 替换字符与控制字符超过可见字符的十分之一时视为乱码（常见于缺少编码表的字体）
+fn garbled(text: &str) -> bool {
+    let visible = text.chars().filter(|c| !c.is_whitespace()).count();
+    let bad = text.chars().filter(|&c| c == '\u{fffd}' || (c.is_control() && !c.is_whitespace())).count();
+    bad > 0 && bad * 10 > visible
+}
+
+/// 整理提取出的文本：连字（ﬁ、ﬂ 等）拆为普通字母，去掉软连字符与零宽空格，不换行空格改为普通空格。
+/// 非版面模式还合并行内的连续空白与连续的空行，并去掉行末断词的连字符、把下一行的词尾接上来，
+/// 使跨行的词与短语可以被搜索到
+fn normalize(text: &str, layout: bool) -> String {
+    let mut mapped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\u{ad}' | '\u{200b}' => {}
+            '\u{a0}' => mapped.push(' '),
+            'ﬀ' => mapped.push_str("ff"),
+            'ﬁ' => mapped.push_str("fi"),
+            'ﬂ' => mapped.push_str("fl"),
+            'ﬃ' => mapped.push_str("ffi"),
+            'ﬄ' => mapped.push_str("ffl"),
+            'ﬅ' | 'ﬆ' => mapped.push_str("st"),
+            c => mapped.push(c),
+        }
+    }
+    if layout {
+        return mapped;
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    for line in mapped.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() && lines.last().is_none_or(String::is_empty) {
+            continue;
+        }
+        if let Some(prev) = lines.last_mut()
+            && prev.strip_suffix('-').is_some_and(|stem| stem.ends_with(char::is_alphabetic))
+            && line.starts_with(char::is_lowercase)
+        {
+            let (word, rest) = line.split_once(' ').unwrap_or((&line, ""));
+            prev.pop();
+            prev.push_str(word);
+            if !rest.is_empty() {
+                lines.push(rest.to_string());
+            }
+            continue;
+        }
+        lines.push(line);
+    }
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+/// 读取 PDF，加密文件尝试用空密码解密
+fn load(path: &Path) -> Result<pdf_extract::Document, FormatError> {
+    let error = |e: &dyn std::fmt::Display| FormatError::Parse(t!("PDF 解析失败: {}", e));
+    let mut pdf = pdf_extract::Document::load(path).map_err(|e| error(&e))?;
+    if pdf.is_encrypted() {
+        pdf.decrypt("").map_err(|e| error(&e))?;
+    }
+    Ok(pdf)
+}
+
+fn page_count(pdf: &pdf_extract::Document) -> u32 {
+    pdf.get_pages().len() as u32
+}
+
+/// 文档信息：标题、作者、主题与创建日期
+#[derive(Default)]
+struct Info {
+    title: Option<String>,
+    author: Option<String>,
+    subject: Option<String>,
+    created: Option<String>,
+}
+
+/// 第一页之前的暗色信息块：文档信息、页数与 PDF 版本，以分隔线结束；
+/// 内置解析器无法读取文档结构时只有页数
+fn header(pdf: Option<&pdf_extract::Document>, pages: u32) -> Document {
+    let info = pdf.map(info).unwrap_or_default();
+    let mut rows = vec![
+        (t!("标题"), info.title.clone()),
+        (t!("作者"), info.author),
+        (t!("主题"), info.subject),
+        (t!("创建日期"), info.created),
+        (t!("页数"), Some(pages.to_string())),
+        (t!("PDF 版本"), pdf.map(|pdf| pdf.version.clone())),
+    ];
+    rows.retain(|(_, value)| value.is_some());
+    let width = rows.iter().map(|(key, _)| key.width()).max().unwrap_or(0);
+
+    let mut doc = Document::default();
+    for (key, value) in rows {
+        doc.lines.push(RenderLine {
+            spans: vec![
+                TextSpan::new(pad_to_width(key, width + 2), TextStyle::DIM),
+                TextSpan::new(value.unwrap_or_default(), TextStyle::DIM),
+            ],
+            indent: 0,
+        });
+    }
+    doc.lines.push(RenderLine {
+        spans: vec![TextSpan::new("────────", TextStyle::DIM)],
+        indent: 0,
+    });
+    doc.lines.push(RenderLine::default());
+    doc.metadata.title = info.title;
+    doc
+}
+
+/// 读取信息字典，缺少的字段从 XMP 元数据补齐
+fn info(pdf: &pdf_extract::Document) -> Info {
+    let mut info = Info::default();
+    if let Ok(dict) = pdf.trailer.get_deref(b"Info", pdf).and_then(Object::as_dict) {
+        let text = |key: &[u8]| {
+            let value = pdf_extract::decode_text_string(dict.get_deref(key, pdf).ok()?).ok()?;
+            Some(value.trim().to_string()).filter(|v| !v.is_empty())
+        };
+        info = Info {
+            title: text(b"Title"),
+            author: text(b"Author"),
+            subject: text(b"Subject"),
+            created: text(b"CreationDate").map(|d| format_date(&d)),
+        };
+    }
+    if let Some(xmp) = xmp(pdf) {
+        info.title = info.title.or(xmp.title);
+        info.author = info.author.or(xmp.author);
+        info.subject = info.subject.or(xmp.subject);
+        info.created = info.created.or(xmp.created);
+    }
+    info
+}
+
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+const XMP_NS: &str = "http://ns.adobe.com/xap/1.0/";
+
+/// 目录（Catalog）中 `/Metadata` 流里的 XMP：dc:title、dc:creator、dc:description 与 xmp:CreateDate
+fn xmp(pdf: &pdf_extract::Document) -> Option<Info> {
+    let stream = pdf.catalog().ok()?.get_deref(b"Metadata", pdf).ok()?.as_stream().ok()?;
+    let content = stream.get_plain_content().ok()?;
+    let text = String::from_utf8_lossy(&content);
+    let xml = roxmltree::Document::parse(text.trim_start_matches('\u{feff}')).ok()?;
+
+    // 取值可以是元素文本（多语言、多作者时在 rdf:li 中），也可以是 rdf:Description 的属性
+    let values = |ns: &str, name: &str| -> Vec<String> {
+        if let Some(value) = xml.descendants().find_map(|n| n.attribute((ns, name))) {
+            return vec![value.trim().to_string()];
+        }
+        let Some(node) = xml.descendants().find(|n| n.has_tag_name((ns, name))) else {
+            return Vec::new();
+        };
+        node.descendants()
+            .filter_map(|n| n.text().filter(|_| n.is_text()))
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let first = |ns: &str, name: &str| values(ns, name).into_iter().next();
+    let creators = values(DC_NS, "creator");
+    Some(Info {
+        title: first(DC_NS, "title"),
+        author: (!creators.is_empty()).then(|| creators.join(", ")),
+        subject: first(DC_NS, "description"),
+        created: first(XMP_NS, "CreateDate").map(|d| format_date(&d)),
+    })
+}
+
+/// PDF 日期（`D:YYYYMMDDHHmmSS+hh'mm'`）或 XMP 日期（ISO 8601）显示为 `YYYY-MM-DD HH:MM`，
+/// 缺少的部分省略，无法识别时原样返回
+fn format_date(date: &str) -> String {
+    let digits: String = date
+        .strip_prefix("D:")
+        .unwrap_or(date)
+        .chars()
+        .take(19)
+        .filter(char::is_ascii_digit)
+        .take(12)
+        .collect();
+    if digits.len() < 4 {
+        return date.to_string();
+    }
+    let part = |range: std::ops::Range<usize>| digits.get(range);
+    let mut formatted = digits[..4].to_string();
+    for (range, separator) in [(4..6, "-"), (6..8, "-"), (8..10, " "), (10..12, ":")] {
+        let Some(part) = part(range) else {
+            break;
+        };
+        formatted.push_str(separator);
+        formatted.push_str(part);
+    }
+    formatted
+}
+
+/// 第 `n` 页（从 1 开始）的文档片段：第一页之后的页以分隔行开头，书签指向该页起始行
+fn page_document(n: u32, page: &PageText, outline: &[(u32, Heading)]) -> Document {
+    let mut doc = Document::default();
+    if n > 1 {
+        doc.lines.push(RenderLine::default());
+    }
+    let start = doc.lines.len();
+    doc.pages.push(start);
+    if n > 1 {
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new(t!("── 第 {} 页 ──", n), TextStyle::DIM)],
+            indent: 0,
+        });
+        doc.lines.push(RenderLine::default());
+    }
+    if page.ocr {
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new("[OCR]", TextStyle::DIM)],
+            indent: 0,
+        });
+    }
+    if page.failed {
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new(t!("第 {} 页提取失败", n), TextStyle::DIM)],
+            indent: 0,
+        });
+    }
+    doc.headings = outline
+        .iter()
+        .filter(|(page, _)| *page == n)
+        .map(|(_, h)| Heading { line: start, ..h.clone() })
+        .collect();
+    let first = doc.lines.len();
+    let lines: Vec<&str> = page.text.lines().collect();
+    let blank = |i: Option<usize>| i.and_then(|i| lines.get(i)).is_none_or(|l| l.trim().is_empty());
+    for (i, &line) in lines.iter().enumerate() {
+        let level = outline.is_empty() && blank(i.checked_sub(1));
+        let level = level.then(|| guess_heading(line, blank(Some(i + 1)))).flatten();
+        if let Some(level) = level {
+            doc.headings.push(Heading {
+                level,
+                line: doc.lines.len(),
+                text: line.trim().to_string(),
+            });
+        }
+        let style = if level.is_some() { TextStyle::HEADING } else { TextStyle::NONE };
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new(line.to_string(), style)],
+            indent: 0,
+        });
+    }
+
+    // 链接按所在行的文字（不计空白）与其前的字符数定位；行被拆开或合并时退而找第一处相同的文字，
+    // 跨行等找不到时依次去掉末尾的词再找
+    let mut linked: Vec<Vec<(Range<usize>, &str)>> = vec![Vec::new(); lines.len()];
+    for link in &page.links {
+        let free = |i: usize, r: &Range<usize>| !linked[i].iter().any(|(l, _)| l.start < r.end && r.start < l.end);
+        let in_row = || {
+            let len = link.text.chars().filter(|c| !c.is_whitespace()).count();
+            lines.iter().enumerate().find_map(|(i, line)| {
+                let chars: Vec<(usize, char)> = line.char_indices().filter(|(_, c)| !c.is_whitespace()).collect();
+                let text: String = chars.iter().map(|&(_, c)| c).collect();
+                let offset = text.find(&link.row)?;
+                let first = text[..offset].chars().count() + link.before;
+                let (&(start, _), &(last, c)) = (chars.get(first)?, chars.get(first + len - 1)?);
+                let range = start..last + c.len_utf8();
+                free(i, &range).then_some((i, range))
+            })
+        };
+        let locate = |text: &str| {
+            lines.iter().enumerate().find_map(|(i, line)| {
+                line.match_indices(text)
+                    .map(|(start, m)| start..start + m.len())
+                    .find(|r| free(i, r))
+                    .map(|r| (i, r))
+            })
+        };
+        let words: Vec<&str> = link.text.split_whitespace().collect();
+        let found = in_row().or_else(|| (1..=words.len()).rev().find_map(|n| locate(&words[..n].join(" "))));
+        let Some((i, range)) = found else {
+            continue;
+        };
+        linked[i].push((range, &link.url));
+        doc.links.push(Link {
+            line: first + i,
+            url: link.url.clone(),
+        });
+    }
+    for (i, mut ranges) in linked.into_iter().enumerate().filter(|(_, r)| !r.is_empty()) {
+        ranges.sort_by_key(|(r, _)| r.start);
+        let (text, style) = (lines[i], doc.lines[first + i].spans[0].style);
+        let mut spans = Vec::new();
+        let mut pos = 0;
+        for (range, url) in ranges {
+            spans.push(TextSpan::new(&text[pos..range.start], style));
+            let mut span = TextSpan::new(&text[range.clone()], style | TextStyle::LINK);
+            span.link = (url.contains("://") || url.starts_with("mailto:")).then(|| url.to_string());
+            spans.push(span);
+            pos = range.end;
+        }
+        spans.push(TextSpan::new(&text[pos..], style));
+        spans.retain(|s| !s.text.is_empty());
+        doc.lines[first + i].spans = spans;
+    }
+    doc.links.sort_by_key(|l| l.line);
+    doc
+}
+
+/// 猜测空行（或页首）之后的一行是否为标题及其层级：不超过 [`HEADING_MAX_CHARS`] 个字符、
+/// 不以标点结尾、字母占多数且不是目录中带引导点的行时，`3.2 Title` 式编号按编号层数，全大写的为一级，
+/// 后面也是空行、以大写字母开头的短行为二级
+fn guess_heading(line: &str, isolated: bool) -> Option<u8> {
+    let line = line.trim();
+    let letters: Vec<char> = line.chars().filter(|c| c.is_alphabetic()).collect();
+    let visible = line.chars().filter(|c| !c.is_whitespace()).count();
+    if letters.len() < 3
+        || letters.len() * 2 < visible
+        || line.chars().count() > HEADING_MAX_CHARS
+        || line.ends_with(['.', ',', ';', ':', '-'])
+        || line.contains(". .")
+        || line.contains("...")
+    {
+        return None;
+    }
+    if let Some((number, title)) = line.split_once(' ') {
+        let number = number.strip_suffix('.').unwrap_or(number);
+        let parts: Vec<&str> = number.split('.').collect();
+        let numbered = parts
+            .iter()
+            .all(|p| (1..=2).contains(&p.len()) && p.bytes().all(|b| b.is_ascii_digit()));
+        if numbered && title.starts_with(char::is_uppercase) {
+            return Some(parts.len().min(6) as u8);
+        }
+    }
+    if letters.len() >= 4 && letters.iter().all(|c| c.is_uppercase()) {
+        return Some(1);
+    }
+    let short = line.split_whitespace().count() <= HEADING_MAX_WORDS;
+    (isolated && short && line.starts_with(char::is_uppercase)).then_some(2)
+}
+
+/// 页面上的一个字符：左上角为原点的坐标、前进宽度与实际字号
+struct Glyph {
+    x: f64,
+    y: f64,
+    width: f64,
+    size: f64,
+    text: String,
+}
+
+/// 收集一页中各字符位置的输出设备，整页收集完后再排版
+#[derive(Default)]
+struct LayoutOutput {
+    height: f64,
+    glyphs: Vec<Glyph>,
+}
+
+impl OutputDev for LayoutOutput {
+    fn begin_page(&mut self, _: u32, media_box: &MediaBox, _: Option<(f64, f64, f64, f64)>) -> Result<(), OutputError> {
+        self.height = media_box.ury - media_box.lly;
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn output_character(
+        &mut self,
+        trm: &Transform,
+        width: f64,
+        _spacing: f64,
+        font_size: f64,
+        char: &str,
+    ) -> Result<(), OutputError> {
+        // 空白由字符间距推断
+        if char.trim().is_empty() {
+            return Ok(());
+        }
+        // 文本矩阵的缩放（取面积的平方根，兼容拉伸与旋转）乘以字号为页面上的字号
+        let size = font_size * (trm.m11 * trm.m22 - trm.m12 * trm.m21).abs().sqrt();
+        self.glyphs.push(Glyph {
+            x: trm.m31,
+            y: self.height - trm.m32,
+            width: width * size,
+            size,
+            text: char.to_string(),
+        });
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
+
+/// 把字符排到等宽网格上：基线相近的字符为一行，与前文相隔超过一个字号的词按横坐标
+/// （以字宽的中位数为列宽）对齐到所在列，多栏与表格列因此保持对齐；普通词间距为一个空格，
+/// 大字号的标题不会被拉开。行距明显大于字号处保留一个空行
+fn layout_text(mut glyphs: Vec<Glyph>) -> String {
+    let mut widths: Vec<f64> = glyphs.iter().map(|g| g.width).filter(|&w| w > 0.0).collect();
+    widths.sort_by(f64::total_cmp);
+    let cell = widths.get(widths.len() / 2).copied().unwrap_or(6.0).max(1.0);
+    let left = glyphs.iter().map(|g| g.x).fold(f64::INFINITY, f64::min);
+
+    glyphs.sort_by(|a, b| a.y.total_cmp(&b.y));
+    let mut rows: Vec<Vec<Glyph>> = Vec::new();
+    for glyph in glyphs {
+        match rows.last_mut() {
+            Some(row) if glyph.y - row[0].y < row[0].size.min(glyph.size) * 0.5 => row.push(glyph),
+            _ => rows.push(vec![glyph]),
+        }
+    }
+
+    let mut text = String::new();
+    let mut last_y: Option<f64> = None;
+    for mut row in rows {
+        row.sort_by(|a, b| a.x.total_cmp(&b.x));
+        let (y, size) = (row[0].y, row[0].size);
+        if last_y.is_some_and(|last| y - last > size * 2.0) {
+            text.push('\n');
+        }
+        last_y = Some(y);
+
+        let mut line = String::new();
+        let mut end = f64::NEG_INFINITY;
+        for glyph in row {
+            let gap = glyph.x - end;
+            let column = ((glyph.x - left) / cell).round() as usize;
+            let width = line.width();
+            if (gap > glyph.size || line.is_empty()) && column > width {
+                line.push_str(&" ".repeat(column - width));
+            } else if gap > glyph.size * 0.15 && !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&glyph.text);
+            end = glyph.x + glyph.width;
+        }
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// 书签及其所在页（从 1 开始），标题的行号在该页提取后确定；没有书签时为空
+fn outline(pdf: &pdf_extract::Document) -> Vec<(u32, Heading)> {
+    let Ok(toc) = pdf.get_toc() else {
+        return Vec::new();
+    };
+    toc.toc
+        .into_iter()
+        .map(|entry| {
+            let heading = Heading {
+                level: entry.level.clamp(1, 6) as u8,
+                line: 0,
+                text: entry.title,
+            };
+            (entry.page as u32, heading)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pdf_extract::{Object, Stream, dictionary};
+
+    /// 生成一个每页一行文字的 PDF，`outline` 为书签标题及其所在页（从 1 开始）
+    fn sample_pdf(name: &str, pages: &[&str], outline: &[(&str, usize)]) -> std::path::PathBuf {
+        let mut pdf = pdf_extract::Document::with_version("1.5");
+        let pages_id = pdf.new_object_id();
+        let font_id = pdf.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let page_ids: Vec<_> = pages
+            .iter()
+            .map(|text| {
+                let content = format!("BT /F1 12 Tf 72 720 Td ({text}) Tj ET");
+                let content_id = pdf.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+                pdf.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                    "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+                    "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+                })
+            })
+            .collect();
+        pdf.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => page_ids.iter().map(|&id| id.into()).collect::<Vec<Object>>(),
+                "Count" => page_ids.len() as i64,
+            }),
+        );
+
+        let outlines_id = pdf.new_object_id();
+        let item_ids: Vec<_> = outline.iter().map(|_| pdf.new_object_id()).collect();
+        for (i, &(title, page)) in outline.iter().enumerate() {
+            let mut item = dictionary! {
+                "Title" => Object::string_literal(title),
+                "Parent" => outlines_id,
+                "Dest" => vec![page_ids[page - 1].into(), "Fit".into()],
+            };
+            if i > 0 {
+                item.set("Prev", item_ids[i - 1]);
+            }
+            if i + 1 < item_ids.len() {
+                item.set("Next", item_ids[i + 1]);
+            }
+            pdf.objects.insert(item_ids[i], Object::Dictionary(item));
+        }
+        let mut catalog = dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+        if let (Some(&first), Some(&last)) = (item_ids.first(), item_ids.last()) {
+            pdf.objects.insert(
+                outlines_id,
+                Object::Dictionary(dictionary! {
+                    "Type" => "Outlines",
+                    "First" => first,
+                    "Last" => last,
+                    "Count" => item_ids.len() as i64,
+                }),
+            );
+            catalog.set("Outlines", outlines_id);
+        }
+        let catalog_id = pdf.add_object(catalog);
+        pdf.trailer.set("Root", catalog_id);
+        let info_id = pdf.add_object(dictionary! {
+            "Title" => Object::string_literal("Sample"),
+            "CreationDate" => Object::string_literal("D:20240102030405Z"),
+        });
+        pdf.trailer.set("Info", info_id);
+
+        let path = std::env::temp_dir().join(format!("xcat-{name}-{}.pdf", std::process::id()));
+        pdf.save(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_outline() {
+        let path = sample_pdf("outline", &["Intro", "Body", "End"], &[("Start", 1), ("Finish", 3)]);
+        let doc = PdfFormat { options: PdfOptions::default() }.parse(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(doc.pages.len(), 3);
+        assert_eq!(doc.lines[doc.pages[2]].spans[0].text, t!("── 第 {} 页 ──", 3));
+        let headings: Vec<_> = doc.headings.iter().map(|h| (h.text.as_str(), h.line)).collect();
+        assert_eq!(headings, [("Start", doc.pages[0]), ("Finish", doc.pages[2])]);
+    }
+
+    #[test]
+    fn test_guess_heading() {
+        assert_eq!(guess_heading("3.2 Parsing rules", false), Some(2));
+        assert_eq!(guess_heading("1. Introduction", false), Some(1));
+        assert_eq!(guess_heading("GNU GENERAL PUBLIC LICENSE", false), Some(1));
+        assert_eq!(guess_heading("Table of Contents", true), Some(2));
+        assert_eq!(guess_heading("Table of Contents", false), None);
+        assert_eq!(guess_heading("2022 August release notes", false), None);
+        assert_eq!(guess_heading("1 Introduction . . . . . . 1", false), None);
+        assert_eq!(guess_heading("This sentence ends with a period.", true), None);
+        assert_eq!(guess_heading("ii", true), None);
+        assert_eq!(guess_heading("┌─ QOI_OP_RGB ──────────────────", false), None);
+
+        let page = PageText {
+            text: "1 Introduction\nSome text.\n\nUsage\n\nmore".into(),
+            ..Default::default()
+        };
+        let doc = page_document(1, &page, &[]);
+        let headings: Vec<_> = doc.headings.iter().map(|h| (h.level, h.text.as_str(), h.line)).collect();
+        assert_eq!(headings, [(1, "1 Introduction", 0), (2, "Usage", 3)]);
+        assert_eq!(doc.lines[3].spans[0].style, TextStyle::HEADING);
+    }
+
+    #[test]
+    fn test_page_links() {
+        let link = |text: &str, url: &str, row: &str, before| PageLink {
+            text: text.into(),
+            url: url.into(),
+            row: row.into(),
+            before,
+        };
+        let page = PageText {
+            text: "See page 2 and page 2.\nVisit example\n1 Intro . . 1\n".into(),
+            links: vec![
+                link("page 2", "#page=2", "Seepage2andpage2.", 3),
+                link("page 2", "#page=3", "Seepage2andpage2.", 11),
+                link("example site", "https://example.com", "Visitexamplesite", 5),
+                link("1", "#page=4", "1Intro..1", 8),
+            ],
+            ..Default::default()
+        };
+        let doc = page_document(1, &page, &[]);
+        let links: Vec<_> = doc.links.iter().map(|l| (l.line, l.url.as_str())).collect();
+        assert_eq!(links, [(0, "#page=2"), (0, "#page=3"), (1, "https://example.com"), (2, "#page=4")]);
+        let spans: Vec<_> = doc.lines[0].spans.iter().map(|s| (s.text.as_str(), s.style.contains(TextStyle::LINK))).collect();
+        assert_eq!(spans, [("See ", false), ("page 2", true), (" and ", false), ("page 2", true), (".", false)]);
+        assert_eq!(doc.lines[1].spans[1].link.as_deref(), Some("https://example.com"));
+        assert_eq!(doc.lines[2].spans[1].text, "1");
+        assert_eq!(doc.lines[2].spans[0].text, "1 Intro . . ");
+    }
+
+    #[test]
+    fn test_header() {
+        let path = sample_pdf("header", &["Intro", "Body"], &[]);
+        let doc = PdfFormat { options: PdfOptions::default() }.parse(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let rows: Vec<String> = doc.lines[..doc.pages[0]]
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect::<String>())
+            .collect();
+        assert_eq!(rows[0], format!("{}  Sample", pad_to_width(t!("标题"), t!("创建日期").width())));
+        assert!(rows[1].ends_with("2024-01-02 03:04"));
+        assert!(rows[2].ends_with(" 2"));
+        assert!(rows[3].ends_with(" 1.5"));
+        assert_eq!(doc.metadata.title.as_deref(), Some("Sample"));
+        assert_eq!(format_date("2022-08-18T12:00:00+02:00"), "2022-08-18 12:00");
+    }
+
+    /// 返回固定结果的后端
+    struct Fixed(Result<&'static str, &'static str>);
+
+    impl Extractor for Fixed {
+        fn page_text(&self, _: u32, _: bool) -> Result<String, String> {
+            self.0.map(str::to_string).map_err(str::to_string)
+        }
+    }
+
+    /// 第 `n` 页提取失败，其余页返回页码
+    struct FailOn(u32);
+
+    impl Extractor for FailOn {
+        fn page_text(&self, n: u32, _: bool) -> Result<String, String> {
+            if n == self.0 { Err("bad".into()) } else { Ok(format!("page {n}")) }
+        }
+    }
+
+    #[test]
+    fn test_failed_page() {
+        let source = Source {
+            pdf: None,
+            extractor: Box::new(FailOn(2)),
+            pages: 3,
+            #[cfg(feature = "ocr")]
+            path: PathBuf::new(),
+            options: PdfOptions::default(),
+        };
+        let doc = source.document();
+        assert_eq!(doc.pages.len(), 3);
+        let rows: Vec<String> = doc.lines.iter().map(|l| l.spans.iter().map(|s| s.text.as_str()).collect()).collect();
+        let failed = rows.iter().position(|r| *r == t!("第 {} 页提取失败", 2)).unwrap();
+        assert!(doc.pages[1] < failed && failed < doc.pages[2]);
+        assert_eq!(rows.last().unwrap(), "page 3");
+    }
+
+    #[test]
+    fn test_fallback() {
+        let text = |first, second| Fallback(Box::new(Fixed(first)), Box::new(Fixed(second))).page_text(1, false);
+        assert_eq!(text(Ok("text"), Ok("other")), Ok("text".into()));
+        assert_eq!(text(Err("bad"), Ok("other")), Ok("other".into()));
+        assert_eq!(text(Ok("\u{fffd}\u{fffd} x"), Ok("other")), Ok("other".into()));
+        assert_eq!(text(Err("bad"), Err("worse")), Err("bad".into()));
+        assert_eq!("poppler".parse::<PdfBackend>(), Ok(PdfBackend::Poppler));
+    }
+
+    #[test]
+    fn test_normalize() {
+        let text = "\n\nStructures  man-\nagement and ﬁles\n\n\n\nself -\nNext\u{ad}line\n";
+        assert_eq!(normalize(text, false), "Structures management\nand files\n\nself -\nNextline\n");
+        assert_eq!(normalize("a  ﬂ", true), "a  fl");
+    }
+
+    #[test]
+    fn test_layout_text() {
+        let glyph = |x: f64, y: f64, text: &str| Glyph {
+            x,
+            y,
+            width: 6.0 * text.chars().count() as f64,
+            size: 12.0,
+            text: text.to_string(),
+        };
+        // 两栏，右栏的第二行比左栏短；第三行前有段落间距
+        let glyphs = vec![
+            glyph(300.0, 100.0, "Right"),
+            glyph(72.0, 100.0, "Left"),
+            glyph(72.0, 114.0, "Longer"),
+            glyph(300.0, 114.5, "R2"),
+            glyph(72.0, 160.0, "Next"),
+        ];
+        let text = layout_text(glyphs);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].find("Right"), lines[1].find("R2"));
+        assert!(lines[0].starts_with("Left "));
+        assert_eq!(lines[2], "");
+        assert_eq!(lines[3], "Next");
+    }
+
+    #[test]
+    fn test_parse_lazily() {
+        let path = sample_pdf("lazy", &["Intro", "Body", "End"], &[("Finish", 3)]);
+        let full = PdfFormat { options: PdfOptions::default() }.parse(&path).unwrap();
+        let (mut doc, mut loader) = parse_lazily(&path, &PdfOptions::default()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(doc.pages.len(), 1);
+        assert_eq!(loader.progress(), (1, 3));
+        while let Some(pages) = loader.poll() {
+            pages.into_iter().for_each(|page| doc.append(page));
+        }
+        assert_eq!(loader.progress(), (3, 3));
+        assert_eq!(doc.lines.len(), full.lines.len());
+        assert_eq!(doc.pages, full.pages);
+        assert_eq!(doc.headings[0].line, full.headings[0].line);
     }
 }
@@ -0,0 +1,631 @@
+//! Protocol Buffers：`.proto` 定义文件与二进制消息
+//!
+//! `.proto` 文件按原有排版着色：关键字、内置类型、字符串、数字与注释分别着色，message / enum / service
+//! 加入目录，花括号块可以折叠。
+//!
+//! 二进制消息解码为 JSON 值后交给 [`json::render_value`](super::json::render_value) 排版。`--schema` 指定
+//! `protoc --descriptor_set_out` 生成的描述符集时按其中的消息类型解码，显示字段名、枚举名与有符号数；
+//! 没有描述符集时类似 `protoc --decode_raw`，以字段编号为键，长度分隔的值依次尝试按文本、嵌套消息解码。
+
+use crate::document::*;
+use crate::format::binary::{bytes_value, float};
+use crate::format::json::render_value;
+use crate::format::text::clean_line;
+use crate::format::{FileFormat, FormatError};
+use crate::t;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// 最大嵌套深度，防止恶意数据导致栈溢出
+const MAX_DEPTH: usize = 64;
+
+const KEYWORDS: &[&str] = &[
+    "syntax", "edition", "package", "import", "public", "weak", "option", "message", "enum", "service", "rpc",
+    "returns", "stream", "repeated", "optional", "required", "oneof", "map", "reserved", "extensions", "extend",
+    "to", "max", "group",
+];
+
+const SCALAR_TYPES: &[&str] = &[
+    "double", "float", "int32", "int64", "uint32", "uint64", "sint32", "sint64", "fixed32", "fixed64", "sfixed32",
+    "sfixed64", "bool", "string", "bytes",
+];
+
+/// 之后的标识符是定义的名称
+const DEFINITIONS: &[&str] = &["message", "enum", "service", "rpc", "oneof", "extend"];
+
+pub struct ProtoFormat;
+
+impl FileFormat for ProtoFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(parse_proto(&content))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["proto"]
+    }
+
+    fn name(&self) -> &'static str {
+        "proto"
+    }
+}
+
+pub struct ProtobufFormat {
+    pub schema: Option<Schema>,
+}
+
+impl FileFormat for ProtobufFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let data = std::fs::read(path)?;
+        let value = match &self.schema {
+            Some(schema) => {
+                let descriptors = Descriptors::load(&schema.path)?;
+                let message = match &schema.message {
+                    Some(name) => name.trim_start_matches('.').to_string(),
+                    None => descriptors.first.clone().ok_or_else(|| {
+                        FormatError::Parse(t!("描述符集 {} 中没有消息类型", schema.path.display()))
+                    })?,
+                };
+                if !descriptors.messages.contains_key(&message) {
+                    return Err(FormatError::Parse(t!("描述符集中没有消息类型 {}", message)));
+                }
+                descriptors.decode(&data, &message, 0)
+            }
+            None => decode_raw(&data, 0),
+        };
+        let value = value.ok_or_else(|| FormatError::Parse(t!("Protobuf 数据无效或与消息类型不符").into()))?;
+        Ok(render_value(&value))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pb", "binpb"]
+    }
+
+    fn name(&self) -> &'static str {
+        "protobuf"
+    }
+}
+
+/// `--schema`：描述符集文件，可以用 `:` 接上要解码的消息类型全名
+#[derive(Clone, Debug)]
+pub struct Schema {
+    pub path: PathBuf,
+    pub message: Option<String>,
+}
+
+impl FromStr for Schema {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 冒号之后是合法的类型名时才拆开，Windows 盘符（`C:\...`）保持原样
+        let split = s.rsplit_once(':').filter(|(path, message)| {
+            !path.is_empty()
+                && !message.is_empty()
+                && message.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        });
+        match split {
+            Some((path, message)) => Ok(Schema {
+                path: PathBuf::from(path),
+                message: Some(message.to_string()),
+            }),
+            None if s.is_empty() => Err(t!("描述符集路径为空").into()),
+            None => Ok(Schema {
+                path: PathBuf::from(s),
+                message: None,
+            }),
+        }
+    }
+}
+
+/// `.proto` 文件：逐行着色，定义加入目录，多行的花括号块可折叠
+pub fn parse_proto(content: &str) -> Document {
+    let mut doc = Document::default();
+    let mut in_comment = false;
+    // 未闭合的花括号：所在行
+    let mut open: Vec<usize> = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = clean_line(line);
+        let mut spans = Vec::new();
+        let mut rest = line.as_str();
+        let mut previous = "";
+        while !rest.is_empty() {
+            let (len, style) = if in_comment || rest.starts_with("/*") {
+                // 块注释取到 `*/`，没有结束时延续到下一行
+                let from = if in_comment { 0 } else { 2 };
+                let end = rest[from..].find("*/").map(|e| from + e + 2);
+                in_comment = end.is_none();
+                (end.unwrap_or(rest.len()), TextStyle::DIM)
+            } else if rest.starts_with("//") {
+                (rest.len(), TextStyle::DIM)
+            } else if rest.starts_with(['"', '\'']) {
+                (string_len(rest), TextStyle::STRING)
+            } else if rest.starts_with(|c: char| c.is_ascii_digit())
+                || (rest.starts_with('-') && rest[1..].starts_with(|c: char| c.is_ascii_digit()))
+            {
+                let end = rest[1..].find(|c: char| !c.is_ascii_alphanumeric() && c != '.');
+                let len = end.map_or(rest.len(), |e| e + 1);
+                (len, TextStyle::NUMBER)
+            } else if rest.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '.') {
+                let len = rest.find(|c: char| !c.is_alphanumeric() && c != '_' && c != '.').unwrap_or(rest.len());
+                let word = &rest[..len];
+                let style = if DEFINITIONS.contains(&previous) {
+                    if matches!(previous, "message" | "enum" | "service") {
+                        doc.headings.push(Heading {
+                            level: (open.len() + 1).min(6) as u8,
+                            line: i,
+                            text: format!("{previous} {word}"),
+                        });
+                    }
+                    TextStyle::BOLD
+                } else if KEYWORDS.contains(&word) {
+                    TextStyle::KEY
+                } else if SCALAR_TYPES.contains(&word) || matches!(word, "true" | "false" | "inf" | "nan") {
+                    TextStyle::CONSTANT
+                } else {
+                    TextStyle::NONE
+                };
+                previous = word;
+                (len, style)
+            } else {
+                let c = rest.chars().next().unwrap_or(' ');
+                match c {
+                    '{' => open.push(i),
+                    '}' => {
+                        if let Some(start) = open.pop()
+                            && i > start
+                        {
+                            doc.folds.push(Fold { start, end: i + 1 });
+                        }
+                    }
+                    _ => {}
+                }
+                if !c.is_whitespace() {
+                    previous = "";
+                }
+                (c.len_utf8(), TextStyle::NONE)
+            };
+            push_span(&mut spans, &rest[..len], style);
+            rest = &rest[len..];
+        }
+        doc.lines.push(RenderLine { spans, indent: 0 });
+    }
+    doc.folds.sort_by_key(|f| f.start);
+    doc
+}
+
+/// 引号字符串的长度（含引号），跳过转义字符；未闭合时取到行尾
+fn string_len(s: &str) -> usize {
+    let quote = s.as_bytes()[0];
+    let mut escaped = false;
+    for (i, b) in s.bytes().enumerate().skip(1) {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            _ if b == quote => return i + 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// 与前一个样式相同的片段合并
+fn push_span(spans: &mut Vec<TextSpan>, text: &str, style: TextStyle) {
+    match spans.last_mut() {
+        Some(last) if last.style == style => last.text.push_str(text),
+        _ => spans.push(TextSpan::new(text, style)),
+    }
+}
+
+/// 线格式中的一个值
+enum Wire<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(n)?)?;
+        self.pos += n;
+        Some(bytes)
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// 字段编号与值；不支持已废弃的 group
+    fn field(&mut self) -> Option<(u64, Wire<'a>)> {
+        let key = self.varint()?;
+        let number = key >> 3;
+        if number == 0 {
+            return None;
+        }
+        let wire = match key & 7 {
+            0 => Wire::Varint(self.varint()?),
+            1 => Wire::Fixed64(u64::from_le_bytes(self.take(8)?.try_into().ok()?)),
+            2 => {
+                let n = usize::try_from(self.varint()?).ok()?;
+                Wire::Bytes(self.take(n)?)
+            }
+            5 => Wire::Fixed32(u32::from_le_bytes(self.take(4)?.try_into().ok()?)),
+            _ => return None,
+        };
+        Some((number, wire))
+    }
+
+    /// 读完所有字段，数据无效时返回 None
+    fn fields(mut self) -> Option<Vec<(u64, Wire<'a>)>> {
+        let mut fields = Vec::new();
+        while !self.at_end() {
+            fields.push(self.field()?);
+        }
+        Some(fields)
+    }
+}
+
+/// 同一编号出现多次时收集为数组
+fn insert_repeated(map: &mut Map<String, Value>, key: String, value: Value) {
+    match map.get_mut(&key) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+        None => {
+            map.insert(key, value);
+        }
+    }
+}
+
+/// 没有描述符时按字段编号解码
+fn decode_raw(data: &[u8], depth: usize) -> Option<Value> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let mut map = Map::new();
+    for (number, wire) in Reader::new(data).fields()? {
+        let value = match wire {
+            Wire::Varint(v) | Wire::Fixed64(v) => Value::from(v),
+            Wire::Fixed32(v) => Value::from(v),
+            Wire::Bytes(bytes) => raw_bytes(bytes, depth),
+        };
+        insert_repeated(&mut map, number.to_string(), value);
+    }
+    Some(Value::Object(map))
+}
+
+/// 长度分隔的值：可打印的 UTF-8 文本、嵌套消息，最后按字节串显示
+fn raw_bytes(bytes: &[u8], depth: usize) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => Value::String(text.to_string()),
+        _ => match decode_raw(bytes, depth + 1) {
+            Some(value) if !bytes.is_empty() => value,
+            _ => bytes_value(bytes),
+        },
+    }
+}
+
+/// 字段描述：`kind` 为 FieldDescriptorProto.Type 的编号
+struct Field {
+    name: String,
+    kind: u64,
+    type_name: String,
+    repeated: bool,
+}
+
+#[derive(Default)]
+struct Message {
+    fields: HashMap<u64, Field>,
+    /// `map<K, V>` 字段生成的条目类型
+    map_entry: bool,
+}
+
+/// 描述符集中的消息与枚举，以不带前导点的全名为键
+#[derive(Default)]
+struct Descriptors {
+    messages: HashMap<String, Message>,
+    enums: HashMap<String, HashMap<i64, String>>,
+    /// 最后一个文件（`protoc` 命令行上的文件，依赖排在前面）的第一个消息
+    first: Option<String>,
+}
+
+impl Descriptors {
+    fn load(path: &Path) -> Result<Self, FormatError> {
+        let data = std::fs::read(path)
+            .map_err(|e| FormatError::Parse(t!("无法读取描述符集 {}: {}", path.display(), e)))?;
+        Self::parse(&data).ok_or_else(|| FormatError::Parse(t!("描述符集 {} 无效", path.display())))
+    }
+
+    /// FileDescriptorSet：file = 1；FileDescriptorProto：package = 2、message_type = 4、enum_type = 5
+    fn parse(data: &[u8]) -> Option<Self> {
+        let mut descriptors = Descriptors::default();
+        for (number, wire) in Reader::new(data).fields()? {
+            let (1, Wire::Bytes(file)) = (number, wire) else {
+                continue;
+            };
+            let fields = Reader::new(file).fields()?;
+            let package = fields
+                .iter()
+                .find_map(|f| match f {
+                    (2, Wire::Bytes(b)) => Some(String::from_utf8_lossy(b).into_owned()),
+                    _ => None,
+                })
+                .unwrap_or_default();
+            let mut first = None;
+            for (number, wire) in fields {
+                match (number, wire) {
+                    (4, Wire::Bytes(message)) => {
+                        let name = descriptors.message(&package, message)?;
+                        first.get_or_insert(name);
+                    }
+                    (5, Wire::Bytes(e)) => descriptors.enumeration(&package, e)?,
+                    _ => {}
+                }
+            }
+            if first.is_some() {
+                descriptors.first = first;
+            }
+        }
+        Some(descriptors)
+    }
+
+    /// DescriptorProto：name = 1、field = 2、nested_type = 3、enum_type = 4、options = 7（map_entry = 7）
+    fn message(&mut self, scope: &str, data: &[u8]) -> Option<String> {
+        let fields = Reader::new(data).fields()?;
+        let name = fields.iter().find_map(|f| match f {
+            (1, Wire::Bytes(b)) => Some(String::from_utf8_lossy(b).into_owned()),
+            _ => None,
+        })?;
+        let full = if scope.is_empty() { name } else { format!("{scope}.{name}") };
+        let mut message = Message::default();
+        for (number, wire) in fields {
+            match (number, wire) {
+                (2, Wire::Bytes(field)) => {
+                    let (number, field) = parse_field(field)?;
+                    message.fields.insert(number, field);
+                }
+                (3, Wire::Bytes(nested)) => {
+                    self.message(&full, nested)?;
+                }
+                (4, Wire::Bytes(e)) => self.enumeration(&full, e)?,
+                (7, Wire::Bytes(options)) => {
+                    message.map_entry = Reader::new(options)
+                        .fields()?
+                        .iter()
+                        .any(|f| matches!(f, (7, Wire::Varint(1))));
+                }
+                _ => {}
+            }
+        }
+        self.messages.insert(full.clone(), message);
+        Some(full)
+    }
+
+    /// EnumDescriptorProto：name = 1、value = 2；EnumValueDescriptorProto：name = 1、number = 2
+    fn enumeration(&mut self, scope: &str, data: &[u8]) -> Option<()> {
+        let mut name = String::new();
+        let mut values = HashMap::new();
+        for (number, wire) in Reader::new(data).fields()? {
+            match (number, wire) {
+                (1, Wire::Bytes(b)) => name = String::from_utf8_lossy(b).into_owned(),
+                (2, Wire::Bytes(value)) => {
+                    let mut value_name = String::new();
+                    let mut value_number = 0;
+                    for field in Reader::new(value).fields()? {
+                        match field {
+                            (1, Wire::Bytes(b)) => value_name = String::from_utf8_lossy(b).into_owned(),
+                            (2, Wire::Varint(n)) => value_number = n as i32 as i64,
+                            _ => {}
+                        }
+                    }
+                    values.insert(value_number, value_name);
+                }
+                _ => {}
+            }
+        }
+        let full = if scope.is_empty() { name } else { format!("{scope}.{name}") };
+        self.enums.insert(full, values);
+        Some(())
+    }
+
+    /// 按消息类型解码，描述符中没有的字段按编号原样解码
+    fn decode(&self, data: &[u8], message: &str, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let descriptor = self.messages.get(message)?;
+        let mut map = Map::new();
+        for (number, wire) in Reader::new(data).fields()? {
+            let Some(field) = descriptor.fields.get(&number) else {
+                let value = match wire {
+                    Wire::Varint(v) | Wire::Fixed64(v) => Value::from(v),
+                    Wire::Fixed32(v) => Value::from(v),
+                    Wire::Bytes(bytes) => raw_bytes(bytes, depth),
+                };
+                insert_repeated(&mut map, number.to_string(), value);
+                continue;
+            };
+            // 打包编码的重复标量字段：一个长度分隔的值中依次排列
+            let values = match wire {
+                Wire::Bytes(bytes) if field.repeated && is_packable(field.kind) => {
+                    let mut reader = Reader::new(bytes);
+                    let mut values = Vec::new();
+                    while !reader.at_end() {
+                        let wire = match field.kind {
+                            1 | 6 | 16 => Wire::Fixed64(u64::from_le_bytes(reader.take(8)?.try_into().ok()?)),
+                            2 | 7 | 15 => Wire::Fixed32(u32::from_le_bytes(reader.take(4)?.try_into().ok()?)),
+                            _ => Wire::Varint(reader.varint()?),
+                        };
+                        values.push(self.scalar(field, wire, depth)?);
+                    }
+                    values
+                }
+                wire => vec![self.scalar(field, wire, depth)?],
+            };
+            let entry = map.entry(field.name.clone());
+            if field.repeated {
+                let items = entry.or_insert_with(|| Value::Array(Vec::new()));
+                if let Value::Array(items) = items {
+                    items.extend(values);
+                }
+            } else if let Some(value) = values.into_iter().last() {
+                map.insert(field.name.clone(), value);
+            }
+        }
+        // map<K, V> 字段的条目数组转为对象
+        for field in descriptor.fields.values() {
+            let is_map = field.kind == 11
+                && self.messages.get(field.type_name.trim_start_matches('.')).is_some_and(|m| m.map_entry);
+            if is_map && let Some(Value::Array(entries)) = map.get_mut(&field.name) {
+                let object = entries
+                    .drain(..)
+                    .map(|entry| {
+                        let key = match entry.get("key") {
+                            Some(Value::String(s)) => s.clone(),
+                            Some(other) => other.to_string(),
+                            None => String::new(),
+                        };
+                        (key, entry.get("value").cloned().unwrap_or(Value::Null))
+                    })
+                    .collect();
+                map.insert(field.name.clone(), Value::Object(object));
+            }
+        }
+        Some(Value::Object(map))
+    }
+
+    /// 按字段类型转换一个值，线格式与类型不符时返回 None
+    fn scalar(&self, field: &Field, wire: Wire, depth: usize) -> Option<Value> {
+        let value = match (field.kind, wire) {
+            (1, Wire::Fixed64(v)) => float(f64::from_bits(v)),
+            (2, Wire::Fixed32(v)) => float(f64::from(f32::from_bits(v))),
+            (3, Wire::Varint(v)) | (16, Wire::Fixed64(v)) => Value::from(v as i64),
+            (4, Wire::Varint(v)) | (6, Wire::Fixed64(v)) => Value::from(v),
+            (5, Wire::Varint(v)) => Value::from(v as i32),
+            (7, Wire::Fixed32(v)) => Value::from(v),
+            (8, Wire::Varint(v)) => Value::Bool(v != 0),
+            (9, Wire::Bytes(b)) => Value::String(String::from_utf8_lossy(b).into_owned()),
+            (11, Wire::Bytes(b)) => self.decode(b, field.type_name.trim_start_matches('.'), depth + 1)?,
+            (12, Wire::Bytes(b)) => bytes_value(b),
+            (13, Wire::Varint(v)) => Value::from(v as u32),
+            (14, Wire::Varint(v)) => {
+                let number = v as i32 as i64;
+                let name = self.enums.get(field.type_name.trim_start_matches('.')).and_then(|e| e.get(&number));
+                name.map_or_else(|| Value::from(number), |name| Value::String(name.clone()))
+            }
+            (15, Wire::Fixed32(v)) => Value::from(v as i32),
+            (17, Wire::Varint(v)) => Value::from(zigzag(v) as i32),
+            (18, Wire::Varint(v)) => Value::from(zigzag(v)),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// FieldDescriptorProto：name = 1、number = 3、label = 4（3 为 repeated）、type = 5、type_name = 6
+fn parse_field(data: &[u8]) -> Option<(u64, Field)> {
+    let mut number = 0;
+    let mut field = Field {
+        name: String::new(),
+        kind: 0,
+        type_name: String::new(),
+        repeated: false,
+    };
+    for (key, wire) in Reader::new(data).fields()? {
+        match (key, wire) {
+            (1, Wire::Bytes(b)) => field.name = String::from_utf8_lossy(b).into_owned(),
+            (3, Wire::Varint(n)) => number = n,
+            (4, Wire::Varint(label)) => field.repeated = label == 3,
+            (5, Wire::Varint(kind)) => field.kind = kind,
+            (6, Wire::Bytes(b)) => field.type_name = String::from_utf8_lossy(b).into_owned(),
+            _ => {}
+        }
+    }
+    Some((number, field))
+}
+
+/// 数值类型（字符串、字节串与消息以外）可以打包编码
+fn is_packable(kind: u64) -> bool {
+    !(9..=12).contains(&kind)
+}
+
+fn zigzag(v: u64) -> i64 {
+    (v >> 1) as i64 ^ -((v & 1) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 长度分隔的字段
+    fn bytes(number: u64, data: &[u8]) -> Vec<u8> {
+        let mut out = vec![(number << 3 | 2) as u8, data.len() as u8];
+        out.extend(data);
+        out
+    }
+
+    fn varint(number: u64, value: u8) -> Vec<u8> {
+        vec![(number << 3) as u8, value]
+    }
+
+    #[test]
+    fn test_decode() {
+        // message Point { sint32 x = 1; Color color = 2; repeated int32 tags = 3; string name = 4; }
+        // enum Color { RED = 0; GREEN = 1; }
+        let field = |name: &str, number: u8, kind: u8, label: u8, type_name: &str| {
+            let mut f = bytes(1, name.as_bytes());
+            f.extend(varint(3, number));
+            f.extend(varint(4, label));
+            f.extend(varint(5, kind));
+            f.extend(bytes(6, type_name.as_bytes()));
+            bytes(2, &f)
+        };
+        let mut message = bytes(1, b"Point");
+        message.extend(field("x", 1, 17, 1, ""));
+        message.extend(field("color", 2, 14, 1, ".geo.Color"));
+        message.extend(field("tags", 3, 5, 3, ""));
+        message.extend(field("name", 4, 9, 1, ""));
+        let mut color = bytes(1, b"Color");
+        color.extend(bytes(2, &[bytes(1, b"RED"), varint(2, 0)].concat()));
+        color.extend(bytes(2, &[bytes(1, b"GREEN"), varint(2, 1)].concat()));
+        let file = [bytes(2, b"geo"), bytes(4, &message), bytes(5, &color)].concat();
+        let descriptors = Descriptors::parse(&bytes(1, &file)).unwrap();
+        assert_eq!(descriptors.first.as_deref(), Some("geo.Point"));
+
+        // x = -2（zigzag 3），color = GREEN，tags 打包为 [1, 2]，name = "p"，未知字段 9 = 7
+        let data = [varint(1, 3), varint(2, 1), bytes(3, &[1, 2]), bytes(4, b"p"), varint(9, 7)].concat();
+        let value = descriptors.decode(&data, "geo.Point", 0).unwrap();
+        assert_eq!(value.to_string(), r#"{"x":-2,"color":"GREEN","tags":[1,2],"name":"p","9":7}"#);
+        assert_eq!(decode_raw(&data, 0).unwrap().to_string(), r#"{"1":3,"2":1,"3":"0x0102","4":"p","9":7}"#);
+        assert!(decode_raw(&[0x0a, 0x05, 0x01], 0).is_none());
+
+        let doc = parse_proto("syntax = \"proto3\";\nmessage Point {\n  sint32 x = 1; // 横坐标\n}\n");
+        assert_eq!(doc.headings[0].text, "message Point");
+        assert_eq!(doc.folds, [Fold { start: 1, end: 4 }]);
+        let styles: Vec<TextStyle> = doc.lines[2].spans.iter().map(|s| s.style).collect();
+        assert!(styles.contains(&TextStyle::CONSTANT) && styles.contains(&TextStyle::NUMBER));
+        assert_eq!(doc.lines[2].spans.last().unwrap().text, "// 横坐标");
+    }
+}
@@ -0,0 +1,822 @@
+//! reStructuredText 解析
+//!
+//! 逐块转换为 Markdown 后交给 Markdown 解析器，复用其标题、列表、代码块、提示块与表格的排版：
+//! 章节标题按装饰符首次出现的顺序确定级别，`::` 之后的缩进块为字面块，
+//! 常用指令（代码、提示、图片、公式、表格）映射为对应的 Markdown 结构，
+//! 字段列表显示为加粗的字段名，网格表、简单表与 `list-table` 转换为管道表格。
+
+use crate::document::Document;
+use crate::format::markdown::{parse_markdown, MarkdownOptions};
+use crate::format::{csv, FileFormat, FormatError};
+use std::collections::HashMap;
+use std::path::Path;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+pub struct RstFormat {
+    pub options: MarkdownOptions,
+}
+
+impl FileFormat for RstFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let content = std::fs::read_to_string(path)?;
+        let mut options = self.options.clone();
+        options.base_dir = path.parent().map(Path::to_path_buf);
+        Ok(parse_rst(&content, &options))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["rst", "rest"]
+    }
+
+    fn name(&self) -> &'static str {
+        "rst"
+    }
+}
+
+pub fn parse_rst(content: &str, opts: &MarkdownOptions) -> Document {
+    parse_markdown(&to_markdown(content), opts)
+}
+
+/// 标题装饰符可用的字符
+const ADORNMENTS: &str = "=-~^\"'`*+#<>_:.!$%&,;/?@[\\]{|}()";
+
+/// 不显示内容的指令（目录、索引、配置类）
+const HIDDEN_DIRECTIVES: &[&str] = &[
+    "toctree", "contents", "index", "meta", "highlight", "include", "literalinclude", "raw", "role",
+    "default-role", "sectnum", "target-notes", "tabularcolumns", "currentmodule", "module",
+];
+
+/// 缩进块的含义，由其前一个块决定
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Context {
+    /// 块引用
+    Quote,
+    /// `::` 之后的字面块
+    Literal,
+    /// 列表条目的续行，按新标记的宽度缩进
+    ListItem(usize),
+    /// 定义列表的定义
+    Definition,
+}
+
+/// 将 reStructuredText 转换为 Markdown
+pub fn to_markdown(content: &str) -> String {
+    let lines: Vec<String> = content
+        .lines()
+        .map(|l| l.replace('\t', "        ").trim_end().to_string())
+        .collect();
+    let mut converter = Converter::default();
+    converter.collect_targets(&lines);
+    converter.convert(&lines).join("\n") + "\n"
+}
+
+#[derive(Default)]
+struct Converter {
+    /// 超链接目标 `.. _name: url`（名称小写）
+    targets: HashMap<String, String>,
+    /// 替换定义 `.. |name| replace:: text`
+    substitutions: HashMap<String, String>,
+    /// 已出现的标题样式（装饰符, 是否有上划线），下标即级别
+    styles: Vec<(char, bool)>,
+}
+
+impl Converter {
+    fn collect_targets(&mut self, lines: &[String]) {
+        for line in lines {
+            let Some(rest) = line.trim_start().strip_prefix(".. ") else {
+                continue;
+            };
+            if let Some((name, url)) = rest.strip_prefix('_').and_then(|r| r.split_once(": ")) {
+                let name = name.trim_matches('`').to_lowercase();
+                self.targets.insert(name, url.trim().to_string());
+            } else if let Some((name, text)) = rest
+                .strip_prefix('|')
+                .and_then(|r| r.split_once("| replace:: "))
+            {
+                self.substitutions.insert(name.to_string(), text.trim().to_string());
+            }
+        }
+    }
+
+    fn convert(&mut self, lines: &[String]) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut context = Context::Quote;
+        let mut i = 0;
+        while i < lines.len() {
+            let line = &lines[i];
+            if line.is_empty() {
+                out.push(String::new());
+                i += 1;
+                continue;
+            }
+
+            if indent_of(line) > 0 {
+                let end = block_end(lines, i);
+                let block = dedent(&lines[i..end]);
+                match context {
+                    Context::Literal => push_fence(&mut out, "", &block),
+                    Context::ListItem(width) => {
+                        let pad = " ".repeat(width);
+                        out.extend(self.convert(&block).into_iter().map(|l| indent(&pad, l)));
+                    }
+                    Context::Definition => out.extend(self.convert(&block)),
+                    Context::Quote => {
+                        out.extend(self.convert(&block).into_iter().map(|l| quote(&l)));
+                    }
+                }
+                i = end;
+                continue;
+            }
+            // 缩进块只属于紧邻的前一个块
+            let next_indented = lines.get(i + 1).is_some_and(|l| indent_of(l) > 0);
+            context = Context::Quote;
+
+            // 带上划线的标题
+            if let (Some(title), Some(under)) = (lines.get(i + 1), lines.get(i + 2))
+                && adornment(line).is_some()
+                && adornment(line) == adornment(under)
+                && !title.trim().is_empty()
+            {
+                let c = adornment(line).unwrap();
+                self.push_heading(&mut out, (c, true), title.trim());
+                i += 3;
+                continue;
+            }
+            // 带下划线的标题
+            if let Some(c) = lines.get(i + 1).and_then(|l| adornment(l))
+                && adornment(line).is_none()
+                && lines[i + 1].len() >= line.trim().width().min(4)
+            {
+                self.push_heading(&mut out, (c, false), line.trim());
+                i += 2;
+                continue;
+            }
+            // 过渡线
+            if adornment(line).is_some() && line.len() >= 4 {
+                out.push("---".into());
+                i += 1;
+                continue;
+            }
+
+            if let Some(rest) = line.strip_prefix(".. ").or((line == "..").then_some("")) {
+                let end = block_end(lines, i + 1);
+                let body = dedent(&lines[i + 1..end]);
+                self.directive(&mut out, rest, &body);
+                i = end;
+                continue;
+            }
+            if is_table_border(line) {
+                i = self.simple_table(&mut out, lines, i);
+                continue;
+            }
+            if line.starts_with("+-") && line.ends_with('+') {
+                i = self.grid_table(&mut out, lines, i);
+                continue;
+            }
+            if field(line).is_some() {
+                i = self.field_list(&mut out, lines, i);
+                continue;
+            }
+            if let Some((marker, width)) = list_marker(line) {
+                let text = line[width..].trim_start();
+                let marker = format!("{marker} ");
+                out.push(format!("{marker}{}", self.inline(text)));
+                context = Context::ListItem(marker.len());
+                i += 1;
+                continue;
+            }
+            // 定义列表：术语下一行直接是缩进的定义
+            if next_indented && i.checked_sub(1).is_none_or(|p| lines[p].is_empty()) {
+                out.push(format!("**{}**\\", self.inline(line.trim())));
+                context = Context::Definition;
+                i += 1;
+                continue;
+            }
+
+            // 普通段落行，行尾的 `::` 引出字面块
+            let text = match line.strip_suffix("::") {
+                Some(head) => {
+                    context = Context::Literal;
+                    match head.strip_suffix(' ') {
+                        Some(head) => head.trim_end().to_string(),
+                        None if head.is_empty() => String::new(),
+                        None => format!("{head}:"),
+                    }
+                }
+                None => line.clone(),
+            };
+            if !text.is_empty() {
+                out.push(self.inline(&text));
+            }
+            i += 1;
+        }
+        out
+    }
+
+    fn push_heading(&mut self, out: &mut Vec<String>, style: (char, bool), title: &str) {
+        let level = match self.styles.iter().position(|s| *s == style) {
+            Some(level) => level + 1,
+            None => {
+                self.styles.push(style);
+                self.styles.len()
+            }
+        };
+        out.push(String::new());
+        out.push(format!("{} {}", "#".repeat(level.min(6)), self.inline(title)));
+        out.push(String::new());
+    }
+
+    /// 指令、注释与各类目标定义（`..` 开头的块）
+    fn directive(&mut self, out: &mut Vec<String>, head: &str, body: &[String]) {
+        // 脚注与引用
+        if let Some((label, text)) = head.strip_prefix('[').and_then(|h| h.split_once("] ")) {
+            let mut text = self.inline(text);
+            for line in body.iter().filter(|l| !l.is_empty()) {
+                text.push(' ');
+                text.push_str(&self.inline(line.trim()));
+            }
+            out.push(format!("\\[{label}\\] {text}"));
+            return;
+        }
+        let Some((name, args)) = head.split_once("::") else {
+            // 注释或超链接目标
+            return;
+        };
+        let name = name.trim();
+        let args = args.trim();
+        if name.starts_with('|') || name.starts_with("auto") || HIDDEN_DIRECTIVES.contains(&name) {
+            return;
+        }
+
+        // 指令选项在内容之前，以空行分隔
+        let split = body.iter().position(|l| !l.starts_with(':')).unwrap_or(body.len());
+        let options: HashMap<&str, &str> = body[..split]
+            .iter()
+            .filter_map(|l| field(l))
+            .collect();
+        let content: Vec<String> = body[split..]
+            .iter()
+            .skip_while(|l| l.is_empty())
+            .cloned()
+            .collect();
+
+        match name {
+            "code" | "code-block" | "sourcecode" => push_fence(out, args, &content),
+            "math" => {
+                out.push(String::new());
+                out.push("$$".into());
+                if !args.is_empty() {
+                    out.push(args.to_string());
+                }
+                out.extend(content);
+                out.push("$$".into());
+                out.push(String::new());
+            }
+            "image" | "figure" => {
+                let alt = options.get("alt").copied().unwrap_or_default();
+                out.push(String::new());
+                out.push(format!("![{alt}]({args})"));
+                out.push(String::new());
+                out.extend(self.convert(&content));
+            }
+            "list-table" | "csv-table" | "table" => {
+                let header_rows: usize = options.get("header-rows").and_then(|n| n.parse().ok()).unwrap_or(0);
+                let rows = match name {
+                    "list-table" => list_table_rows(&content),
+                    "csv-table" => csv::split_rows(&content.join("\n"), ',')
+                        .into_iter()
+                        .map(|row| row.into_iter().map(|c| c.trim().to_string()).collect())
+                        .collect(),
+                    // `table` 指令只是给内容中的表格加标题
+                    _ => {
+                        out.extend(self.convert(&content));
+                        return;
+                    }
+                };
+                if !args.is_empty() {
+                    out.push(String::new());
+                    out.push(format!("**{}**", self.inline(args)));
+                }
+                self.push_table(out, rows, header_rows > 0);
+            }
+            _ => {
+                let (kind, title) = match name {
+                    "note" => ("NOTE", None),
+                    "tip" | "hint" => ("TIP", None),
+                    "important" => ("IMPORTANT", None),
+                    "warning" | "attention" => ("WARNING", None),
+                    "caution" | "danger" | "error" => ("CAUTION", None),
+                    "admonition" => ("", Some(args.to_string())),
+                    // 其他指令（seealso、deprecated 等）显示指令名与参数
+                    _ => ("", Some(format!("{name} {args}").trim_end().to_string())),
+                };
+                let mut inner = Vec::new();
+                if !args.is_empty() && title.is_none() {
+                    inner.push(self.inline(args));
+                }
+                inner.extend(self.convert(&content));
+                out.push(String::new());
+                if kind.is_empty() {
+                    out.push(format!("> **{}**", self.inline(&title.unwrap_or_default())));
+                    out.push(">".into());
+                } else {
+                    out.push(format!("> [!{kind}]"));
+                }
+                out.extend(inner.iter().map(|l| quote(l)));
+                out.push(String::new());
+            }
+        }
+    }
+
+    /// 连续的字段列表，每个字段一行
+    fn field_list(&self, out: &mut Vec<String>, lines: &[String], mut i: usize) -> usize {
+        let mut fields = Vec::new();
+        while let Some((name, value)) = lines.get(i).and_then(|l| field(l)) {
+            let end = block_end(lines, i + 1);
+            let mut value = value.to_string();
+            for line in lines[i + 1..end].iter().filter(|l| !l.is_empty()) {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            fields.push(format!("**{}:** {}", self.inline(name), self.inline(value.trim())));
+            i = end;
+        }
+        let last = fields.len() - 1;
+        out.push(String::new());
+        for (k, field) in fields.into_iter().enumerate() {
+            out.push(if k < last { field + "\\" } else { field });
+        }
+        out.push(String::new());
+        i
+    }
+
+    /// 网格表：按首行边框中 `+` 的位置切分各列
+    fn grid_table(&self, out: &mut Vec<String>, lines: &[String], start: usize) -> usize {
+        let border: Vec<usize> = lines[start].char_indices().filter(|(_, c)| *c == '+').map(|(i, _)| i).collect();
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        let mut row: Option<Vec<String>> = None;
+        let mut has_header = false;
+        let mut i = start + 1;
+        while let Some(line) = lines.get(i).filter(|l| l.starts_with(['+', '|'])) {
+            if line.starts_with('+') {
+                // `=` 边框之上为表头
+                has_header |= line.contains('=');
+                rows.extend(row.take());
+            } else {
+                let cells = row.get_or_insert_with(|| vec![String::new(); border.len() - 1]);
+                for (k, cell) in cells.iter_mut().enumerate() {
+                    let text = slice_columns(line, border[k] + 1, border[k + 1]);
+                    if !text.is_empty() {
+                        if !cell.is_empty() {
+                            cell.push(' ');
+                        }
+                        cell.push_str(&text);
+                    }
+                }
+            }
+            i += 1;
+        }
+        rows.extend(row);
+        self.push_table(out, rows, has_header);
+        i
+    }
+
+    /// 简单表：列由 `=` 边框划分，表头与表体之间也是边框，以边框后的空行结束
+    fn simple_table(&self, out: &mut Vec<String>, lines: &[String], start: usize) -> usize {
+        let border = &lines[start];
+        let mut columns = Vec::new();
+        let mut run = None;
+        for (i, c) in border.char_indices().chain([(border.len(), ' ')]) {
+            match (c, run) {
+                ('=', None) => run = Some(i),
+                (' ', Some(from)) => {
+                    columns.push(from);
+                    run = None;
+                }
+                _ => {}
+            }
+        }
+
+        let mut segments: Vec<Vec<Vec<String>>> = vec![Vec::new()];
+        let mut i = start + 1;
+        while let Some(line) = lines.get(i) {
+            i += 1;
+            if is_table_border(line) {
+                if lines.get(i).is_none_or(|l| l.is_empty()) {
+                    break;
+                }
+                segments.push(Vec::new());
+                continue;
+            }
+            // 跨列下划线与空行
+            if line.is_empty() || line.chars().all(|c| c == '-' || c == ' ') {
+                continue;
+            }
+            let cells: Vec<String> = columns
+                .iter()
+                .enumerate()
+                .map(|(k, &from)| slice_columns(line, from, columns.get(k + 1).copied().unwrap_or(usize::MAX)))
+                .collect();
+            let rows = segments.last_mut().unwrap();
+            match rows.last_mut() {
+                // 首列为空的行是上一行的续行
+                Some(row) if cells[0].is_empty() => {
+                    for (cell, more) in row.iter_mut().zip(cells) {
+                        if !more.is_empty() {
+                            cell.push(' ');
+                            cell.push_str(&more);
+                        }
+                    }
+                }
+                _ => rows.push(cells),
+            }
+        }
+
+        let has_header = segments.len() > 1;
+        let rows = if has_header {
+            let header: Vec<Vec<String>> = segments.remove(0);
+            let mut merged = header.into_iter().reduce(|mut a, b| {
+                for (cell, more) in a.iter_mut().zip(b) {
+                    cell.push(' ');
+                    cell.push_str(&more);
+                }
+                a
+            });
+            merged.iter_mut().flatten().for_each(|c| *c = c.trim().to_string());
+            merged.into_iter().chain(segments.concat()).collect()
+        } else {
+            segments.concat()
+        };
+        self.push_table(out, rows, has_header);
+        i
+    }
+
+    /// 输出管道表格；没有表头时补一行空表头
+    fn push_table(&self, out: &mut Vec<String>, mut rows: Vec<Vec<String>>, has_header: bool) {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        if !has_header {
+            rows.insert(0, vec![String::new(); columns]);
+        }
+        let row = |cells: &[String]| {
+            let cells: Vec<String> = (0..columns)
+                .map(|k| self.inline(cells.get(k).map_or("", String::as_str)).replace('|', "\\|"))
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        };
+        out.push(String::new());
+        out.push(row(&rows[0]));
+        out.push(format!("|{}", " --- |".repeat(columns)));
+        out.extend(rows[1..].iter().map(|r| row(r)));
+        out.push(String::new());
+    }
+
+    /// 行内标记：字面量、角色、链接、替换与脚注引用
+    fn inline(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            let boundary = out.chars().last().is_none_or(|p| p.is_whitespace() || "([{<'\"-/".contains(p));
+
+            if let Some(inner) = rest.strip_prefix("``")
+                && let Some(end) = inner.find("``")
+            {
+                out.push_str(&code_span(&inner[..end]));
+                rest = &inner[end + 2..];
+                continue;
+            }
+            if c == ':'
+                && boundary
+                && let Some((role, content, after)) = role(rest)
+            {
+                out.push_str(&self.role(role, content));
+                rest = after;
+                continue;
+            }
+            if c == '`'
+                && boundary
+                && let Some(end) = rest[1..].find('`')
+            {
+                let content = &rest[1..end + 1];
+                let after = &rest[end + 2..];
+                let (link, after) = match after.strip_prefix("__").or(after.strip_prefix('_')) {
+                    Some(after) => (true, after),
+                    None => (false, after),
+                };
+                out.push_str(&if link { self.reference(content) } else { format!("*{content}*") });
+                rest = after;
+                continue;
+            }
+            if c == '|'
+                && boundary
+                && let Some(end) = rest[1..].find('|')
+                && let Some(value) = self.substitutions.get(&rest[1..end + 1])
+            {
+                out.push_str(value);
+                rest = rest[end + 2..].trim_start_matches('_');
+                continue;
+            }
+            if c == '_' {
+                let next = rest[1..].chars().next();
+                let ends_word = next.is_none_or(|n| !n.is_alphanumeric() && n != '_');
+                let prev = out.chars().last();
+                if ends_word && prev == Some(']') {
+                    // 脚注引用 `[1]_`
+                    rest = &rest[1..];
+                    continue;
+                }
+                if ends_word && prev.is_some_and(char::is_alphanumeric) {
+                    // 单词引用 `name_`
+                    let start = out
+                        .rfind(|p: char| !(p.is_alphanumeric() || p == '-' || p == '.'))
+                        .map_or(0, |p| p + out[p..].chars().next().unwrap().len_utf8());
+                    let name = out.split_off(start);
+                    out.push_str(&self.reference(&name));
+                    rest = &rest[1..];
+                    continue;
+                }
+            }
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        out
+    }
+
+    /// 超链接引用：`text <url>` 或已定义的目标名
+    fn reference(&self, content: &str) -> String {
+        if let Some((text, target)) = content.strip_suffix('>').and_then(|c| c.rsplit_once('<')) {
+            let text = text.trim();
+            let target = match target.strip_suffix('_') {
+                Some(name) => self.targets.get(&name.to_lowercase()).cloned().unwrap_or_default(),
+                None => target.to_string(),
+            };
+            let text = if text.is_empty() { target.as_str() } else { text };
+            return format!("[{text}]({target})");
+        }
+        match self.targets.get(&content.to_lowercase()) {
+            Some(url) => format!("[{content}]({url})"),
+            None => content.to_string(),
+        }
+    }
+
+    fn role(&self, role: &str, content: &str) -> String {
+        // `title <target>` 形式只显示标题
+        let title = content
+            .strip_suffix('>')
+            .and_then(|c| c.rsplit_once(" <"))
+            .map_or(content, |(title, _)| title);
+        match role.rsplit(':').next().unwrap_or(role) {
+            "math" => format!("${content}$"),
+            "emphasis" | "title-reference" | "title" | "t" => format!("*{title}*"),
+            "strong" => format!("**{title}**"),
+            "ref" | "doc" | "term" | "numref" | "abbr" | "sub" | "sup" | "subscript" | "superscript" => {
+                title.to_string()
+            }
+            // 代码类角色（:func:、:class:、:code: 等），`~` 只显示最后一段
+            _ => {
+                let title = title.trim_start_matches('!');
+                let title = match title.strip_prefix('~') {
+                    Some(path) => path.rsplit('.').next().unwrap_or(path),
+                    None => title,
+                };
+                code_span(title)
+            }
+        }
+    }
+}
+
+/// 行首空格数
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// 从 `start` 开始的缩进块（含其中的空行，不含末尾空行）的结束位置
+fn block_end(lines: &[String], start: usize) -> usize {
+    let mut end = start;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        if line.is_empty() {
+            continue;
+        }
+        if indent_of(line) == 0 {
+            break;
+        }
+        end = i + 1;
+    }
+    end
+}
+
+/// 去掉公共缩进
+fn dedent(lines: &[String]) -> Vec<String> {
+    let min = lines.iter().filter(|l| !l.is_empty()).map(|l| indent_of(l)).min().unwrap_or(0);
+    lines.iter().map(|l| l.get(min..).unwrap_or_default().to_string()).collect()
+}
+
+fn indent(pad: &str, line: String) -> String {
+    if line.is_empty() { line } else { format!("{pad}{line}") }
+}
+
+fn quote(line: &str) -> String {
+    if line.is_empty() { ">".into() } else { format!("> {line}") }
+}
+
+/// 整行由同一个装饰符组成时返回该字符
+fn adornment(line: &str) -> Option<char> {
+    let c = line.chars().next()?;
+    (line.len() >= 2 && ADORNMENTS.contains(c) && line.chars().all(|x| x == c)).then_some(c)
+}
+
+/// 简单表的边框：至少两列 `=`，以空格分隔
+fn is_table_border(line: &str) -> bool {
+    line.starts_with('=') && line.contains(" =") && line.chars().all(|c| c == '=' || c == ' ')
+}
+
+/// 字段 `:name: value`
+fn field(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix(':')?;
+    let end = rest.find(':').filter(|&e| e > 0)?;
+    let value = &rest[end + 1..];
+    (value.is_empty() || value.starts_with(' ')).then(|| (&rest[..end], value.trim()))
+}
+
+/// 列表标记：返回转换后的 Markdown 标记与原标记（含后随空格）的长度
+fn list_marker(line: &str) -> Option<(String, usize)> {
+    let (marker, rest) = line.split_once(' ')?;
+    if rest.trim().is_empty() {
+        return None;
+    }
+    let width = marker.len() + 1 + rest.len() - rest.trim_start().len();
+    let converted = match marker {
+        "-" | "*" | "+" => marker.to_string(),
+        "•" | "‣" | "⁃" => "-".into(),
+        "#." | "#)" | "(#)" => "1.".into(),
+        _ => {
+            let number = marker
+                .strip_prefix('(')
+                .and_then(|m| m.strip_suffix(')'))
+                .or(marker.strip_suffix('.'))
+                .or(marker.strip_suffix(')'))?;
+            number.parse::<u32>().ok()?;
+            format!("{number}.")
+        }
+    };
+    Some((converted, width))
+}
+
+/// 角色 `:name:`content`` ，返回角色名、内容与剩余文本
+fn role(text: &str) -> Option<(&str, &str, &str)> {
+    let rest = &text[1..];
+    let end = rest.find(":`")?;
+    let name = &rest[..end];
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || "-_.+:".contains(c)) {
+        return None;
+    }
+    let body = &rest[end + 2..];
+    let close = body.find('`')?;
+    Some((name, &body[..close], &body[close + 1..]))
+}
+
+fn code_span(code: &str) -> String {
+    if code.contains('`') { format!("`` {code} ``") } else { format!("`{code}`") }
+}
+
+fn push_fence(out: &mut Vec<String>, lang: &str, lines: &[String]) {
+    let longest = lines
+        .iter()
+        .flat_map(|l| l.split(|c| c != '`'))
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    let end = lines.iter().rposition(|l| !l.is_empty()).map_or(0, |e| e + 1);
+    out.push(String::new());
+    out.push(format!("{fence}{lang}"));
+    out.extend(lines[..end].iter().cloned());
+    out.push(fence);
+    out.push(String::new());
+}
+
+/// 按显示列截取 `[start, end)` 并去掉首尾空白
+fn slice_columns(line: &str, start: usize, end: usize) -> String {
+    let mut col = 0;
+    let mut out = String::new();
+    for c in line.chars() {
+        if col >= end {
+            break;
+        }
+        if col >= start {
+            out.push(c);
+        }
+        col += c.width().unwrap_or(0);
+    }
+    out.trim().to_string()
+}
+
+/// `list-table` 的内容：一级列表为行，二级列表为单元格
+fn list_table_rows(content: &[String]) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for line in content.iter().filter(|l| !l.is_empty()) {
+        let trimmed = line.trim_start();
+        let row_start = indent_of(line) == 0 && trimmed.starts_with(['*', '-']);
+        if row_start {
+            rows.push(Vec::new());
+        }
+        let item = if row_start { trimmed[1..].trim_start() } else { trimmed };
+        let Some(row) = rows.last_mut() else {
+            continue;
+        };
+        match item.strip_prefix("- ").or((item == "-").then_some("")) {
+            Some(cell) => row.push(cell.trim().to_string()),
+            None => {
+                if let Some(cell) = row.last_mut() {
+                    cell.push(' ');
+                    cell.push_str(item);
+                }
+            }
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_markdown() {
+        let rst = "\
+=====
+标题
+=====
+
+章节
+----
+
+See ``x`` and :func:`~pkg.mod.run` or `Python <https://python.org>`_ and docs_.
+
+.. _docs: https://docs.rs
+
+Example::
+
+    print(1)
+
+- one
+  more
+- two
+
+:param x: 值
+:returns: 结果
+
+.. note:: 注意事项
+
+.. code-block:: rust
+
+   fn main() {}
+
+=====  =====
+A      B
+=====  =====
+1      2
+3      4
+=====  =====
+
++----+----+
+| a  | b  |
++====+====+
+| 1  | 2  |
++----+----+
+";
+        let md = to_markdown(rst);
+        let lines: Vec<&str> = md.lines().filter(|l| !l.is_empty()).collect();
+        assert_eq!(
+            lines,
+            [
+                "# 标题",
+                "## 章节",
+                "See `x` and `run` or [Python](https://python.org) and [docs](https://docs.rs).",
+                "Example:",
+                "```",
+                "print(1)",
+                "```",
+                "- one",
+                "  more",
+                "- two",
+                "**param x:** 值\\",
+                "**returns:** 结果",
+                "> [!NOTE]",
+                "> 注意事项",
+                "```rust",
+                "fn main() {}",
+                "```",
+                "| A | B |",
+                "| --- | --- |",
+                "| 1 | 2 |",
+                "| 3 | 4 |",
+                "| a | b |",
+                "| --- | --- |",
+                "| 1 | 2 |",
+            ]
+        );
+    }
+}
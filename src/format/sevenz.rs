@@ -0,0 +1,700 @@
+//! 7z 压缩包读取
+//!
+//! 解析 7z 的文件头（通常以 LZMA 压缩）得到文件列表，按需解码成员所在的数据块。
+//! 支持不压缩、LZMA、LZMA2 与 Deflate 编码的数据块；BCJ 等过滤器、PPMd、BZip2 与加密的数据块
+//! 只能列出，解压时报错。
+
+use crate::format::archive::MAX_EXTRACTED_SIZE;
+use crate::format::lzma;
+use crate::t;
+use flate2::read::DeflateDecoder;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// 文件头开头的签名
+pub const SIGNATURE: &[u8] = &[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C];
+/// 签名头的长度，其后是数据块
+const SIGNATURE_HEADER_LEN: u64 = 32;
+/// 文件头的大小上限
+const MAX_HEADER_SIZE: u64 = 64 * 1024 * 1024;
+
+// 文件头中的属性 ID
+const END: u8 = 0x00;
+const HEADER: u8 = 0x01;
+const ARCHIVE_PROPERTIES: u8 = 0x02;
+const MAIN_STREAMS_INFO: u8 = 0x04;
+const FILES_INFO: u8 = 0x05;
+const PACK_INFO: u8 = 0x06;
+const UNPACK_INFO: u8 = 0x07;
+const SUBSTREAMS_INFO: u8 = 0x08;
+const SIZE: u8 = 0x09;
+const CRC: u8 = 0x0A;
+const FOLDER: u8 = 0x0B;
+const CODERS_UNPACK_SIZE: u8 = 0x0C;
+const NUM_UNPACK_STREAM: u8 = 0x0D;
+const EMPTY_STREAM: u8 = 0x0E;
+const EMPTY_FILE: u8 = 0x0F;
+const NAME: u8 = 0x11;
+const MTIME: u8 = 0x14;
+const WIN_ATTRIBUTES: u8 = 0x15;
+const ENCODED_HEADER: u8 = 0x17;
+
+/// Windows 文件属性中的目录位
+const ATTRIBUTE_DIRECTORY: u32 = 0x10;
+/// FILETIME（1601 年起的 100 纳秒数）与 Unix 纪元相差的秒数
+const FILETIME_UNIX_OFFSET: u64 = 11_644_473_600;
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, t!("7z 文件头无效"))
+}
+
+/// 压缩包中的一项
+#[derive(Debug)]
+pub struct Entry {
+    pub name: String,
+    pub size: u64,
+    /// 所在数据块只有这一个文件时为数据块压缩后的大小；固实压缩的多个文件共享数据块时为 None
+    pub packed: Option<u64>,
+    pub modified: Option<SystemTime>,
+    pub is_dir: bool,
+    /// 所在数据块及其中的起始偏移，空文件与目录为 None
+    location: Option<(usize, u64)>,
+}
+
+/// 数据块（7z 中称为 folder）：一组编码器把若干压缩流解码为一个输出
+#[derive(Debug, Default)]
+struct Folder {
+    coders: Vec<Coder>,
+    /// 各编码器输出流的大小
+    unpack_sizes: Vec<u64>,
+    /// 是否记录了整个数据块的 CRC
+    has_crc: bool,
+    /// 第一个压缩流的序号
+    first_pack: usize,
+    num_packs: usize,
+    /// 其中的文件数
+    num_streams: usize,
+}
+
+#[derive(Debug)]
+struct Coder {
+    id: Vec<u8>,
+    num_in: usize,
+    num_out: usize,
+    props: Vec<u8>,
+}
+
+/// 数据流信息：压缩流的位置与大小，以及各数据块
+#[derive(Debug, Default)]
+struct Streams {
+    pack_pos: u64,
+    pack_sizes: Vec<u64>,
+    folders: Vec<Folder>,
+    /// 各文件（子流）的大小，按数据块顺序排列
+    stream_sizes: Vec<u64>,
+}
+
+/// 打开的 7z 压缩包
+pub struct Archive {
+    file: File,
+    streams: Streams,
+    pub entries: Vec<Entry>,
+}
+
+impl Archive {
+    pub fn open(path: &Path) -> io::Result<Archive> {
+        let mut file = File::open(path)?;
+        let mut start = [0u8; SIGNATURE_HEADER_LEN as usize];
+        file.read_exact(&mut start)?;
+        if &start[..6] != SIGNATURE {
+            return Err(invalid());
+        }
+        let offset = u64::from_le_bytes(start[12..20].try_into().unwrap());
+        let size = u64::from_le_bytes(start[20..28].try_into().unwrap());
+        if size == 0 {
+            // 空压缩包
+            return Ok(Archive {
+                file,
+                streams: Streams::default(),
+                entries: Vec::new(),
+            });
+        }
+        if size > MAX_HEADER_SIZE {
+            return Err(invalid());
+        }
+        let header_pos = SIGNATURE_HEADER_LEN.checked_add(offset).ok_or_else(invalid)?;
+        let mut header = read_at(&mut file, header_pos, size)?;
+
+        // 压缩过的文件头先解码，解码结果可能仍是压缩过的文件头
+        loop {
+            let mut reader = Reader::new(&header);
+            match reader.byte()? {
+                HEADER => break,
+                ENCODED_HEADER => {
+                    let streams = reader.streams()?;
+                    let folder = streams.folders.first().ok_or_else(invalid)?;
+                    header = decode_folder(&mut file, &streams, folder, folder_size(folder)?)?;
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        let mut reader = Reader::new(&header[1..]);
+        let mut streams = Streams::default();
+        let mut files = Files::default();
+        loop {
+            match reader.byte()? {
+                END => break,
+                ARCHIVE_PROPERTIES => reader.skip_properties()?,
+                MAIN_STREAMS_INFO => streams = reader.streams()?,
+                FILES_INFO => files = reader.files()?,
+                _ => return Err(invalid()),
+            }
+        }
+        let entries = entries(&streams, files)?;
+        Ok(Archive { file, streams, entries })
+    }
+
+    /// 解压一项，整个数据块解码到该项为止
+    pub fn read(&mut self, index: usize) -> io::Result<Vec<u8>> {
+        let entry = self.entries.get(index).ok_or_else(invalid)?;
+        let Some((folder, offset)) = entry.location else {
+            return Ok(Vec::new());
+        };
+        let end = offset.checked_add(entry.size).ok_or_else(invalid)?;
+        if end > MAX_EXTRACTED_SIZE {
+            return Err(io::Error::other(t!(
+                "解压后的文件过大（上限 {} MiB）",
+                MAX_EXTRACTED_SIZE / (1024 * 1024)
+            )));
+        }
+        let folder = &self.streams.folders[folder];
+        let data = decode_folder(&mut self.file, &self.streams, folder, end)?;
+        data.get(offset as usize..end as usize)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(invalid)
+    }
+}
+
+/// 文件属性
+#[derive(Debug, Default)]
+struct Files {
+    names: Vec<String>,
+    empty_stream: Vec<bool>,
+    empty_file: Vec<bool>,
+    modified: Vec<Option<SystemTime>>,
+    attributes: Vec<Option<u32>>,
+}
+
+/// 把文件属性与数据流对应起来：非空文件依次占用各数据块中的子流
+fn entries(streams: &Streams, files: Files) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::with_capacity(files.names.len());
+    let mut folder = 0;
+    let mut in_folder = 0;
+    let mut offset = 0;
+    let mut stream = 0;
+    let mut empty_index = 0;
+    for (i, name) in files.names.into_iter().enumerate() {
+        let empty = files.empty_stream.get(i).copied().unwrap_or(false);
+        let attributes = files.attributes.get(i).copied().flatten();
+        let modified = files.modified.get(i).copied().flatten();
+        if empty {
+            let is_file = files.empty_file.get(empty_index).copied().unwrap_or(false);
+            empty_index += 1;
+            let is_dir = !is_file || attributes.is_some_and(|a| a & ATTRIBUTE_DIRECTORY != 0);
+            entries.push(Entry {
+                name,
+                size: 0,
+                packed: None,
+                modified,
+                is_dir,
+                location: None,
+            });
+            continue;
+        }
+        // 跳过不含文件的数据块
+        while streams.folders.get(folder).is_some_and(|f| in_folder >= f.num_streams) {
+            folder += 1;
+            in_folder = 0;
+            offset = 0;
+        }
+        let f = streams.folders.get(folder).ok_or_else(invalid)?;
+        let size = *streams.stream_sizes.get(stream).ok_or_else(invalid)?;
+        let packed = (f.num_streams == 1).then(|| streams.pack_sizes.iter().skip(f.first_pack).take(f.num_packs).sum());
+        entries.push(Entry {
+            name,
+            size,
+            packed,
+            modified,
+            is_dir: false,
+            location: Some((folder, offset)),
+        });
+        offset += size;
+        in_folder += 1;
+        stream += 1;
+    }
+    Ok(entries)
+}
+
+/// 数据块的最终输出：没有被其他编码器作为输入的输出流，单编码器时即第一个
+fn folder_size(folder: &Folder) -> io::Result<u64> {
+    folder.unpack_sizes.last().copied().ok_or_else(invalid)
+}
+
+/// 解码数据块的前 `end` 字节；只支持由单个编码器构成、只有一个压缩流的数据块
+fn decode_folder(file: &mut File, streams: &Streams, folder: &Folder, end: u64) -> io::Result<Vec<u8>> {
+    let [coder] = &folder.coders[..] else {
+        return Err(unsupported(&folder.coders));
+    };
+    if folder.num_packs != 1 || coder.num_in != 1 || coder.num_out != 1 {
+        return Err(unsupported(&folder.coders));
+    }
+    let size = folder_size(folder)?;
+    let pos = streams.pack_sizes[..folder.first_pack]
+        .iter()
+        .try_fold(SIGNATURE_HEADER_LEN + streams.pack_pos, |pos, &s| pos.checked_add(s))
+        .ok_or_else(invalid)?;
+    let packed = *streams.pack_sizes.get(folder.first_pack).ok_or_else(invalid)?;
+    let data = read_at(file, pos, packed)?;
+
+    let mut out = match coder.id.as_slice() {
+        [0x00] => data,
+        [0x03, 0x01, 0x01] => lzma::decode_lzma(&coder.props, &data, Some(end.min(size)))?,
+        [0x21] => lzma::decode_lzma2(&data, Some(end))?,
+        [0x04, 0x01, 0x08] => {
+            let mut out = Vec::new();
+            DeflateDecoder::new(data.as_slice()).take(end).read_to_end(&mut out)?;
+            out
+        }
+        _ => return Err(unsupported(&folder.coders)),
+    };
+    if (out.len() as u64) < end {
+        return Err(invalid());
+    }
+    out.truncate(end as usize);
+    Ok(out)
+}
+
+fn unsupported(coders: &[Coder]) -> io::Error {
+    let names: Vec<&str> = coders
+        .iter()
+        .map(|c| match c.id.as_slice() {
+            [0x00] => "Copy",
+            [0x03, 0x01, 0x01] => "LZMA",
+            [0x21] => "LZMA2",
+            [0x03, 0x03, 0x01, 0x03] => "BCJ",
+            [0x03, 0x03, 0x01, 0x1B] => "BCJ2",
+            [0x03, 0x04, 0x01] => "PPMd",
+            [0x04, 0x01, 0x08] => "Deflate",
+            [0x04, 0x02, 0x02] => "BZip2",
+            [0x06, 0xF1, 0x07, 0x01] => "AES",
+            _ => "?",
+        })
+        .collect();
+    io::Error::other(t!("不支持的 7z 压缩方法: {}", names.join(" + ")))
+}
+
+/// 读取文件中 `pos` 处的 `len` 字节，超出文件末尾时报错而不预先分配
+fn read_at(file: &mut File, pos: u64, len: u64) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(pos))?;
+    let mut data = Vec::new();
+    file.take(len).read_to_end(&mut data)?;
+    if (data.len() as u64) < len {
+        return Err(invalid());
+    }
+    Ok(data)
+}
+
+/// 文件头的读取位置
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn byte(&mut self) -> io::Result<u8> {
+        let byte = *self.data.get(self.pos).ok_or_else(invalid)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self, len: u64) -> io::Result<&'a [u8]> {
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.pos.checked_add(len))
+            .ok_or_else(invalid)?;
+        let bytes = self.data.get(self.pos..end).ok_or_else(invalid)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// 变长整数：首字节高位连续的 1 表示后面还有几个字节（小端），其余位是最高部分
+    fn number(&mut self) -> io::Result<u64> {
+        let first = self.byte()?;
+        let mut value = 0u64;
+        for i in 0..8 {
+            let mask = 0x80 >> i;
+            if first & mask == 0 {
+                let high = u64::from(first & (mask - 1));
+                return Ok(value | (high << (8 * i)));
+            }
+            value |= u64::from(self.byte()?) << (8 * i);
+        }
+        Ok(value)
+    }
+
+    /// 用作数量的变长整数，超过剩余字节数的必然无效，避免按它分配过大的内存
+    fn count(&mut self) -> io::Result<usize> {
+        let n = self.number()?;
+        usize::try_from(n)
+            .ok()
+            .filter(|&n| n <= self.data.len())
+            .ok_or_else(invalid)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    /// 位向量，高位在前
+    fn bits(&mut self, n: usize) -> io::Result<Vec<bool>> {
+        let bytes = self.bytes(n.div_ceil(8) as u64)?;
+        Ok((0..n).map(|i| bytes[i / 8] & (0x80 >> (i % 8)) != 0).collect())
+    }
+
+    /// 先以一个字节表示是否全部为真，不是时再跟位向量
+    fn defined(&mut self, n: usize) -> io::Result<Vec<bool>> {
+        if self.byte()? != 0 {
+            Ok(vec![true; n])
+        } else {
+            self.bits(n)
+        }
+    }
+
+    /// CRC 列表，只需跳过
+    fn skip_digests(&mut self, n: usize) -> io::Result<()> {
+        let defined = self.defined(n)?;
+        self.bytes(4 * defined.iter().filter(|&&d| d).count() as u64)?;
+        Ok(())
+    }
+
+    fn skip_properties(&mut self) -> io::Result<()> {
+        while self.byte()? != END {
+            let size = self.number()?;
+            self.bytes(size)?;
+        }
+        Ok(())
+    }
+
+    fn streams(&mut self) -> io::Result<Streams> {
+        let mut streams = Streams::default();
+        loop {
+            match self.byte()? {
+                END => break,
+                PACK_INFO => self.pack_info(&mut streams)?,
+                UNPACK_INFO => self.unpack_info(&mut streams)?,
+                SUBSTREAMS_INFO => self.substreams_info(&mut streams)?,
+                _ => return Err(invalid()),
+            }
+        }
+        if streams.stream_sizes.is_empty() {
+            // 没有子流信息时每个数据块一个文件
+            for folder in &mut streams.folders {
+                folder.num_streams = 1;
+            }
+            streams.stream_sizes = streams.folders.iter().map(folder_size).collect::<io::Result<_>>()?;
+        }
+        Ok(streams)
+    }
+
+    fn pack_info(&mut self, streams: &mut Streams) -> io::Result<()> {
+        streams.pack_pos = self.number()?;
+        let n = self.count()?;
+        loop {
+            match self.byte()? {
+                END => return Ok(()),
+                SIZE => streams.pack_sizes = (0..n).map(|_| self.number()).collect::<io::Result<_>>()?,
+                CRC => self.skip_digests(n)?,
+                _ => return Err(invalid()),
+            }
+        }
+    }
+
+    fn unpack_info(&mut self, streams: &mut Streams) -> io::Result<()> {
+        if self.byte()? != FOLDER {
+            return Err(invalid());
+        }
+        let n = self.count()?;
+        if self.byte()? != 0 {
+            // 文件头放在别处（External）的情况实际不会出现
+            return Err(invalid());
+        }
+        let mut first_pack = 0;
+        for _ in 0..n {
+            let mut folder = self.folder()?;
+            folder.first_pack = first_pack;
+            first_pack += folder.num_packs;
+            streams.folders.push(folder);
+        }
+        if self.byte()? != CODERS_UNPACK_SIZE {
+            return Err(invalid());
+        }
+        for folder in &mut streams.folders {
+            let outputs: usize = folder.coders.iter().map(|c| c.num_out).sum();
+            folder.unpack_sizes = (0..outputs).map(|_| self.number()).collect::<io::Result<_>>()?;
+        }
+        loop {
+            match self.byte()? {
+                END => return Ok(()),
+                CRC => {
+                    let defined = self.defined(n)?;
+                    for (folder, defined) in streams.folders.iter_mut().zip(defined) {
+                        if defined {
+                            self.u32()?;
+                            folder.has_crc = true;
+                        }
+                    }
+                }
+                _ => return Err(invalid()),
+            }
+        }
+    }
+
+    fn folder(&mut self) -> io::Result<Folder> {
+        let mut folder = Folder::default();
+        let num_coders = self.count()?;
+        let (mut total_in, mut total_out) = (0, 0);
+        for _ in 0..num_coders {
+            let flags = self.byte()?;
+            let id = self.bytes(u64::from(flags & 0x0F))?.to_vec();
+            let (num_in, num_out) = if flags & 0x10 != 0 {
+                (self.count()?, self.count()?)
+            } else {
+                (1, 1)
+            };
+            let props = if flags & 0x20 != 0 {
+                let size = self.number()?;
+                self.bytes(size)?.to_vec()
+            } else {
+                Vec::new()
+            };
+            total_in += num_in;
+            total_out += num_out;
+            folder.coders.push(Coder {
+                id,
+                num_in,
+                num_out,
+                props,
+            });
+        }
+        // 编码器之间的连接，只需跳过
+        let bind_pairs = total_out.checked_sub(1).ok_or_else(invalid)?;
+        for _ in 0..bind_pairs {
+            self.number()?;
+            self.number()?;
+        }
+        folder.num_packs = total_in.checked_sub(bind_pairs).ok_or_else(invalid)?;
+        if folder.num_packs > 1 {
+            for _ in 0..folder.num_packs {
+                self.number()?;
+            }
+        }
+        Ok(folder)
+    }
+
+    fn substreams_info(&mut self, streams: &mut Streams) -> io::Result<()> {
+        for folder in &mut streams.folders {
+            folder.num_streams = 1;
+        }
+        let mut kind = self.byte()?;
+        if kind == NUM_UNPACK_STREAM {
+            for folder in &mut streams.folders {
+                folder.num_streams = self.count()?;
+            }
+            kind = self.byte()?;
+        }
+        // 每个数据块中除最后一个文件外的大小，最后一个取剩余部分
+        for folder in &streams.folders {
+            if folder.num_streams == 0 {
+                continue;
+            }
+            let total = folder_size(folder)?;
+            let mut sum = 0u64;
+            if kind == SIZE {
+                for _ in 1..folder.num_streams {
+                    let size = self.number()?;
+                    sum = sum.checked_add(size).ok_or_else(invalid)?;
+                    streams.stream_sizes.push(size);
+                }
+            }
+            streams.stream_sizes.push(total.checked_sub(sum).ok_or_else(invalid)?);
+        }
+        if kind == SIZE {
+            kind = self.byte()?;
+        }
+        loop {
+            match kind {
+                END => return Ok(()),
+                CRC => {
+                    let n = streams
+                        .folders
+                        .iter()
+                        .filter(|f| !(f.num_streams == 1 && f.has_crc))
+                        .map(|f| f.num_streams)
+                        .sum();
+                    self.skip_digests(n)?;
+                }
+                _ => return Err(invalid()),
+            }
+            kind = self.byte()?;
+        }
+    }
+
+    fn files(&mut self) -> io::Result<Files> {
+        let n = self.count()?;
+        let mut files = Files {
+            names: vec![String::new(); n],
+            ..Default::default()
+        };
+        loop {
+            let kind = self.byte()?;
+            if kind == END {
+                break;
+            }
+            let size = self.number()?;
+            let mut property = Reader::new(self.bytes(size)?);
+            match kind {
+                EMPTY_STREAM => files.empty_stream = property.bits(n)?,
+                EMPTY_FILE => {
+                    let empty = files.empty_stream.iter().filter(|&&e| e).count();
+                    files.empty_file = property.bits(empty)?;
+                }
+                NAME => files.names = property.names(n)?,
+                MTIME => {
+                    files.modified = property.optional(n, |r| {
+                        let time = u64::from_le_bytes(r.bytes(8)?.try_into().unwrap());
+                        let secs = (time / 10_000_000).checked_sub(FILETIME_UNIX_OFFSET);
+                        Ok(secs.map(|s| SystemTime::UNIX_EPOCH + Duration::from_secs(s)))
+                    })?;
+                }
+                WIN_ATTRIBUTES => files.attributes = property.optional(n, |r| r.u32().map(Some))?,
+                // 其他属性（创建时间、访问时间、占位等）不需要
+                _ => {}
+            }
+        }
+        Ok(files)
+    }
+
+    /// UTF-16LE、以 0 结尾的文件名
+    fn names(&mut self, n: usize) -> io::Result<Vec<String>> {
+        if self.byte()? != 0 {
+            return Err(invalid());
+        }
+        let mut names = Vec::with_capacity(n);
+        for _ in 0..n {
+            let mut units = Vec::new();
+            loop {
+                let unit = u16::from_le_bytes(self.bytes(2)?.try_into().unwrap());
+                if unit == 0 {
+                    break;
+                }
+                units.push(unit);
+            }
+            names.push(String::from_utf16_lossy(&units).replace('\\', "/"));
+        }
+        Ok(names)
+    }
+
+    /// 部分文件才有的属性：定义位向量、External 字节，之后依次是有定义的值
+    fn optional<T>(
+        &mut self,
+        n: usize,
+        mut read: impl FnMut(&mut Self) -> io::Result<Option<T>>,
+    ) -> io::Result<Vec<Option<T>>> {
+        let defined = self.defined(n)?;
+        if self.byte()? != 0 {
+            return Err(invalid());
+        }
+        defined
+            .into_iter()
+            .map(|d| if d { read(self) } else { Ok(None) })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        let data = [0x05, 0x80, 0xFF, 0xC1, 0x02, 0x03, 0xFF, 1, 2, 3, 4, 5, 6, 7, 8];
+        let mut reader = Reader::new(&data);
+        assert_eq!(reader.number().unwrap(), 5);
+        assert_eq!(reader.number().unwrap(), 0xFF);
+        assert_eq!(reader.number().unwrap(), 0x01_0302);
+        assert_eq!(reader.number().unwrap(), 0x0807_0605_0403_0201);
+        assert!(reader.number().is_err());
+    }
+
+    #[test]
+    fn test_archive() {
+        // 用 libarchive 的 bsdtar 生成压缩包（文件头以 LZMA 压缩，数据固实压缩），未安装时跳过
+        let dir = std::env::temp_dir().join(format!("xcat-7z-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let nums: String = (1..=2000).map(|i| format!("{i}\n")).collect();
+        std::fs::write(dir.join("a.md"), "# Hello\n").unwrap();
+        std::fs::write(dir.join("sub/nums.txt"), &nums).unwrap();
+        std::fs::write(dir.join("empty.txt"), "").unwrap();
+
+        for method in ["copy", "lzma1", "lzma2", "deflate"] {
+            let path = dir.join(format!("{method}.7z"));
+            let Ok(status) = std::process::Command::new("bsdtar")
+                .args([
+                    "--format",
+                    "7zip",
+                    "--options",
+                    &format!("7zip:compression={method}"),
+                    "-ncf",
+                ])
+                .arg(&path)
+                .args(["-C"])
+                .arg(&dir)
+                .args(["a.md", "sub/nums.txt", "empty.txt", "sub"])
+                .stderr(std::process::Stdio::null())
+                .status()
+            else {
+                break;
+            };
+            assert!(status.success(), "{method}");
+
+            let mut archive = Archive::open(&path).unwrap();
+            let entries: Vec<(&str, u64, bool)> = archive
+                .entries
+                .iter()
+                .map(|e| (e.name.as_str(), e.size, e.is_dir))
+                .collect();
+            assert_eq!(
+                entries,
+                [
+                    ("a.md", 8, false),
+                    ("sub/nums.txt", 8893, false),
+                    ("empty.txt", 0, false),
+                    ("sub", 0, true)
+                ]
+            );
+            assert!(archive.entries[0].modified.is_some());
+            assert_eq!(archive.read(0).unwrap(), b"# Hello\n", "{method}");
+            assert_eq!(archive.read(1).unwrap(), nums.as_bytes(), "{method}");
+            assert_eq!(archive.read(2).unwrap(), b"");
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
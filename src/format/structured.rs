@@ -0,0 +1,490 @@
+//! YAML / TOML 结构化渲染
+//!
+//! 逐行着色而不重新序列化，保留注释、键的顺序与锚点：键、字符串、数字与常量分别着色，
+//! 嵌套层级画出缩进线。YAML 的别名后标注锚点所在的行；TOML 的表头记录为标题。
+//! 嵌套的映射、序列与 TOML 的表都可以折叠。
+
+use crate::document::*;
+use crate::format::{FileFormat, FormatError};
+use crate::t;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub struct YamlFormat;
+
+impl FileFormat for YamlFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        Ok(parse_yaml(&std::fs::read_to_string(path)?))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["yaml", "yml"]
+    }
+
+    fn name(&self) -> &'static str {
+        "yaml"
+    }
+}
+
+pub struct TomlFormat;
+
+impl FileFormat for TomlFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        Ok(parse_toml(&std::fs::read_to_string(path)?))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["toml"]
+    }
+
+    fn name(&self) -> &'static str {
+        "toml"
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Yaml,
+    Toml,
+}
+
+/// 值部分的着色，记录 YAML 锚点所在的行
+struct Scanner {
+    syntax: Syntax,
+    anchors: HashMap<String, usize>,
+}
+
+impl Scanner {
+    fn new(syntax: Syntax) -> Self {
+        Self {
+            syntax,
+            anchors: HashMap::new(),
+        }
+    }
+
+    fn value(&mut self, text: &str, line: usize, spans: &mut Vec<TextSpan>) {
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            let (len, style) = if c.is_whitespace() {
+                (rest.len() - rest.trim_start().len(), TextStyle::NONE)
+            } else if c == '#' {
+                (rest.len(), TextStyle::DIM)
+            } else if c == '"' || c == '\'' {
+                (quoted_len(rest, c, self.syntax), TextStyle::STRING)
+            } else if matches!(c, '[' | ']' | '{' | '}' | ',') {
+                (1, TextStyle::NONE)
+            } else if self.syntax == Syntax::Yaml && matches!(c, '&' | '*') && word_len(rest) > 1 {
+                let len = word_len(rest);
+                let name = &rest[1..len];
+                spans.push(TextSpan::new(&rest[..len], TextStyle::LINK));
+                if c == '&' {
+                    self.anchors.insert(name.to_string(), line);
+                } else if let Some(at) = self.anchors.get(name) {
+                    spans.push(TextSpan::new(t!("（第 {} 行）", at + 1), TextStyle::DIM));
+                }
+                rest = &rest[len..];
+                continue;
+            } else if self.syntax == Syntax::Yaml && c == '!' {
+                (word_len(rest), TextStyle::DIM)
+            } else {
+                let len = word_len(rest);
+                (len, self.classify(&rest[..len]))
+            };
+            spans.push(TextSpan::new(&rest[..len], style));
+            rest = &rest[len..];
+        }
+    }
+
+    fn classify(&self, word: &str) -> TextStyle {
+        let constants: &[&str] = match self.syntax {
+            Syntax::Yaml => &[
+                "true", "false", "True", "False", "TRUE", "FALSE", "yes", "no", "on", "off", "null",
+                "Null", "NULL", "~",
+            ],
+            Syntax::Toml => &["true", "false"],
+        };
+        if constants.contains(&word) {
+            TextStyle::CONSTANT
+        } else if is_number(word) {
+            TextStyle::NUMBER
+        } else if self.syntax == Syntax::Yaml {
+            TextStyle::STRING
+        } else {
+            TextStyle::NONE
+        }
+    }
+}
+
+/// 解析 YAML 文本
+pub fn parse_yaml(content: &str) -> Document {
+    let mut doc = Document::default();
+    let mut scanner = Scanner::new(Syntax::Yaml);
+    // 祖先行的缩进
+    let mut parents: Vec<usize> = Vec::new();
+    // 块标量（`|`、`>`）所属行的缩进
+    let mut block: Option<usize> = None;
+    // 各行的缩进，空行与注释为 None，用于计算折叠区域
+    let mut levels = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let body = &line[indent..];
+        let mut spans = Vec::new();
+
+        if let Some(owner) = block {
+            if body.is_empty() || indent > owner {
+                push_guides(&mut spans, &parents, indent);
+                spans.push(TextSpan::new(body, TextStyle::STRING));
+                levels.push((!body.is_empty()).then_some(indent));
+                doc.lines.push(RenderLine { spans, indent: 0 });
+                continue;
+            }
+            block = None;
+        }
+        if body.trim().is_empty() {
+            levels.push(None);
+            doc.lines.push(RenderLine::default());
+            continue;
+        }
+
+        // 注释不影响嵌套关系
+        let is_comment = body.starts_with('#');
+        if !is_comment {
+            while parents.last().is_some_and(|&p| p >= indent) {
+                parents.pop();
+            }
+        }
+        push_guides(&mut spans, &parents, indent);
+
+        let is_marker = indent == 0
+            && (body == "---" || body.starts_with("--- ") || body == "..." || body.starts_with('%'));
+        if is_comment || is_marker {
+            spans.push(TextSpan::new(body, TextStyle::DIM));
+            levels.push(None);
+        } else {
+            // 序列标记（可能有多个，如 `- - a`）
+            let mut rest = body;
+            loop {
+                if rest == "-" {
+                    spans.push(TextSpan::new("-", TextStyle::NONE));
+                    rest = "";
+                } else if let Some(after) = rest.strip_prefix("- ") {
+                    let item = after.trim_start();
+                    spans.push(TextSpan::new(&rest[..rest.len() - item.len()], TextStyle::NONE));
+                    rest = item;
+                    continue;
+                }
+                break;
+            }
+            let value = match key_end(rest) {
+                Some(end) => {
+                    spans.push(TextSpan::new(&rest[..end], TextStyle::KEY));
+                    spans.push(TextSpan::new(":", TextStyle::NONE));
+                    &rest[end + 1..]
+                }
+                None => rest,
+            };
+            scanner.value(value, i, &mut spans);
+            if is_block_indicator(value) {
+                block = Some(indent);
+            }
+            parents.push(indent);
+            levels.push(Some(indent));
+        }
+        doc.lines.push(RenderLine { spans, indent: 0 });
+    }
+
+    doc.folds = indent_folds(&levels);
+    doc
+}
+
+/// 解析 TOML 文本
+pub fn parse_toml(content: &str) -> Document {
+    let mut doc = Document::default();
+    let mut scanner = Scanner::new(Syntax::Toml);
+    // 当前表的层级（`[a.b]` 为 2）
+    let mut depth = 0;
+    // 未闭合的多行字符串的引号
+    let mut multiline: Option<&str> = None;
+    // 跨行数组中未闭合的括号数
+    let mut brackets = 0;
+
+    for (i, line) in content.lines().enumerate() {
+        let body = line.trim_start();
+        let mut spans = Vec::new();
+
+        if let Some(quote) = multiline {
+            spans.push(guides(depth));
+            match line.find(quote) {
+                Some(end) => {
+                    let end = end + quote.len();
+                    spans.push(TextSpan::new(&line[..end], TextStyle::STRING));
+                    scanner.value(&line[end..], i, &mut spans);
+                    multiline = None;
+                }
+                None => spans.push(TextSpan::new(line, TextStyle::STRING)),
+            }
+        } else if body.is_empty() {
+        } else if brackets > 0 {
+            spans.push(guides(depth));
+            let start = spans.len();
+            scanner.value(body, i, &mut spans);
+            brackets += bracket_delta(&spans[start..]);
+        } else if body.starts_with('#') {
+            spans.push(guides(depth));
+            spans.push(TextSpan::new(body, TextStyle::DIM));
+        } else if body.starts_with('[') {
+            let (header, comment) = match body.find(']').and_then(|i| body[i..].find('#').map(|c| i + c)) {
+                Some(at) => body.split_at(at),
+                None => (body, ""),
+            };
+            let name = header.trim().trim_matches(['[', ']']).trim();
+            depth = name.split('.').count();
+            spans.push(guides(depth - 1));
+            spans.push(TextSpan::new(header, TextStyle::BOLD | TextStyle::HEADING));
+            if !comment.is_empty() {
+                spans.push(TextSpan::new(comment, TextStyle::DIM));
+            }
+            doc.headings.push(Heading {
+                level: depth.min(6) as u8,
+                line: i,
+                text: name.to_string(),
+            });
+        } else {
+            spans.push(guides(depth));
+            let value = match toml_key_end(body) {
+                Some(end) => {
+                    spans.push(TextSpan::new(&body[..end], TextStyle::KEY));
+                    spans.push(TextSpan::new("=", TextStyle::NONE));
+                    &body[end + 1..]
+                }
+                None => body,
+            };
+            let trimmed = value.trim_start();
+            let quote = ["\"\"\"", "'''"].into_iter().find(|q| trimmed.starts_with(q));
+            match quote {
+                Some(q) if !trimmed[3..].contains(q) => {
+                    spans.push(TextSpan::new(value, TextStyle::STRING));
+                    multiline = Some(q);
+                }
+                _ => {
+                    let start = spans.len();
+                    scanner.value(value, i, &mut spans);
+                    brackets = bracket_delta(&spans[start..]).max(0);
+                }
+            }
+        }
+        spans.retain(|s| !s.text.is_empty());
+        doc.lines.push(RenderLine { spans, indent: 0 });
+    }
+
+    // 每个表从表头折叠到下一个表头之前（不含末尾的空行）
+    let starts: Vec<usize> = doc.headings.iter().map(|h| h.line).collect();
+    for (k, &start) in starts.iter().enumerate() {
+        let mut end = starts.get(k + 1).copied().unwrap_or(doc.lines.len());
+        while end > start + 1 && doc.lines[end - 1].spans.is_empty() {
+            end -= 1;
+        }
+        if end > start + 1 {
+            doc.folds.push(Fold { start, end });
+        }
+    }
+    doc
+}
+
+/// 行首缩进，祖先所在的列画出缩进线
+fn push_guides(spans: &mut Vec<TextSpan>, parents: &[usize], indent: usize) {
+    if indent == 0 {
+        return;
+    }
+    let mut columns = vec![' '; indent];
+    for &p in parents.iter().filter(|&&p| p < indent) {
+        columns[p] = '│';
+    }
+    spans.push(TextSpan::new(columns.into_iter().collect::<String>(), TextStyle::DIM));
+}
+
+/// TOML 每层表一条缩进线
+fn guides(depth: usize) -> TextSpan {
+    TextSpan::new("│ ".repeat(depth), TextStyle::DIM)
+}
+
+/// 按缩进计算折叠区域：一行到其后第一个缩进不大于它的行之前
+fn indent_folds(levels: &[Option<usize>]) -> Vec<Fold> {
+    let mut folds = Vec::new();
+    for (start, level) in levels.iter().enumerate() {
+        let Some(level) = *level else {
+            continue;
+        };
+        let mut last = start;
+        for (i, other) in levels.iter().enumerate().skip(start + 1) {
+            match other {
+                Some(other) if *other <= level => break,
+                Some(_) => last = i,
+                None => {}
+            }
+        }
+        if last > start {
+            folds.push(Fold {
+                start,
+                end: last + 1,
+            });
+        }
+    }
+    folds
+}
+
+/// YAML 键结尾的冒号位置：冒号后为空白或行尾，且不在引号与注释中
+fn key_end(s: &str) -> Option<usize> {
+    if s.starts_with(['[', '{', '#', '&', '*', '!', '|', '>']) {
+        return None;
+    }
+    let start = match s.chars().next()? {
+        q @ ('"' | '\'') => quoted_len(s, q, Syntax::Yaml),
+        _ => 0,
+    };
+    let bytes = s.as_bytes();
+    for (i, c) in s[start..].char_indices() {
+        let i = i + start;
+        if c == '#' && i > 0 && bytes[i - 1] == b' ' {
+            return None;
+        }
+        if c == ':' && (i + 1 == s.len() || bytes[i + 1] == b' ') {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// TOML 键结尾的等号位置
+fn toml_key_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '=') => return Some(i),
+            (None, '#') => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 引号字符串的长度（含引号），未闭合时到行尾
+fn quoted_len(s: &str, quote: char, syntax: Syntax) -> usize {
+    let mut chars = s.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' && quote == '"' {
+            chars.next();
+        } else if c == quote {
+            // YAML 单引号字符串中 `''` 表示一个单引号
+            if quote == '\'' && syntax == Syntax::Yaml && chars.peek().is_some_and(|&(_, n)| n == '\'') {
+                chars.next();
+                continue;
+            }
+            return i + 1;
+        }
+    }
+    s.len()
+}
+
+/// 不带引号的值的长度
+fn word_len(s: &str) -> usize {
+    s.find(|c: char| c.is_whitespace() || matches!(c, ',' | '[' | ']' | '{' | '}'))
+        .unwrap_or(s.len())
+}
+
+/// 整数、浮点数（含 `inf`、`nan`）、进制前缀与日期时间
+fn is_number(word: &str) -> bool {
+    let digits = word.trim_start_matches(['+', '-']);
+    if matches!(digits, "inf" | "nan" | ".inf" | ".nan" | ".Inf" | ".NaN" | ".INF" | ".NAN") {
+        return true;
+    }
+    digits.starts_with(|c: char| c.is_ascii_digit())
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || matches!(c, '_' | '.' | ':' | '+' | '-' | 'x' | 'o' | 'T' | 'Z'))
+}
+
+/// 值是否为块标量的开始（`|`、`>`，可带缩进与截断标记）
+fn is_block_indicator(value: &str) -> bool {
+    let value = value.split(" #").next().unwrap_or_default();
+    let last = value.split_whitespace().last().unwrap_or_default();
+    last.starts_with(['|', '>']) && last[1..].chars().all(|c| matches!(c, '-' | '+' | '1'..='9'))
+}
+
+/// 数组括号的增减（不计字符串与注释中的括号）
+fn bracket_delta(spans: &[TextSpan]) -> i32 {
+    spans
+        .iter()
+        .filter(|s| s.style == TextStyle::NONE)
+        .map(|s| match s.text.as_str() {
+            "[" => 1,
+            "]" => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(doc: &Document) -> Vec<String> {
+        doc.lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect())
+            .collect()
+    }
+
+    fn style_of(doc: &Document, line: usize, text: &str) -> TextStyle {
+        doc.lines[line].spans.iter().find(|s| s.text == text).unwrap().style
+    }
+
+    #[test]
+    fn test_parse_yaml() {
+        let content = "# 配置\nbase: &base\n  host: \"a:b\"\n  port: 80\nlist:\n  - name: x\n    on: true\n  - 1.5\nother: *base\ntext: |\n  多行\n  文本\nend: ~\n";
+        let doc = parse_yaml(content);
+        assert_eq!(
+            texts(&doc),
+            [
+                "# 配置", "base: &base", "│ host: \"a:b\"", "│ port: 80", "list:", "│ - name: x",
+                "│ │ on: true", "│ - 1.5", "other: *base（第 2 行）", "text: |", "│ 多行", "│ 文本", "end: ~",
+            ]
+        );
+        assert_eq!(style_of(&doc, 2, "host"), TextStyle::KEY);
+        assert_eq!(style_of(&doc, 2, "\"a:b\""), TextStyle::STRING);
+        assert_eq!(style_of(&doc, 3, "80"), TextStyle::NUMBER);
+        assert_eq!(style_of(&doc, 6, "true"), TextStyle::CONSTANT);
+        assert_eq!(style_of(&doc, 8, "*base"), TextStyle::LINK);
+        assert_eq!(style_of(&doc, 10, "多行"), TextStyle::STRING);
+        assert_eq!(
+            doc.folds,
+            [
+                Fold { start: 1, end: 4 },
+                Fold { start: 4, end: 8 },
+                Fold { start: 5, end: 7 },
+                Fold { start: 9, end: 12 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_toml() {
+        let content = "title = \"x\" # 注释\n\n[server]\nports = [\n  80,\n  443,\n]\n\n[server.tls]\nkey = '''\n多行\n'''\n";
+        let doc = parse_toml(content);
+        assert_eq!(
+            texts(&doc),
+            [
+                "title = \"x\" # 注释", "", "[server]", "│ ports = [", "│ 80,", "│ 443,", "│ ]", "",
+                "│ [server.tls]", "│ │ key = '''", "│ │ 多行", "│ │ '''",
+            ]
+        );
+        assert_eq!(style_of(&doc, 0, "title "), TextStyle::KEY);
+        assert_eq!(style_of(&doc, 4, "80"), TextStyle::NUMBER);
+        let headings: Vec<(u8, usize)> = doc.headings.iter().map(|h| (h.level, h.line)).collect();
+        assert_eq!(headings, [(1, 2), (2, 8)]);
+        assert_eq!(doc.folds, [Fold { start: 2, end: 7 }, Fold { start: 8, end: 12 }]);
+    }
+}
@@ -0,0 +1,119 @@
+//! 表格排版：各格式解析出的单元格文本按列宽对齐为渲染行
+
+use crate::document::*;
+use pulldown_cmark::Alignment;
+use unicode_width::UnicodeWidthStr;
+
+/// 将表格追加到文档末尾并记录表格区域，第一行作为表头
+///
+/// `alignments` 为各列的对齐方式，缺省的列左对齐。
+pub fn push_table(doc: &mut Document, rows: &[Vec<String>], alignments: &[Alignment], indent: u16) {
+    let num_cols = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    if num_cols == 0 {
+        return;
+    }
+
+    // 计算每列最大显示宽度
+    let mut col_widths = vec![0usize; num_cols];
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            let w = UnicodeWidthStr::width(cell.as_str());
+            col_widths[i] = col_widths[i].max(w);
+        }
+    }
+
+    let start = doc.lines.len();
+    let table_width = col_widths.iter().sum::<usize>() + 2 * (num_cols - 1);
+
+    for (r, row) in rows.iter().enumerate() {
+        let is_header = r == 0;
+        let attrs = if is_header {
+            TextStyle::BOLD | TextStyle::HEADING
+        } else {
+            TextStyle::NONE
+        };
+
+        let mut line = RenderLine {
+            indent,
+            ..Default::default()
+        };
+
+        for (c, &width) in col_widths.iter().enumerate() {
+            if c > 0 {
+                line.spans.push(TextSpan::new("  ", TextStyle::NONE));
+            }
+            let cell_text = row.get(c).map(|s| s.as_str()).unwrap_or("");
+            let align = alignments.get(c).copied().unwrap_or(Alignment::None);
+            let padded = align_to_width(cell_text, width, align);
+            line.spans.push(TextSpan::new(padded, attrs));
+        }
+        doc.lines.push(line);
+
+        // 表头后插入分隔线
+        if is_header {
+            let mut sep = RenderLine {
+                indent,
+                ..Default::default()
+            };
+            for (c, &width) in col_widths.iter().enumerate() {
+                if c > 0 {
+                    sep.spans.push(TextSpan::new("  ", TextStyle::NONE));
+                }
+                let dash = "─".repeat(width);
+                sep.spans.push(TextSpan::new(dash, TextStyle::DIM));
+            }
+            doc.lines.push(sep);
+        }
+    }
+
+    doc.tables.push(TableBlock {
+        start,
+        end: doc.lines.len(),
+        width: table_width,
+    });
+}
+
+/// 按列对齐方式补齐到目标宽度（未指定时左对齐）
+fn align_to_width(text: &str, target: usize, align: Alignment) -> String {
+    let gap = target.saturating_sub(UnicodeWidthStr::width(text));
+    match align {
+        Alignment::Right => format!("{}{}", " ".repeat(gap), text),
+        Alignment::Center => {
+            let left = gap / 2;
+            format!("{}{}{}", " ".repeat(left), text, " ".repeat(gap - left))
+        }
+        Alignment::Left | Alignment::None => pad_to_width(text, target),
+    }
+}
+
+/// 在右侧补空格到目标显示宽度
+pub fn pad_to_width(text: &str, target: usize) -> String {
+    let current = UnicodeWidthStr::width(text);
+    if current >= target {
+        text.to_string()
+    } else {
+        format!("{}{}", text, " ".repeat(target - current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_table_alignment() {
+        let rows = vec![
+            vec!["名称".to_string(), "数量".to_string(), "备注".to_string()],
+            vec!["a".to_string(), "12".to_string(), "x".to_string()],
+        ];
+        let mut doc = Document::default();
+        push_table(&mut doc, &rows, &[Alignment::Left, Alignment::Right, Alignment::Center], 0);
+        let lines: Vec<String> = doc
+            .lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect())
+            .collect();
+        assert_eq!(lines, ["名称  数量  备注", "────  ────  ────", "a       12   x  "]);
+        assert_eq!(doc.tables[0].width, 16);
+    }
+}
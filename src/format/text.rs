@@ -1,14 +1,137 @@
 //! 纯文本格式处理模块
 //!
-//! 文本文件使用直接模式显示（不进入 TUI 预览）
+//! 文本文件使用直接模式显示（不进入 TUI 预览），
+//! 标准输入的内容则转换为文档在预览模式中显示。
 
-use std::fs;
-use std::io;
+use crate::config;
+use crate::document::*;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
 use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use unicode_width::UnicodeWidthChar;
 
-/// 直接在终端打印文本文件内容
-pub fn display(path: &Path) -> io::Result<()> {
-    let content = fs::read_to_string(path)?;
-    println!("{}", content);
-    Ok(())
-}
\ No newline at end of file
+/// 直接在终端打印文本文件内容，`lines` 为只打印的行范围（从 0 开始）
+///
+/// 按行流式读取，大文件也不会整体读入内存；下游关闭管道（如 `head`）时正常结束。
+pub fn display(path: &Path, lines: Option<Range<usize>>) -> io::Result<()> {
+    let reader = BufReader::new(File::open(path)?);
+    let range = lines.unwrap_or(0..usize::MAX);
+    let mut out = io::stdout().lock();
+    let result = reader
+        .lines()
+        .skip(range.start)
+        .take(range.len())
+        .try_for_each(|line| writeln!(out, "{}", line?));
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+/// 检查被跟踪文件是否增长的间隔
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 打印文本文件后持续输出追加的内容（类似 `tail -f`），文件被截断时从头输出
+///
+/// 只在写入失败时返回，下游关闭管道（如 `head`）时正常结束。
+pub fn follow(path: &Path, lines: Option<Range<usize>>) -> io::Result<()> {
+    let mut out = io::stdout();
+    let content = fs::read(path)?;
+    let mut offset = content.len() as u64;
+    let shown = match lines {
+        Some(range) => {
+            let text = String::from_utf8_lossy(&content);
+            let mut shown = String::new();
+            for line in text.lines().skip(range.start).take(range.len()) {
+                shown.push_str(line);
+                shown.push('\n');
+            }
+            shown.into_bytes()
+        }
+        None => content,
+    };
+    let mut result = out.write_all(&shown).and_then(|_| out.flush());
+    while result.is_ok() {
+        thread::sleep(FOLLOW_INTERVAL);
+        let len = fs::metadata(path)?.len();
+        if len < offset {
+            offset = 0;
+        }
+        if len == offset {
+            continue;
+        }
+        let mut appended = Vec::new();
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        file.take(len - offset).read_to_end(&mut appended)?;
+        offset += appended.len() as u64;
+        result = out.write_all(&appended).and_then(|_| out.flush());
+    }
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+/// 将纯文本（如管道输入）转换为文档，供预览模式显示
+pub fn to_document(content: &str) -> Document {
+    let mut doc = Document::default();
+    for line in content.lines() {
+        let text = clean_line(line);
+        let spans = if text.is_empty() {
+            Vec::new()
+        } else {
+            vec![TextSpan::new(text, TextStyle::NONE)]
+        };
+        doc.lines.push(RenderLine { spans, indent: 0 });
+    }
+    doc
+}
+
+/// 去除 ANSI 转义序列并将制表符展开为空格
+///
+/// git 等工具输出到分页器时会带颜色，预览模式按自己的样式重新着色。
+pub fn clean_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    let mut col = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI：ESC [ 参数... 结束字节
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC：ESC ] ... BEL 或 ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\t' => {
+                let tab_width = config::get().tab_width;
+                let n = tab_width - col % tab_width;
+                out.push_str(&" ".repeat(n));
+                col += n;
+            }
+            '\r' => {}
+            _ => {
+                out.push(c);
+                col += c.width().unwrap_or(0);
+            }
+        }
+    }
+    out
+}
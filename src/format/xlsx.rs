@@ -0,0 +1,165 @@
+//! Excel 工作簿解析
+//!
+//! 通过 calamine 读取 xlsx / xlsm / xlsb / xls / ods，每个工作表排版为对齐的表格，首行为表头，
+//! 数字列右对齐。工作表前插入分隔线并记录为章节，预览模式中用 `(` / `)` 切换工作表。
+
+use crate::document::*;
+use crate::format::{table, FileFormat, FormatError};
+use crate::t;
+use calamine::{open_workbook_auto, Data, Reader};
+use pulldown_cmark::Alignment;
+use std::path::Path;
+
+pub struct XlsxFormat;
+
+impl FileFormat for XlsxFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let mut workbook = open_workbook_auto(path).map_err(xlsx_error)?;
+        let mut sheets = Vec::new();
+        for name in workbook.sheet_names() {
+            let range = workbook.worksheet_range(&name).map_err(xlsx_error)?;
+            let rows: Vec<Vec<Data>> = range.rows().map(<[Data]>::to_vec).collect();
+            sheets.push((name, rows));
+        }
+        Ok(assemble(&sheets))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["xlsx", "xlsm", "xlsb", "xls", "ods"]
+    }
+
+    fn name(&self) -> &'static str {
+        "xlsx"
+    }
+}
+
+fn xlsx_error(e: impl std::fmt::Display) -> FormatError {
+    FormatError::Parse(t!("表格文件解析失败: {}", e))
+}
+
+/// 依次排版各工作表，每个工作表前插入分隔线
+fn assemble(sheets: &[(String, Vec<Vec<Data>>)]) -> Document {
+    let mut doc = Document::default();
+    let total = sheets.len();
+    for (i, (name, rows)) in sheets.iter().enumerate() {
+        if i > 0 {
+            doc.lines.push(RenderLine::default());
+        }
+        doc.sections.push(doc.lines.len());
+        doc.headings.push(Heading {
+            level: 1,
+            line: doc.lines.len(),
+            text: name.clone(),
+        });
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new(format!("── {}/{} {} ──", i + 1, total, name), TextStyle::DIM)],
+            indent: 0,
+        });
+        doc.lines.push(RenderLine::default());
+
+        if rows.iter().flatten().all(|c| *c == Data::Empty) {
+            doc.lines.push(RenderLine {
+                spans: vec![TextSpan::new(t!("（空工作表）"), TextStyle::DIM)],
+                indent: 0,
+            });
+            continue;
+        }
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        // 表头以外的非空单元格都是数字的列右对齐
+        let alignments: Vec<Alignment> = (0..columns)
+            .map(|c| {
+                let mut cells = rows.iter().skip(1).filter_map(|r| r.get(c)).filter(|d| **d != Data::Empty);
+                let numeric = cells.clone().next().is_some()
+                    && cells.all(|d| matches!(d, Data::Int(_) | Data::Float(_)));
+                if numeric { Alignment::Right } else { Alignment::Left }
+            })
+            .collect();
+        let text: Vec<Vec<String>> = rows.iter().map(|r| r.iter().map(cell_text).collect()).collect();
+        table::push_table(&mut doc, &text, &alignments, 0);
+    }
+    doc
+}
+
+/// 单元格的显示文本：整数值的浮点数不带小数，日期按 ISO 格式
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Float(f) => number(*f),
+        Data::Bool(b) => if *b { "TRUE" } else { "FALSE" }.into(),
+        Data::DateTime(dt) => excel_date(dt.as_f64()),
+        Data::String(s) => s.replace(['\r', '\n'], " "),
+        other => other.to_string(),
+    }
+}
+
+/// 最多保留 10 位小数并去掉末尾的 0
+fn number(f: f64) -> String {
+    if f.fract() == 0.0 && f.abs() < 1e15 {
+        return format!("{f:.0}");
+    }
+    let text = format!("{f:.10}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Excel 日期序列号（1900 日期系统，自 1899-12-30 起的天数）转为 `YYYY-MM-DD HH:MM:SS`，
+/// 整天时省略时间，小于一天时只显示时间
+fn excel_date(serial: f64) -> String {
+    let total = (serial * 86400.0).round() as i64;
+    let (days, secs) = (total.div_euclid(86400), total.rem_euclid(86400));
+    let time = format!("{:02}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60);
+    if days == 0 {
+        return time;
+    }
+    let (y, m, d) = civil_from_days(days - 25569);
+    let date = format!("{y:04}-{m:02}-{d:02}");
+    if secs == 0 { date } else { format!("{date} {time}") }
+}
+
+/// 距 1970-01-01 的天数转为公历日期
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(m <= 2), m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assemble() {
+        assert_eq!(number(3.0), "3");
+        assert_eq!(number(0.1 + 0.2), "0.3");
+        assert_eq!(excel_date(45292.0), "2024-01-01");
+        assert_eq!(excel_date(45292.5), "2024-01-01 12:00:00");
+        assert_eq!(excel_date(0.25), "06:00:00");
+
+        let sheets = vec![
+            (
+                "销售".to_string(),
+                vec![
+                    vec![Data::String("品名".into()), Data::String("数量".into())],
+                    vec![Data::String("苹果".into()), Data::Float(12.0)],
+                    vec![Data::String("梨".into()), Data::Int(3)],
+                ],
+            ),
+            ("空".to_string(), vec![]),
+        ];
+        let doc = assemble(&sheets);
+        let lines: Vec<String> = doc
+            .lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.text.as_str()).collect::<String>().trim_end().to_string())
+            .collect();
+        assert_eq!(doc.sections, [0, 7]);
+        assert_eq!(lines[0], "── 1/2 销售 ──");
+        assert_eq!(lines[2..6], ["品名  数量", "────  ────", "苹果    12", "梨       3"]);
+        assert_eq!(lines[9], "（空工作表）");
+        assert_eq!(doc.headings[1].text, "空");
+    }
+}
@@ -0,0 +1,208 @@
+//! XML 树形视图
+//!
+//! 解析后按元素嵌套重新缩进：标签名、属性名与属性值分别着色，注释与处理指令变暗。
+//! 只含一段短文本的元素排在一行内，多行文本逐行去除原有缩进。每个跨多行的元素记录为
+//! 可折叠区域，预览模式中用空格键折叠 / 展开。
+
+use crate::document::*;
+use crate::format::container::parse_xml;
+use crate::format::{FileFormat, FormatError};
+use crate::t;
+use roxmltree::{Node, NodeType};
+use std::path::Path;
+use unicode_width::UnicodeWidthStr;
+
+/// 每层嵌套的缩进
+const INDENT: u16 = 2;
+
+/// 与标签排在同一行的文本的最大宽度
+const MAX_INLINE_WIDTH: usize = 60;
+
+pub struct XmlFormat;
+
+impl FileFormat for XmlFormat {
+    fn parse(&self, path: &Path) -> Result<Document, FormatError> {
+        let content = std::fs::read_to_string(path)?;
+        parse_xml_tree(&content)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["xml", "xsd", "xsl", "xslt", "rss", "atom", "wsdl", "kml", "gpx", "xaml", "csproj"]
+    }
+
+    fn name(&self) -> &'static str {
+        "xml"
+    }
+}
+
+pub fn parse_xml_tree(content: &str) -> Result<Document, FormatError> {
+    let xml = parse_xml(content).map_err(|e| FormatError::Parse(t!("XML 解析失败: {}", e)))?;
+    let mut doc = Document::default();
+    // XML 声明不是文档树中的节点，从源文本中取出
+    let source = content.trim_start_matches('\u{feff}').trim_start();
+    if source.starts_with("<?xml")
+        && let Some(end) = source.find("?>")
+    {
+        doc.lines.push(RenderLine {
+            spans: vec![TextSpan::new(&source[..end + 2], TextStyle::DIM)],
+            indent: 0,
+        });
+    }
+    for child in xml.root().children() {
+        push_node(&mut doc, content, child, 0);
+    }
+    doc.folds.sort_by_key(|f| f.start);
+    Ok(doc)
+}
+
+fn push_node(doc: &mut Document, source: &str, node: Node, indent: u16) {
+    match node.node_type() {
+        NodeType::Element => push_element(doc, source, node, indent),
+        NodeType::Text => {
+            for line in node.text().unwrap_or("").lines().map(str::trim).filter(|l| !l.is_empty()) {
+                doc.lines.push(RenderLine {
+                    spans: vec![TextSpan::new(escape_text(line), TextStyle::NONE)],
+                    indent,
+                });
+            }
+        }
+        NodeType::Comment => {
+            let text = node.text().unwrap_or("").trim();
+            let lines: Vec<&str> = text.lines().map(str::trim).collect();
+            for (i, line) in lines.iter().enumerate() {
+                let mut text = if i == 0 { format!("<!-- {line}") } else { format!("     {line}") };
+                if i == lines.len() - 1 {
+                    text.push_str(" -->");
+                }
+                doc.lines.push(RenderLine {
+                    spans: vec![TextSpan::new(text, TextStyle::DIM)],
+                    indent,
+                });
+            }
+        }
+        NodeType::PI => {
+            let text = &source[node.range()];
+            doc.lines.push(RenderLine {
+                spans: vec![TextSpan::new(text, TextStyle::DIM)],
+                indent,
+            });
+        }
+        NodeType::Root => {}
+    }
+}
+
+fn push_element(doc: &mut Document, source: &str, node: Node, indent: u16) {
+    let name = qname(source, node);
+    let mut open = vec![TextSpan::new("<", TextStyle::DIM), TextSpan::new(name, TextStyle::KEY)];
+    // 本元素新声明的命名空间（`xml` 前缀是预定义的，不会出现在源文本中）
+    let inherited: Vec<_> = node.parent_element().map_or_else(Vec::new, |p| p.namespaces().collect());
+    for ns in node.namespaces().filter(|ns| ns.name() != Some("xml") && !inherited.contains(ns)) {
+        let attr = ns.name().map_or_else(|| "xmlns".to_string(), |prefix| format!("xmlns:{prefix}"));
+        push_attribute(&mut open, &attr, ns.uri());
+    }
+    for attr in node.attributes() {
+        push_attribute(&mut open, &source[attr.range_qname()], attr.value());
+    }
+
+    // 只含空白的文本不算子节点，没有子节点的元素自闭合
+    let children: Vec<Node> = node
+        .children()
+        .filter(|c| !c.is_text() || c.text().is_some_and(|t| !t.trim().is_empty()))
+        .collect();
+    let close = || vec![TextSpan::new(format!("</{name}>"), TextStyle::DIM)];
+    match children.as_slice() {
+        [] => {
+            open.push(TextSpan::new("/>", TextStyle::DIM));
+            doc.lines.push(RenderLine { spans: open, indent });
+        }
+        [text] if text.is_text() && is_inline(text.text().unwrap_or("")) => {
+            open.push(TextSpan::new(">", TextStyle::DIM));
+            open.push(TextSpan::new(escape_text(text.text().unwrap_or("").trim()), TextStyle::NONE));
+            open.extend(close());
+            doc.lines.push(RenderLine { spans: open, indent });
+        }
+        _ => {
+            let start = doc.lines.len();
+            open.push(TextSpan::new(">", TextStyle::DIM));
+            doc.lines.push(RenderLine { spans: open, indent });
+            for child in children {
+                push_node(doc, source, child, indent + INDENT);
+            }
+            doc.lines.push(RenderLine { spans: close(), indent });
+            doc.folds.push(Fold {
+                start,
+                end: doc.lines.len(),
+            });
+        }
+    }
+}
+
+fn push_attribute(spans: &mut Vec<TextSpan>, name: &str, value: &str) {
+    spans.push(TextSpan::new(format!(" {name}"), TextStyle::CONSTANT));
+    spans.push(TextSpan::new("=", TextStyle::DIM));
+    spans.push(TextSpan::new(format!("\"{}\"", escape_attribute(value)), TextStyle::STRING));
+}
+
+/// 源文本中的标签名，保留命名空间前缀
+fn qname<'a>(source: &'a str, node: Node) -> &'a str {
+    let tag = &source[node.range().start + 1..];
+    let end = tag.find(|c: char| c.is_whitespace() || c == '/' || c == '>').unwrap_or(tag.len());
+    &tag[..end]
+}
+
+fn is_inline(text: &str) -> bool {
+    let text = text.trim();
+    !text.contains('\n') && text.width() <= MAX_INLINE_WIDTH
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;")
+}
+
+fn escape_attribute(value: &str) -> String {
+    escape_text(value).replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xml_tree() {
+        let content = r#"<?xml version="1.0"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:g="urn:g"><title>A &amp; B</title>
+<!-- entries -->
+      <entry id="1"><g:tag   g:k='v"w'/>
+          first line
+              second line
+      </entry><empty></empty></feed>"#;
+        let doc = parse_xml_tree(content).unwrap();
+        let lines: Vec<String> = doc
+            .lines
+            .iter()
+            .map(|l| {
+                let text: String = l.spans.iter().map(|s| s.text.as_str()).collect();
+                format!("{}{}", " ".repeat(l.indent as usize), text)
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                r#"<?xml version="1.0"?>"#,
+                r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:g="urn:g">"#,
+                "  <title>A &amp; B</title>",
+                "  <!-- entries -->",
+                r#"  <entry id="1">"#,
+                r#"    <g:tag g:k="v&quot;w"/>"#,
+                "    first line",
+                "    second line",
+                "  </entry>",
+                "  <empty/>",
+                "</feed>",
+            ]
+        );
+        assert_eq!(doc.folds, [Fold { start: 1, end: 11 }, Fold { start: 4, end: 9 }]);
+        assert_eq!(doc.lines[2].spans[1].style, TextStyle::KEY);
+        assert!(parse_xml_tree("<a><b></a>").is_err());
+    }
+}
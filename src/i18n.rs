@@ -0,0 +1,350 @@
+//! 界面文字的多语言支持
+//!
+//! 源码中的中文界面文字即为消息键：[`t!`](crate::t) 按当前语言在消息目录中查找译文，
+//! 找不到时原样使用中文。参数以 `{}` 占位，按顺序替换。
+//!
+//! 语言由 `--lang` 或 `LC_ALL` / `LC_MESSAGES` / `LANG` 决定：`zh*`、`C`、`POSIX` 或未设置时为中文，
+//! 其他语言使用英文。调试日志不翻译。
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// 查找当前语言的译文，带参数时返回替换后的 `String`
+///
+/// ```
+/// use xcat::t;
+/// assert_eq!(t!("文件不存在"), "文件不存在");
+/// assert_eq!(t!("{} 分钟前", 5), "5 分钟前");
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::tr($key)
+    };
+    ($key:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format($key, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    Zh,
+    En,
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_ascii_lowercase();
+        if lower.starts_with("zh") {
+            Ok(Locale::Zh)
+        } else if lower.starts_with("en") {
+            Ok(Locale::En)
+        } else {
+            Err(crate::t!("无效的语言: {}（可选 zh、en）", s))
+        }
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// 设置界面语言，只在启动时调用一次
+pub fn init(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+/// 当前界面语言，未调用 [`init`] 时为中文
+pub fn current() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+/// 按 `--lang` 参数与环境变量确定界面语言
+pub fn detect(flag: Option<&str>) -> Locale {
+    if let Some(locale) = flag.and_then(|f| f.parse().ok()) {
+        return locale;
+    }
+    let env = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match env.as_deref() {
+        None | Some("C" | "POSIX") => Locale::Zh,
+        Some(value) if value.starts_with("C.") || value.starts_with("zh") => Locale::Zh,
+        Some(_) => Locale::En,
+    }
+}
+
+/// 查找译文
+pub fn tr(key: &'static str) -> &'static str {
+    let catalog = match current() {
+        Locale::Zh => return key,
+        Locale::En => EN,
+    };
+    catalog
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map_or(key, |(_, text)| text)
+}
+
+/// 查找译文并按顺序替换 `{}` 占位符
+pub fn format(key: &'static str, args: &[&dyn Display]) -> String {
+    let template = tr(key);
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() {
+        out.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+/// 英文消息目录
+static EN: &[(&str, &str)] = &[
+    // 命令行与启动
+    ("缺少要预览的文件", "no file to preview"),
+    ("日志写入 {}", "writing log to {}"),
+    ("无效的语言: {}（可选 zh、en）", "invalid language: {} (expected zh or en)"),
+    ("配置文件无效，使用默认配置 - {} - {}", "invalid config file, using defaults - {} - {}"),
+    ("无法打开日志文件 {} - {}", "cannot open log file {} - {}"),
+    ("无法保存会话 {} - {}", "cannot save session {} - {}"),
+    ("警告: {}", "warning: {}"),
+    ("错误: {}", "error: {}"),
+    ("错误: {} - {}", "error: {} - {}"),
+    ("读取标准输入失败: {}", "failed to read standard input: {}"),
+    ("导出失败: {}", "export failed: {}"),
+    ("无效的颜色选项: {}（可选 auto、always、never）", "invalid color option: {} (expected auto, always or never)"),
+    ("无效的行范围: {}（如 100:200、100:、:200）", "invalid line range: {} (e.g. 100:200, 100: or :200)"),
+    ("无效的格式: {}（可选 md、pdf、txt、image、hex、pb）", "invalid format: {} (expected md, pdf, txt, image, hex or pb)"),
+    ("描述符集路径为空", "descriptor set path is empty"),
+    ("无效的链接显示方式: {}（可选 inline、ref、osc8）", "invalid link style: {} (expected inline, ref or osc8)"),
+    ("无效的导出格式: {}（可选 html、ansi）", "invalid export format: {} (expected html or ansi)"),
+    ("输出文件与输入相同: {}", "output file is the same as an input: {}"),
+    // 打开文件
+    ("文件不存在", "file not found"),
+    ("不是普通文件", "not a regular file"),
+    ("不支持的文件格式", "unsupported file format"),
+    ("文件为空或无法解析", "file is empty or could not be parsed"),
+    ("无法在预览模式中显示", "cannot be shown in preview mode"),
+    ("文件过大（{} MiB，上限 {} MiB），使用 --force 打开", "file too large ({} MiB, limit {} MiB); use --force to open it"),
+    ("{} 较大（{} MiB，上限 {} MiB），仍要打开？[y/N] ", "{} is large ({} MiB, limit {} MiB). Open anyway? [y/N] "),
+    ("IO错误: {}", "I/O error: {}"),
+    ("解析错误: {}", "parse error: {}"),
+    ("PDF 解析失败: {}", "failed to parse PDF: {}"),
+    ("无效的 PDF 后端: {}（可选 auto、builtin、poppler）", "invalid PDF backend: {} (expected auto, builtin or poppler)"),
+    ("按页显示图片需要安装 poppler（pdftoppm）", "showing pages as images requires poppler (pdftoppm)"),
+    ("页面渲染失败: {}", "failed to render page: {}"),
+    ("只有 PDF 可以按页显示为图片", "only PDFs can be shown as page images"),
+    ("--ocr 需要以 `ocr` 特性编译", "--ocr requires building with the `ocr` feature"),
+    ("未启用 poppler 后端（需要以 `--features poppler` 编译）", "the poppler backend is not enabled (build with `--features poppler`)"),
+    ("无法运行 pdftotext（需要安装 poppler）: {}", "cannot run pdftotext (poppler needs to be installed): {}"),
+    ("pdftotext 退出码 {}", "pdftotext exited with code {}"),
+    ("加载中… 第 {}/{} 页", "loading… page {}/{}"),
+    ("DOCX 解析失败: {}", "failed to parse DOCX: {}"),
+    ("ODF 解析失败: {}", "failed to parse ODF: {}"),
+    ("附件", "Attachment"),
+    ("类型", "Type"),
+    ("（未命名）", "(unnamed)"),
+    ("iCalendar 解析失败: {}", "failed to parse iCalendar: {}"),
+    ("（没有日程）", "(no events)"),
+    ("（无标题）", "(untitled)"),
+    ("{} 解析失败: 第 {} 字节处数据无效", "failed to parse {}: invalid data at byte {}"),
+    ("0x{}…（{} 字节）", "0x{}… ({} bytes)"),
+    ("SVG 解析失败: {}", "failed to parse SVG: {}"),
+    ("尺寸", "Dimensions"),
+    ("格式", "Format"),
+    ("颜色", "Color"),
+    ("拍摄时间", "Taken"),
+    ("相机", "Camera"),
+    ("音频解析失败: {}", "failed to parse audio: {}"),
+    ("标题", "Title"),
+    ("作者", "Author"),
+    ("主题", "Subject"),
+    ("创建日期", "Created"),
+    ("页数", "Pages"),
+    ("PDF 版本", "PDF version"),
+    ("艺术家", "Artist"),
+    ("专辑", "Album"),
+    ("专辑艺术家", "Album artist"),
+    ("日期", "Date"),
+    ("音轨", "Track"),
+    ("流派", "Genre"),
+    ("时长", "Duration"),
+    ("码率", "Bitrate"),
+    ("编码", "Codec"),
+    ("{} 声道", "{} channels"),
+    ("[封面]", "[cover]"),
+    ("封面", "cover"),
+    ("可执行文件解析失败: {}", "failed to parse executable: {}"),
+    ("不是 ELF、PE 或 Mach-O 文件", "not an ELF, PE or Mach-O file"),
+    ("Mach-O 多架构（{}）", "Mach-O universal ({})"),
+    ("目标文件", "object file"),
+    ("共享库", "shared library"),
+    ("可执行文件", "executable"),
+    ("核心转储", "core dump"),
+    ("动态链接库", "DLL"),
+    ("可加载模块", "bundle"),
+    ("架构", "Architecture"),
+    ("字节序", "Byte order"),
+    ("小端", "little endian"),
+    ("大端", "big endian"),
+    ("入口地址", "Entry point"),
+    ("解释器", "Interpreter"),
+    ("符号数", "Symbols"),
+    ("节（{}）", "Sections ({})"),
+    ("名称", "Name"),
+    ("地址", "Address"),
+    ("动态库（{}）", "Dynamic libraries ({})"),
+    ("DOT 解析失败: 第 {} 行附近语法错误", "failed to parse DOT: syntax error near line {}"),
+    ("（空图）", "(empty graph)"),
+    ("XML 解析失败: {}", "failed to parse XML: {}"),
+    ("无法读取描述符集 {}: {}", "cannot read descriptor set {}: {}"),
+    ("描述符集 {} 无效", "invalid descriptor set {}"),
+    ("描述符集 {} 中没有消息类型", "descriptor set {} contains no message types"),
+    ("描述符集中没有消息类型 {}", "message type {} not found in the descriptor set"),
+    ("Protobuf 数据无效或与消息类型不符", "invalid protobuf data or wrong message type"),
+    ("全天", "all day"),
+    ("周一", "Mon"),
+    ("周二", "Tue"),
+    ("周三", "Wed"),
+    ("周四", "Thu"),
+    ("周五", "Fri"),
+    ("周六", "Sat"),
+    ("周日", "Sun"),
+    ("EPUB 解析失败: {}", "failed to parse EPUB: {}"),
+    ("JSON 解析失败: {}", "failed to parse JSON: {}"),
+    ("压缩包读取失败: {}", "failed to read archive: {}"),
+    ("表格文件解析失败: {}", "failed to parse spreadsheet: {}"),
+    ("Notebook 解析失败: {}", "failed to parse notebook: {}"),
+    ("图片显示失败: {}", "failed to display image: {}"),
+    ("(剪贴板)", "(clipboard)"),
+    ("无法访问剪贴板: {}", "cannot access the clipboard: {}"),
+    ("剪贴板中没有文本或图片", "the clipboard contains no text or image"),
+    ("无法保存剪贴板图片: {}", "cannot save the clipboard image: {}"),
+    ("无法确定会话目录", "cannot determine the session directory"),
+    // --type
+    ("{}: 目录", "{}: directory"),
+    ("{}: {}（{}），{}", "{}: {} ({}), {}"),
+    ("指定格式", "forced"),
+    ("文件签名 {}", "{} signature"),
+    ("扩展名 .{}", "extension .{}"),
+    ("二进制内容", "binary content"),
+    ("内容为 diff", "diff content"),
+    ("无法识别，按纯文本处理", "unrecognised, treated as text"),
+    // 远程文件
+    ("无法访问 {} - {}", "cannot access {} - {}"),
+    ("无法获取远程文件大小", "cannot determine the remote file size"),
+    ("读取 {} 失败", "failed to read {}"),
+    ("下载 {} 失败", "failed to download {}"),
+    ("下载 {} {}%", "downloading {} {}%"),
+    ("下载 {} {} KiB", "downloading {} {} KiB"),
+    ("远程文件过大（{} MiB，上限 {} MiB）", "remote file too large ({} MiB, limit {} MiB)"),
+    ("解压后的文件过大（上限 {} MiB）", "decompressed file too large (limit {} MiB)"),
+    ("不是压缩文件", "not a compressed file"),
+    ("解压此文件需要安装 {}", "{} needs to be installed to decompress this file"),
+    ("解压失败: {}", "decompression failed: {}"),
+    ("LZMA 数据损坏", "corrupt LZMA data"),
+    ("7z 文件头无效", "invalid 7z header"),
+    ("不支持的 7z 压缩方法: {}", "unsupported 7z compression method: {}"),
+    // 配置文件
+    ("tab_width 必须大于 0", "tab_width must be greater than 0"),
+    ("无效的大小: {}（如 500K、20M、1G）", "invalid size: {} (e.g. 500K, 20M or 1G)"),
+    ("无效的颜色: {}", "invalid color: {}"),
+    ("未知的主题颜色: {}", "unknown theme color: {}"),
+    ("无效的换行方式: {}（可选 clip、wrap）", "invalid wrap mode: {} (expected clip or wrap)"),
+    ("未知的操作: {}", "unknown action: {}"),
+    ("无效的按键: {}", "invalid key: {}"),
+    // 预览模式状态栏
+    ("文件已更新", "file updated"),
+    ("新增 {} 行", "{} new lines"),
+    ("重新加载失败: {}", "reload failed: {}"),
+    ("无法打开 {}: {}", "cannot open {}: {}"),
+    ("已打开 {}", "opened {}"),
+    ("不支持打开文件", "opening files is not supported"),
+    ("光标不在代码块内", "the cursor is not in a code block"),
+    ("光标处没有图片", "there is no image at the cursor"),
+    ("文档中没有标题", "the document has no headings"),
+    ("找不到标题: #{}", "no heading for #{}"),
+    ("没有可以返回的文档", "nothing to go back to"),
+    ("源文件第 {} 行", "source line {}"),
+    ("第 {}/{} 页", "page {}/{}"),
+    ("用法: :page <页码>", "usage: :page <number>"),
+    ("未知命令: {}", "unknown command: {}"),
+    ("文档没有分页", "the document has no pages"),
+    ("没有第 {} 页（共 {} 页）", "no page {} ({} pages)"),
+    ("第 {} 页尚未加载", "page {} is still loading"),
+    ("没有搜索词", "no search pattern"),
+    ("找不到: {}", "pattern not found: {}"),
+    ("已到末尾，从头继续搜索", "search hit bottom, continuing at top"),
+    ("已到开头，从末尾继续搜索", "search hit top, continuing at bottom"),
+    ("匹配 {}/{}", "match {}/{}"),
+    ("{} 处匹配", "{} matches"),
+    ("没有可编辑的源文件", "no source file to edit"),
+    ("无法启动编辑器 {}: {}", "cannot start editor {}: {}"),
+    ("无法读取图片信息: {}", "cannot read image info: {}"),
+    ("已复制代码块（{} 行）", "copied code block ({} lines)"),
+    ("复制失败: {}", "copy failed: {}"),
+    ("已保存代码块到 {}", "saved code block to {}"),
+    ("保存失败: {}", "save failed: {}"),
+    // 文档内容
+    ("[图片: {}]", "[image: {}]"),
+    ("── 第 {} 页 ──", "── page {} ──"),
+    ("第 {} 页提取失败", "failed to extract page {}"),
+    ("备注", "Note"),
+    ("提示", "Tip"),
+    ("重要", "Important"),
+    ("警告", "Warning"),
+    ("注意", "Caution"),
+    ("按名称排序", "sorted by name"),
+    ("按大小排序", "sorted by size"),
+    ("按修改时间排序", "sorted by modification time"),
+    ("（空目录）", "(empty directory)"),
+    ("{} 个文件", "{} files"),
+    ("（空压缩包）", "(empty archive)"),
+    ("（空工作表）", "(empty sheet)"),
+    ("路径", "Path"),
+    ("大小", "Size"),
+    ("压缩后", "Packed"),
+    ("修改时间", "Modified"),
+    (" … （{} 行）", " … ({} lines)"),
+    ("（第 {} 行）", " (line {})"),
+    ("刚刚", "just now"),
+    ("{} 分钟前", "{} min ago"),
+    ("{} 小时前", "{} hours ago"),
+    ("{} 天前", "{} days ago"),
+    ("{} 个月前", "{} months ago"),
+    ("{} 年前", "{} years ago"),
+    // --time
+    ("格式检测: {}", "detection:    {}"),
+    ("解析:     {}", "parsing:      {}"),
+    ("首次渲染: {}", "first render: {}"),
+    ("内存峰值: {} MiB", "peak memory:  {} MiB"),
+    ("内存峰值: 未知", "peak memory:  unknown"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog() {
+        for (i, (key, text)) in EN.iter().enumerate() {
+            assert!(EN[..i].iter().all(|(k, _)| k != key), "重复的消息键: {key}");
+            assert_eq!(key.matches("{}").count(), text.matches("{}").count(), "{key}");
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format("已复制代码块（{} 行）", &[&3]), "已复制代码块（3 行）");
+        assert_eq!(format("无法打开 {}: {}", &[&"a.md", &"x"]), "无法打开 a.md: x");
+    }
+}
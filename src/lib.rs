@@ -0,0 +1,12 @@
+//! xcat 的文档模型、格式解析与渲染
+//!
+//! 命令行程序之外，也可以直接解析文档并用 [`snapshot`] 离屏渲染为字符串，
+//! 用于快照测试与性能基准。
+
+pub mod config;
+pub mod document;
+pub mod export;
+pub mod format;
+pub mod i18n;
+pub mod renderer;
+pub mod snapshot;
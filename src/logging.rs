@@ -0,0 +1,46 @@
+//! 调试日志
+//!
+//! 通过 `--verbose` 或环境变量 `XCAT_LOG` 开启，日志写入文件而不是终端，
+//! 避免破坏预览模式的画面。`XCAT_LOG` 使用 tracing 的过滤语法，
+//! 例如 `XCAT_LOG=debug` 或 `XCAT_LOG=xcat::format=trace`。
+//! 日志文件默认为临时目录下的 `xcat.log`，可用 `XCAT_LOG_FILE` 指定。
+
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing_subscriber::EnvFilter;
+use xcat::t;
+
+/// 日志文件路径
+pub fn log_path() -> PathBuf {
+    std::env::var_os("XCAT_LOG_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("xcat.log"))
+}
+
+/// 初始化日志，返回日志文件路径；未开启时返回 None
+pub fn init(verbose: bool) -> Option<PathBuf> {
+    let filter = match std::env::var("XCAT_LOG") {
+        Ok(spec) if !spec.is_empty() => EnvFilter::new(spec),
+        _ if verbose => EnvFilter::new("debug"),
+        _ => return None,
+    };
+
+    let path = log_path();
+    let file = match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(file) => file,
+        Err(e) => {
+            crate::report::warn(&t!("无法打开日志文件 {} - {}", path.display(), e));
+            return None;
+        }
+    };
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .init();
+
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "xcat 启动");
+    Some(path)
+}
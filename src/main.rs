@@ -1,81 +1,737 @@
-mod document;
-mod format;
-mod renderer;
+mod cli;
+mod clipboard;
+mod expand;
+mod logging;
+mod remote;
+mod report;
+mod session;
+mod stats;
 mod viewer;
 
-use format::FormatKind;
-use std::path::Path;
+use xcat::{config, document, format, i18n, renderer, t};
+
+use clap::{FromArgMatches, error::ErrorKind};
+use cli::{Cli, ColorMode};
+use report::{Error, Failure, Mode};
+use viewer::Reload;
+
+use document::Document;
+use format::{FormatKind, FormatOptions};
+use xcat::export::{self, ExportFormat};
+use std::io::{self, IsTerminal, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info};
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    // 界面语言需在解析命令行前确定，错误与帮助信息才能使用对应语言
+    i18n::init(i18n::detect(cli::early_value("--lang").as_deref()));
+    let cli = match cli::command()
+        .try_get_matches()
+        .and_then(|matches| Cli::from_arg_matches(&matches))
+    {
+        Ok(cli) => cli,
+        Err(e) => {
+            // 参数无效时按预先扫描到的 `--quiet` / `--json-errors` 报告
+            report::init(if cli::early_flag("--json-errors") {
+                Mode::Json
+            } else if cli::early_flag("--quiet") || cli::early_flag("-q") {
+                Mode::Quiet
+            } else {
+                Mode::Text
+            });
+            report::usage(e);
+        }
+    };
+    report::init(match (cli.quiet, cli.json_errors) {
+        (_, true) => Mode::Json,
+        (true, _) => Mode::Quiet,
+        _ => Mode::Text,
+    });
+
+    // 配置文件提供默认值，命令行参数优先
+    let config_path = cli.config.clone().or_else(config::default_path);
+    let mut config = match &config_path {
+        Some(path) => config::Config::load(path).unwrap_or_else(|e| {
+            report::warn(&t!("配置文件无效，使用默认配置 - {} - {}", path.display(), e));
+            config::Config::default()
+        }),
+        None => config::Config::default(),
+    };
+    if let Some(width) = cli.tab_width {
+        config.tab_width = width as usize;
+    }
+    if let Some(links) = cli.links {
+        config.markdown.links = links;
+    }
+    config.markdown.number_headings |= cli.number_headings;
+    config.markdown.code_line_numbers |= cli.code_line_numbers;
+    config.markdown.commonmark |= cli.commonmark;
+    if let Some(size) = cli.max_size {
+        config.max_size = size;
+    }
 
-    if args.len() != 2 {
-        eprintln!("用法: {} <file>", args[0]);
-        process::exit(1);
+    let mut options = FormatOptions::default();
+    options.markdown.link_style = config.markdown.links;
+    options.markdown.code_line_numbers = config.markdown.code_line_numbers;
+    options.markdown.smart_punctuation = config.markdown.smart_punctuation;
+    if config.markdown.commonmark {
+        options.markdown.extensions = format::markdown::MarkdownExtensions::commonmark();
+        options.markdown.smart_punctuation = false;
     }
+    options.forced = if cli.hex { Some(format::ForcedFormat::Hex) } else { cli.format };
+    options.schema = cli.schema;
+    options.pdf.layout = cli.layout;
+    if cli.ocr && !cfg!(feature = "ocr") {
+        report::usage(cli::command().error(ErrorKind::ArgumentConflict, t!("--ocr 需要以 `ocr` 特性编译")));
+    }
+    options.pdf.ocr = cli.ocr;
+    options.pdf.backend = cli.pdf_backend.unwrap_or_default();
+
+    // `+N`：从第 N 行开始显示
+    let mut files = cli.files;
+    let start_line = take_start_line(&mut files);
+    let mut files = expand::expand(files, cli.recursive, &options);
 
-    let path = Path::new(&args[1]);
+    let export = match cli.export.as_slice() {
+        [format, output] => match format.parse::<ExportFormat>() {
+            Ok(format) => Some((format, (output != "-").then(|| PathBuf::from(output)))),
+            Err(e) => report::usage(cli::command().error(ErrorKind::InvalidValue, e)),
+        },
+        _ => None,
+    };
+
+    // 输出被重定向（如 `xcat notes.md | grep foo`）时不启动预览界面
+    let piped = !io::stdout().is_terminal();
+    let view = ViewOptions {
+        plain: cli.plain || piped,
+        info: cli.info,
+        styled: match cli.color {
+            ColorMode::Auto => !piped,
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        },
+        number_headings: config.markdown.number_headings,
+        slides: cli.slides,
+        watch: cli.watch,
+        follow: cli.follow,
+        session: cli.session,
+        lines: cli.lines.map(cli::LineRange::to_range),
+        max_size: (!cli.force && config.max_size > 0).then_some(config.max_size),
+        confirm_large: true,
+        start_line,
+        export,
+    };
 
+    if let Some(log) = logging::init(cli.verbose) {
+        eprintln!("{}", t!("日志写入 {}", log.display()));
+    }
+    debug!(?options, piped, "命令行参数解析完成");
+    // 恢复会话时沿用上次的配色方案
+    if let Some(name) = &view.session
+        && let Some(theme) = session::Session::load(name).theme
+    {
+        config.syntax.theme = theme;
+    }
+    debug!(path = ?config_path, ?config, "配置加载完成");
+    config::init(config);
+
+    if cli.clipboard {
+        preview_clipboard(&options, &view);
+        return;
+    }
+
+    // 只指定会话时，打开会话中记录的文件
+    if files.is_empty()
+        && let Some(name) = &view.session
+    {
+        let session = session::Session::load(name);
+        files = session
+            .buffers
+            .iter()
+            .map(|b| b.path.to_string_lossy().into_owned())
+            .collect();
+    }
+
+    if cli.show_type && !files.is_empty() {
+        process::exit(print_types(&files, &options));
+    }
+
+    // 未指定文件或为 "-" 时读取标准输入（如作为 git 的分页器）
+    if files.is_empty() || files == ["-"] {
+        if io::stdin().is_terminal() {
+            report::usage(
+                cli::command().error(ErrorKind::MissingRequiredArgument, t!("缺少要预览的文件")),
+            );
+        }
+        let parse_started = Instant::now();
+        let doc = read_stdin(cli.git_pager);
+        let mut timings = stats::Timings {
+            parse: Some(parse_started.elapsed()),
+            ..Default::default()
+        };
+        // 空输入（如没有改动的 git diff）直接退出
+        if !doc.lines.is_empty() {
+            let buffer = new_buffer(doc, "(stdin)".into(), None, &view);
+            timings.render = preview(vec![buffer], &options, &view);
+        }
+        if cli.time {
+            timings.report();
+        }
+        return;
+    }
+
+    // user@host:/path 与 URL：先下载到临时目录
+    let mut downloads = Vec::new();
+    let mut paths = Vec::new();
+    for file in &files {
+        let fetched = if let Some(url) = remote::parse_url(file) {
+            remote::fetch_url(url, remote::MAX_SIZE)
+        } else if let Some((host, remote_path)) = remote::parse_remote(file) {
+            remote::fetch(host, remote_path, remote::MAX_SIZE)
+        } else {
+            paths.push(PathBuf::from(file));
+            continue;
+        };
+        match fetched {
+            Ok(download) => {
+                paths.push(download.path.clone());
+                downloads.push(download);
+            }
+            Err(e) => {
+                error!(file, "远程文件获取失败: {e}");
+                report::fail(Error::new(Failure::Other, e.to_string()), None);
+            }
+        }
+    }
+
+    let single = paths.len() == 1;
+    let mut timings = stats::Timings::default();
+    let mut buffers = Vec::new();
+    let mut failure = None;
+    for path in &paths {
+        match open(path, &options, &view, single, &mut timings) {
+            Ok(Opened::Preview(buffer)) => buffers.push(*buffer),
+            Ok(Opened::Direct(kind, file)) => {
+                let render_started = Instant::now();
+                display_direct(&kind, &file, &view);
+                timings.render = Some(render_started.elapsed());
+            }
+            Err(e) => {
+                error!(path = %path.display(), "{e}");
+                // 打开多个文件时跳过出错的文件
+                if single {
+                    report::fail(e, Some(path));
+                }
+                report::error(&e, Some(path));
+                failure = Some(e.failure);
+            }
+        }
+    }
+
+    // 全部文件都无法打开时，以最后一个错误的退出码退出
+    let previewed = !buffers.is_empty();
+    if previewed {
+        timings.render = preview(buffers, &options, &view);
+    }
+    format::archive::remove_extracted();
+    if let Some(failure) = failure.filter(|_| !previewed) {
+        process::exit(failure.code());
+    }
+
+    if cli.time {
+        timings.report();
+    }
+}
+
+/// 文件的打开方式
+enum Opened {
+    /// 在预览模式中显示的文档
+    Preview(Box<viewer::Buffer>),
+    /// 直接输出到终端（单独打开的图片、纯文本），路径为解压后的文件
+    Direct(FormatKind, PathBuf),
+}
+
+/// 检测并解析文件，耗时累计到 `timings`
+///
+/// 打开多个文件时图片与纯文本也转换为文档，以便在预览模式中切换。
+fn open(
+    path: &Path,
+    options: &FormatOptions,
+    view: &ViewOptions,
+    single: bool,
+    timings: &mut stats::Timings,
+) -> Result<Opened, Error> {
+    // 压缩包列表中的一项：解压后按普通文件打开
+    if !path.exists()
+        && let Some((archive, member)) = format::archive::split_member(path)
+    {
+        let extracted = format::archive::extract(&archive, &member)?;
+        return open(&extracted, options, view, single, timings);
+    }
     if !path.exists() {
-        eprintln!("错误: 文件不存在 - {}", path.display());
-        process::exit(1);
+        return Err(Error::new(Failure::NotFound, t!("文件不存在")));
+    }
+
+    if path.is_dir() {
+        let started = Instant::now();
+        let doc = format::dir::parse_dir(path, options)?;
+        timings.parse = Some(timings.parse.unwrap_or_default() + started.elapsed());
+        let dir = path.to_path_buf();
+        let options = options.clone();
+        let reload: Reload = Box::new(move || format::dir::parse_dir(&dir, &options));
+        let source = Some((path, reload));
+        let buffer = new_buffer(doc, path.display().to_string(), source, view);
+        return Ok(Opened::Preview(Box::new(buffer)));
     }
 
     if !path.is_file() {
-        eprintln!("错误: 不是普通文件 - {}", path.display());
-        process::exit(1);
+        return Err(Error::new(Failure::Unsupported, t!("不是普通文件")));
     }
 
-    let format_kind = match format::detect_format(path) {
-        Some(k) => k,
-        None => {
-            eprintln!("错误: 不支持的文件格式 - {}", path.display());
-            process::exit(1);
+    let started = Instant::now();
+    let format_kind = format::detect_format(path, options)
+        .ok_or_else(|| Error::new(Failure::Unsupported, t!("不支持的文件格式")))?;
+    timings.detect += started.elapsed();
+    debug!(path = %path.display(), elapsed = ?started.elapsed(), "格式检测完成");
+
+    let filename = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    // 直接输出的纯文本按行流式读取，不受大小上限限制
+    let size = std::fs::metadata(path)?.len();
+    let parse_started = Instant::now();
+    let mut loader = None;
+    let (doc, reload): (Document, Option<Reload>) = match format_kind {
+        // 压缩流解压到临时文件后按内层格式打开
+        FormatKind::Compressed => {
+            let inner = format::compressed::decompress(path)?;
+            return open(&inner, options, view, single, timings);
+        }
+        // 指定起始行时需要滚动、导出时需要文档，在终端中跟踪时需要高亮新增的行，
+        // 这些情况下纯文本也进入预览模式
+        FormatKind::Image | FormatKind::Text
+            if single
+                && view.start_line.is_none()
+                && view.export.is_none()
+                && (!view.follow || view.plain) =>
+        {
+            return Ok(Opened::Direct(format_kind, path.to_path_buf()));
+        }
+        // 跟踪日志并输出到管道时与纯文本一样按行流式输出
+        FormatKind::Document(formatter)
+            if formatter.name() == "log"
+                && single
+                && view.start_line.is_none()
+                && view.export.is_none()
+                && view.follow
+                && view.plain =>
+        {
+            return Ok(Opened::Direct(FormatKind::Text, path.to_path_buf()));
+        }
+        _ if view.max_size.is_some_and(|max| size > max) && !confirm_large(path, size, view) => {
+            let limit = mib(view.max_size.unwrap_or(0));
+            let message = t!("文件过大（{} MiB，上限 {} MiB），使用 --force 打开", mib(size), limit);
+            return Err(Error::new(Failure::TooLarge, message));
+        }
+        FormatKind::Image => {
+            let rows = crossterm::terminal::size().map_or(23, |(_, rows)| rows.saturating_sub(1));
+            (format::image::to_document(path, rows), None)
+        }
+        FormatKind::Text => {
+            let content = std::fs::read_to_string(path)?;
+            let file = path.to_path_buf();
+            let reload: Reload = Box::new(move || {
+                Ok(format::text::to_document(&std::fs::read_to_string(&file)?))
+            });
+            (format::text::to_document(&content), Some(reload))
+        }
+        // 交互预览 PDF 时先显示第一页，其余页在后台提取；
+        // 标题编号与行范围需要完整文档，此时仍一次解析
+        FormatKind::Document(formatter)
+            if formatter.name() == "pdf"
+                && !view.plain
+                && view.export.is_none()
+                && view.lines.is_none()
+                && !view.number_headings =>
+        {
+            let (doc, pages) = format::pdf::parse_lazily(path, &options.pdf)?;
+            loader = Some(pages);
+            let file = path.to_path_buf();
+            (doc, Some(Box::new(move || formatter.parse(&file))))
+        }
+        FormatKind::Document(formatter) => {
+            let doc = formatter.parse(path)?;
+            let file = path.to_path_buf();
+            (doc, Some(Box::new(move || formatter.parse(&file))))
         }
     };
+    let elapsed = parse_started.elapsed();
+    timings.parse = Some(timings.parse.unwrap_or_default() + elapsed);
+    info!(
+        path = %path.display(),
+        lines = doc.lines.len(),
+        images = doc.images.len(),
+        ?elapsed,
+        "解析完成"
+    );
 
-    match format_kind {
-        FormatKind::Image => {
-            // 图片：直接模式
-            if let Err(e) = format::image::display(path) {
-                eprintln!("错误: {} - {}", e, path.display());
-                process::exit(1);
+    if doc.lines.is_empty() {
+        return Err(Error::new(Failure::Parse, t!("文件为空或无法解析")));
+    }
+    let source = reload.map(|reload| (path, reload));
+    let buffer = new_buffer(doc, filename, source, view);
+    Ok(Opened::Preview(Box::new(match loader {
+        Some(loader) => buffer.with_loader(loader),
+        None => buffer,
+    })))
+}
+
+/// `--type`：输出每个文件检测到的格式、检测依据与字符集，返回退出码
+fn print_types(files: &[String], options: &FormatOptions) -> i32 {
+    let mut code = 0;
+    for file in files {
+        let path = Path::new(file);
+        if path.is_dir() {
+            println!("{}", t!("{}: 目录", file));
+            continue;
+        }
+        let detected = if path.is_file() {
+            format::detect_format_with_reason(path, options)
+                .ok_or_else(|| Error::new(Failure::Unsupported, t!("不支持的文件格式")))
+                .and_then(|detected| Ok((detected, format::magic::detect_file_charset(path)?)))
+        } else if path.exists() {
+            Err(Error::new(Failure::Unsupported, t!("不是普通文件")))
+        } else {
+            Err(Error::new(Failure::NotFound, t!("文件不存在")))
+        };
+        match detected {
+            Ok(((kind, by), charset)) => {
+                let by = match by {
+                    format::DetectedBy::Forced => t!("指定格式").to_string(),
+                    format::DetectedBy::Magic(sig) => t!("文件签名 {}", sig.name()),
+                    format::DetectedBy::Extension(ext) => t!("扩展名 .{}", ext),
+                    format::DetectedBy::Binary => t!("二进制内容").to_string(),
+                    format::DetectedBy::Diff => t!("内容为 diff").to_string(),
+                    format::DetectedBy::Fallback => t!("无法识别，按纯文本处理").to_string(),
+                };
+                println!("{}", t!("{}: {}（{}），{}", file, kind.name(), by, charset.name()));
+            }
+            Err(e) => {
+                report::error(&e, Some(path));
+                code = e.failure.code();
             }
         }
-        FormatKind::Text => {
-            // 纯文本：直接模式
-            if let Err(e) = format::text::display(path) {
-                eprintln!("错误: {} - {}", e, path.display());
-                process::exit(1);
+    }
+    code
+}
+
+/// 文件超过大小上限时在终端中询问是否仍要打开
+fn confirm_large(path: &Path, size: u64, view: &ViewOptions) -> bool {
+    let interactive = io::stdin().is_terminal() && io::stderr().is_terminal();
+    if !view.confirm_large || report::silent() || !interactive {
+        return false;
+    }
+    let limit = mib(view.max_size.unwrap_or(0));
+    let question = t!("{} 较大（{} MiB，上限 {} MiB），仍要打开？[y/N] ", path.display(), mib(size), limit);
+    eprint!("{question}");
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+/// 字节数换算为 MiB，保留一位小数
+fn mib(bytes: u64) -> String {
+    format!("{:.1}", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// 直接模式：图片与纯文本直接输出到终端
+///
+/// `plain` 时不在终端中显示图片，与 `cat` 一样原样输出文件内容；`--info` 时只输出图片信息。
+fn display_direct(kind: &FormatKind, path: &Path, view: &ViewOptions) {
+    let result = match kind {
+        FormatKind::Image if view.info && view.plain => format::image::display_info(path).map_err(Error::from),
+        FormatKind::Image if view.plain => copy_to_stdout(path).map_err(Error::from),
+        FormatKind::Image => format::image::display(path)
+            .map_err(|e| Error::new(Failure::Terminal, e.to_string()))
+            .and_then(|()| match view.info {
+                true => format::image::display_info(path).map_err(Error::from),
+                false => Ok(()),
+            }),
+        _ if view.follow => format::text::follow(path, view.lines.clone()).map_err(Error::from),
+        _ => format::text::display(path, view.lines.clone()).map_err(Error::from),
+    };
+    if let Err(e) = result {
+        error!(path = %path.display(), "显示失败: {e}");
+        report::fail(e, Some(path));
+    }
+}
+
+/// 将文件原样写到标准输出
+fn copy_to_stdout(path: &Path) -> io::Result<()> {
+    let mut file = std::fs::File::open(path)?;
+    io::copy(&mut file, &mut io::stdout().lock())?;
+    Ok(())
+}
+
+/// 预览剪贴板内容：文本按 Markdown 渲染，图片直接显示
+fn preview_clipboard(options: &FormatOptions, view: &ViewOptions) {
+    match clipboard::read() {
+        Ok(clipboard::Content::Text(text)) => {
+            let doc = format::markdown::parse_markdown(&text, &options.markdown);
+            if !doc.lines.is_empty() {
+                let buffer = new_buffer(doc, t!("(剪贴板)").into(), None, view);
+                preview(vec![buffer], options, view);
             }
         }
-        FormatKind::Document(formatter) => {
-            // Markdown/PDF：预览模式
-            let doc = match formatter.parse(path) {
-                Ok(doc) => doc,
-                Err(e) => {
-                    eprintln!("错误: {} - {}", e, path.display());
-                    process::exit(1);
-                }
+        Ok(clipboard::Content::Image(path)) => {
+            let shown = if view.plain {
+                copy_to_stdout(&path).map_err(Error::from)
+            } else {
+                format::image::display(&path).map_err(|e| Error::new(Failure::Terminal, e.to_string()))
             };
-
-            if doc.lines.is_empty() {
-                eprintln!("错误: 文件为空或无法解析 - {}", path.display());
-                process::exit(1);
+            let _ = std::fs::remove_file(&path);
+            if let Err(e) = shown {
+                report::fail(e, None);
             }
+        }
+        Err(e) => {
+            error!("{e}");
+            report::fail(Error::new(Failure::Other, e), None);
+        }
+    }
+}
 
-            let filename = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string();
+/// 读取标准输入：git 输出按 diff 着色，其余作为纯文本
+fn read_stdin(git_pager: bool) -> Document {
+    let mut bytes = Vec::new();
+    if let Err(e) = io::stdin().read_to_end(&mut bytes) {
+        error!("读取标准输入失败: {e}");
+        report::fail(Error::new(Failure::Other, t!("读取标准输入失败: {}", e)), None);
+    }
+    let content = String::from_utf8_lossy(&bytes);
 
-            let mut viewer = viewer::Viewer::new(doc, filename);
-            if let Err(e) = viewer.run() {
-                eprintln!("错误: {}", e);
-                process::exit(1);
+    if git_pager || format::diff::looks_like_git(&content) {
+        debug!("标准输入按 git 输出处理");
+        format::diff::parse_diff(&content)
+    } else {
+        format::text::to_document(&content)
+    }
+}
+
+/// 预览模式选项
+#[derive(Clone, Default)]
+struct ViewOptions {
+    /// 直接输出到标准输出，不进入预览界面
+    plain: bool,
+    /// 显示图片后输出尺寸与 EXIF 信息（`--info`）
+    info: bool,
+    /// 直接输出时带 ANSI 样式
+    styled: bool,
+    number_headings: bool,
+    slides: bool,
+    watch: bool,
+    /// 跟踪文件末尾（`--follow`）
+    follow: bool,
+    /// 会话名，退出时保存阅读位置
+    session: Option<String>,
+    /// 只显示的行范围（`--lines`）
+    lines: Option<Range<usize>>,
+    /// 完整解析前需要确认的文件大小，None 为不限制（`--force`）
+    max_size: Option<u64>,
+    /// 超过大小上限时是否可以在终端中询问，否则直接报错
+    confirm_large: bool,
+    /// 起始行（`+N`），从 0 开始
+    start_line: Option<usize>,
+    /// 导出格式及输出文件（`None` 为标准输出）
+    export: Option<(ExportFormat, Option<PathBuf>)>,
+}
+
+/// 创建预览文档：按选项为标题编号、截取行范围，`source` 为来源文件及重新解析的方法
+fn new_buffer(
+    doc: Document,
+    filename: String,
+    source: Option<(&Path, Reload)>,
+    view: &ViewOptions,
+) -> viewer::Buffer {
+    let number_headings = view.number_headings;
+    let lines = view.lines.clone();
+    let prepare = move |mut doc: Document| {
+        // 先编号再截取，编号与完整文档一致
+        if number_headings {
+            doc.number_headings();
+        }
+        match &lines {
+            Some(range) => doc.slice(range.clone()),
+            None => doc,
+        }
+    };
+
+    let buffer = viewer::Buffer::new(prepare(doc), filename);
+    match source {
+        Some((path, reload)) => {
+            buffer.with_source(absolute(path), Box::new(move || reload().map(&prepare)))
+        }
+        None => buffer,
+    }
+}
+
+/// 进入预览模式，返回首帧绘制耗时
+fn preview(
+    mut buffers: Vec<viewer::Buffer>,
+    options: &FormatOptions,
+    view: &ViewOptions,
+) -> Option<Duration> {
+    if let Some((format, output)) = &view.export {
+        let started = Instant::now();
+        if let Err(e) = export_buffers(&buffers, *format, output.as_deref()) {
+            error!("导出失败: {e}");
+            report::fail(Error::new(Failure::Other, t!("导出失败: {}", e)), None);
+        }
+        return Some(started.elapsed());
+    }
+
+    if view.plain {
+        let started = Instant::now();
+        dump(&buffers, view.styled);
+        return Some(started.elapsed());
+    }
+
+    // 恢复会话中记录的阅读位置，标准输入等没有来源文件的文档不参与
+    if let Some(name) = &view.session {
+        let session = session::Session::load(name);
+        for buffer in &mut buffers {
+            let saved = buffer.path().and_then(|p| session.find(p));
+            if let Some(saved) = saved {
+                let (top_line, cursor_line) = (saved.top_line, saved.cursor_line);
+                buffer.set_position(top_line, cursor_line);
             }
         }
     }
+
+    if let (Some(line), Some(first)) = (view.start_line, buffers.first_mut()) {
+        first.set_position(line, line);
+    }
+
+    let mut viewer = viewer::Viewer::new(buffers);
+    // 目录预览中打开的文件同样经过格式检测与解析
+    // 预览界面中无法询问，超过大小上限的文件在状态栏提示
+    let opener_view = ViewOptions {
+        confirm_large: false,
+        ..view.clone()
+    };
+    let options = options.clone();
+    viewer.set_opener(Box::new(move |path, sort| {
+        let options = FormatOptions { sort, ..options.clone() };
+        let mut timings = stats::Timings::default();
+        match open(path, &options, &opener_view, false, &mut timings).map_err(|e| e.message)? {
+            Opened::Preview(buffer) => Ok(*buffer),
+            Opened::Direct(..) => Err(t!("无法在预览模式中显示").into()),
+        }
+    }));
+    if view.slides {
+        viewer.enable_slides();
+    }
+    if view.watch {
+        viewer.enable_watch();
+    }
+    if view.follow {
+        viewer.enable_follow();
+    }
+    if let Err(e) = viewer.run() {
+        error!("预览模式异常退出: {e}");
+        report::fail(Error::new(Failure::Terminal, e.to_string()), None);
+    }
+
+    if let Some(name) = &view.session {
+        let buffers = viewer
+            .buffers()
+            .iter()
+            .filter_map(|b| {
+                let (top_line, cursor_line) = b.position();
+                Some(session::Buffer {
+                    path: b.path()?.to_path_buf(),
+                    top_line,
+                    cursor_line,
+                })
+            })
+            .collect();
+        let session = session::Session {
+            theme: Some(config::get().syntax.theme.clone()),
+            buffers,
+        };
+        if let Err(e) = session.save(name) {
+            report::warn(&t!("无法保存会话 {} - {}", name, e));
+        }
+    }
+    viewer.first_render()
+}
+
+/// 将文档输出到标准输出，`styled` 时保留预览模式的样式
+fn dump(buffers: &[viewer::Buffer], styled: bool) {
+    let mut out = io::stdout().lock();
+    for buffer in buffers {
+        let doc = buffer.document();
+        let text = if styled {
+            export::to_ansi(&[doc])
+        } else {
+            xcat::snapshot::render_plain(doc, xcat::snapshot::fit_width(doc))
+        };
+        // 下游关闭管道（如 head）时静默退出
+        if out.write_all(text.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+/// 导出到文件或标准输出
+fn export_buffers(
+    buffers: &[viewer::Buffer],
+    format: ExportFormat,
+    output: Option<&Path>,
+) -> io::Result<()> {
+    // 防止 `--export html a.md b.md` 这类误用覆盖输入文件
+    if let Some(output) = output.and_then(|p| std::fs::canonicalize(p).ok())
+        && buffers.iter().any(|b| b.path() == Some(output.as_path()))
+    {
+        return Err(io::Error::other(t!("输出文件与输入相同: {}", output.display())));
+    }
+
+    let content = match format {
+        ExportFormat::Html => {
+            let docs: Vec<(&str, &Document)> =
+                buffers.iter().map(|b| (b.filename(), b.document())).collect();
+            export::to_html(&docs)
+        }
+        ExportFormat::Ansi => {
+            let docs: Vec<&Document> = buffers.iter().map(|b| b.document()).collect();
+            export::to_ansi(&docs)
+        }
+    };
+    match output {
+        Some(path) => std::fs::write(path, content),
+        None => io::stdout().lock().write_all(content.as_bytes()),
+    }
+}
+
+/// 取出 `+N` 参数，返回从 0 开始的起始行；同名文件存在时仍作为文件
+fn take_start_line(files: &mut Vec<String>) -> Option<usize> {
+    let index = files.iter().position(|f| {
+        let digits = f.strip_prefix('+').unwrap_or_default();
+        !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) && !Path::new(f).exists()
+    })?;
+    let line: usize = files.remove(index)[1..].parse().ok()?;
+    Some(line.saturating_sub(1))
+}
+
+/// 转换为绝对路径，便于会话在不同工作目录下恢复
+fn absolute(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
 }
@@ -0,0 +1,231 @@
+//! 远程文件（`user@host:/path` 与 `http(s)://` URL）
+//!
+//! 通过系统的 ssh / curl 命令下载到临时目录，再走正常的格式检测与预览流程。
+//! 认证沿用 ssh 自身的配置（密钥、agent、`~/.ssh/config`），代理等设置沿用 curl 的环境变量。
+
+use std::fs::{self, File};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use xcat::t;
+
+/// 远程文件大小上限
+pub const MAX_SIZE: u64 = 100 * 1024 * 1024;
+
+/// 解析 `[user@]host:/path` 形式的参数
+///
+/// 本地存在同名文件时不视为远程路径。
+pub fn parse_remote(arg: &str) -> Option<(&str, &str)> {
+    if Path::new(arg).exists() || parse_url(arg).is_some() {
+        return None;
+    }
+    let (host, path) = arg.split_once(':')?;
+    if host.is_empty() || path.is_empty() || host.contains('/') {
+        return None;
+    }
+    Some((host, path))
+}
+
+/// 识别 `http://` 与 `https://` URL
+pub fn parse_url(arg: &str) -> Option<&str> {
+    let lower = arg.to_ascii_lowercase();
+    (lower.starts_with("http://") || lower.starts_with("https://")).then_some(arg)
+}
+
+/// 下载到临时目录的远程文件，离开作用域时删除
+pub struct Download {
+    pub path: PathBuf,
+    dir: PathBuf,
+}
+
+impl Drop for Download {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// 通过 ssh 获取远程文件，超过 `max_size` 时拒绝下载
+pub fn fetch(host: &str, path: &str, max_size: u64) -> io::Result<Download> {
+    let quoted = shell_quote(path);
+
+    let output = Command::new("ssh")
+        .args([host, "--", &format!("wc -c < {quoted}")])
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(t!("无法访问 {} - {}", format!("{host}:{path}"), stderr.trim())));
+    }
+    let size: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .map_err(|_| io::Error::other(t!("无法获取远程文件大小")))?;
+    if size > max_size {
+        return Err(too_large(size, max_size));
+    }
+
+    // 保留原文件名，扩展名检测才能生效
+    let name = Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("remote");
+    let download = Download::new(name)?;
+
+    let mut command = Command::new("ssh");
+    command.args([host, "--", &format!("cat {quoted}")]);
+    let label = format!("{host}:{path}");
+    if !receive(command, &download.path, Some(size), max_size, &label)? {
+        return Err(io::Error::other(t!("读取 {} 失败", label)));
+    }
+    Ok(download)
+}
+
+/// 通过 curl 下载 URL，超过 `max_size` 时中止
+///
+/// 格式由下载后的文件内容（magic number）与 URL 中的扩展名确定。
+pub fn fetch_url(url: &str, max_size: u64) -> io::Result<Download> {
+    // 先用 HEAD 请求获取大小以显示进度，服务器不支持时只显示已下载的字节数
+    let size = Command::new("curl")
+        .args(["-sIL", "--max-time", "10", url])
+        .stdin(Stdio::null())
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| content_length(&String::from_utf8_lossy(&o.stdout)));
+    if let Some(size) = size.filter(|&s| s > max_size) {
+        return Err(too_large(size, max_size));
+    }
+
+    let download = Download::new(url_file_name(url))?;
+    let mut command = Command::new("curl");
+    command.args(["-fsSL", "--max-filesize", &max_size.to_string(), url]);
+    if !receive(command, &download.path, size, max_size, url)? {
+        return Err(io::Error::other(t!("下载 {} 失败", url)));
+    }
+    Ok(download)
+}
+
+impl Download {
+    /// 在独立的临时目录中准备下载文件
+    fn new(name: &str) -> io::Result<Download> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNT.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("xcat-remote-{}-{n}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        Ok(Download {
+            path: dir.join(name),
+            dir,
+        })
+    }
+}
+
+/// 运行下载命令，将其标准输出写入 `path` 并在终端显示进度，返回命令是否成功
+fn receive(
+    mut command: Command,
+    path: &Path,
+    size: Option<u64>,
+    max_size: u64,
+    label: &str,
+) -> io::Result<bool> {
+    let mut child = command.stdin(Stdio::null()).stdout(Stdio::piped()).spawn()?;
+    let mut stdout = child.stdout.take().expect("stdout 已设置为管道");
+    let mut file = File::create(path)?;
+    let show_progress = io::stderr().is_terminal() && !crate::report::silent();
+
+    let mut buf = [0u8; 64 * 1024];
+    let mut received: u64 = 0;
+    loop {
+        let n = stdout.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n])?;
+        received += n as u64;
+        if received > max_size {
+            let _ = child.kill();
+            return Err(too_large(received, max_size));
+        }
+        if show_progress {
+            match size {
+                Some(size) if size > 0 => {
+                    eprint!("\r{}", t!("下载 {} {}%", label, received * 100 / size));
+                }
+                _ => eprint!("\r{}", t!("下载 {} {} KiB", label, received / 1024)),
+            }
+        }
+    }
+    if show_progress {
+        eprint!("\r\x1b[K");
+    }
+    Ok(child.wait()?.success())
+}
+
+fn too_large(size: u64, max_size: u64) -> io::Error {
+    io::Error::other(t!(
+        "远程文件过大（{} MiB，上限 {} MiB）",
+        size / (1024 * 1024),
+        max_size / (1024 * 1024)
+    ))
+}
+
+/// 响应头中最后一个（重定向之后的）Content-Length
+fn content_length(headers: &str) -> Option<u64> {
+    headers.lines().rev().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if !name.trim().eq_ignore_ascii_case("content-length") {
+            return None;
+        }
+        value.trim().parse().ok()
+    })
+}
+
+/// URL 路径的最后一段作为文件名，去掉查询参数与锚点
+fn url_file_name(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let after_scheme = path.split_once("://").map_or(path, |(_, rest)| rest);
+    match after_scheme.split_once('/') {
+        Some((_, path)) => {
+            let name = path.rsplit('/').next().unwrap_or_default();
+            if name.is_empty() { "download" } else { name }
+        }
+        None => "download",
+    }
+}
+
+/// 单引号转义，作为远程 shell 的参数
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_remote() {
+        assert_eq!(
+            parse_remote("me@box:/var/log/app.log"),
+            Some(("me@box", "/var/log/app.log"))
+        );
+        assert_eq!(parse_remote("box:notes.md"), Some(("box", "notes.md")));
+        assert_eq!(parse_remote("./a:b"), None);
+        assert_eq!(parse_remote("notes.md"), None);
+        assert_eq!(parse_remote("https://example.com/a.pdf"), None);
+    }
+
+    #[test]
+    fn test_url_file_name() {
+        assert_eq!(url_file_name("https://example.com/docs/spec.pdf?raw=1"), "spec.pdf");
+        assert_eq!(url_file_name("https://example.com/"), "download");
+        assert_eq!(url_file_name("http://example.com"), "download");
+        let headers = "HTTP/1.1 302 Found\r\nContent-Length: 0\r\n\r\n\
+                       HTTP/1.1 200 OK\r\ncontent-length: 1234\r\n";
+        assert_eq!(content_length(headers), Some(1234));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("it's.md"), r"'it'\''s.md'");
+    }
+}
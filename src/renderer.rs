@@ -1,22 +1,55 @@
+use crate::config;
 use crate::document::*;
+use crate::t;
 use ratatui::{
+    buffer::Buffer,
     layout::Rect,
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::Paragraph,
     Frame,
 };
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-/// TextStyle → ratatui Style
+/// TextStyle → ratatui Style，颜色取自配置的主题
 pub fn to_style(ts: TextStyle) -> Style {
+    let theme = &config::get().theme;
     let mut s = Style::default();
 
-    if ts.contains(TextStyle::HEADING) {
-        s = s.fg(Color::Cyan);
+    if ts.contains(TextStyle::SUBHEADING) {
+        s = s.fg(theme.subheading);
+    } else if ts.contains(TextStyle::HEADING) {
+        s = s.fg(theme.heading);
+    } else if ts.contains(TextStyle::LINK) {
+        s = s.fg(theme.link).add_modifier(Modifier::UNDERLINED);
+    } else if ts.contains(TextStyle::NOTE) {
+        s = s.fg(theme.note);
+    } else if ts.contains(TextStyle::TIP) {
+        s = s.fg(theme.tip);
+    } else if ts.contains(TextStyle::IMPORTANT) {
+        s = s.fg(theme.important);
+    } else if ts.contains(TextStyle::WARNING) {
+        s = s.fg(theme.warning);
+    } else if ts.contains(TextStyle::CAUTION) {
+        s = s.fg(theme.caution);
+    } else if ts.contains(TextStyle::MATH) {
+        s = s.fg(theme.math);
+    } else if ts.contains(TextStyle::ADDED) {
+        s = s.fg(theme.added);
+    } else if ts.contains(TextStyle::REMOVED) {
+        s = s.fg(theme.removed);
     } else if ts.contains(TextStyle::QUOTE) {
-        s = s.fg(Color::Yellow);
+        s = s.fg(theme.quote);
     } else if ts.contains(TextStyle::CODE) {
-        s = s.fg(Color::Green);
+        s = s.fg(theme.code);
+    } else if ts.contains(TextStyle::KEY) {
+        s = s.fg(theme.key);
+    } else if ts.contains(TextStyle::STRING) {
+        s = s.fg(theme.string);
+    } else if ts.contains(TextStyle::NUMBER) {
+        s = s.fg(theme.number);
+    } else if ts.contains(TextStyle::CONSTANT) {
+        s = s.fg(theme.constant);
     }
 
     if ts.contains(TextStyle::BOLD) {
@@ -28,20 +61,65 @@ pub fn to_style(ts: TextStyle) -> Style {
     if ts.contains(TextStyle::DIM) {
         s = s.add_modifier(Modifier::DIM);
     }
+    if ts.contains(TextStyle::STRIKETHROUGH) {
+        s = s.add_modifier(Modifier::CROSSED_OUT);
+    }
+    if ts.contains(TextStyle::NEW) {
+        s = s.bg(theme.new_line);
+    }
+    if ts.contains(TextStyle::SEARCH_MATCH) {
+        s = s.fg(Color::Black).bg(theme.search_match);
+    }
 
     s
 }
 
+/// 片段的样式：具体颜色（代码高亮）优先于主题颜色
+pub fn span_style(span: &TextSpan) -> Style {
+    let style = to_style(span.style);
+    match span.color {
+        Some((r, g, b)) => style.fg(Color::Rgb(r, g, b)),
+        None => style,
+    }
+}
+
+/// 计算完整位于可见区域内的图片及其屏幕区域
+///
+/// 返回 (图片在 `doc.images` 中的下标, 区域)。只露出一部分的图片不绘制，保留占位文本。
+pub fn visible_images(doc: &Document, area: Rect, top_line: usize) -> Vec<(usize, Rect)> {
+    let rows = area.height as usize;
+    doc.images
+        .iter()
+        .enumerate()
+        .filter(|(_, img)| img.line >= top_line && img.line + img.height as usize <= top_line + rows)
+        .map(|(i, img)| {
+            let indent = doc.lines[img.line].indent.min(area.width);
+            let rect = Rect::new(
+                area.x + indent,
+                area.y + (img.line - top_line) as u16,
+                area.width - indent,
+                img.height,
+            );
+            (i, rect)
+        })
+        .collect()
+}
+
 /// 绘制文档内容
+///
+/// `matches` 为搜索匹配的位置（见 [`Document::find`]），绘制时标为 [`TextStyle::SEARCH_MATCH`]
 pub fn draw_document(
     frame: &mut Frame,
     doc: &Document,
     area: Rect,
     top_line: usize,
-    _left_col: usize,
+    left_col: usize,
     cursor_line: usize,
+    matches: &[(usize, std::ops::Range<usize>)],
 ) {
     let rows = area.height as usize;
+    let theme = &config::get().theme;
+    let cursor_bg = theme.cursor_line;
 
     for row in 0..rows {
         let doc_line_idx = top_line + row;
@@ -58,13 +136,27 @@ pub fn draw_document(
             continue;
         }
 
-        // 当前行高亮背景
-        if is_cursor {
-            let bg = Paragraph::new("").style(Style::default().bg(Color::DarkGray));
-            frame.render_widget(bg, line_area);
+        // 当前行高亮背景，代码块（含上下分隔线）整行铺底色
+        let line_bg = if is_cursor {
+            Some(cursor_bg)
+        } else {
+            doc.code_block_at(doc_line_idx).map(|_| theme.code_block)
+        };
+        if let Some(bg) = line_bg {
+            frame.render_widget(Paragraph::new("").style(Style::default().bg(bg)), line_area);
         }
 
         let render_line = &doc.lines[doc_line_idx];
+        let first = matches.partition_point(|(line, _)| *line < doc_line_idx);
+        let ranges = matches[first..].iter().take_while(|(line, _)| *line == doc_line_idx);
+        let marked;
+        let line_spans = match ranges.clone().next() {
+            Some(_) => {
+                marked = mark_matches(&render_line.spans, ranges.map(|(_, r)| r.clone()));
+                &marked
+            }
+            None => &render_line.spans,
+        };
         let indent = render_line.indent as usize;
         let mut spans: Vec<Span> = Vec::new();
 
@@ -72,34 +164,312 @@ pub fn draw_document(
             spans.push(Span::raw(" ".repeat(indent)));
         }
 
-        for text_span in &render_line.spans {
-            let mut style = to_style(text_span.style);
-            if is_cursor {
-                style = style.bg(Color::DarkGray);
+        // 超宽表格独立横向滚动，其余行不受 left_col 影响
+        let avail = (area.width as usize).saturating_sub(indent);
+        let mut clipped = (false, false);
+        let shifted;
+        let line_spans = match doc.table_at(doc_line_idx) {
+            Some(table) if table.width > avail => {
+                let offset = left_col.min(table.width - avail);
+                clipped = (offset > 0, offset + avail < table.width);
+                shifted = slice_spans(line_spans, offset, avail);
+                &shifted
+            }
+            _ => line_spans,
+        };
+
+        for text_span in line_spans {
+            let mut style = span_style(text_span);
+            if let Some(bg) = line_bg
+                && !text_span.style.contains(TextStyle::SEARCH_MATCH)
+            {
+                style = style.bg(bg);
             }
             spans.push(Span::styled(text_span.text.clone(), style));
         }
 
         let line = Line::from(spans);
         frame.render_widget(Paragraph::new(line), line_area);
+
+        // 表格被裁切的一侧显示指示箭头
+        let arrow_style = to_style(TextStyle::DIM);
+        if clipped.0 && indent < area.width as usize {
+            frame.buffer_mut()[(area.x + indent as u16, y)]
+                .set_symbol("◀")
+                .set_style(arrow_style);
+        }
+        if clipped.1 && area.width > 0 {
+            frame.buffer_mut()[(area.x + area.width - 1, y)]
+                .set_symbol("▶")
+                .set_style(arrow_style);
+        }
+
+        // 为带链接的片段包裹 OSC 8 超链接序列
+        let mut x = line_area.x as usize + indent;
+        let right = (line_area.x + line_area.width) as usize;
+        for text_span in &render_line.spans {
+            let width = UnicodeWidthStr::width(text_span.text.as_str());
+            if let Some(url) = &text_span.link {
+                let end = (x + width).min(right);
+                if x < end {
+                    apply_hyperlink(frame.buffer_mut(), url, x as u16, end as u16, y);
+                }
+            }
+            x += width;
+        }
+    }
+
+    // 图片区域交给图片后端绘制，ratatui 不覆盖这些单元格
+    for (_, rect) in visible_images(doc, area, top_line) {
+        let buf = frame.buffer_mut();
+        for y in rect.top()..rect.bottom() {
+            for x in rect.left()..rect.right() {
+                buf[(x, y)].set_skip(true);
+            }
+        }
+    }
+}
+
+/// 绘制一页幻灯片：内容整体水平、垂直居中，一级标题加粗并拉开字距
+pub fn draw_slide(frame: &mut Frame, doc: &Document, range: std::ops::Range<usize>, area: Rect) {
+    let is_blank = |i: &usize| doc.lines[*i].spans.is_empty();
+    let mut indices: Vec<usize> = range.skip_while(is_blank).collect();
+    while indices.last().is_some_and(is_blank) {
+        indices.pop();
+    }
+
+    let title_lines: Vec<usize> = doc
+        .headings
+        .iter()
+        .filter(|h| h.level == 1)
+        .map(|h| h.line)
+        .collect();
+
+    // 预先生成每行的片段，以便计算内容块宽度
+    let lines: Vec<(usize, Vec<Span>)> = indices
+        .iter()
+        .map(|&i| {
+            let line = &doc.lines[i];
+            if title_lines.contains(&i) {
+                let text: String = line.spans.iter().map(|s| s.text.as_str()).collect();
+                let spaced = text.chars().map(|c| c.to_string()).collect::<Vec<_>>().join(" ");
+                let style = to_style(TextStyle::BOLD | TextStyle::HEADING);
+                (0, vec![Span::styled(spaced, style)])
+            } else {
+                let spans = line
+                    .spans
+                    .iter()
+                    .map(|s| Span::styled(s.text.clone(), span_style(s)))
+                    .collect();
+                (line.indent as usize, spans)
+            }
+        })
+        .collect();
+
+    let block_width = lines
+        .iter()
+        .map(|(indent, spans)| indent + spans.iter().map(|s| s.width()).sum::<usize>())
+        .max()
+        .unwrap_or(0);
+    let left = (area.width as usize).saturating_sub(block_width) / 2;
+    let top = (area.height as usize).saturating_sub(lines.len()) / 2;
+
+    for (row, (indent, spans)) in lines.into_iter().enumerate() {
+        let y = top + row;
+        if y >= area.height as usize {
+            break;
+        }
+        let x = (left + indent).min(area.width as usize) as u16;
+        let line_area = Rect::new(area.x + x, area.y + y as u16, area.width - x, 1);
+        frame.render_widget(Paragraph::new(Line::from(spans)), line_area);
     }
 }
 
+/// 绘制目录栏：按标题级别缩进，选中项高亮，右侧以竖线与正文分隔
+pub fn draw_toc(frame: &mut Frame, headings: &[Heading], area: Rect, selected: usize) {
+    let rows = area.height as usize;
+    let width = area.width.saturating_sub(1) as usize;
+    let top = selected.saturating_sub(rows.saturating_sub(1));
+    let cursor_bg = config::get().theme.cursor_line;
+
+    for row in 0..rows {
+        let y = area.y + row as u16;
+        let border = Span::styled("│", to_style(TextStyle::DIM));
+        frame.render_widget(Paragraph::new(Line::from(border)), Rect::new(area.x + width as u16, y, 1, 1));
+        let Some(heading) = headings.get(top + row) else {
+            continue;
+        };
+        let indent = usize::from(heading.level.saturating_sub(1)) * 2;
+        let mut text = format!("{}{}", " ".repeat(indent), heading.text);
+        if text.width() > width {
+            while text.width() + 1 > width && text.pop().is_some() {}
+            text.push('…');
+        }
+        let level_style = match heading.level {
+            1 => TextStyle::BOLD | TextStyle::HEADING,
+            2 => TextStyle::HEADING,
+            _ => TextStyle::NONE,
+        };
+        let mut style = to_style(level_style);
+        if top + row == selected {
+            style = style.bg(cursor_bg);
+        }
+        let line = Paragraph::new(Line::from(Span::styled(text, style))).style(style);
+        frame.render_widget(line, Rect::new(area.x, y, width as u16, 1));
+    }
+}
+
+/// 在 `ranges`（行内文字的字节范围，按位置排序）处拆开片段并加上 [`TextStyle::SEARCH_MATCH`]
+fn mark_matches(spans: &[TextSpan], ranges: impl Iterator<Item = std::ops::Range<usize>>) -> Vec<TextSpan> {
+    let ranges: Vec<_> = ranges.collect();
+    let mut result = Vec::new();
+    let mut offset = 0;
+    for span in spans {
+        let end = offset + span.text.len();
+        // 片段内的切分点：匹配的起止位置
+        let mut cuts: Vec<usize> = ranges
+            .iter()
+            .flat_map(|r| [r.start, r.end])
+            .filter(|&p| p > offset && p < end)
+            .map(|p| p - offset)
+            .collect();
+        cuts.push(span.text.len());
+        cuts.dedup();
+        let mut start = 0;
+        for cut in cuts {
+            let mut piece = span.clone();
+            piece.text = span.text[start..cut].to_string();
+            if ranges.iter().any(|r| r.start <= offset + start && offset + start < r.end) {
+                piece.style.insert(TextStyle::SEARCH_MATCH);
+            }
+            result.push(piece);
+            start = cut;
+        }
+        offset = end;
+    }
+    result
+}
+
+/// 按显示列截取片段：跳过前 `skip` 列，最多保留 `max` 列
+///
+/// 被边界切开的宽字符以空格代替，保证列对齐。
+fn slice_spans(spans: &[TextSpan], skip: usize, max: usize) -> Vec<TextSpan> {
+    let mut result = Vec::new();
+    let mut col = 0;
+    let end = skip + max;
+
+    for span in spans {
+        let mut text = String::new();
+        for ch in span.text.chars() {
+            let w = ch.width().unwrap_or(0);
+            let (start, stop) = (col, col + w);
+            col = stop;
+            if stop <= skip || start >= end {
+                continue;
+            }
+            if start < skip || stop > end {
+                text.push_str(&" ".repeat(stop.min(end) - start.max(skip)));
+            } else {
+                text.push(ch);
+            }
+        }
+        if !text.is_empty() {
+            result.push(TextSpan::new(text, span.style));
+        }
+        if col >= end {
+            break;
+        }
+    }
+    result
+}
+
+/// 将 [x0, x1) 范围内的单元格包裹进 OSC 8 超链接
+///
+/// 转义序列会被计入符号宽度，差分时其后的一个单元格会被跳过，
+/// 因此把两个窄字符合并进前一个单元格，并将后一个单元格标记为 skip。
+/// 落单的窄字符不做处理，避免吞掉相邻的普通文本。
+fn apply_hyperlink(buf: &mut Buffer, url: &str, x0: u16, x1: u16, y: u16) {
+    let wrap = |text: &str| format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\");
+
+    let mut x = x0;
+    while x < x1 {
+        let symbol = buf[(x, y)].symbol().to_string();
+        let width = UnicodeWidthStr::width(symbol.as_str()).max(1) as u16;
+        if width > 1 {
+            buf[(x, y)].set_symbol(&wrap(&symbol));
+            x += width;
+        } else if x + 1 < x1 && UnicodeWidthStr::width(buf[(x + 1, y)].symbol()) == 1 {
+            let pair = format!("{symbol}{}", buf[(x + 1, y)].symbol());
+            buf[(x, y)].set_symbol(&wrap(&pair));
+            buf[(x + 1, y)].set_skip(true);
+            x += 2;
+        } else {
+            x += 1;
+        }
+    }
+}
+
+/// 状态栏右侧的位置信息
+#[derive(Default)]
+pub struct StatusPosition {
+    /// 当前行（幻灯片模式为当前页），从 0 开始
+    pub line: usize,
+    pub total: usize,
+    /// 光标行对应的源文件行，从 0 开始
+    pub source_line: Option<usize>,
+    /// 分页文档中光标所在页与总页数，从 1 开始
+    pub page: Option<(usize, usize)>,
+    /// 搜索匹配：光标行第一处匹配的序号（从 1 开始，光标行没有匹配时为 None）与匹配总数
+    pub matches: Option<(Option<usize>, usize)>,
+}
+
 /// 绘制底部状态栏
-pub fn draw_status_bar(
-    frame: &mut Frame,
-    area: Rect,
-    filename: &str,
-    current_line: usize,
-    total_lines: usize,
-) {
+pub fn draw_status_bar(frame: &mut Frame, area: Rect, filename: &str, breadcrumb: &str, position: &StatusPosition) {
     let width = area.width as usize;
-    let left = format!(" {}", filename);
-    let right = format!("{}/{} ", current_line + 1, total_lines);
-    let padding = width.saturating_sub(left.len() + right.len());
+    let mut left = format!(" {}", filename);
+    let mut right = format!("{}/{} ", position.line + 1, position.total);
+    if let Some(source) = position.source_line {
+        right = format!("{}  {right}", t!("源文件第 {} 行", source + 1));
+    }
+    if let Some((page, pages)) = position.page {
+        right = format!("{}  {right}", t!("第 {}/{} 页", page, pages));
+    }
+    match position.matches {
+        Some((Some(current), total)) => right = format!("{}  {right}", t!("匹配 {}/{}", current, total)),
+        Some((None, total)) => right = format!("{}  {right}", t!("{} 处匹配", total)),
+        None => {}
+    }
+
+    // 章节路径放在文件名之后，空间不足时从左侧截断
+    if !breadcrumb.is_empty() {
+        let room = width.saturating_sub(left.width() + right.width() + 4);
+        let mut crumb = breadcrumb.to_string();
+        if crumb.width() > room {
+            while crumb.width() + 1 > room && !crumb.is_empty() {
+                crumb.remove(0);
+            }
+            crumb.insert(0, '…');
+        }
+        if crumb.width() <= room {
+            left = format!("{left}  {crumb}");
+        }
+    }
+
+    let padding = width.saturating_sub(left.width() + right.width());
     let bar_text = format!("{}{}{}", left, " ".repeat(padding), right);
 
-    let style = Style::default().fg(Color::Black).bg(Color::White);
+    let theme = &config::get().theme;
+    let style = Style::default().fg(theme.status_bar_fg).bg(theme.status_bar_bg);
     let bar = Line::from(Span::styled(bar_text, style));
     frame.render_widget(Paragraph::new(bar), area);
 }
+
+/// 在状态栏位置绘制正在输入的命令或搜索词，`prefix` 为 `:` 或 `/`
+pub fn draw_command_line(frame: &mut Frame, area: Rect, prefix: char, input: &str) {
+    let theme = &config::get().theme;
+    let style = Style::default().fg(theme.status_bar_fg).bg(theme.status_bar_bg);
+    let text = format!("{prefix}{input}█");
+    let padding = (area.width as usize).saturating_sub(text.width());
+    let line = Line::from(Span::styled(format!("{text}{}", " ".repeat(padding)), style));
+    frame.render_widget(Paragraph::new(line), area);
+}
@@ -0,0 +1,228 @@
+//! 错误输出与退出码
+//!
+//! 不同的失败原因使用不同的退出码，便于脚本区分；`--quiet` 时不输出错误与警告，
+//! `--json-errors` 时每条错误或警告输出为标准错误上的一行 JSON：
+//!
+//! ```text
+//! {"level":"error","kind":"not_found","code":3,"message":"文件不存在","path":"a.md"}
+//! ```
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::process;
+use std::sync::OnceLock;
+use xcat::format::FormatError;
+use xcat::t;
+
+/// 失败原因，决定退出码
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// 读写失败、导出失败、远程文件获取失败等
+    Other,
+    /// 文件不存在
+    NotFound,
+    /// 不支持的文件格式或文件类型
+    Unsupported,
+    /// 文件无法解析或解析结果为空
+    Parse,
+    /// 终端操作失败（预览界面、图片显示）
+    Terminal,
+    /// 文件超过大小上限且未确认打开
+    TooLarge,
+}
+
+/// 命令行参数错误的退出码，与 clap 一致
+const USAGE_CODE: i32 = 2;
+
+impl Failure {
+    pub fn code(self) -> i32 {
+        match self {
+            Failure::Other => 1,
+            Failure::NotFound => 3,
+            Failure::Unsupported => 4,
+            Failure::Parse => 5,
+            Failure::Terminal => 6,
+            Failure::TooLarge => 7,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Failure::Other => "other",
+            Failure::NotFound => "not_found",
+            Failure::Unsupported => "unsupported_format",
+            Failure::Parse => "parse_error",
+            Failure::Terminal => "terminal_error",
+            Failure::TooLarge => "too_large",
+        }
+    }
+}
+
+/// 带失败原因的错误
+#[derive(Debug)]
+pub struct Error {
+    pub failure: Failure,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(failure: Failure, message: impl Into<String>) -> Self {
+        Error {
+            failure,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        let failure = match e.kind() {
+            io::ErrorKind::NotFound => Failure::NotFound,
+            io::ErrorKind::InvalidData => Failure::Parse,
+            _ => Failure::Other,
+        };
+        Error::new(failure, t!("IO错误: {}", e))
+    }
+}
+
+impl From<FormatError> for Error {
+    fn from(e: FormatError) -> Self {
+        match e {
+            FormatError::Io(e) => e.into(),
+            FormatError::Parse(_) => Error::new(Failure::Parse, e.to_string()),
+        }
+    }
+}
+
+/// 错误与警告的输出方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Text,
+    /// `--quiet`：只以退出码表示结果
+    Quiet,
+    /// `--json-errors`
+    Json,
+}
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+/// 设置输出方式，只在启动时调用一次
+pub fn init(mode: Mode) {
+    let _ = MODE.set(mode);
+}
+
+fn mode() -> Mode {
+    MODE.get().copied().unwrap_or_default()
+}
+
+/// 是否不应在标准错误上输出进度等提示
+pub fn silent() -> bool {
+    mode() != Mode::Text
+}
+
+/// 输出警告
+pub fn warn(message: &str) {
+    match mode() {
+        Mode::Text => eprintln!("{}", t!("警告: {}", message)),
+        Mode::Quiet => {}
+        Mode::Json => eprintln!("{}", json_line("warning", None, message, None)),
+    }
+}
+
+/// 输出错误，`path` 为出错的文件
+pub fn error(err: &Error, path: Option<&Path>) {
+    match mode() {
+        Mode::Text => match path {
+            Some(path) => eprintln!("{}", t!("错误: {} - {}", err, path.display())),
+            None => eprintln!("{}", t!("错误: {}", err)),
+        },
+        Mode::Quiet => {}
+        Mode::Json => {
+            let kind = Some((err.failure.name(), err.failure.code()));
+            eprintln!("{}", json_line("error", kind, &err.message, path));
+        }
+    }
+}
+
+/// 输出错误并以对应的退出码退出
+pub fn fail(err: Error, path: Option<&Path>) -> ! {
+    error(&err, path);
+    process::exit(err.failure.code())
+}
+
+/// 命令行参数错误：按输出方式报告后退出，`--help` 与 `--version` 照常输出
+pub fn usage(e: clap::Error) -> ! {
+    if !e.use_stderr() {
+        e.exit();
+    }
+    match mode() {
+        Mode::Text => e.exit(),
+        Mode::Quiet => process::exit(USAGE_CODE),
+        Mode::Json => {
+            let rendered = e.to_string();
+            let first = rendered.lines().next().unwrap_or_default();
+            let message = first.strip_prefix("error: ").unwrap_or(first);
+            eprintln!("{}", json_line("error", Some(("usage", USAGE_CODE)), message, None));
+            process::exit(USAGE_CODE)
+        }
+    }
+}
+
+fn json_line(level: &str, kind: Option<(&str, i32)>, message: &str, path: Option<&Path>) -> String {
+    let mut line = format!("{{\"level\":\"{level}\"");
+    if let Some((name, code)) = kind {
+        line.push_str(&format!(",\"kind\":\"{name}\",\"code\":{code}"));
+    }
+    line.push_str(&format!(",\"message\":{}", json_string(message)));
+    if let Some(path) = path {
+        line.push_str(&format!(",\"path\":{}", json_string(&path.to_string_lossy())));
+    }
+    line.push('}');
+    line
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_line() {
+        let line = json_line(
+            "error",
+            Some(("not_found", 3)),
+            "文件不存在",
+            Some(Path::new("a \"b\".md")),
+        );
+        assert_eq!(
+            line,
+            r#"{"level":"error","kind":"not_found","code":3,"message":"文件不存在","path":"a \"b\".md"}"#
+        );
+        assert_eq!(json_string("a\nb\x01"), r#""a\nb\u0001""#);
+    }
+}
@@ -0,0 +1,110 @@
+//! 会话（`--session <name>`）
+//!
+//! 退出时记录打开的文件及阅读位置，下次以同名会话启动时恢复。
+//! 会话文件位于 `$XDG_STATE_HOME/xcat/sessions/<name>`（默认 `~/.local/state`），
+//! 每行一个文件：`top_line<TAB>cursor_line<TAB>path`；另有一行 `theme<TAB>name` 记录
+//! 当时使用的配色方案，恢复会话时沿用。
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use xcat::t;
+
+/// 会话中的一个文件
+#[derive(Clone, Debug, PartialEq)]
+pub struct Buffer {
+    pub path: PathBuf,
+    pub top_line: usize,
+    pub cursor_line: usize,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Session {
+    /// 配色方案名（`[syntax] theme`）
+    pub theme: Option<String>,
+    pub buffers: Vec<Buffer>,
+}
+
+impl Session {
+    /// 读取会话，不存在时返回空会话
+    pub fn load(name: &str) -> Session {
+        session_path(name)
+            .and_then(|p| fs::read_to_string(p).ok())
+            .map(|s| Session::parse(&s))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, name: &str) -> io::Result<()> {
+        let path = session_path(name).ok_or_else(|| io::Error::other(t!("无法确定会话目录")))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_string())
+    }
+
+    /// 查找文件的记录位置
+    pub fn find(&self, path: &Path) -> Option<&Buffer> {
+        self.buffers.iter().find(|b| b.path == path)
+    }
+
+    fn parse(content: &str) -> Session {
+        let mut session = Session::default();
+        for line in content.lines() {
+            if let Some(theme) = line.strip_prefix("theme\t") {
+                session.theme = Some(theme.to_string());
+                continue;
+            }
+            let mut fields = line.splitn(3, '\t');
+            let (Some(top_line), Some(cursor_line), Some(path)) = (
+                fields.next().and_then(|f| f.parse().ok()),
+                fields.next().and_then(|f| f.parse().ok()),
+                fields.next(),
+            ) else {
+                continue;
+            };
+            session.buffers.push(Buffer {
+                path: PathBuf::from(path),
+                top_line,
+                cursor_line,
+            });
+        }
+        session
+    }
+}
+
+impl std::fmt::Display for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(theme) = &self.theme {
+            writeln!(f, "theme\t{theme}")?;
+        }
+        for b in &self.buffers {
+            writeln!(f, "{}\t{}\t{}", b.top_line, b.cursor_line, b.path.display())?;
+        }
+        Ok(())
+    }
+}
+
+fn session_path(name: &str) -> Option<PathBuf> {
+    let state = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/state")))?;
+    Some(state.join("xcat/sessions").join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let session = Session {
+            theme: Some("InspiredGitHub".into()),
+            buffers: vec![Buffer {
+                path: PathBuf::from("/tmp/a b.pdf"),
+                top_line: 120,
+                cursor_line: 130,
+            }],
+        };
+        assert_eq!(Session::parse(&session.to_string()), session);
+    }
+}
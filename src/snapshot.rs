@@ -0,0 +1,170 @@
+//! 离屏渲染
+//!
+//! 不启动终端，将文档按预览模式的样式完整渲染为字符串：
+//! `render_ansi` 保留颜色与修饰（SGR 序列），`render_plain` 只保留文本。
+//! 用于快照测试，格式插件也可以用它验证自己的输出。
+
+use crate::document::Document;
+use crate::format::text::clean_line;
+use crate::renderer;
+use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// 渲染为带 ANSI 样式的字符串，每个文档行对应一行输出
+pub fn render_ansi(doc: &Document, width: u16) -> String {
+    render(doc, width, true)
+}
+
+/// 渲染为纯文本
+pub fn render_plain(doc: &Document, width: u16) -> String {
+    render(doc, width, false)
+}
+
+/// 按文档最宽的行确定输出宽度，避免截断表格等超宽内容
+///
+/// 超长的行（如没有换行的长段落）截断到 [`MAX_WIDTH`]。
+pub fn fit_width(doc: &Document) -> u16 {
+    doc.width().clamp(1, MAX_WIDTH as usize) as u16
+}
+
+/// 离屏渲染的最大宽度
+pub const MAX_WIDTH: u16 = 8192;
+
+/// 每次离屏绘制的单元格数上限，长文档分段渲染
+const CHUNK_CELLS: usize = 1 << 20;
+
+fn render(doc: &Document, width: u16, styled: bool) -> String {
+    let mut out = String::new();
+    let width = width.min(MAX_WIDTH);
+    let chunk_rows = (CHUNK_CELLS / width.max(1) as usize).max(1);
+    for top in (0..doc.lines.len()).step_by(chunk_rows) {
+        let rows = (doc.lines.len() - top).min(chunk_rows) as u16;
+        let mut terminal = match Terminal::new(TestBackend::new(width, rows)) {
+            Ok(terminal) => terminal,
+            Err(_) => break,
+        };
+        let drawn = terminal.draw(|frame| {
+            let area = Rect::new(0, 0, width, rows);
+            // 光标行设为文档之外，不绘制高亮
+            renderer::draw_document(frame, doc, area, top, 0, usize::MAX, &[]);
+        });
+        if drawn.is_err() {
+            break;
+        }
+
+        let buffer = terminal.backend().buffer();
+        for y in 0..rows {
+            let line = buffer_line(buffer, y, styled);
+            if styled {
+                out.push_str(&line);
+            } else {
+                // 超链接等转义序列嵌在单元格内容里，纯文本输出时去掉
+                out.push_str(&clean_line(&line));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// 输出一行单元格，宽字符之后被占用的单元格与跳过的单元格不输出
+///
+/// 只在 SGR 序列变化时输出样式，行尾的无样式空白不输出。
+fn buffer_line(buffer: &Buffer, y: u16, styled: bool) -> String {
+    let mut cells = Vec::new();
+    let mut hidden = 0;
+    for x in 0..buffer.area.width {
+        let cell = &buffer[(x, y)];
+        if hidden > 0 {
+            hidden -= 1;
+            continue;
+        }
+        if cell.skip {
+            continue;
+        }
+        let symbol = cell.symbol();
+        hidden = UnicodeWidthStr::width(symbol).saturating_sub(1);
+        let code = if styled { sgr(cell.style()) } else { String::new() };
+        cells.push((symbol, code));
+    }
+    while cells.last().is_some_and(|(symbol, code)| *symbol == " " && code.is_empty()) {
+        cells.pop();
+    }
+
+    let mut line = String::new();
+    let mut current = String::new();
+    for (symbol, code) in cells {
+        if code != current {
+            line.push_str("\x1b[0m");
+            line.push_str(&code);
+            current = code;
+        }
+        line.push_str(symbol);
+    }
+    if !current.is_empty() {
+        line.push_str("\x1b[0m");
+    }
+    line
+}
+
+/// 样式对应的 SGR 序列
+fn sgr(style: Style) -> String {
+    let mut codes: Vec<String> = Vec::new();
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    for (modifier, code) in modifiers {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.into());
+        }
+    }
+    if let Some(fg) = style.fg.and_then(|c| color_code(c, false)) {
+        codes.push(fg);
+    }
+    if let Some(bg) = style.bg.and_then(|c| color_code(c, true)) {
+        codes.push(bg);
+    }
+
+    if codes.is_empty() {
+        String::new()
+    } else {
+        format!("\x1b[{}m", codes.join(";"))
+    }
+}
+
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let base = if background { 40 } else { 30 };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+        Color::Indexed(i) => return Some(format!("{};5;{i}", base + 8)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{r};{g};{b}", base + 8)),
+    };
+    Some(code.to_string())
+}
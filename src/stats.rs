@@ -0,0 +1,45 @@
+//! 性能统计（`--time`）
+//!
+//! 记录格式检测、解析与首次渲染耗时，退出后连同内存峰值输出到 stderr，
+//! 便于定位慢格式与性能回退。
+
+use std::time::Duration;
+use xcat::t;
+
+/// 各阶段耗时
+#[derive(Debug, Default)]
+pub struct Timings {
+    pub detect: Duration,
+    pub parse: Option<Duration>,
+    /// 预览模式为首帧绘制耗时，直接模式为输出耗时
+    pub render: Option<Duration>,
+}
+
+impl Timings {
+    /// 输出统计结果到 stderr
+    pub fn report(&self) {
+        eprintln!("{}", t!("格式检测: {}", format_duration(self.detect)));
+        if let Some(parse) = self.parse {
+            eprintln!("{}", t!("解析:     {}", format_duration(parse)));
+        }
+        if let Some(render) = self.render {
+            eprintln!("{}", t!("首次渲染: {}", format_duration(render)));
+        }
+        match peak_memory() {
+            Some(bytes) => eprintln!("{}", t!("内存峰值: {} MiB", format!("{:.1}", bytes as f64 / (1024.0 * 1024.0)))),
+            None => eprintln!("{}", t!("内存峰值: 未知")),
+        }
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    format!("{:.2} ms", d.as_secs_f64() * 1000.0)
+}
+
+/// 进程常驻内存峰值（字节），目前仅支持 Linux
+fn peak_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
@@ -1,32 +1,528 @@
-use crate::document::Document;
-use crate::renderer;
+use crate::config::{self, Action, WrapMode};
+use crate::document::{CodeBlock, Document, Heading, TextStyle};
+use crate::format::FormatError;
+use crate::format::dir::SortKey;
+use crate::format::pdf::PageLoader;
+use base64::Engine;
+use crate::format;
+use crate::renderer::{self, StatusPosition};
 use crossterm::{
-    event::{self, Event, KeyCode},
-    execute,
+    cursor::MoveTo,
+    event::{self, Event, KeyCode, KeyModifiers},
+    execute, queue,
+    style::Print,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use notify::{EventKind, RecursiveMode, Watcher};
 use ratatui::{backend::CrosstermBackend, layout::Rect, Terminal};
-use std::io::{self, stdout};
+use std::collections::HashMap;
+use std::io::{self, stdout, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime};
+use xcat::t;
 
-pub struct Viewer {
+/// 处理文件变化通知（或无法监视时检查修改时间）的间隔
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 目录栏的最大宽度（含分隔线），窄终端中不超过三分之一
+const TOC_WIDTH: u16 = 32;
+
+/// 重新解析文档的回调，用于 `--watch`
+pub type Reload = Box<dyn Fn() -> Result<Document, FormatError>>;
+
+/// 打开文件或目录的回调，用于在目录预览中打开选中项
+pub type Opener = Box<dyn Fn(&Path, SortKey) -> Result<Buffer, String>>;
+
+/// 打开的文档及其阅读位置
+pub struct Buffer {
     doc: Document,
     filename: String,
+    /// 来源文件及重新解析的方法，用于 `--watch` 与会话
+    source: Option<(PathBuf, Reload)>,
+    modified: Option<SystemTime>,
     top_line: usize,
     left_col: usize,
     cursor_line: usize,
+    /// 目录预览的排序方式
+    sort: SortKey,
+    /// `--follow`：自上次按键以来新增的第一行，其后的行高亮显示
+    fresh: Option<usize>,
+    /// 有折叠的区域时，`doc` 为折叠后的文档
+    folded: Option<Folded>,
+    /// 折行显示时的原始文档，折叠在折行后的文档上进行
+    wrapped: Option<Wrapped>,
+    /// 仍在后台提取的 PDF 页
+    loader: Option<PageLoader>,
+    /// 要恢复的阅读位置超出已加载的内容时，等加载到该处再跳转
+    target: Option<(usize, usize)>,
 }
 
-impl Viewer {
+/// 折行状态
+struct Wrapped {
+    /// 折行前的文档
+    raw: Document,
+    /// 折行时的内容宽度
+    width: usize,
+    /// 折行后每行对应的原始行号
+    lines: Vec<usize>,
+}
+
+/// 折叠状态
+struct Folded {
+    /// 未折叠的完整文档
+    full: Document,
+    /// 已折叠区域的起始行（完整文档中的行号）
+    collapsed: Vec<usize>,
+    /// 折叠后每行对应的完整文档行号
+    lines: Vec<usize>,
+}
+
+impl Buffer {
     pub fn new(doc: Document, filename: String) -> Self {
         Self {
             doc,
             filename,
+            source: None,
+            modified: None,
             top_line: 0,
             left_col: 0,
             cursor_line: 0,
+            sort: SortKey::default(),
+            fresh: None,
+            folded: None,
+            wrapped: None,
+            loader: None,
+            target: None,
+        }
+    }
+
+    /// 其余内容在后台加载，完成前状态栏显示进度
+    pub fn with_loader(mut self, loader: PageLoader) -> Self {
+        self.loader = Some(loader);
+        self
+    }
+
+    /// 记录来源文件，`--watch` 时文件变化用 `reload` 重新解析
+    pub fn with_source(mut self, path: PathBuf, reload: Reload) -> Self {
+        self.modified = modified_time(&path);
+        self.source = Some((path, reload));
+        self
+    }
+
+    /// 完整文档（不受折叠与折行影响）
+    pub fn document(&self) -> &Document {
+        match &self.wrapped {
+            Some(w) => &w.raw,
+            None => self.folded.as_ref().map_or(&self.doc, |f| &f.full),
+        }
+    }
+
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.source.as_ref().map(|(path, _)| path.as_path())
+    }
+
+    /// 当前阅读位置 (top_line, cursor_line)，为完整文档（未折行）中的行号
+    pub fn position(&self) -> (usize, usize) {
+        let (top, cursor) = self.full_position();
+        match &self.wrapped {
+            Some(w) => (w.lines.get(top).copied().unwrap_or(0), w.lines.get(cursor).copied().unwrap_or(0)),
+            None => (top, cursor),
+        }
+    }
+
+    /// 折叠前（折行后）的文档中的阅读位置
+    fn full_position(&self) -> (usize, usize) {
+        match &self.folded {
+            Some(f) => (f.lines[self.top_line], f.lines[self.cursor_line]),
+            None => (self.top_line, self.cursor_line),
+        }
+    }
+
+    /// 恢复阅读位置（如会话中记录的位置）
+    pub fn set_position(&mut self, top_line: usize, cursor_line: usize) {
+        if self.loader.is_some() && cursor_line >= self.doc.lines.len() {
+            self.target = Some((top_line, cursor_line));
+        }
+        let max_line = self.doc.lines.len().saturating_sub(1);
+        self.top_line = top_line.min(max_line);
+        self.cursor_line = cursor_line.min(max_line);
+    }
+
+    /// 源文件修改时间变化则重新加载，返回状态栏提示；`follow` 时按文件增长处理
+    fn check_reload(&mut self, follow: bool) -> Option<String> {
+        let (path, reload) = self.source.as_ref()?;
+        let modified = modified_time(path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+
+        let result = reload();
+        self.unfold();
+        self.unwrap_lines();
+        // 重新解析得到的是完整文档
+        self.loader = None;
+        self.target = None;
+        match result {
+            Ok(doc) if follow && doc.lines.len() >= self.doc.lines.len() => self.follow_document(doc),
+            Ok(doc) => {
+                self.fresh = None;
+                self.replace_document(doc);
+                Some(t!("文件已更新").into())
+            }
+            Err(e) => Some(t!("重新加载失败: {}", e)),
+        }
+    }
+
+    /// 追加后台提取完的页，返回是否有变化；全部加载完后移除加载器
+    fn receive_pages(&mut self) -> bool {
+        let Some(loader) = &mut self.loader else {
+            return false;
+        };
+        let pages = loader.poll();
+        if pages.as_ref().is_some_and(Vec::is_empty) {
+            return false;
+        }
+        // 折行随宽度在下次绘制前重新进行
+        self.unwrap_lines();
+        match pages {
+            Some(pages) => pages.into_iter().for_each(|page| self.doc.append(page)),
+            None => self.loader = None,
+        }
+        if let Some((top, cursor)) = self.target
+            && (cursor < self.doc.lines.len() || self.loader.is_none())
+        {
+            self.target = None;
+            self.set_position(top, cursor);
+        }
+        true
+    }
+
+    /// 光标所在页与总页数（从 1 开始），没有分页时为 None；后台加载中按 PDF 的总页数计
+    fn page(&self) -> Option<(usize, usize)> {
+        if self.doc.pages.is_empty() {
+            return None;
+        }
+        let current = self.doc.pages.partition_point(|&l| l <= self.cursor_line).max(1);
+        let total = self.loader.as_ref().map_or(self.doc.pages.len(), |l| l.progress().1 as usize);
+        Some((current, total))
+    }
+
+    /// 加载进度提示，已全部加载时为 None
+    fn loading(&self) -> Option<String> {
+        let (loaded, total) = self.loader.as_ref()?.progress();
+        Some(t!("加载中… 第 {}/{} 页", loaded, total))
+    }
+
+    /// 替换为增长后的文档，高亮新增的行；光标原本在末行时跟随到新的末行
+    fn follow_document(&mut self, mut doc: Document) -> Option<String> {
+        let (old_len, new_len) = (self.doc.lines.len(), doc.lines.len());
+        let at_end = self.cursor_line + 1 >= old_len;
+        let start = self.fresh.unwrap_or(old_len);
+        for line in &mut doc.lines[start.min(new_len)..] {
+            for span in &mut line.spans {
+                span.style.insert(TextStyle::NEW);
+            }
+        }
+        self.doc = doc;
+        if new_len == old_len {
+            return None;
+        }
+        self.fresh = Some(start);
+        if at_end {
+            self.cursor_line = new_len - 1;
+        }
+        Some(t!("新增 {} 行", new_len - old_len))
+    }
+
+    /// 折叠 / 展开光标所在的最内层区域，光标移到区域首行
+    fn toggle_fold(&mut self) {
+        let (top, line) = self.full_position();
+        let (full, collapsed) = match &self.folded {
+            Some(f) => (&f.full, f.collapsed.as_slice()),
+            None => (&self.doc, &[][..]),
+        };
+        let target = full
+            .folds
+            .iter()
+            .filter(|f| f.start <= line && line < f.end)
+            .map(|f| f.start)
+            .max();
+        let Some(target) = target else {
+            return;
+        };
+        let mut collapsed = collapsed.to_vec();
+        match collapsed.iter().position(|&s| s == target) {
+            Some(i) => {
+                collapsed.remove(i);
+            }
+            None => collapsed.push(target),
+        }
+
+        let full = match self.folded.take() {
+            Some(f) => f.full,
+            None => std::mem::take(&mut self.doc),
+        };
+        if collapsed.is_empty() {
+            self.doc = full;
+            (self.top_line, self.cursor_line) = (top, target);
+            return;
+        }
+        let (doc, lines) = full.fold(&collapsed);
+        self.top_line = lines.iter().position(|&l| l >= top).unwrap_or(0);
+        self.cursor_line = lines.iter().position(|&l| l == target).unwrap_or(0);
+        self.doc = doc;
+        self.folded = Some(Folded {
+            full,
+            collapsed,
+            lines,
+        });
+    }
+
+    /// 折叠所有区域，嵌套的区域随外层一起收起；光标移到所在位置的可见行
+    fn fold_all(&mut self) {
+        let (top, line) = self.full_position();
+        self.unfold();
+        let collapsed: Vec<usize> = self.doc.folds.iter().map(|f| f.start).collect();
+        if collapsed.is_empty() {
+            return;
+        }
+        let full = std::mem::take(&mut self.doc);
+        let (doc, lines) = full.fold(&collapsed);
+        self.top_line = lines.iter().rposition(|&l| l <= top).unwrap_or(0);
+        self.cursor_line = lines.iter().rposition(|&l| l <= line).unwrap_or(0);
+        self.doc = doc;
+        self.folded = Some(Folded {
+            full,
+            collapsed,
+            lines,
+        });
+    }
+
+    /// 展开所有折叠，阅读位置换算为完整文档中的行号
+    fn unfold(&mut self) {
+        if let Some(f) = self.folded.take() {
+            self.top_line = f.lines[self.top_line];
+            self.cursor_line = f.lines[self.cursor_line];
+            self.doc = f.full;
+        }
+    }
+
+    /// 配置为折行显示时按内容宽度折行；宽度变化时从原始文档重新折行，已有的折叠随之展开
+    fn ensure_wrap(&mut self, width: usize) {
+        if config::get().wrap != WrapMode::Wrap || self.wrapped.as_ref().is_some_and(|w| w.width == width) {
+            return;
+        }
+        let (top, cursor) = self.position();
+        self.unfold();
+        let raw = match self.wrapped.take() {
+            Some(w) => w.raw,
+            None => std::mem::take(&mut self.doc),
+        };
+        let (doc, lines) = raw.wrap(width);
+        let max_line = lines.len().saturating_sub(1);
+        self.top_line = lines.partition_point(|&l| l < top).min(max_line);
+        self.cursor_line = lines.partition_point(|&l| l < cursor).min(max_line);
+        self.doc = doc;
+        self.wrapped = Some(Wrapped { raw, width, lines });
+    }
+
+    /// 取消折行，阅读位置换算为原始文档中的行号
+    fn unwrap_lines(&mut self) {
+        self.unfold();
+        if let Some(w) = self.wrapped.take() {
+            self.top_line = w.lines.get(self.top_line).copied().unwrap_or(0);
+            self.cursor_line = w.lines.get(self.cursor_line).copied().unwrap_or(0);
+            self.doc = w.raw;
+        }
+    }
+
+    /// 取消新增行的高亮
+    fn clear_fresh(&mut self) {
+        let Some(start) = self.fresh.take() else {
+            return;
+        };
+        for line in self.doc.lines.iter_mut().skip(start) {
+            for span in &mut line.spans {
+                span.style.remove(TextStyle::NEW);
+            }
+        }
+    }
+
+    /// 替换文档，以视口上方最近的标题为锚点保持滚动位置
+    ///
+    /// 在视口上方编辑时行号会整体偏移，按标题定位可以避免画面跳动。
+    fn replace_document(&mut self, doc: Document) {
+        let cursor_offset = self.cursor_line.saturating_sub(self.top_line);
+        let anchor = self
+            .doc
+            .headings
+            .iter()
+            .rev()
+            .find(|h| h.line <= self.top_line)
+            .map(|h| {
+                let same = |o: &&Heading| o.level == h.level && o.text == h.text;
+                let nth = self.doc.headings.iter().filter(same).position(|o| o.line == h.line);
+                (h.level, h.text.clone(), nth.unwrap_or(0), self.top_line - h.line)
+            });
+
+        let new_top = anchor.and_then(|(level, text, nth, offset)| {
+            doc.headings
+                .iter()
+                .filter(|h| h.level == level && h.text == text)
+                .nth(nth)
+                .map(|h| h.line + offset)
+        });
+
+        self.doc = doc;
+        let max_line = self.doc.lines.len().saturating_sub(1);
+        self.top_line = new_top.unwrap_or(self.top_line).min(max_line);
+        self.cursor_line = (self.top_line + cursor_offset).min(max_line);
+    }
+
+    /// 确保 cursor_line 在可见区域内，必要时滚动
+    fn scroll_to_cursor(&mut self, page: usize) {
+        if self.cursor_line < self.top_line {
+            self.top_line = self.cursor_line;
+        } else if self.cursor_line >= self.top_line + page {
+            self.top_line = self.cursor_line.saturating_sub(page.saturating_sub(1));
+        }
+    }
+}
+
+pub struct Viewer {
+    buffers: Vec<Buffer>,
+    /// 当前显示的文档下标
+    current: usize,
+    /// 上一帧已绘制的内嵌图片，位置不变时不重复绘制
+    drawn_images: Vec<(usize, Rect)>,
+    /// 整页图片模式（`v`）：PDF 的当前页显示为图片
+    page_image: bool,
+    /// 整页图片模式中上一帧绘制的页（文件、页码与区域），未变化时不重复绘制
+    drawn_page: Option<(PathBuf, usize, Rect)>,
+    /// 已渲染的页面图片，退出时删除
+    page_files: HashMap<(PathBuf, usize), PathBuf>,
+    /// 状态栏提示信息，下次按键后清除
+    message: Option<String>,
+    /// 幻灯片模式：每页的行范围
+    slides: Option<Vec<Range<usize>>>,
+    slide: usize,
+    /// 首帧（含内嵌图片）绘制耗时
+    first_render: Option<Duration>,
+    /// 是否监视源文件变化
+    watch: bool,
+    /// 文件系统通知，无法创建时为 None，退回定时检查当前文档的修改时间
+    watcher: Option<FileWatcher>,
+    /// 是否跟踪文件末尾
+    follow: bool,
+    /// 目录预览中打开选中项
+    opener: Option<Opener>,
+    /// diff 中按下 `]` / `[` 后等待下一个键：`c` 跳转 hunk，其他键照常切换文档；
+    /// 按下 `z` 后等待折叠命令
+    pending: Option<Action>,
+    /// 目录栏打开时为选中的标题序号
+    toc: Option<usize>,
+    /// 按下 `:` 或 `/` 后正在输入的命令或搜索词（前缀与内容）
+    command: Option<(char, String)>,
+    /// 当前的搜索词：匹配处高亮，`n` / `N` 跳转，`Esc` 清除
+    search: Option<String>,
+    /// 通过链接离开的文档，`Ctrl-o` 依次返回
+    history: Vec<Buffer>,
+}
+
+impl Viewer {
+    pub fn new(buffers: Vec<Buffer>) -> Self {
+        Self {
+            buffers,
+            current: 0,
+            drawn_images: Vec::new(),
+            page_image: false,
+            drawn_page: None,
+            page_files: HashMap::new(),
+            message: None,
+            slides: None,
+            slide: 0,
+            first_render: None,
+            watch: false,
+            watcher: None,
+            follow: false,
+            opener: None,
+            pending: None,
+            toc: None,
+            command: None,
+            search: None,
+            history: Vec::new(),
+        }
+    }
+
+    /// 设置打开文件的方法，目录预览中可用 Enter 打开选中项
+    pub fn set_opener(&mut self, opener: Opener) {
+        self.opener = Some(opener);
+    }
+
+    /// 切换为幻灯片模式（按分隔线与一级标题分页）
+    pub fn enable_slides(&mut self) {
+        self.slides = Some(self.buffer().doc.slides());
+        self.slide = 0;
+    }
+
+    /// 监视各文档的源文件，变化时重新解析并保持阅读位置
+    pub fn enable_watch(&mut self) {
+        if self.watch {
+            return;
+        }
+        self.watch = true;
+        match FileWatcher::new() {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(e) => tracing::warn!("无法创建文件监视，改为定时检查: {e}"),
+        }
+        for i in 0..self.buffers.len() {
+            self.watch_buffer(i);
+        }
+    }
+
+    /// 监视文档的源文件（未开启监视时不做处理）
+    fn watch_buffer(&mut self, index: usize) {
+        if let (Some(watcher), Some(path)) = (&mut self.watcher, self.buffers[index].path()) {
+            watcher.add(path);
         }
     }
 
+    /// 跟踪各文档的末尾：从末行开始显示，文件增长时滚动到底部并高亮新增的行
+    pub fn enable_follow(&mut self) {
+        self.enable_watch();
+        self.follow = true;
+        let page = self.content_rows();
+        for buf in &mut self.buffers {
+            buf.cursor_line = buf.doc.lines.len().saturating_sub(1);
+            buf.scroll_to_cursor(page);
+        }
+    }
+
+    pub fn buffers(&self) -> &[Buffer] {
+        &self.buffers
+    }
+
+    /// 首帧绘制耗时，尚未绘制时为 None
+    pub fn first_render(&self) -> Option<Duration> {
+        self.first_render
+    }
+
+    fn buffer(&self) -> &Buffer {
+        &self.buffers[self.current]
+    }
+
+    fn buffer_mut(&mut self) -> &mut Buffer {
+        &mut self.buffers[self.current]
+    }
+
     pub fn run(&mut self) -> io::Result<()> {
         enable_raw_mode()?;
         let mut stdout = stdout();
@@ -40,6 +536,9 @@ impl Viewer {
         terminal.show_cursor()?;
         disable_raw_mode()?;
         execute!(io::stdout(), LeaveAlternateScreen)?;
+        for file in self.page_files.values() {
+            std::fs::remove_file(file).ok();
+        }
 
         result
     }
@@ -48,85 +547,1064 @@ impl Viewer {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
+        let started = Instant::now();
         loop {
+            self.receive_pages();
+            // 幻灯片按原始行分页，不折行
+            if self.slides.is_none() {
+                let cols = self.content_cols();
+                self.buffers[self.current].ensure_wrap(cols);
+            }
+            // 离开整页图片模式（或切换到非 PDF 文档）后删除终端中的图片并整屏重绘
+            let shown_page = self.shown_page();
+            if shown_page.is_none() && self.drawn_page.take().is_some() {
+                format::image::clear_graphics()?;
+                terminal.clear()?;
+                self.drawn_images.clear();
+            }
             terminal.draw(|frame| {
                 let size = frame.area();
-                let content_area = Rect::new(0, 0, size.width, size.height.saturating_sub(1));
+                let content_area = self.content_area(size.width, size.height);
                 let status_area =
                     Rect::new(0, size.height.saturating_sub(1), size.width, 1);
+                let buf = &self.buffers[self.current];
+
+                // 打开多个文件时文件名后显示序号
+                let filename = if self.buffers.len() > 1 {
+                    format!("{} [{}/{}]", buf.filename, self.current + 1, self.buffers.len())
+                } else {
+                    buf.filename.clone()
+                };
 
-                renderer::draw_document(
-                    frame,
-                    &self.doc,
-                    content_area,
-                    self.top_line,
-                    self.left_col,
-                    self.cursor_line,
-                );
-                renderer::draw_status_bar(
-                    frame,
-                    status_area,
-                    &self.filename,
-                    self.cursor_line,
-                    self.doc.lines.len(),
-                );
+                if let Some(slides) = &self.slides {
+                    if let Some(range) = slides.get(self.slide) {
+                        renderer::draw_slide(frame, &buf.doc, range.clone(), content_area);
+                    }
+                    let position = StatusPosition {
+                        line: self.slide,
+                        total: slides.len(),
+                        ..Default::default()
+                    };
+                    renderer::draw_status_bar(frame, status_area, &filename, "", &position);
+                    return;
+                }
+
+                if let Some(selected) = self.toc {
+                    let toc_area = Rect::new(0, 0, content_area.x, content_area.height);
+                    let selected = selected.min(buf.doc.headings.len().saturating_sub(1));
+                    renderer::draw_toc(frame, &buf.doc.headings, toc_area, selected);
+                }
+                let search_matches = self.search.as_deref().map(|p| buf.doc.find(p)).unwrap_or_default();
+                if shown_page.is_none() {
+                    renderer::draw_document(
+                        frame,
+                        &buf.doc,
+                        content_area,
+                        buf.top_line,
+                        buf.left_col,
+                        buf.cursor_line,
+                        &search_matches,
+                    );
+                }
+                let breadcrumb = match (&self.message, buf.loading()) {
+                    (Some(msg), _) => msg.clone(),
+                    (None, Some(loading)) => loading,
+                    (None, None) => buf
+                        .doc
+                        .breadcrumb(buf.cursor_line)
+                        .iter()
+                        .map(|h| h.text.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" › "),
+                };
+                let matches = self.search.as_ref().map(|_| {
+                    let first = search_matches.partition_point(|(line, _)| *line < buf.cursor_line);
+                    let current = search_matches.get(first).filter(|(line, _)| *line == buf.cursor_line);
+                    (current.map(|_| first + 1), search_matches.len())
+                });
+                let position = StatusPosition {
+                    line: buf.cursor_line,
+                    total: buf.doc.lines.len(),
+                    source_line: buf.doc.source_line(buf.cursor_line),
+                    page: buf.page(),
+                    matches,
+                };
+                match &self.command {
+                    Some((prefix, input)) => renderer::draw_command_line(frame, status_area, *prefix, input),
+                    None => renderer::draw_status_bar(frame, status_area, &filename, &breadcrumb, &position),
+                }
             })?;
 
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Char('Q') => break,
-                    _ => self.handle_key(key.code),
+            if let Some((path, page)) = shown_page {
+                self.draw_page(terminal, path, page)?;
+            } else if self.slides.is_none() {
+                self.draw_images(terminal)?;
+            }
+            if self.first_render.is_none() {
+                self.first_render = Some(started.elapsed());
+            }
+
+            // 监视文件或仍在后台加载时定时醒来
+            let loading = self.buffers.iter().any(|b| b.loader.is_some());
+            if (self.watch || loading) && !event::poll(WATCH_INTERVAL)? {
+                if self.watch {
+                    self.check_reload();
                 }
+                continue;
+            }
+
+            let event = event::read()?;
+            if matches!(event, Event::Key(_)) {
+                // 按键表示已经看到新增的行，取消高亮；已经开始阅读时不再跳到恢复的位置
+                let buf = self.buffer_mut();
+                buf.clear_fresh();
+                buf.target = None;
+            }
+            if let (Event::Key(key), Some(_)) = (&event, &self.command) {
+                self.handle_command_key(key.code);
+                continue;
+            }
+            if let (Event::Key(key), Some(pending)) = (&event, self.pending.take()) {
+                if pending == Action::Fold {
+                    self.handle_fold_key(key.code);
+                    continue;
+                }
+                if key.code == KeyCode::Char('c') {
+                    let jump = if pending == Action::NextFile { Action::NextHunk } else { Action::PrevHunk };
+                    self.handle_action(jump);
+                    continue;
+                }
+                self.switch_buffer(if pending == Action::NextFile { 1 } else { self.buffers.len() - 1 });
+            }
+            match event {
+                Event::Key(key) => match config::get().keys.key_action(key.code, key.modifiers) {
+                    Some(Action::Quit) => break,
+                    Some(action @ (Action::NextFile | Action::PrevFile))
+                        if self.slides.is_none() && !self.buffer().doc.hunks.is_empty() =>
+                    {
+                        self.pending = Some(action);
+                    }
+                    Some(Action::Fold) if self.slides.is_none() => self.pending = Some(Action::Fold),
+                    Some(action @ (Action::Command | Action::Search)) if self.slides.is_none() => {
+                        self.message = None;
+                        let prefix = if action == Action::Search { '/' } else { ':' };
+                        self.command = Some((prefix, String::new()));
+                    }
+                    Some(Action::NextFile) => self.switch_buffer(1),
+                    Some(Action::Edit) if self.slides.is_none() => self.edit_source(terminal)?,
+                    Some(Action::PrevFile) => self.switch_buffer(self.buffers.len() - 1),
+                    _ if self.slides.is_some() => self.handle_slide_key(key.code),
+                    _ if self.toc.is_some() => self.handle_toc_key(key.code, key.modifiers),
+                    Some(action) => self.handle_action(action),
+                    None if key.code == KeyCode::Esc => {
+                        self.search = None;
+                        self.message = None;
+                    }
+                    None => self.message = None,
+                },
+                // 尺寸变化会整屏重绘，图片需要重新输出
+                Event::Resize(..) => {
+                    self.drawn_images.clear();
+                    self.drawn_page = None;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// 用 `$VISUAL` / `$EDITOR`（默认 `vi`）在光标行对应的源文件行打开当前文档，退出编辑器后重新加载
+    fn edit_source(&mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
+        let buf = self.buffer();
+        let Some(path) = buf.path().filter(|p| p.is_file()).map(Path::to_path_buf) else {
+            self.message = Some(t!("没有可编辑的源文件").into());
+            return Ok(());
+        };
+        let line = buf.doc.source_line(buf.cursor_line).map_or(1, |l| l + 1);
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".into());
+        // 编辑器命令可以带参数，如 `code --wait`
+        let mut words = editor.split_whitespace();
+        let program = words.next().unwrap_or("vi");
+
+        disable_raw_mode()?;
+        execute!(io::stdout(), LeaveAlternateScreen)?;
+        let status = Command::new(program).args(words).arg(format!("+{line}")).arg(&path).status();
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        terminal.clear()?;
+        self.drawn_images.clear();
+        self.drawn_page = None;
+
+        match status {
+            Ok(_) => self.reload_buffer(self.current),
+            Err(e) => self.message = Some(t!("无法启动编辑器 {}: {}", program, e)),
+        }
+        Ok(())
+    }
+
+    /// 切换到后面第 `offset` 个文档（循环）
+    fn switch_buffer(&mut self, offset: usize) {
+        if self.buffers.len() < 2 {
+            return;
+        }
+        self.current = (self.current + offset) % self.buffers.len();
+        self.drawn_images.clear();
+        self.message = None;
+        if self.slides.is_some() {
+            self.enable_slides();
+        }
+        if self.toc.is_some() {
+            self.open_toc();
+        }
+    }
+
+    /// 检查源文件是否变化：有文件系统通知时处理所有受影响的文档，否则检查当前文档
+    fn check_reload(&mut self) {
+        let Some(watcher) = &self.watcher else {
+            self.reload_buffer(self.current);
+            return;
+        };
+        let changed = watcher.changed();
+        if changed.is_empty() {
+            return;
+        }
+        for i in 0..self.buffers.len() {
+            // 目录预览在其中的文件增删时也需要刷新
+            let affected = self.buffers[i]
+                .path()
+                .is_some_and(|p| changed.iter().any(|c| c == p || c.parent() == Some(p)));
+            if affected {
+                self.reload_buffer(i);
+            }
+        }
+    }
+
+    /// 接收各文档在后台提取完的内容，当前文档同时更新幻灯片分页
+    fn receive_pages(&mut self) {
+        for i in 0..self.buffers.len() {
+            if self.buffers[i].receive_pages() && i == self.current && self.slides.is_some() {
+                let slides = self.buffer().doc.slides();
+                self.slide = self.slide.min(slides.len().saturating_sub(1));
+                self.slides = Some(slides);
+            }
+        }
+    }
+
+    /// 源文件修改时间变化时重新加载文档，当前文档同时更新状态栏与幻灯片分页
+    fn reload_buffer(&mut self, index: usize) {
+        let (follow, page) = (self.follow, self.content_rows());
+        let buf = &mut self.buffers[index];
+        let Some(message) = buf.check_reload(follow) else {
+            return;
+        };
+        buf.scroll_to_cursor(page);
+        // 页面图片按修改后的文件重新渲染
+        if let Some(path) = self.buffers[index].path() {
+            self.page_files.retain(|(p, _), file| p != path || std::fs::remove_file(file).is_err());
+            self.drawn_page = None;
+        }
+        if index != self.current {
+            return;
+        }
+        self.message = Some(message);
+        self.drawn_images.clear();
+        if self.slides.is_some() {
+            let slides = self.buffer().doc.slides();
+            self.slide = self.slide.min(slides.len().saturating_sub(1));
+            self.slides = Some(slides);
+        }
+    }
+
+    /// 整页图片模式中当前显示的页（文件与页码），不在该模式或当前文档不分页时为 None
+    fn shown_page(&self) -> Option<(PathBuf, usize)> {
+        if !self.page_image || self.slides.is_some() {
+            return None;
+        }
+        let buf = self.buffer();
+        Some((buf.path()?.to_path_buf(), buf.page()?.0))
+    }
+
+    /// 切换整页图片模式，只用于 PDF 等分页文档；当前页无法渲染时提示原因
+    fn toggle_page_image(&mut self) {
+        if self.page_image {
+            self.page_image = false;
+            return;
+        }
+        let buf = self.buffer();
+        let (Some(path), Some((page, _))) = (buf.path(), buf.page()) else {
+            self.message = Some(t!("只有 PDF 可以按页显示为图片").into());
+            return;
+        };
+        let path = path.to_path_buf();
+        match self.page_file(&path, page) {
+            Ok(_) => {
+                // 跳过开头的文档信息，之后按页翻动不会停在第一页
+                let buf = self.buffer_mut();
+                buf.cursor_line = buf.cursor_line.max(buf.doc.pages[0]);
+                self.page_image = true;
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    /// 第 `page` 页的图片，首次显示时渲染
+    fn page_file(&mut self, path: &Path, page: usize) -> io::Result<PathBuf> {
+        let key = (path.to_path_buf(), page);
+        if let Some(file) = self.page_files.get(&key) {
+            return Ok(file.clone());
+        }
+        let file = format::pdf::render_page(path, page)?;
+        self.page_files.insert(key, file.clone());
+        Ok(file)
+    }
+
+    /// 整页图片模式：在正文区域绘制当前页，换页时先删除上一页的图片
+    fn draw_page(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+        path: PathBuf,
+        page: usize,
+    ) -> io::Result<()> {
+        let size = terminal.size()?;
+        let area = self.content_area(size.width, size.height);
+        let drawn = Some((path, page, area));
+        if self.drawn_page == drawn {
+            return Ok(());
+        }
+        let Some((path, _, _)) = &drawn else {
+            return Ok(());
+        };
+
+        format::image::clear_graphics()?;
+        let out = terminal.backend_mut();
+        for y in area.top()..area.bottom() {
+            queue!(out, MoveTo(area.x, y), Print(" ".repeat(area.width as usize)))?;
+        }
+        out.flush()?;
+
+        let shown = self
+            .page_file(path, page)
+            .and_then(|file| format::image::print_page(&file, area.x, area.y, area.width, area.height));
+        if let Err(e) = shown {
+            // 退回文本显示，下一帧整屏重绘
+            tracing::warn!(page, "页面图片显示失败: {e}");
+            self.page_image = false;
+            self.message = Some(e.to_string());
+        }
+        terminal.hide_cursor()?;
+        self.drawn_page = drawn;
+        Ok(())
+    }
+
+    /// 在 ratatui 绘制完成后输出可见的内嵌图片（滚动到可见区域时才加载）
+    fn draw_images(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let buf = &self.buffers[self.current];
+        if buf.doc.images.is_empty() {
+            return Ok(());
+        }
+
+        let size = terminal.size()?;
+        let content_area = self.content_area(size.width, size.height);
+        let visible = renderer::visible_images(&buf.doc, content_area, buf.top_line);
+        if visible == self.drawn_images {
+            return Ok(());
+        }
+
+        for &(i, rect) in &visible {
+            if self.drawn_images.contains(&(i, rect)) {
+                continue;
+            }
+            // 图片区域被 ratatui 跳过，先清空残留内容
+            let out = terminal.backend_mut();
+            for y in rect.top()..rect.bottom() {
+                queue!(out, MoveTo(rect.x, y), Print(" ".repeat(rect.width as usize)))?;
+            }
+            out.flush()?;
+
+            let img = &buf.doc.images[i];
+            let shown =
+                format::image::print_in_area(&img.path, rect.x, rect.y, rect.width, rect.height);
+            if let Err(e) = &shown {
+                tracing::warn!(path = %img.path.display(), "内嵌图片显示失败: {e}");
+                let out = terminal.backend_mut();
+                queue!(out, MoveTo(rect.x, rect.y), Print(t!("[图片: {}]", img.alt)))?;
+                out.flush()?;
             }
         }
+        terminal.hide_cursor()?;
+        self.drawn_images = visible;
         Ok(())
     }
 
-    fn handle_key(&mut self, code: KeyCode) {
+    fn handle_action(&mut self, action: Action) {
+        self.message = None;
+        // 整页图片模式中上下翻动按页进行
+        let action = match action {
+            Action::Down | Action::HalfPageDown if self.shown_page().is_some() => Action::NextHeading,
+            Action::Up | Action::HalfPageUp if self.shown_page().is_some() => Action::PrevHeading,
+            action => action,
+        };
         let page = self.content_rows();
-        let max_line = self.doc.lines.len().saturating_sub(1);
+        let cols = self.content_cols();
+        let buf = &mut self.buffers[self.current];
+        let max_line = buf.doc.lines.len().saturating_sub(1);
 
-        match code {
-            KeyCode::Char('j') => {
-                self.cursor_line = (self.cursor_line + 1).min(max_line);
+        match action {
+            Action::Down => {
+                buf.cursor_line = (buf.cursor_line + 1).min(max_line);
             }
-            KeyCode::Char('k') => {
-                self.cursor_line = self.cursor_line.saturating_sub(1);
+            Action::Up => {
+                buf.cursor_line = buf.cursor_line.saturating_sub(1);
             }
-            KeyCode::Char('l') => self.left_col += 1,
-            KeyCode::Char('h') => self.left_col = self.left_col.saturating_sub(1),
-            KeyCode::Char('d') => {
-                self.cursor_line = (self.cursor_line + page / 2).min(max_line);
+            Action::Right => {
+                // 滚到最宽表格的右边缘为止，继续按键不再累积偏移
+                let max_col = buf
+                    .doc
+                    .tables
+                    .iter()
+                    .map(|t| (t.width + usize::from(buf.doc.lines[t.start].indent)).saturating_sub(cols))
+                    .max()
+                    .unwrap_or(0);
+                buf.left_col = (buf.left_col + 1).min(max_col);
             }
-            KeyCode::Char('u') => {
-                self.cursor_line = self.cursor_line.saturating_sub(page / 2);
+            Action::Left => buf.left_col = buf.left_col.saturating_sub(1),
+            Action::HalfPageDown => {
+                buf.cursor_line = (buf.cursor_line + page / 2).min(max_line);
             }
-            KeyCode::Char('g') => {
-                self.cursor_line = 0;
-                self.left_col = 0;
+            Action::HalfPageUp => {
+                buf.cursor_line = buf.cursor_line.saturating_sub(page / 2);
             }
-            KeyCode::Char('G') => {
-                self.cursor_line = max_line;
+            Action::Top => {
+                buf.cursor_line = 0;
+                buf.left_col = 0;
+            }
+            Action::Bottom => {
+                buf.cursor_line = max_line;
+            }
+            // 分页文档（PDF）中按页跳转
+            Action::NextHeading if !buf.doc.pages.is_empty() => {
+                let next = buf.doc.pages.iter().find(|&&l| l > buf.cursor_line);
+                if let Some(&line) = next {
+                    buf.cursor_line = line;
+                    buf.top_line = line;
+                }
             }
+            Action::PrevHeading if !buf.doc.pages.is_empty() => {
+                let prev = buf.doc.pages.iter().rev().find(|&&l| l < buf.cursor_line);
+                if let Some(&line) = prev {
+                    buf.cursor_line = line;
+                    buf.top_line = line;
+                }
+            }
+            Action::NextHeading => {
+                let next = buf.doc.headings.iter().find(|h| h.line > buf.cursor_line);
+                if let Some(line) = next.map(|h| h.line) {
+                    buf.cursor_line = line;
+                    buf.top_line = line;
+                }
+            }
+            Action::PrevHeading => {
+                let prev = buf.doc.headings.iter().rev().find(|h| h.line < buf.cursor_line);
+                if let Some(line) = prev.map(|h| h.line) {
+                    buf.cursor_line = line;
+                    buf.top_line = line;
+                }
+            }
+            Action::NextSection => {
+                let next = buf.doc.sections.iter().find(|&&l| l > buf.cursor_line);
+                if let Some(&line) = next {
+                    buf.cursor_line = line;
+                    buf.top_line = line;
+                }
+            }
+            Action::PrevSection => {
+                let prev = buf.doc.sections.iter().rev().find(|&&l| l < buf.cursor_line);
+                if let Some(&line) = prev {
+                    buf.cursor_line = line;
+                    buf.top_line = line;
+                }
+            }
+            Action::NextHunk => {
+                let next = buf.doc.hunks.iter().find(|&&l| l > buf.cursor_line);
+                if let Some(&line) = next {
+                    buf.cursor_line = line;
+                }
+            }
+            Action::PrevHunk => {
+                let prev = buf.doc.hunks.iter().rev().find(|&&l| l < buf.cursor_line);
+                if let Some(&line) = prev {
+                    buf.cursor_line = line;
+                }
+            }
+            Action::ToggleFold => buf.toggle_fold(),
+            Action::FoldAll => buf.fold_all(),
+            Action::UnfoldAll => buf.unfold(),
+            Action::ToggleListing => {
+                // 目录预览：在 README 与文件列表之间切换
+                if let Some(listing) = buf.doc.listing {
+                    let target = if buf.cursor_line >= listing { 0 } else { listing };
+                    buf.cursor_line = target;
+                    buf.top_line = target;
+                }
+            }
+            Action::ToggleToc => self.open_toc(),
+            Action::PageImage => self.toggle_page_image(),
+            Action::NextMatch => self.jump_to_match(true),
+            Action::PrevMatch => self.jump_to_match(false),
+            Action::Open => self.open_entry(),
+            Action::Back => self.go_back(),
+            Action::Parent => self.open_parent(),
+            Action::Sort => self.cycle_sort(),
+            Action::Yank => self.yank_code_block(),
+            Action::Save => self.save_code_block(),
+            Action::Info => self.show_image_info(),
+            Action::Quit | Action::NextFile | Action::PrevFile | Action::Edit | Action::Fold | Action::Command | Action::Search => {}
+        }
+
+        self.buffers[self.current].scroll_to_cursor(page);
+    }
+
+    /// 命令输入中的按键：`Enter` 执行，`Esc` 取消，退格删除最后一个字符
+    fn handle_command_key(&mut self, code: KeyCode) {
+        let Some((_, input)) = &mut self.command else {
+            return;
+        };
+        match code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace if input.is_empty() => self.command = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => match self.command.take() {
+                Some(('/', pattern)) => self.search_for(pattern),
+                Some((_, input)) => self.run_command(input.trim()),
+                None => {}
+            },
+            KeyCode::Esc => self.command = None,
             _ => {}
         }
+    }
 
-        self.scroll_to_cursor();
+    /// 执行 `:` 命令：`N` 跳到第 N 行，`N%` 跳到文档的百分之 N 处，`$` 跳到末行，`page N` 跳到第 N 页
+    fn run_command(&mut self, command: &str) {
+        let total = self.buffer().doc.lines.len();
+        if command == "$" {
+            self.goto_line(total.saturating_sub(1));
+            return;
+        }
+        if let Some(Ok(percent)) = command.strip_suffix('%').map(str::parse::<usize>) {
+            self.goto_line(total * percent.min(100) / 100);
+            return;
+        }
+        if let Ok(n) = command.parse::<usize>() {
+            self.goto_line(n.saturating_sub(1));
+            return;
+        }
+        let mut words = command.split_whitespace();
+        match (words.next(), words.next().map(str::parse::<usize>), words.next()) {
+            (None, ..) => {}
+            (Some("page"), Some(Ok(n)), None) => self.jump_to_page(n),
+            (Some("page"), ..) => self.message = Some(t!("用法: :page <页码>").into()),
+            _ => self.message = Some(t!("未知命令: {}", command)),
+        }
     }
 
-    /// 确保 cursor_line 在可见区域内，必要时滚动
-    fn scroll_to_cursor(&mut self) {
+    /// 搜索 `pattern` 并跳到光标之后的第一处匹配；为空时沿用上一次的搜索词
+    fn search_for(&mut self, pattern: String) {
+        if !pattern.is_empty() {
+            self.search = Some(pattern);
+        }
+        self.jump_to_match(true);
+    }
+
+    /// 跳到光标之后（`forward`）或之前最近的有匹配的行，到达一端时从另一端继续
+    fn jump_to_match(&mut self, forward: bool) {
+        let Some(pattern) = &self.search else {
+            self.message = Some(t!("没有搜索词").into());
+            return;
+        };
         let page = self.content_rows();
-        if self.cursor_line < self.top_line {
-            self.top_line = self.cursor_line;
-        } else if self.cursor_line >= self.top_line + page {
-            self.top_line = self.cursor_line.saturating_sub(page.saturating_sub(1));
+        let buf = &mut self.buffers[self.current];
+        let lines: Vec<usize> = buf.doc.find(pattern).into_iter().map(|(line, _)| line).collect();
+        let (Some(&first), Some(&last)) = (lines.first(), lines.last()) else {
+            self.message = Some(t!("找不到: {}", pattern));
+            return;
+        };
+        let cursor = buf.cursor_line;
+        let next = if forward {
+            lines.iter().find(|&&l| l > cursor)
+        } else {
+            lines.iter().rev().find(|&&l| l < cursor)
+        };
+        buf.cursor_line = match next {
+            Some(&line) => line,
+            None if forward => {
+                self.message = Some(t!("已到末尾，从头继续搜索").into());
+                first
+            }
+            None => {
+                self.message = Some(t!("已到开头，从末尾继续搜索").into());
+                last
+            }
+        };
+        buf.scroll_to_cursor(page);
+    }
+
+    /// 光标移到第 `line` 行（从 0 开始，超出时为末行）
+    fn goto_line(&mut self, line: usize) {
+        let page = self.content_rows();
+        let buf = &mut self.buffers[self.current];
+        buf.cursor_line = line.min(buf.doc.lines.len().saturating_sub(1));
+        buf.scroll_to_cursor(page);
+    }
+
+    /// 跳到第 `n` 页（从 1 开始）
+    fn jump_to_page(&mut self, n: usize) {
+        let page = self.content_rows();
+        let buf = &mut self.buffers[self.current];
+        let Some((_, pages)) = buf.page() else {
+            self.message = Some(t!("文档没有分页").into());
+            return;
+        };
+        if n == 0 || n > pages {
+            self.message = Some(t!("没有第 {} 页（共 {} 页）", n, pages));
+            return;
+        }
+        let Some(&line) = buf.doc.pages.get(n - 1) else {
+            self.message = Some(t!("第 {} 页尚未加载", n));
+            return;
+        };
+        buf.cursor_line = line;
+        buf.top_line = line;
+        buf.scroll_to_cursor(page);
+    }
+
+    /// `z` 之后的按键：`a` 切换光标处的折叠，`M` 全部折叠，`R` 全部展开
+    fn handle_fold_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('a') => self.handle_action(Action::ToggleFold),
+            KeyCode::Char('M') => self.handle_action(Action::FoldAll),
+            KeyCode::Char('R') => self.handle_action(Action::UnfoldAll),
+            _ => self.message = None,
+        }
+    }
+
+    fn handle_slide_key(&mut self, code: KeyCode) {
+        let last = self.slides.as_ref().map_or(0, |s| s.len().saturating_sub(1));
+        match code {
+            KeyCode::Char('n' | 'l' | 'j' | ' ') | KeyCode::Right | KeyCode::PageDown => {
+                self.slide = (self.slide + 1).min(last);
+            }
+            KeyCode::Char('p' | 'h' | 'k') | KeyCode::Left | KeyCode::PageUp => {
+                self.slide = self.slide.saturating_sub(1);
+            }
+            KeyCode::Char('g') => self.slide = 0,
+            KeyCode::Char('G') => self.slide = last,
+            _ => {}
+        }
+    }
+
+    /// 目录栏打开时的按键：上下移动选中项，`Enter` 跳转到选中的标题，`Esc` 或再按 `t` 关闭
+    fn handle_toc_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let page = self.content_rows();
+        let buf = &mut self.buffers[self.current];
+        let last = buf.doc.headings.len().saturating_sub(1);
+        let selected = self.toc.unwrap_or(0).min(last);
+        let action = config::get().keys.key_action(code, modifiers);
+        self.toc = match action {
+            Some(Action::Down) => Some((selected + 1).min(last)),
+            Some(Action::Up) => Some(selected.saturating_sub(1)),
+            Some(Action::HalfPageDown) => Some((selected + page / 2).min(last)),
+            Some(Action::HalfPageUp) => Some(selected.saturating_sub(page / 2)),
+            Some(Action::Top) => Some(0),
+            Some(Action::Bottom) => Some(last),
+            Some(Action::Open) => {
+                if let Some(heading) = buf.doc.headings.get(selected) {
+                    buf.cursor_line = heading.line;
+                    buf.top_line = heading.line;
+                    buf.scroll_to_cursor(page);
+                }
+                None
+            }
+            Some(Action::ToggleToc) => None,
+            _ if code == KeyCode::Esc => None,
+            _ => Some(selected),
+        };
+        self.drawn_images.clear();
+    }
+
+    /// 打开目录栏，选中光标所在章节的标题
+    fn open_toc(&mut self) {
+        let buf = self.buffer();
+        if buf.doc.headings.is_empty() {
+            self.toc = None;
+            self.message = Some(t!("文档中没有标题").into());
+            return;
+        }
+        let selected = buf.doc.headings.iter().rposition(|h| h.line <= buf.cursor_line);
+        self.toc = Some(selected.unwrap_or(0));
+        self.drawn_images.clear();
+    }
+
+    /// 正文区域：去掉底部状态栏，目录栏打开时让出左侧
+    fn content_area(&self, width: u16, height: u16) -> Rect {
+        let toc = if self.toc.is_some() { TOC_WIDTH.min(width / 3) } else { 0 };
+        Rect::new(toc, 0, width - toc, height.saturating_sub(1))
+    }
+
+    /// 目录预览：打开光标所在项，目录在当前位置进入，文件作为新文档打开；其他文档中打开光标行的链接
+    fn open_entry(&mut self) {
+        let buf = self.buffer();
+        let Some(entry) = buf.doc.entry_at(buf.cursor_line) else {
+            self.open_link();
+            return;
+        };
+        let (path, sort) = (entry.path.clone(), buf.sort);
+        if path.is_dir() {
+            let from = buf.path().map(Path::to_path_buf);
+            self.browse(&path, sort, from.as_deref());
+            return;
+        }
+
+        // 已经打开的文件直接切换过去
+        let target = std::fs::canonicalize(&path).unwrap_or(path);
+        if let Some(i) = self.buffers.iter().position(|b| b.path() == Some(target.as_path())) {
+            self.switch_buffer(i + self.buffers.len() - self.current);
+            return;
+        }
+        match self.open(&target, sort) {
+            Ok(buffer) => {
+                self.buffers.insert(self.current + 1, buffer);
+                self.watch_buffer(self.current + 1);
+                self.switch_buffer(1);
+            }
+            Err(e) => self.message = Some(t!("无法打开 {}: {}", target.display(), e)),
+        }
+    }
+
+    /// 打开光标行的第一个链接：文内锚点跳转到对应标题，相对路径与 Wiki 链接在当前位置打开目标文件，
+    /// 原文档放入返回栈
+    fn open_link(&mut self) {
+        let buf = self.buffer();
+        let Some(link) = buf.doc.links_at(buf.cursor_line).next() else {
+            return;
+        };
+        let url = link.url.clone();
+        if url.contains("://") || url.starts_with("mailto:") {
+            self.open_url(&url);
+            return;
+        }
+        let (target, fragment) = match url.split_once('#') {
+            Some((target, fragment)) => (target, Some(fragment)),
+            None => (url.as_str(), None),
+        };
+        if target.is_empty() {
+            self.jump_to_anchor(fragment.unwrap_or(""));
+            return;
+        }
+
+        let target = target.replace("%20", " ");
+        let base = buf.path().and_then(Path::parent).unwrap_or(Path::new("."));
+        let mut path = base.join(&target);
+        // Wiki 链接与省略扩展名的链接指向同名的 Markdown 文件
+        if !path.exists() && path.extension().is_none() {
+            path = base.join(format!("{target}.md"));
+        }
+        let sort = buf.sort;
+        let mut buffer = match self.open(&path, sort) {
+            Ok(buffer) => buffer,
+            Err(e) => {
+                self.message = Some(t!("无法打开 {}: {}", path.display(), e));
+                return;
+            }
+        };
+        let page = self.content_rows();
+        buffer.scroll_to_cursor(page);
+        let previous = std::mem::replace(&mut self.buffers[self.current], buffer);
+        self.history.push(previous);
+        self.watch_buffer(self.current);
+        self.drawn_images.clear();
+        if let Some(fragment) = fragment {
+            self.jump_to_anchor(fragment);
+        }
+    }
+
+    /// 用系统默认程序打开网址（macOS 为 `open`，Windows 为 `start`，其他为 `xdg-open`），状态栏显示网址
+    fn open_url(&mut self, url: &str) {
+        let mut command = if cfg!(target_os = "macos") {
+            Command::new("open")
+        } else if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        } else {
+            Command::new("xdg-open")
+        };
+        let opened = command
+            .arg(url)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        self.message = Some(match opened {
+            Ok(_) => t!("已打开 {}", url),
+            Err(e) => t!("无法打开 {}: {}", url, e),
+        });
+    }
+
+    /// 跳转到锚点对应的标题（按 GitHub 的规则由标题文本生成锚点）；PDF 中 `page=N` 跳到第 N 页
+    fn jump_to_anchor(&mut self, anchor: &str) {
+        if let Some(n) = anchor.strip_prefix("page=").and_then(|n| n.parse().ok())
+            && !self.buffer().doc.pages.is_empty()
+        {
+            self.jump_to_page(n);
+            return;
+        }
+        let page = self.content_rows();
+        let buf = &mut self.buffers[self.current];
+        let anchor = anchor.to_lowercase();
+        match buf.doc.headings.iter().find(|h| heading_anchor(&h.text) == anchor) {
+            Some(heading) => {
+                buf.cursor_line = heading.line;
+                buf.top_line = heading.line;
+                buf.scroll_to_cursor(page);
+            }
+            None => self.message = Some(t!("找不到标题: #{}", anchor)),
         }
     }
 
+    /// 返回通过链接离开的上一个文档
+    fn go_back(&mut self) {
+        let Some(previous) = self.history.pop() else {
+            self.message = Some(t!("没有可以返回的文档").into());
+            return;
+        };
+        self.buffers[self.current] = previous;
+        self.drawn_images.clear();
+        if self.toc.is_some() {
+            self.open_toc();
+        }
+    }
+
+    /// 目录预览：进入上级目录
+    fn open_parent(&mut self) {
+        let buf = self.buffer();
+        if buf.doc.listing.is_none() {
+            return;
+        }
+        let Some(dir) = buf.path().map(Path::to_path_buf) else {
+            return;
+        };
+        let sort = buf.sort;
+        if let Some(parent) = dir.parent() {
+            self.browse(parent, sort, Some(&dir));
+        }
+    }
+
+    /// 目录预览：切换排序方式，光标保持在同一项上
+    fn cycle_sort(&mut self) {
+        let buf = self.buffer();
+        if buf.doc.listing.is_none() {
+            return;
+        }
+        let Some(dir) = buf.path().map(Path::to_path_buf) else {
+            return;
+        };
+        let selected = buf.doc.entry_at(buf.cursor_line).map(|e| e.path.clone());
+        let sort = buf.sort.next();
+        self.browse(&dir, sort, selected.as_deref());
+        self.message = Some(sort.label().into());
+    }
+
+    /// 在当前文档位置打开目录，光标移到 `select` 对应的项（没有则为第一项）
+    fn browse(&mut self, dir: &Path, sort: SortKey, select: Option<&Path>) {
+        let mut buffer = match self.open(dir, sort) {
+            Ok(buffer) => buffer,
+            Err(e) => {
+                self.message = Some(t!("无法打开 {}: {}", dir.display(), e));
+                return;
+            }
+        };
+        buffer.sort = sort;
+        // 默认选中第一项，跳过上级目录
+        let entries = &buffer.doc.entries;
+        let line = entries
+            .iter()
+            .find(|e| select.is_some_and(|s| e.path == s))
+            .or_else(|| entries.iter().find(|e| !dir.starts_with(&e.path)))
+            .or(entries.first())
+            .map(|e| e.line);
+        if let Some(line) = line {
+            buffer.top_line = buffer.doc.listing.unwrap_or(0);
+            buffer.cursor_line = line;
+        }
+
+        let page = self.content_rows();
+        buffer.scroll_to_cursor(page);
+        self.buffers[self.current] = buffer;
+        self.watch_buffer(self.current);
+        self.drawn_images.clear();
+    }
+
+    fn open(&self, path: &Path, sort: SortKey) -> Result<Buffer, String> {
+        match &self.opener {
+            Some(opener) => opener(path, sort),
+            None => Err(t!("不支持打开文件").into()),
+        }
+    }
+
+    /// 通过 OSC 52 将光标所在代码块复制到终端剪贴板
+    fn yank_code_block(&mut self) {
+        let buf = &self.buffers[self.current];
+        let Some(block) = buf.doc.code_block_at(buf.cursor_line) else {
+            self.message = Some(t!("光标不在代码块内").into());
+            return;
+        };
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&block.text);
+        let mut out = stdout();
+        let result = write!(out, "\x1b]52;c;{encoded}\x07").and_then(|_| out.flush());
+        self.message = Some(match result {
+            Ok(()) => t!("已复制代码块（{} 行）", block.end - block.start),
+            Err(e) => t!("复制失败: {}", e),
+        });
+    }
+
+    /// 在状态栏显示光标处（或文档中第一张）图片的尺寸与 EXIF 信息
+    fn show_image_info(&mut self) {
+        let buf = &self.buffers[self.current];
+        let images = &buf.doc.images;
+        let image = images
+            .iter()
+            .rev()
+            .find(|img| (img.line..img.line + usize::from(img.height)).contains(&buf.cursor_line))
+            .or(images.first());
+        let Some(image) = image else {
+            self.message = Some(t!("光标处没有图片").into());
+            return;
+        };
+        self.message = Some(match format::image::info(&image.path) {
+            Ok(rows) => rows.iter().map(|(label, value)| format!("{label} {value}")).collect::<Vec<_>>().join(" · "),
+            Err(e) => t!("无法读取图片信息: {}", e),
+        });
+    }
+
+    /// 将光标所在代码块写入当前目录下的文件
+    fn save_code_block(&mut self) {
+        let buf = &self.buffers[self.current];
+        let Some(block) = buf.doc.code_block_at(buf.cursor_line) else {
+            self.message = Some(t!("光标不在代码块内").into());
+            return;
+        };
+
+        let index = buf
+            .doc
+            .code_blocks
+            .iter()
+            .position(|b| b.start == block.start)
+            .unwrap_or(0);
+        let stem = Path::new(&buf.filename)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("xcat");
+        let name = format!("{stem}-code{}.{}", index + 1, code_extension(block));
+
+        self.message = Some(match std::fs::write(&name, &block.text) {
+            Ok(()) => t!("已保存代码块到 {}", name),
+            Err(e) => t!("保存失败: {}", e),
+        });
+    }
+
     /// 内容区域行数（总行数减去状态栏）
     fn content_rows(&self) -> usize {
         let (_, rows) = crossterm::terminal::size().unwrap_or((80, 24));
         (rows as usize).saturating_sub(1)
     }
+
+    /// 内容区域列数（目录栏打开时去掉其宽度）
+    fn content_cols(&self) -> usize {
+        let (cols, rows) = crossterm::terminal::size().unwrap_or((80, 24));
+        self.content_area(cols, rows).width as usize
+    }
+}
+
+/// 标题的锚点：小写，空白换成 `-`，去掉其他标点
+fn heading_anchor(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_whitespace() => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 根据代码块语言推断保存时的扩展名
+fn code_extension(block: &CodeBlock) -> &'static str {
+    match block.lang.as_deref().map(str::to_lowercase).as_deref() {
+        Some("rust" | "rs") => "rs",
+        Some("python" | "py") => "py",
+        Some("javascript" | "js") => "js",
+        Some("typescript" | "ts") => "ts",
+        Some("bash" | "sh" | "shell" | "zsh") => "sh",
+        Some("go" | "golang") => "go",
+        Some("c") => "c",
+        Some("cpp" | "c++") => "cpp",
+        Some("java") => "java",
+        Some("json") => "json",
+        Some("yaml" | "yml") => "yaml",
+        Some("toml") => "toml",
+        Some("html") => "html",
+        Some("css") => "css",
+        Some("sql") => "sql",
+        Some("markdown" | "md") => "md",
+        _ => "txt",
+    }
+}
+
+/// 通过文件系统通知得知源文件的变化
+struct FileWatcher {
+    watcher: notify::RecommendedWatcher,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+}
+
+impl FileWatcher {
+    fn new() -> notify::Result<Self> {
+        let (tx, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(tx)?;
+        Ok(Self { watcher, events })
+    }
+
+    /// 监视文件所在的目录（编辑器常以写入新文件再改名的方式保存），目录则监视其本身
+    fn add(&mut self, path: &Path) {
+        let target = match path.parent() {
+            Some(parent) if !path.is_dir() => parent,
+            _ => path,
+        };
+        if let Err(e) = self.watcher.watch(target, RecursiveMode::NonRecursive) {
+            tracing::warn!(path = %target.display(), "无法监视: {e}");
+        }
+    }
+
+    /// 取出上次调用以来发生变化的路径
+    fn changed(&self) -> Vec<PathBuf> {
+        self.events
+            .try_iter()
+            .filter_map(Result::ok)
+            .filter(|event| !matches!(event.kind, EventKind::Access(_)))
+            .flat_map(|event| event.paths)
+            .collect()
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
 }
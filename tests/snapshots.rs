@@ -0,0 +1,53 @@
+//! 快照测试：将 `tests/fixtures` 下的文档离屏渲染，与同名 `.txt` 文件对比
+//!
+//! 修改解析或渲染后，用 `UPDATE_SNAPSHOTS=1 cargo test` 重新生成快照，再检查差异。
+
+use std::fs;
+use std::path::Path;
+use xcat::document::Document;
+use xcat::format::{diff, markdown};
+use xcat::snapshot::render_plain;
+
+const WIDTH: u16 = 80;
+
+/// 按扩展名解析；`commonmark.md` 关闭所有扩展语法，验证严格模式
+fn parse(path: &Path) -> Document {
+    let content = fs::read_to_string(path).unwrap();
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("md") if path.file_stem().is_some_and(|s| s == "commonmark") => {
+            let options = markdown::MarkdownOptions {
+                extensions: markdown::MarkdownExtensions::commonmark(),
+                smart_punctuation: false,
+                ..Default::default()
+            };
+            markdown::parse_markdown(&content, &options)
+        }
+        Some("md") => markdown::parse_markdown(&content, &markdown::MarkdownOptions::default()),
+        Some("diff") => diff::parse_diff(&content),
+        _ => unreachable!("未知的快照输入 {}", path.display()),
+    }
+}
+
+#[test]
+fn test_snapshots() {
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut failures = Vec::new();
+
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "txt") {
+            continue;
+        }
+        let actual = render_plain(&parse(&path), WIDTH);
+        let snapshot = path.with_extension("txt");
+
+        if update {
+            fs::write(&snapshot, &actual).unwrap();
+        } else if fs::read_to_string(&snapshot).ok().as_deref() != Some(actual.as_str()) {
+            failures.push(format!("{}:\n{actual}", path.display()));
+        }
+    }
+
+    assert!(failures.is_empty(), "快照不一致:\n{}", failures.join("\n"));
+}